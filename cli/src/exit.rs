@@ -0,0 +1,120 @@
+use maven_artifact::artifact::ParseArtifactError;
+use maven_artifact::resolver::ResolveError;
+use std::fmt::{Display, Formatter};
+use std::process::ExitCode;
+
+pub const EXIT_CODES: &str = "\
+Exit codes:
+  0  success
+  1  unclassified failure
+  2  bad arguments or coordinates
+  3  artifact or metadata not found
+  4  authentication or authorization failure
+  5  checksum mismatch
+  6  network error or rate limiting
+  7  artifact refused by the ban policy or minimum release age
+130  download interrupted by Ctrl-C";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Exit {
+    Success = 0,
+    Failure = 1,
+    BadArguments = 2,
+    NotFound = 3,
+    Auth = 4,
+    ChecksumMismatch = 5,
+    Network = 6,
+    Banned = 7,
+    Interrupted = 130,
+}
+
+impl From<Exit> for ExitCode {
+    fn from(value: Exit) -> Self {
+        ExitCode::from(value as u8)
+    }
+}
+
+#[derive(Debug)]
+pub struct NotFound(pub String);
+
+impl Display for NotFound {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for NotFound {}
+
+#[derive(Debug)]
+pub struct Drift(pub usize);
+
+impl Display for Drift {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} file(s) differ from the repository", self.0)
+    }
+}
+
+impl std::error::Error for Drift {}
+
+#[derive(Debug)]
+pub struct UnknownCommand(pub String);
+
+impl Display for UnknownCommand {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "no such command: `{}`, and no `maven-artifact-{}` found on PATH",
+            self.0, self.0
+        )
+    }
+}
+
+impl std::error::Error for UnknownCommand {}
+
+impl Exit {
+    pub fn classify(err: &anyhow::Error) -> Exit {
+        for cause in err.chain() {
+            if cause.is::<NotFound>() {
+                return Exit::NotFound;
+            }
+            if cause.is::<Drift>() {
+                return Exit::ChecksumMismatch;
+            }
+            if let Some(e) = cause.downcast_ref::<ResolveError>() {
+                match e {
+                    ResolveError::GenericHttpError { status: 404, .. }
+                    | ResolveError::UnknownPluginPrefix(_) => return Exit::NotFound,
+                    ResolveError::GenericHttpError {
+                        status: 401 | 403, ..
+                    } => return Exit::Auth,
+                    ResolveError::Parse(_)
+                    | ResolveError::UrlError(_)
+                    | ResolveError::UnsafePath(_) => {
+                        return Exit::BadArguments;
+                    }
+                    ResolveError::ChecksumMismatch { .. } => return Exit::ChecksumMismatch,
+                    ResolveError::Banned(_) | ResolveError::TooRecent { .. } => {
+                        return Exit::Banned;
+                    }
+                    ResolveError::RateLimited { .. } | ResolveError::Timeout { .. } => {
+                        return Exit::Network;
+                    }
+                    ResolveError::Cancelled(_) => return Exit::Interrupted,
+                    _ => (),
+                }
+            }
+            if let Some(e) = cause.downcast_ref::<reqwest::Error>()
+                && (e.is_connect() || e.is_timeout() || e.is_request())
+            {
+                return Exit::Network;
+            }
+            if cause.is::<UnknownCommand>() {
+                return Exit::BadArguments;
+            }
+            if cause.is::<ParseArtifactError>() || cause.is::<url::ParseError>() {
+                return Exit::BadArguments;
+            }
+        }
+        Exit::Failure
+    }
+}
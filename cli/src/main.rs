@@ -1,19 +1,69 @@
+mod exit;
+mod progress;
+
+use crate::exit::{Drift, EXIT_CODES, Exit, NotFound, UnknownCommand};
+use crate::progress::ProgressBars;
 use anyhow::{Context, bail};
-use base64::Engine;
-use base64::prelude::BASE64_STANDARD;
-use clap::{Parser, Subcommand};
-use maven_artifact::Repository;
-use maven_artifact::artifact::{Artifact, PartialArtifact};
-use maven_artifact::resolver::Resolver;
-use reqwest::header::{AUTHORIZATION, HeaderMap, HeaderValue};
-use reqwest::{Client, ClientBuilder};
-use std::path::PathBuf;
+use clap::{ArgAction, Parser, Subcommand};
+use futures_util::StreamExt;
+use maven_artifact::analysis::analyze_overlap;
+use maven_artifact::artifact::{Artifact, ParseArtifactError, PartialArtifact, ResolvedArtifact};
+use maven_artifact::auth::{Auth, CredentialStore};
+use maven_artifact::bundle::Bundle;
+use maven_artifact::cache::{DescriptorCache, UpdatePolicy};
+use maven_artifact::cadence::release_stats;
+use maven_artifact::checksum::{self, ChecksumPolicy};
+use maven_artifact::conditional::ValidatorCache;
+use maven_artifact::deploy::{self, DeployFile, StagingRepository};
+use maven_artifact::disk::LinkStrategy;
+use maven_artifact::gradle;
+use maven_artifact::graph::{DependencyGraph, DependencyNode, GraphLimits, Omitted};
+use maven_artifact::jar::{self, GavCheck, JarDiff};
+use maven_artifact::layout;
+use maven_artifact::listing::PublishedVersion;
+use maven_artifact::local::LocalRepository;
+use maven_artifact::policy::BanPolicy;
+use maven_artifact::pom::{Dependency, NamespaceCheck, PomParser, Project, dependencies_xml};
+use maven_artifact::proxy::ProxyConfig;
+use maven_artifact::ratelimit::RateLimiter;
+use maven_artifact::resolver::{
+    DEFAULT_PLUGIN_GROUPS, FileCheck, PollOptions, ResolveError, Resolver,
+};
+use maven_artifact::retry::RetryPolicy;
+use maven_artifact::sbom;
+use maven_artifact::settings::Settings;
+use maven_artifact::substitution::Substitutions;
+use maven_artifact::sync::{self, SyncReason};
+use maven_artifact::validation::validate_for_central;
+use maven_artifact::version::{self, VersionRange};
+use maven_artifact::withdrawn::{OSV_QUERY_URL, Withdrawals};
+use maven_artifact::workspace::Workspace;
+use maven_artifact::{Classifier, GroupId, Repository, RepositoryOverrides, Version};
+use serde_json::json;
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::pin::pin;
+use std::process::ExitCode;
 use std::str::FromStr;
-use url::Url;
+use std::time::{Duration, Instant, SystemTime};
+use tracing::warn;
+use tracing_subscriber::EnvFilter;
 
 // Name your user agent after your app?
 static APP_USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"),);
 
+const VERSION_SYNTAX: &str = "\
+Versions:
+  1.2.3               exact version
+  1.2.3-SNAPSHOT      latest timestamped snapshot
+  RELEASE, LATEST     from maven-metadata.xml
+  [1.0,2.0)           highest version in a Maven range, across all repositories
+  latest.release      highest non-snapshot version
+  latest.integration  highest version, snapshots included
+  1.+                 highest version starting with \"1.\"";
+
 #[derive(Clone)]
 enum Select {
     Latest,
@@ -34,9 +84,250 @@ impl FromStr for Select {
     }
 }
 
+// classifier[:extension]=path, the extension defaulting to jar
+fn parse_attachment(value: &str) -> anyhow::Result<DeployFile> {
+    let Some((spec, path)) = value.split_once('=') else {
+        bail!("Expected classifier[:extension]=path, got {}", value);
+    };
+    let (classifier, extension) = spec.split_once(':').unwrap_or((spec, "jar"));
+    if classifier.is_empty() || extension.is_empty() || path.is_empty() {
+        bail!("Expected classifier[:extension]=path, got {}", value);
+    }
+    Ok(DeployFile {
+        classifier: Some(Classifier::from(classifier)),
+        extension: extension.to_string(),
+        source: PathBuf::from(path),
+    })
+}
+
+// local targets, as a file:// URL or a plain directory
+fn staging_dir(target: &str) -> anyhow::Result<PathBuf> {
+    if let Some(rest) = target.strip_prefix("file://") {
+        let url = url::Url::parse(target).with_context(|| format!("Invalid URL {}", target))?;
+        return url
+            .to_file_path()
+            .map_err(|_| anyhow::anyhow!("Invalid file URL {}", rest));
+    }
+    if target.contains("://") {
+        bail!("Only file:// targets are supported, got {}", target);
+    }
+    Ok(PathBuf::from(target))
+}
+
+fn parse_date(value: &str) -> anyhow::Result<String> {
+    let bytes = value.as_bytes();
+    let valid = bytes.len() == 10
+        && bytes[4] == b'-'
+        && bytes[7] == b'-'
+        && bytes
+            .iter()
+            .enumerate()
+            .all(|(i, b)| i == 4 || i == 7 || b.is_ascii_digit());
+    if !valid {
+        bail!("Expected a date like 2024-01-01, got {}", value);
+    }
+    Ok(value.to_string())
+}
+
+// prefix[:version], the latest release when no version is given
+fn parse_plugin(value: &str) -> anyhow::Result<(String, Version)> {
+    let (prefix, version) = value.split_once(':').unwrap_or((value, "RELEASE"));
+    if prefix.is_empty() || version.is_empty() || version.contains(':') {
+        bail!(
+            "Expected prefix[:version], like surefire:RELEASE, got {}",
+            value
+        );
+    }
+    Ok((prefix.to_string(), Version::from(version)))
+}
+
+fn parse_rate(value: &str) -> anyhow::Result<f64> {
+    match value.parse::<f64>() {
+        Ok(rate) if rate > 0.0 && rate.is_finite() => Ok(rate),
+        _ => bail!("Expected a number of requests above zero, got {}", value),
+    }
+}
+
+#[derive(Clone)]
+enum ArtifactInput {
+    Stdin,
+    Artifact(Artifact),
+}
+
+impl ArtifactInput {
+    fn parse(input: &str) -> Result<ArtifactInput, ParseArtifactError> {
+        if input == "-" {
+            Ok(ArtifactInput::Stdin)
+        } else if input.starts_with("pkg:") {
+            Artifact::parse_purl(input).map(ArtifactInput::Artifact)
+        } else {
+            Artifact::parse(input).map(ArtifactInput::Artifact)
+        }
+    }
+}
+
+type Artifacts = Box<dyn Iterator<Item = anyhow::Result<Artifact>>>;
+
+// Coordinates or package URLs one per line, each handed out as soon as it is read, or a
+// CycloneDX JSON SBOM, which can only be parsed once complete
+fn read_artifacts(input: Option<&Path>) -> anyhow::Result<Artifacts> {
+    let reader: Box<dyn BufRead> = match input {
+        Some(path) => Box::new(BufReader::new(
+            File::open(path).with_context(|| format!("Unable to read {}", path.display()))?,
+        )),
+        None => Box::new(std::io::stdin().lock()),
+    };
+    let mut lines = reader.lines().enumerate().peekable();
+    while lines
+        .next_if(|(_, line)| line.as_ref().is_ok_and(|l| l.trim().is_empty()))
+        .is_some()
+    {}
+    if let Some((_, Ok(first))) = lines.peek()
+        && first.trim_start().starts_with('{')
+    {
+        let content = lines
+            .map(|(_, line)| line)
+            .collect::<std::io::Result<Vec<String>>>()?
+            .join("\n");
+        let artifacts = sbom::parse_artifact_list(&content)?;
+        return Ok(Box::new(artifacts.into_iter().map(Ok)));
+    }
+    Ok(Box::new(lines.filter_map(|(index, line)| {
+        match line {
+            Ok(line) => sbom::parse_artifact_line(&line, index + 1)
+                .map_err(anyhow::Error::from)
+                .transpose(),
+            Err(e) => Some(Err(e.into())),
+        }
+    })))
+}
+
 #[derive(Parser)]
-#[command(version, about, long_about, arg_required_else_help = true)]
+#[command(
+    version,
+    about,
+    long_about,
+    arg_required_else_help = true,
+    after_long_help = EXIT_CODES
+)]
 struct Cli {
+    #[arg(short, long, action = ArgAction::Count, global = true, help = "Increase log verbosity")]
+    verbose: u8,
+    #[arg(
+        short,
+        long,
+        global = true,
+        conflicts_with = "verbose",
+        help = "Only log errors"
+    )]
+    quiet: bool,
+    #[arg(long, global = true, help = "Fail dependency graphs deeper than this")]
+    max_depth: Option<usize>,
+    #[arg(
+        long,
+        global = true,
+        help = "Fail dependency graphs with more nodes than this"
+    )]
+    max_nodes: Option<usize>,
+    #[arg(
+        long,
+        global = true,
+        help = "Cut dependency graphs short at --max-depth or --max-nodes instead of failing"
+    )]
+    partial: bool,
+    #[arg(
+        long,
+        global = true,
+        help = "Keep parsed POMs on disk between runs, in $MAVEN_DESCRIPTOR_CACHE or ~/.cache/maven-artifact/descriptors"
+    )]
+    cache_descriptors: bool,
+    #[arg(
+        long,
+        global = true,
+        value_name = "always|daily|never|interval:MINUTES",
+        help = "How long repository metadata and cached snapshot POMs are used, for every repository [default: daily]"
+    )]
+    update_policy: Option<UpdatePolicy>,
+    #[arg(
+        long,
+        global = true,
+        conflicts_with = "no_snapshots",
+        help = "Resolve snapshots from every repository"
+    )]
+    snapshots: bool,
+    #[arg(long, global = true, help = "Resolve snapshots from no repository")]
+    no_snapshots: bool,
+    #[arg(
+        long,
+        global = true,
+        value_name = "fail|warn|ignore",
+        help = "What a checksum mismatch does, for every repository [default: fail]"
+    )]
+    checksum_policy: Option<ChecksumPolicy>,
+    #[arg(
+        long,
+        global = true,
+        value_name = "FILE",
+        help = "Rules replacing or forcing dependencies in graphs, one `replace FROM TO` or `force PATTERN VERSION` per line"
+    )]
+    substitutions: Option<PathBuf>,
+    #[arg(
+        long,
+        global = true,
+        value_name = "FILE",
+        help = "Ban policy, one `deny|warn PATTERN [RANGE]` or `deny|warn license NAME` per line"
+    )]
+    policy: Option<PathBuf>,
+    #[arg(
+        long,
+        global = true,
+        value_name = "DIR",
+        help = "Write downloads here, moving them into place once verified"
+    )]
+    staging_dir: Option<PathBuf>,
+    #[arg(
+        long,
+        global = true,
+        value_name = "N",
+        help = "Times a request failing to connect, timing out or answered 408, 429, 500, 502, 503 or 504 is retried, after the wait of any Retry-After [default: 2]"
+    )]
+    retries: Option<u32>,
+    #[arg(
+        long,
+        global = true,
+        value_name = "N",
+        value_parser = parse_rate,
+        help = "Requests per second sent to each repository host, fractions allowed"
+    )]
+    rate_limit: Option<f64>,
+    #[arg(
+        long,
+        global = true,
+        value_name = "SECONDS",
+        help = "Time allowed to connect to a repository [default: 10]"
+    )]
+    connect_timeout: Option<u64>,
+    #[arg(
+        long,
+        global = true,
+        value_name = "SECONDS",
+        help = "Time a response may go without sending data [default: 60]"
+    )]
+    read_timeout: Option<u64>,
+    #[arg(
+        long,
+        global = true,
+        value_name = "SECONDS",
+        help = "Time allowed for the whole download of a file, retries included"
+    )]
+    download_deadline: Option<u64>,
+    #[arg(
+        long,
+        global = true,
+        value_name = "URL",
+        help = "Proxy for all requests, before $HTTPS_PROXY, $HTTP_PROXY and the proxies of settings.xml"
+    )]
+    proxy: Option<url::Url>,
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -53,37 +344,745 @@ enum Commands {
         select: Option<Select>,
         #[arg(long)]
         size: Option<usize>,
+        #[arg(
+            long,
+            value_parser = VersionRange::from_query,
+            conflicts_with = "json",
+            help = "Only versions matching a query like ^5.2, ~5.2.1 or \">=5, <6\""
+        )]
+        query: Option<VersionRange>,
+        #[arg(
+            long,
+            value_parser = parse_date,
+            conflicts_with = "json",
+            value_name = "YYYY-MM-DD",
+            help = "Only versions published on or after this date, from repository directory listings"
+        )]
+        since: Option<String>,
+        #[arg(
+            long,
+            default_value_t = false,
+            conflicts_with_all = ["json", "select", "size"],
+            help = "Print the newest version of each major version, preferring releases"
+        )]
+        latest_per_major: bool,
+        #[arg(
+            long,
+            value_name = "FILE|URL",
+            conflicts_with = "json",
+            help = "Flag versions listed in this feed, one `groupId:artifactId VERSION|RANGE [reason]` per line"
+        )]
+        withdrawn: Vec<String>,
+        #[arg(
+            long,
+            value_name = "URL",
+            num_args = 0..=1,
+            default_missing_value = OSV_QUERY_URL,
+            conflicts_with = "json",
+            help = "Flag versions with advisories in OSV, or in another database speaking its API"
+        )]
+        osv: Option<url::Url>,
     },
+    #[command(
+        about = "Release cadence and age of an artifact, from metadata and repository directory listings"
+    )]
+    Stats {
+        #[arg(value_parser=PartialArtifact::parse, help = "groupId:artifactId")]
+        coordinates: PartialArtifact,
+        #[arg(
+            long,
+            value_name = "VERSION",
+            help = "Also report the age of this version and the releases since"
+        )]
+        current: Option<Version>,
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+    #[command(after_long_help = VERSION_SYNTAX)]
     Resolve {
-        #[arg(value_parser=Artifact::parse, help = "groupId:artifactId[:packaging[:classifier]]:version"
+        #[arg(value_parser=ArtifactInput::parse, help = "groupId:artifactId[:packaging[:classifier]]:version or package URL, or - to read a list or CycloneDX SBOM from stdin"
         )]
-        coordinates: Artifact,
+        coordinates: ArtifactInput,
+        #[arg()]
+        path: PathBuf,
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+        #[arg(long, default_value_t = false)]
+        json: bool,
+        #[arg(
+            long,
+            value_name = "DAYS",
+            help = "Refuse releases published fewer than DAYS days ago, going by repository listings"
+        )]
+        min_age: Option<u64>,
+        #[arg(
+            long,
+            value_name = "copy|hardlink|symlink",
+            default_value = "copy",
+            help = "How files already in the local repository are placed in PATH, falling back to a hard link and then a copy"
+        )]
+        link: LinkStrategy,
+        #[arg(
+            long,
+            default_value_t = false,
+            help = "Keep files already in PATH that match the published checksum instead of downloading them again"
+        )]
+        skip_existing: bool,
+    },
+    #[command(
+        about = "Download a plugin by its prefix, looked up in the metadata of the plugin groups"
+    )]
+    ResolvePlugin {
+        #[arg(value_parser = parse_plugin, help = "prefix[:version], like surefire:RELEASE")]
+        plugin: (String, Version),
         #[arg()]
         path: PathBuf,
+        #[arg(
+            long,
+            value_name = "GROUP",
+            help = "Search this group first, then the pluginGroups of settings.xml, org.apache.maven.plugins and org.codehaus.mojo"
+        )]
+        plugin_group: Vec<String>,
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+    Tree {
+        #[arg(value_parser=Artifact::parse, help = "groupId:artifactId[:packaging[:classifier]]:version")]
+        coordinates: Artifact,
+        #[arg(
+            long,
+            value_parser = PartialArtifact::parse,
+            help = "Only show paths leading to groupId:artifactId, omitted occurrences included"
+        )]
+        include: Vec<PartialArtifact>,
+        #[arg(
+            long,
+            default_value_t = false,
+            help = "Print every resolution decision to stderr, worded like mvn -X"
+        )]
+        trace: bool,
+        #[arg(
+            long,
+            default_value_t = false,
+            conflicts_with_all = ["include", "trace"],
+            help = "Print nodes as JSON lines while they are resolved"
+        )]
+        stream: bool,
+        #[arg(long, default_value_t = false)]
+        json: bool,
+        #[arg(
+            long,
+            value_name = "DAYS",
+            help = "Refuse releases published fewer than DAYS days ago, going by repository listings"
+        )]
+        min_age: Option<u64>,
+    },
+    Exclusions {
+        #[arg(value_parser=Artifact::parse, help = "groupId:artifactId[:packaging[:classifier]]:version")]
+        coordinates: Artifact,
+        #[arg(value_parser=PartialArtifact::parse, help = "groupId:artifactId to force")]
+        artifact: PartialArtifact,
+        #[arg(help = "Version that should win mediation")]
+        version: String,
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+    Export {
+        #[arg(value_parser=Artifact::parse, help = "groupId:artifactId[:packaging[:classifier]]:version")]
+        coordinates: Artifact,
+        #[arg(long, help = "Only export dependencies in these scopes")]
+        scope: Vec<String>,
+        #[arg(
+            long,
+            default_value_t = false,
+            help = "Write a skeleton POM for the artifact instead of a <dependencies> block"
+        )]
+        pom: bool,
+    },
+    MakeBom {
+        #[arg(value_parser=Artifact::parse, help = "groupId:artifactId:version of the BOM")]
+        coordinates: Artifact,
+        #[arg(
+            long,
+            help = "File with one groupId:artifactId[:packaging[:classifier]]:version or package URL per line, or a CycloneDX SBOM; stdin if omitted"
+        )]
+        input: Option<PathBuf>,
+        #[arg(long, help = "Write the BOM here instead of stdout")]
+        output: Option<PathBuf>,
+    },
+    Duplicates {
+        #[arg(value_parser=Artifact::parse, help = "groupId:artifactId[:packaging[:classifier]]:version")]
+        coordinates: Artifact,
+        #[arg(
+            long,
+            default_value_t = false,
+            help = "Include test and provided dependencies"
+        )]
+        all_scopes: bool,
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+    #[command(about = "Check that artifacts can be resolved, exiting with 3 when any can't")]
+    Exists {
+        #[arg(value_parser=ArtifactInput::parse, help = "groupId:artifactId[:packaging[:classifier]]:version or package URL, or - to read a list or CycloneDX SBOM from stdin")]
+        coordinates: ArtifactInput,
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+    Checksum {
+        #[arg(
+            long,
+            num_args = 1..,
+            required = true,
+            value_name = "FILE",
+            help = "Files to write checksum sidecars for, - reads one path per line from stdin"
+        )]
+        generate: Vec<PathBuf>,
+    },
+    Deploy {
+        #[arg(value_parser=Artifact::parse, help = "groupId:artifactId[:packaging[:classifier]]:version")]
+        coordinates: Artifact,
+        #[arg(help = "The main artifact file")]
+        file: PathBuf,
+        #[arg(long, help = "POM to deploy alongside the main file")]
+        pom: Option<PathBuf>,
+        #[arg(
+            long = "attach",
+            value_parser = parse_attachment,
+            value_name = "CLASSIFIER[:EXTENSION]=PATH",
+            help = "Additional file, such as sources=target/lib-sources.jar"
+        )]
+        attachments: Vec<DeployFile>,
+        #[arg(
+            long,
+            help = "Repository to upload to with PUT, or a staging directory in repository layout as a path or file:// URL"
+        )]
+        to: String,
+    },
+    Install {
+        #[arg(value_parser=Artifact::parse, help = "groupId:artifactId[:packaging]:version")]
+        coordinates: Artifact,
+        #[arg(help = "The main artifact file")]
+        file: PathBuf,
+        #[arg(
+            long,
+            help = "POM to install alongside the main file, one with just the coordinates is generated otherwise"
+        )]
+        pom: Option<PathBuf>,
+        #[arg(
+            long = "attach",
+            value_parser = parse_attachment,
+            value_name = "CLASSIFIER[:EXTENSION]=PATH",
+            help = "Additional file, such as sources=target/lib-sources.jar"
+        )]
+        attachments: Vec<DeployFile>,
+        #[arg(
+            long,
+            help = "Local repository, defaults to $MAVEN_REPO_LOCAL or ~/.m2/repository"
+        )]
+        local_repository: Option<PathBuf>,
+    },
+    Bundle {
+        #[arg(value_parser=Artifact::parse, help = "groupId:artifactId[:packaging]:version")]
+        coordinates: Artifact,
+        #[arg(help = "Directory containing the built files")]
+        dir: PathBuf,
+        #[arg(
+            long,
+            help = "Output zip, defaults to <artifactId>-<version>-bundle.zip"
+        )]
+        output: Option<PathBuf>,
+    },
+    ExportDescriptors {
+        #[arg(
+            value_parser=Artifact::parse,
+            num_args = 1..,
+            required = true,
+            help = "groupId:artifactId[:packaging]:version whose dependency graph to include"
+        )]
+        coordinates: Vec<Artifact>,
+        #[arg(long, default_value = "descriptors.zip", help = "Output zip")]
+        output: PathBuf,
+    },
+    ImportDescriptors {
+        #[arg(help = "Zip written by export-descriptors")]
+        bundle: PathBuf,
+        #[arg(
+            long,
+            value_parser = Repository::parse,
+            help = "Well-known repository name or url to file the descriptors under, when it differs from where they were exported"
+        )]
+        repository: Option<Repository>,
+    },
+    ValidatePom {
+        #[arg(help = "Path to a pom.xml")]
+        path: PathBuf,
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+    Verify {
+        #[arg(value_parser=ArtifactInput::parse, help = "groupId:artifactId[:packaging[:classifier]]:version or package URL, or - to read a list or CycloneDX SBOM from stdin")]
+        coordinates: ArtifactInput,
+        #[arg(long, help = "Inspect a local file instead of downloading")]
+        file: Option<PathBuf>,
+        #[arg(
+            long,
+            default_value_t = false,
+            help = "Also check that the provenance attestations published next to the artifact cover it"
+        )]
+        attestations: bool,
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+    JarDiff {
+        #[arg(value_parser=Artifact::parse, help = "groupId:artifactId[:packaging[:classifier]]:version")]
+        coordinates: Artifact,
+        #[arg(help = "Version to compare against")]
+        version: String,
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+    #[command(
+        about = "Print the source of a class from the sources jar, kept in $MAVEN_REPO_LOCAL for offline use"
+    )]
+    Source {
+        #[arg(value_parser=Artifact::parse, help = "groupId:artifactId:version")]
+        coordinates: Artifact,
+        #[arg(help = "Fully qualified class name, like org.example.Outer$Inner")]
+        class: String,
+        #[arg(long, help = "Write the source here instead of stdout")]
+        output: Option<PathBuf>,
+    },
+    #[command(alias = "healthcheck")]
+    Ping {
+        #[arg(
+            long,
+            value_parser = PartialArtifact::parse,
+            help = "groupId:artifactId whose metadata every repository must serve"
+        )]
+        artifact: Option<PartialArtifact>,
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+    VerifyDir {
+        #[arg(help = "Directory in repository layout, or a subtree of one")]
+        path: PathBuf,
+        #[arg(long, help = "Repository root, defaults to <PATH>")]
+        root: Option<PathBuf>,
+        #[arg(
+            long,
+            value_name = "SECONDS",
+            help = "Poll until every file and its checksum is published, e.g. after a deploy"
+        )]
+        wait: Option<u64>,
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+    Modules {
+        #[arg(help = "Aggregator pom.xml or the directory containing it")]
+        path: PathBuf,
+        #[arg(
+            long,
+            default_value_t = false,
+            help = "Fetch parents that are not part of the checkout"
+        )]
+        remote_parents: bool,
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+    Properties {
+        #[arg(help = "pom.xml to report on")]
+        path: PathBuf,
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+    Matrix {
+        #[arg(value_parser=PartialArtifact::parse, help = "groupId:artifactId")]
+        coordinates: PartialArtifact,
+        #[arg(
+            long,
+            default_value_t = 5,
+            help = "How many of the newest versions to check"
+        )]
+        versions: usize,
+        #[arg(long, value_delimiter = ',', default_value = "jar,pom")]
+        extensions: Vec<String>,
+        #[arg(
+            long,
+            value_delimiter = ',',
+            help = "Classifiers to check, as jar files"
+        )]
+        classifiers: Vec<String>,
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+    ListArtifacts {
+        #[arg(help = "groupId to enumerate, needs a repository serving directory listings")]
+        group_id: String,
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+    ImportGradle {
+        #[arg(help = "Path to gradle/verification-metadata.xml")]
+        path: PathBuf,
+        #[arg(
+            long,
+            help = "Keep the downloaded files in this directory, in repository layout"
+        )]
+        dir: Option<PathBuf>,
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+    Sync {
+        #[arg(
+            num_args = 1..,
+            required = true,
+            help = "groupIds to copy, needs a repository serving directory listings"
+        )]
+        groups: Vec<String>,
+        #[arg(
+            long,
+            help = "Directory repository to bring up to date, as a path or file:// URL"
+        )]
+        to: String,
+        #[arg(
+            long,
+            default_value_t = false,
+            help = "Only report what would be copied"
+        )]
+        dry_run: bool,
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+    #[command(
+        about = "Store credentials for a repository host, unencrypted in ~/.config/maven-artifact/credentials.json (mode 0600)"
+    )]
+    Login {
+        #[arg(help = "Well-known repository name or repository url")]
+        repository: String,
+        #[arg(long, conflicts_with = "username", help = "Store a bearer token")]
+        token: Option<String>,
+        #[arg(long, help = "Username, prompted for when omitted")]
+        username: Option<String>,
+    },
+    #[command(about = "Remove the stored credentials for a repository host")]
+    Logout {
+        #[arg(help = "Well-known repository name or repository url")]
+        repository: String,
+    },
+    Settings {
+        #[arg(help = "settings.xml to read, defaults to $MAVEN_SETTINGS or ~/.m2/settings.xml")]
+        path: Option<PathBuf>,
     },
+    #[cfg(feature = "sign")]
+    Sign {
+        #[arg(
+            long,
+            help = "Key id to sign with, defaults to $MAVEN_GPG_KEY or the gpg default key"
+        )]
+        key: Option<String>,
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+    #[cfg(feature = "verify-signatures")]
+    #[command(
+        about = "List the signing keys pinned per groupId with MAVEN_PIN_KEYS=true, or approve another"
+    )]
+    KeyPins {
+        #[arg(help = "Only show the keys of this groupId")]
+        group_id: Option<String>,
+        #[arg(
+            long,
+            value_name = "FINGERPRINT",
+            requires = "group_id",
+            help = "Accept signatures by this key for the groupId from now on"
+        )]
+        approve: Option<String>,
+    },
+    #[command(external_subcommand)]
+    External(Vec<OsString>),
 }
 
 #[tokio::main(flavor = "current_thread")]
-async fn main() -> anyhow::Result<()> {
+async fn main() -> ExitCode {
     let cli = Cli::parse();
-    let repo = match std::env::var("MAVEN_REPOSITORY").ok() {
-        Some(s) if &s == "central" => Ok(Repository::maven_central()),
-        Some(s) if &s == "central-snapshots" => Ok(Repository::maven_central_snapshots()),
-        Some(r) => Url::parse(&r)
-            .context(format!("Unable to parse {}", r))
-            .map(Repository::both),
-        None => Ok(Repository::maven_central()),
-    }?;
+    init_tracing(cli.verbose, cli.quiet);
+    match run(cli).await {
+        Ok(()) => Exit::Success.into(),
+        Err(err) => {
+            eprintln!("Error: {:?}", err);
+            Exit::classify(&err).into()
+        }
+    }
+}
 
+async fn run(cli: Cli) -> anyhow::Result<()> {
+    // settings, policies and the like are the external command's business, so none of them
+    // can keep it from starting
+    if let Some(Commands::External(args)) = &cli.command {
+        return external(&cli, args);
+    }
+    let builder = Resolver::builder()
+        .user_agent(String::from(APP_USER_AGENT))
+        .progress(ProgressBars::default())
+        .graph_limits(GraphLimits {
+            max_depth: cli.max_depth,
+            max_nodes: cli.max_nodes,
+            partial: cli.partial,
+        });
+    let builder = match &cli.staging_dir {
+        Some(dir) => builder.staging_dir(dir),
+        None => builder,
+    };
+    let builder = match cli.retries {
+        Some(retries) => builder.retry(RetryPolicy {
+            max_attempts: retries.saturating_add(1),
+            ..RetryPolicy::default()
+        }),
+        None => builder,
+    };
+    let builder = match cli.connect_timeout {
+        Some(secs) => builder.connect_timeout(Duration::from_secs(secs)),
+        None => builder,
+    };
+    let builder = match cli.read_timeout {
+        Some(secs) => builder.read_timeout(Duration::from_secs(secs)),
+        None => builder,
+    };
+    let builder = match cli.download_deadline {
+        Some(secs) => builder.download_deadline(Duration::from_secs(secs)),
+        None => builder,
+    };
+    let builder = match cli.rate_limit {
+        Some(per_second) => builder.rate_limit(RateLimiter::new(per_second)),
+        None => builder,
+    };
+    let builder = match &cli.proxy {
+        Some(url) => builder.proxy(ProxyConfig::new(url.clone())),
+        None => builder,
+    };
+    let mut builder = builder
+        .from_env()
+        .context("Unable to parse MAVEN_REPOSITORY")?;
+    // a settings.xml the parser can't cope with shouldn't stop commands that don't need it
+    let settings = Settings::load_default().unwrap_or_else(|e| {
+        warn!("Ignoring settings.xml: {}", e);
+        None
+    });
+    for proxy in settings
+        .iter()
+        .flat_map(|s| &s.proxies)
+        .filter(|p| p.active)
+    {
+        let config = proxy
+            .config()
+            .with_context(|| format!("Invalid proxy host {} in settings.xml", proxy.host))?;
+        builder = builder.proxy(config);
+    }
+    // $MAVEN_REPOSITORY wins, otherwise resolve from where mvn would on this machine
+    if std::env::var_os("MAVEN_REPOSITORY").is_none()
+        && let Some(settings) = &settings
+    {
+        let repositories = settings
+            .repositories()
+            .context("Invalid repository url in settings.xml")?;
+        builder = builder.repositories(repositories);
+    }
+    let builder = builder.repository_overrides(RepositoryOverrides {
+        snapshots: match (cli.snapshots, cli.no_snapshots) {
+            (true, _) => Some(true),
+            (_, true) => Some(false),
+            _ => None,
+        },
+        checksum_policy: cli.checksum_policy,
+        update_policy: cli.update_policy,
+    });
+    let builder = match &cli.substitutions {
+        Some(path) => builder.substitutions(Substitutions::load(path)?),
+        None => builder,
+    };
+    let builder = match &cli.policy {
+        Some(path) => builder.ban_policy(BanPolicy::load(path)?),
+        None => builder,
+    };
+    let min_age = match &cli.command {
+        Some(Commands::Resolve { min_age, .. } | Commands::Tree { min_age, .. }) => *min_age,
+        _ => None,
+    };
+    let builder = match min_age {
+        Some(days) => builder.min_release_age(Duration::from_secs(days * 24 * 60 * 60)),
+        None => builder,
+    };
+    let builder = match &cli.command {
+        // only the JSON output reports whether a signature is published
+        Some(Commands::Resolve {
+            link,
+            skip_existing,
+            json,
+            ..
+        }) => builder
+            .link_strategy(*link)
+            .skip_existing(*skip_existing)
+            .probe_signatures(*json),
+        _ => builder,
+    };
+    // files downloaded again are only fetched when the server has a newer one
+    let builder = match ValidatorCache::from_env() {
+        Some(cache) => builder.validator_cache(cache),
+        None => builder,
+    };
+    let builder = match DescriptorCache::from_env().filter(|_| cli.cache_descriptors) {
+        Some(cache) => builder.descriptor_cache(match cli.update_policy {
+            Some(policy) => cache.with_snapshot_policy(policy),
+            None => cache,
+        }),
+        None => builder,
+    };
+    // keyring and keyserver come from $MAVEN_GPG_KEYRING and $MAVEN_GPG_KEYSERVER
+    #[cfg(feature = "verify-signatures")]
+    let builder = match std::env::var("MAVEN_VERIFY_SIGNATURES").as_deref() {
+        Ok("true") => {
+            let verifier = maven_artifact::signatures::Verifier::from_env();
+            builder.verify_signature(match std::env::var("MAVEN_PIN_KEYS").as_deref() {
+                Ok("true") => verifier.with_key_pins(key_pins()?),
+                _ => verifier,
+            })
+        }
+        _ => builder,
+    };
+    let resolver = builder.build()?;
+    // clones share the violations and cache statistics recorded while the command runs
+    let shared = resolver.clone();
+    let json = cli.command.as_ref().is_some_and(json_output);
+    let result = execute(cli, resolver).await;
+    let violations = shared.violations();
+    for violation in violations {
+        warn!("Ban policy: {}", violation);
+    }
+    // on stderr, stdout is the output of the command
+    let stats = shared.cache_stats();
+    if !stats.is_empty() {
+        if json {
+            eprintln!("{}", json!({ "cache": stats }));
+        } else {
+            eprintln!("Cache: {}", stats);
+        }
+    }
+    result
+}
+
+fn json_output(command: &Commands) -> bool {
+    match command {
+        Commands::Versions { json, .. }
+        | Commands::Stats { json, .. }
+        | Commands::Resolve { json, .. }
+        | Commands::ResolvePlugin { json, .. }
+        | Commands::Tree { json, .. }
+        | Commands::Exclusions { json, .. }
+        | Commands::Duplicates { json, .. }
+        | Commands::ValidatePom { json, .. }
+        | Commands::Verify { json, .. }
+        | Commands::Exists { json, .. }
+        | Commands::JarDiff { json, .. }
+        | Commands::Ping { json, .. }
+        | Commands::VerifyDir { json, .. }
+        | Commands::Modules { json, .. }
+        | Commands::Properties { json, .. }
+        | Commands::Matrix { json, .. }
+        | Commands::ListArtifacts { json, .. }
+        | Commands::ImportGradle { json, .. }
+        | Commands::Sync { json, .. } => *json,
+        _ => false,
+    }
+}
+
+async fn execute(cli: Cli, resolver: Resolver) -> anyhow::Result<()> {
     match cli.command {
         Some(Commands::Versions {
             coordinates,
             json,
             select,
             size,
+            query,
+            since,
+            latest_per_major,
+            withdrawn,
+            osv,
         }) => {
-            let client = make_client()?;
-            let resolver = Resolver::new(&client, &repo);
+            let mut withdrawals = Withdrawals::new();
+            for feed in &withdrawn {
+                withdrawals.extend(match feed.parse::<url::Url>() {
+                    Ok(url) if url.scheme().starts_with("http") => {
+                        resolver.withdrawal_feed(&url).await?
+                    }
+                    _ => Withdrawals::load(Path::new(feed))?,
+                });
+            }
+            if let Some(endpoint) = &osv {
+                withdrawals.extend(resolver.osv_withdrawals(endpoint, &coordinates).await?);
+            }
+            let flagged = !withdrawn.is_empty() || osv.is_some();
+            let artifact = coordinates.clone();
+            let print = |p: &PublishedVersion| {
+                let mut line = p.version.to_string();
+                if let Some(published) = &p.published {
+                    line = format!("{}\t{}", line, published);
+                }
+                if let Some(marker) = withdrawals.check(&artifact, &p.version) {
+                    line = format!("{}\twithdrawn: {}", line, marker.reasons.join("; "));
+                }
+                println!("{}", line);
+            };
+            if query.is_some() || since.is_some() || latest_per_major || flagged {
+                let versions = if since.is_some() {
+                    resolver.published_versions(coordinates).await?
+                } else {
+                    let meta = resolver.metadata(coordinates).await?;
+                    meta.versioning
+                        .versions
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|version| PublishedVersion {
+                            version,
+                            published: None,
+                        })
+                        .collect()
+                };
+                let release_only = matches!(select, Some(Select::Release));
+                let mut matching: Vec<&PublishedVersion> = versions
+                    .iter()
+                    .filter(|p| query.as_ref().is_none_or(|q| q.contains(&p.version)))
+                    .filter(|p| {
+                        since
+                            .as_ref()
+                            .is_none_or(|s| p.published.as_ref().is_some_and(|d| d >= s))
+                    })
+                    .filter(|p| !(release_only && p.version.is_snapshot()))
+                    .collect();
+                if matching.is_empty() {
+                    return Err(NotFound(String::from("no matching versions found")).into());
+                }
+                if latest_per_major {
+                    let lines = version::latest_per_major(matching.iter().map(|p| &p.version));
+                    for (_, latest) in lines {
+                        if let Some(p) = matching.iter().find(|p| &p.version == latest) {
+                            print(p);
+                        }
+                    }
+                    return Ok(());
+                }
+                matching.sort_by(|a, b| b.version.compare(&a.version));
+                let size = match select {
+                    Some(Select::Latest) | Some(Select::Release) => 1,
+                    _ => size.unwrap_or(10),
+                };
+                for p in matching.iter().take(size) {
+                    print(p);
+                }
+                return Ok(());
+            }
             let meta = resolver.metadata(coordinates).await?;
             if json {
                 serde_json::to_writer_pretty(std::io::stdout(), &meta)?;
@@ -91,20 +1090,20 @@ async fn main() -> anyhow::Result<()> {
                 match select {
                     Some(Select::Latest) => {
                         let Some(ver) = meta.versioning.latest else {
-                            bail!("no latest version found");
+                            return Err(NotFound(String::from("no latest version found")).into());
                         };
                         println!("{ver}");
                     }
                     Some(Select::Release) => {
                         let Some(ver) = meta.versioning.release else {
-                            bail!("no latest version found");
+                            return Err(NotFound(String::from("no release version found")).into());
                         };
                         println!("{ver}");
                     }
                     Some(Select::Versions) => {
                         let size = size.unwrap_or(10);
                         let Some(ver) = meta.versioning.versions else {
-                            bail!("no versions found");
+                            return Err(NotFound(String::from("no versions found")).into());
                         };
                         let mut reversed = ver.clone();
                         reversed.reverse();
@@ -125,59 +1124,1457 @@ async fn main() -> anyhow::Result<()> {
             }
             Ok(())
         }
-        Some(Commands::Resolve { coordinates, path }) => {
-            let client = make_client()?;
-            let resolver = Resolver::new(&client, &repo);
-            let file = resolver.download(coordinates, path.as_path()).await?;
-            println!("{}", file.as_path().display());
+        Some(Commands::Stats {
+            coordinates,
+            current,
+            json,
+        }) => {
+            let name = coordinates.to_string();
+            let versions = resolver.published_versions(coordinates).await?;
+            let stats = release_stats(&versions, current.as_ref(), SystemTime::now());
+            if json {
+                println!("{}", serde_json::to_string_pretty(&stats)?);
+                return Ok(());
+            }
+            println!("{}", name);
+            println!("releases: {} ({} with a date)", stats.releases, stats.dated);
+            for (label, release) in [("first", &stats.first), ("latest", &stats.latest)] {
+                if let Some(release) = release {
+                    println!(
+                        "{} release: {} {}",
+                        label,
+                        release.version,
+                        release.published.as_deref().unwrap_or_default()
+                    );
+                }
+            }
+            if let Some(days) = stats.latest_age_days {
+                println!("latest release age: {} days", days);
+            }
+            if let Some(per_year) = stats.releases_per_year {
+                println!("releases per year: {:.1}", per_year);
+            }
+            if let Some(gap) = stats.median_gap_days {
+                println!("median gap: {:.0} days", gap);
+            }
+            if let Some(current) = &stats.current {
+                match (&current.published, current.age_days) {
+                    (Some(published), Some(days)) => println!(
+                        "current: {} {}, {} days old",
+                        current.version, published, days
+                    ),
+                    _ => println!("current: {}, publish date unknown", current.version),
+                }
+                println!("releases since current: {}", current.releases_since);
+            }
+            if stats.dated == 0 {
+                warn!("No publish dates, the repositories serve no directory listings");
+            }
+            Ok(())
+        }
+        Some(Commands::Resolve {
+            coordinates,
+            path,
+            dry_run,
+            json,
+            min_age: _,
+            link: _,
+            skip_existing: _,
+        }) => match coordinates {
+            ArtifactInput::Artifact(artifact) => {
+                resolve(&resolver, artifact, &path, dry_run, json).await
+            }
+            ArtifactInput::Stdin => {
+                for artifact in read_artifacts(None)? {
+                    resolve(&resolver, artifact?, &path, dry_run, json).await?;
+                }
+                Ok(())
+            }
+        },
+        Some(Commands::Exists { coordinates, json }) => {
+            let artifacts: Artifacts = match coordinates {
+                ArtifactInput::Artifact(artifact) => Box::new(std::iter::once(Ok(artifact))),
+                ArtifactInput::Stdin => read_artifacts(None)?,
+            };
+            let (mut checked, mut missing) = (0, 0);
+            for artifact in artifacts {
+                let artifact = artifact?;
+                checked += 1;
+                let exists = resolver.exists(artifact.clone()).await?;
+                if json {
+                    println!(
+                        "{}",
+                        json!({ "coordinates": artifact.to_string(), "exists": exists })
+                    );
+                } else {
+                    println!("{} {}", if exists { "found" } else { "missing" }, artifact);
+                }
+                if !exists {
+                    missing += 1;
+                }
+            }
+            if missing > 0 {
+                return Err(
+                    NotFound(format!("{} of {} artifact(s) not found", missing, checked)).into(),
+                );
+            }
+            Ok(())
+        }
+        Some(Commands::Checksum { generate }) => {
+            let write = |file: &Path| -> anyhow::Result<()> {
+                let written = checksum::write_sidecars(file)
+                    .with_context(|| format!("Unable to checksum {}", file.display()))?;
+                for sidecar in written {
+                    println!("{}", sidecar.display());
+                }
+                Ok(())
+            };
+            for file in generate {
+                if file != Path::new("-") {
+                    write(&file)?;
+                    continue;
+                }
+                // each path is checksummed as it arrives
+                for line in std::io::stdin().lock().lines() {
+                    let line = line?;
+                    if !line.trim().is_empty() {
+                        write(Path::new(line.trim()))?;
+                    }
+                }
+            }
+            Ok(())
+        }
+        Some(Commands::Deploy {
+            coordinates,
+            file,
+            pom,
+            attachments,
+            to,
+        }) => {
+            let mut files = vec![DeployFile {
+                classifier: coordinates.classifier.clone(),
+                extension: coordinates
+                    .extension
+                    .clone()
+                    .unwrap_or_else(|| String::from("jar")),
+                source: file,
+            }];
+            if let Some(pom) = pom {
+                files.push(DeployFile {
+                    classifier: None,
+                    extension: String::from("pom"),
+                    source: pom,
+                });
+            }
+            files.extend(attachments);
+            let artifact = Artifact {
+                classifier: None,
+                extension: None,
+                ..coordinates
+            };
+            let timestamp = deploy::timestamp(SystemTime::now());
+            if to.starts_with("http://") || to.starts_with("https://") {
+                // credentials from the environment are for the deploy target only
+                let repository = Repository::parse(&to)?;
+                let deployer = resolver.deployer(match Auth::from_env() {
+                    Some(auth) => repository.with_auth(auth),
+                    None => repository,
+                });
+                for url in deployer.deploy(&artifact, &files, &timestamp).await? {
+                    println!("{}", url);
+                }
+                return Ok(());
+            }
+            let staging = StagingRepository::new(staging_dir(&to)?);
+            for path in staging.deploy(&artifact, &files, &timestamp)? {
+                println!("{}", path.display());
+            }
+            Ok(())
+        }
+        Some(Commands::Install {
+            coordinates,
+            file,
+            pom,
+            attachments,
+            local_repository,
+        }) => {
+            let local = match local_repository {
+                Some(root) => LocalRepository::new(root),
+                None => LocalRepository::from_env()
+                    .context("No home directory to find ~/.m2/repository in")?,
+            };
+            let mut files = vec![DeployFile {
+                classifier: None,
+                extension: coordinates
+                    .extension
+                    .clone()
+                    .unwrap_or_else(|| String::from("jar")),
+                source: file,
+            }];
+            if let Some(pom) = pom {
+                files.push(DeployFile {
+                    classifier: None,
+                    extension: String::from("pom"),
+                    source: pom,
+                });
+            }
+            files.extend(attachments);
+            let artifact = Artifact {
+                classifier: None,
+                extension: None,
+                ..coordinates
+            };
+            let written =
+                local.install(&artifact, &files, &deploy::timestamp(SystemTime::now()))?;
+            for path in written {
+                println!("{}", local.root.join(path).display());
+            }
+            Ok(())
+        }
+        Some(Commands::Bundle {
+            coordinates,
+            dir,
+            output,
+        }) => {
+            let bundle = Bundle::collect(&coordinates, &dir)?;
+            let output = output.unwrap_or_else(|| {
+                PathBuf::from(format!(
+                    "{}-{}-bundle.zip",
+                    coordinates.artifact_id, coordinates.version
+                ))
+            });
+            bundle.write_zip(&output)?;
+            println!("{}", output.display());
+            Ok(())
+        }
+        Some(Commands::ExportDescriptors {
+            coordinates,
+            output,
+        }) => {
+            // an empty cache filled by building the graphs holds exactly their closure
+            let dir = std::env::temp_dir()
+                .join(format!("maven-artifact-descriptors-{}", std::process::id()));
+            let cache = DescriptorCache::new(&dir);
+            let closure = resolver.clone().with_descriptor_cache(cache.clone());
+            let exported = async {
+                for artifact in coordinates {
+                    DependencyGraph::resolve(&closure, artifact).await?;
+                }
+                anyhow::Ok(cache.export(&output)?)
+            }
+            .await;
+            let _ = std::fs::remove_dir_all(&dir);
+            println!(
+                "{} descriptor(s) written to {}",
+                exported?,
+                output.display()
+            );
+            Ok(())
+        }
+        Some(Commands::ImportDescriptors { bundle, repository }) => {
+            let cache = DescriptorCache::from_env()
+                .context("No cache directory, set MAVEN_DESCRIPTOR_CACHE")?;
+            let imported = cache
+                .import(&bundle, repository.as_ref())
+                .with_context(|| format!("Unable to import {}", bundle.display()))?;
+            println!(
+                "{} descriptor(s) imported into {}",
+                imported,
+                cache.root.display()
+            );
             Ok(())
         }
+        Some(Commands::ValidatePom { path, json }) => {
+            let file =
+                File::open(&path).with_context(|| format!("Unable to open {}", path.display()))?;
+            let project = PomParser::new()
+                .with_namespace_check(NamespaceCheck::Validate)
+                .parse(file)?;
+            let violations = validate_for_central(&project);
+            if json {
+                serde_json::to_writer_pretty(std::io::stdout(), &violations)?;
+                println!();
+            } else {
+                for violation in &violations {
+                    println!("{}", violation);
+                }
+            }
+            if violations.is_empty() {
+                Ok(())
+            } else {
+                bail!(
+                    "{} violation(s) found in {}",
+                    violations.len(),
+                    path.display()
+                )
+            }
+        }
+        Some(Commands::Verify {
+            coordinates,
+            file,
+            attestations,
+            json,
+        }) => match coordinates {
+            ArtifactInput::Artifact(artifact) => {
+                verify(&resolver, artifact, file, attestations, json).await
+            }
+            ArtifactInput::Stdin => {
+                if file.is_some() {
+                    bail!("--file needs a single artifact, not a list");
+                }
+                for artifact in read_artifacts(None)? {
+                    verify(&resolver, artifact?, None, attestations, json).await?;
+                }
+                Ok(())
+            }
+        },
+        Some(Commands::ResolvePlugin {
+            plugin: (prefix, version),
+            path,
+            plugin_group,
+            dry_run,
+            json,
+        }) => {
+            // the same search order as mvn, settings.xml was reported already if unreadable
+            let settings = Settings::load_default().ok().flatten().unwrap_or_default();
+            let mut groups: Vec<GroupId> = vec![];
+            for group in plugin_group
+                .iter()
+                .chain(&settings.plugin_groups)
+                .map(String::as_str)
+                .chain(DEFAULT_PLUGIN_GROUPS)
+            {
+                let group = GroupId::from(group);
+                if !groups.contains(&group) {
+                    groups.push(group);
+                }
+            }
+            let plugin = resolver.plugin_by_prefix(&prefix, &groups).await?;
+            resolve(
+                &resolver,
+                plugin.into_artifact(version),
+                &path,
+                dry_run,
+                json,
+            )
+            .await
+        }
+        Some(Commands::Source {
+            coordinates,
+            class,
+            output,
+        }) => {
+            let dir = temp_dir("source")?;
+            let result = resolver.class_source(coordinates, &class, &dir).await;
+            std::fs::remove_dir_all(&dir)?;
+            let Some(source) = result? else {
+                return Err(NotFound(format!("no source for {} in the sources jar", class)).into());
+            };
+            match output {
+                Some(output) => std::fs::write(&output, source.content)
+                    .with_context(|| format!("Unable to write {}", output.display()))?,
+                None => print!("{}", source.content),
+            }
+            Ok(())
+        }
+        Some(Commands::JarDiff {
+            coordinates,
+            version,
+            json,
+        }) => {
+            let dir = temp_dir("jar-diff")?;
+            let result = jar_diff(&resolver, coordinates, Version::from(version), &dir).await;
+            std::fs::remove_dir_all(&dir)?;
+            let (old, new, diff) = result?;
+            if json {
+                let value = json!({
+                    "old": Artifact::from(old).to_string(),
+                    "new": Artifact::from(new).to_string(),
+                    "added": diff.added,
+                    "removed": diff.removed,
+                    "changed": diff.changed,
+                });
+                serde_json::to_writer_pretty(std::io::stdout(), &value)?;
+                println!();
+            } else {
+                for entry in &diff.added {
+                    println!("+ {} ({} bytes)", entry.name, entry.size);
+                }
+                for entry in &diff.removed {
+                    println!("- {} ({} bytes)", entry.name, entry.size);
+                }
+                for entry in &diff.changed {
+                    println!(
+                        "~ {} ({} -> {} bytes)",
+                        entry.name, entry.old_size, entry.new_size
+                    );
+                }
+                println!(
+                    "{} -> {}: {} added, {} removed, {} changed",
+                    old.resolved_version,
+                    new.resolved_version,
+                    diff.added.len(),
+                    diff.removed.len(),
+                    diff.changed.len()
+                );
+            }
+            Ok(())
+        }
+        Some(Commands::Ping { artifact, json }) => {
+            let mut failure: Option<anyhow::Error> = None;
+            let mut report = vec![];
+            for repository in resolver.repositories() {
+                let probes = match resolver.health(repository, artifact.as_ref()).await {
+                    Ok(probes) => probes,
+                    Err(e) => {
+                        if json {
+                            report.push(json!({ "url": repository.url, "error": e.to_string() }));
+                        } else {
+                            println!("{}  unreachable: {}", repository.url, e);
+                        }
+                        failure.get_or_insert(e.into());
+                        continue;
+                    }
+                };
+                for (i, probe) in probes.iter().enumerate() {
+                    let required = i > 0;
+                    let healthy = probe.is_authorized()
+                        && probe.status < 500
+                        && (!required || probe.is_success());
+                    if json {
+                        report.push(json!({
+                            "url": probe.url,
+                            "status": probe.status,
+                            "latencyMs": probe.latency.as_millis(),
+                            "healthy": healthy,
+                        }));
+                    } else {
+                        println!(
+                            "{}  {}  {}ms{}",
+                            probe.url,
+                            probe.status,
+                            probe.latency.as_millis(),
+                            if healthy { "" } else { "  FAILED" }
+                        );
+                    }
+                    if !healthy {
+                        failure.get_or_insert(
+                            ResolveError::GenericHttpError {
+                                url: probe.url.clone(),
+                                status: probe.status,
+                            }
+                            .into(),
+                        );
+                    }
+                }
+            }
+            if json {
+                serde_json::to_writer_pretty(std::io::stdout(), &report)?;
+                println!();
+            }
+            match failure {
+                Some(e) => Err(e),
+                None => Ok(()),
+            }
+        }
+        Some(Commands::Tree {
+            coordinates,
+            include,
+            trace,
+            stream,
+            json,
+            min_age: _,
+        }) => {
+            if stream {
+                let mut nodes = pin!(DependencyGraph::stream(&resolver, coordinates));
+                while let Some(node) = nodes.next().await {
+                    println!("{}", serde_json::to_string(&node?)?);
+                }
+                return Ok(());
+            }
+            let (graph, events) = DependencyGraph::resolve_traced(&resolver, coordinates).await?;
+            if trace {
+                for event in &events {
+                    if json {
+                        eprintln!("{}", serde_json::to_string(event)?);
+                    } else {
+                        eprintln!("[DEBUG] {}", event);
+                    }
+                }
+            }
+            let paths: Vec<Vec<&DependencyNode>> = include
+                .iter()
+                .flat_map(|artifact| graph.who_depends_on(artifact))
+                .collect();
+            if json {
+                let value = if include.is_empty() {
+                    json!(graph)
+                } else {
+                    json!(paths)
+                };
+                serde_json::to_writer_pretty(std::io::stdout(), &value)?;
+                println!();
+                return Ok(());
+            }
+            let visible: Vec<bool> = if include.is_empty() {
+                graph.nodes.iter().map(|n| !n.is_omitted()).collect()
+            } else {
+                let mut visible = vec![false; graph.nodes.len()];
+                for artifact in &include {
+                    for (i, node) in graph.nodes.iter().enumerate() {
+                        if node.is(artifact) {
+                            graph.path_to(i).into_iter().for_each(|n| visible[n] = true);
+                        }
+                    }
+                }
+                visible
+            };
+            if !include.is_empty() && paths.is_empty() {
+                bail!(NotFound(format!(
+                    "{} is not part of the dependency tree",
+                    include
+                        .iter()
+                        .map(|a| a.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )));
+            }
+            if let Some(limit) = graph.truncated {
+                warn!("Tree cut short at the {}", limit);
+            }
+            println!("{}", graph.root().artifact);
+            print_tree(&graph, 0, "", &visible);
+            Ok(())
+        }
+        Some(Commands::Exclusions {
+            coordinates,
+            artifact,
+            version,
+            json,
+        }) => {
+            let graph = DependencyGraph::resolve(&resolver, coordinates).await?;
+            let suggestion = graph.suggest_exclusions(&artifact, &Version::from(version));
+            if json {
+                serde_json::to_writer_pretty(std::io::stdout(), &suggestion)?;
+                println!();
+            } else if suggestion.is_empty() {
+                warn!("{} already resolves to the requested version", artifact);
+            } else {
+                println!("{}", suggestion.to_xml()?);
+            }
+            Ok(())
+        }
+        Some(Commands::Export {
+            coordinates,
+            scope,
+            pom,
+        }) => {
+            let graph = DependencyGraph::resolve(&resolver, coordinates.clone()).await?;
+            let dependencies: Vec<Dependency> = graph
+                .dependencies()
+                .into_iter()
+                .filter(|d| {
+                    scope.is_empty()
+                        || scope
+                            .iter()
+                            .any(|s| s == d.scope.as_deref().unwrap_or("compile"))
+                })
+                .collect();
+            if pom {
+                let mut project = Project::skeleton(&coordinates);
+                project.dependencies = dependencies;
+                println!("{}", project.to_xml()?);
+            } else {
+                println!("{}", dependencies_xml(&dependencies)?);
+            }
+            Ok(())
+        }
+        Some(Commands::MakeBom {
+            coordinates,
+            input,
+            output,
+        }) => {
+            let mut dependencies = vec![];
+            let mut missing = vec![];
+            for artifact in read_artifacts(input.as_deref())? {
+                let artifact = artifact?;
+                // ranges and LATEST/RELEASE are pinned to what they resolve to today
+                let resolved = match resolver.resolve(artifact.clone()).await {
+                    Ok(resolved) => resolved,
+                    Err(e) if e.is_not_found() => {
+                        missing.push(artifact.to_string());
+                        continue;
+                    }
+                    Err(e) => return Err(e.into()),
+                };
+                match resolver.locate(&resolved).await {
+                    Ok(_) => dependencies.push(Dependency::from_artifact(&resolved.artifact)),
+                    Err(e) if e.is_not_found() => missing.push(artifact.to_string()),
+                    Err(e) => return Err(e.into()),
+                }
+            }
+            if !missing.is_empty() {
+                bail!(NotFound(format!(
+                    "Unable to resolve {}",
+                    missing.join(", ")
+                )));
+            }
+            let xml = Project::bom(&coordinates, dependencies).to_xml()?;
+            match output {
+                Some(path) => std::fs::write(&path, xml + "\n")
+                    .with_context(|| format!("Unable to write {}", path.display()))?,
+                None => println!("{}", xml),
+            }
+            Ok(())
+        }
+        Some(Commands::Duplicates {
+            coordinates,
+            all_scopes,
+            json,
+        }) => {
+            let graph = DependencyGraph::resolve(&resolver, coordinates).await?;
+            let dir = temp_dir("duplicates")?;
+            let jars = async {
+                let mut jars = vec![];
+                for node in graph.resolved() {
+                    let scope = node.scope.as_deref().unwrap_or("compile");
+                    if node.artifact.extension.as_deref().unwrap_or("jar") != "jar"
+                        || !(all_scopes || matches!(scope, "compile" | "runtime"))
+                    {
+                        continue;
+                    }
+                    let resolved = resolver.download(node.artifact.clone(), &dir).await?;
+                    let path = resolved.file.clone().unwrap_or_default();
+                    let entries = jar::entries(&path)
+                        .with_context(|| format!("Unable to read {}", path.display()))?;
+                    jars.push((node.artifact.clone(), entries));
+                }
+                anyhow::Ok(jars)
+            }
+            .await;
+            let _ = std::fs::remove_dir_all(&dir);
+            let report = analyze_overlap(&jars?);
+            if json {
+                serde_json::to_writer_pretty(std::io::stdout(), &report)?;
+                println!();
+            } else {
+                for class in &report.duplicate_classes {
+                    let kind = if class.identical {
+                        "identical"
+                    } else {
+                        "differs"
+                    };
+                    println!("{} ({}): {}", class.name, kind, class.artifacts.join(", "));
+                }
+                for split in &report.split_packages {
+                    println!(
+                        "split package {}: {}",
+                        split.package,
+                        split.artifacts.join(", ")
+                    );
+                }
+            }
+            if report.duplicate_classes.is_empty() {
+                Ok(())
+            } else {
+                bail!(
+                    "{} class(es) provided by more than one artifact",
+                    report.duplicate_classes.len()
+                )
+            }
+        }
+        Some(Commands::Properties { path, json }) => {
+            let project = resolver.effective_project_at(&path).await?;
+            let mut report = vec![];
+            for property in project.version_properties() {
+                // the newest value every dependency using the property is published with
+                let mut candidates: Option<Vec<String>> = None;
+                for dependency in &property.dependencies {
+                    let artifact = PartialArtifact::new(
+                        dependency.group_id.clone(),
+                        dependency.artifact_id.clone(),
+                    );
+                    let versions = match resolver.metadata(artifact).await {
+                        Ok(meta) => meta.versioning.versions.unwrap_or_default(),
+                        Err(e) if e.is_not_found() => vec![],
+                        Err(e) => return Err(e.into()),
+                    };
+                    let values: Vec<String> = versions
+                        .iter()
+                        .filter(|v| !v.is_snapshot())
+                        .filter_map(|v| property.value_for(dependency, v))
+                        .collect();
+                    candidates = Some(match candidates {
+                        None => values,
+                        Some(previous) => previous
+                            .into_iter()
+                            .filter(|v| values.contains(v))
+                            .collect(),
+                    });
+                }
+                let latest = candidates
+                    .unwrap_or_default()
+                    .into_iter()
+                    .max_by(|a, b| Version::from(a.as_str()).compare(&Version::from(b.as_str())));
+                report.push((property, latest));
+            }
+            if json {
+                let value: Vec<_> = report
+                    .iter()
+                    .map(|(property, latest)| {
+                        json!({
+                            "name": property.name,
+                            "value": property.value,
+                            "latest": latest,
+                            "dependencies": property.dependencies,
+                        })
+                    })
+                    .collect();
+                serde_json::to_writer_pretty(std::io::stdout(), &value)?;
+                println!();
+                return Ok(());
+            }
+            for (property, latest) in &report {
+                let current = property.value.as_deref().unwrap_or("<undefined>");
+                match latest {
+                    Some(latest) if Some(latest) != property.value.as_ref() => {
+                        println!("{}\t{} -> {}", property.name, current, latest)
+                    }
+                    Some(_) => println!("{}\t{} (latest)", property.name, current),
+                    None => println!("{}\t{} (no common version found)", property.name, current),
+                }
+                for dependency in &property.dependencies {
+                    println!(
+                        "  {}:{}:{}",
+                        dependency.group_id,
+                        dependency.artifact_id,
+                        dependency.version.as_deref().unwrap_or_default()
+                    );
+                }
+            }
+            Ok(())
+        }
+        Some(Commands::Matrix {
+            coordinates,
+            versions,
+            extensions,
+            classifiers,
+            json,
+        }) => {
+            let meta = resolver.metadata(coordinates.clone()).await?;
+            let mut published = meta.versioning.versions.unwrap_or_default();
+            published.sort_by(|a, b| b.compare(a));
+            published.truncate(versions);
+            let columns: Vec<(String, Option<String>, String)> = extensions
+                .iter()
+                .map(|e| (e.clone(), None, e.clone()))
+                .chain(
+                    classifiers
+                        .iter()
+                        .map(|c| (c.clone(), Some(c.clone()), String::from("jar"))),
+                )
+                .collect();
+            let mut rows = vec![];
+            for version in &published {
+                let mut found = vec![];
+                for (_, classifier, extension) in &columns {
+                    let mut artifact = Artifact::new(
+                        coordinates.group_id.clone(),
+                        coordinates.artifact_id.clone(),
+                        version.clone(),
+                    )
+                    .with_extension(extension.clone());
+                    artifact.classifier = classifier.as_deref().map(Classifier::from);
+                    found.push(resolver.exists(artifact).await?);
+                }
+                rows.push((version, found));
+            }
+            if json {
+                let value: Vec<_> = rows
+                    .iter()
+                    .map(|(version, found)| {
+                        let files: serde_json::Map<String, serde_json::Value> = columns
+                            .iter()
+                            .zip(found)
+                            .map(|((label, _, _), exists)| (label.clone(), json!(exists)))
+                            .collect();
+                        json!({ "version": version, "files": files })
+                    })
+                    .collect();
+                serde_json::to_writer_pretty(std::io::stdout(), &value)?;
+                println!();
+                return Ok(());
+            }
+            let width = published.iter().map(|v| v.len()).max().unwrap_or(0).max(7);
+            let mut header = format!("{:width$}", "version");
+            for (label, _, _) in &columns {
+                header += &format!("  {}", label);
+            }
+            println!("{}", header);
+            for (version, found) in &rows {
+                let mut line = format!("{:width$}", version.as_ref());
+                for ((label, _, _), exists) in columns.iter().zip(found) {
+                    let cell = if *exists { "yes" } else { "-" };
+                    line += &format!("  {:<w$}", cell, w = label.len());
+                }
+                println!("{}", line.trim_end());
+            }
+            Ok(())
+        }
+        Some(Commands::ListArtifacts { group_id, json }) => {
+            let artifacts = resolver.list_artifacts(&GroupId::from(group_id)).await?;
+            if json {
+                serde_json::to_writer_pretty(std::io::stdout(), &artifacts)?;
+                println!();
+            } else {
+                for artifact_id in &artifacts {
+                    println!("{}", artifact_id);
+                }
+            }
+            Ok(())
+        }
+        Some(Commands::ImportGradle { path, dir, json }) => {
+            let file =
+                File::open(&path).with_context(|| format!("Unable to open {}", path.display()))?;
+            let metadata = gradle::parse_verification_metadata(file)?;
+            let (root, keep) = match dir {
+                Some(dir) => (dir, true),
+                None => (temp_dir("import-gradle")?, false),
+            };
+            let mut drifted = 0;
+            let mut missing = vec![];
+            let mut report = vec![];
+            for verified in &metadata.artifacts {
+                let target = root.join(verified.artifact.path());
+                std::fs::create_dir_all(&target)
+                    .with_context(|| format!("Unable to create {}", target.display()))?;
+                let mismatches = match resolver.download(verified.artifact.clone(), &target).await {
+                    Ok(resolved) => Some(verified.mismatches(&resolved.file.unwrap_or_default())?),
+                    Err(e) if e.is_not_found() => {
+                        missing.push(verified.artifact.to_string());
+                        None
+                    }
+                    Err(e) => return Err(e.into()),
+                };
+                if mismatches.as_ref().is_some_and(|m| !m.is_empty()) {
+                    drifted += 1;
+                }
+                if json {
+                    report.push(json!({
+                        "coordinates": verified.artifact.to_string(),
+                        "file": verified.file,
+                        "mismatches": mismatches,
+                    }));
+                    continue;
+                }
+                match mismatches {
+                    Some(mismatches) if mismatches.is_empty() => {
+                        println!("ok       {}", verified.artifact)
+                    }
+                    Some(mismatches) => {
+                        for m in mismatches {
+                            println!(
+                                "mismatch {} ({} expected {}, downloaded {})",
+                                verified.artifact,
+                                m.algorithm.extension(),
+                                m.expected,
+                                m.actual
+                            );
+                        }
+                    }
+                    None => println!("missing  {} (not in any repository)", verified.artifact),
+                }
+            }
+            if !keep {
+                std::fs::remove_dir_all(&root)?;
+            }
+            if json {
+                serde_json::to_writer_pretty(std::io::stdout(), &report)?;
+                println!();
+            } else {
+                for file in &metadata.skipped {
+                    println!("skipped  {} (not named after its coordinates)", file);
+                }
+            }
+            if drifted > 0 {
+                Err(Drift(drifted).into())
+            } else if !missing.is_empty() {
+                Err(NotFound(format!("Not found: {}", missing.join(", "))).into())
+            } else {
+                Ok(())
+            }
+        }
+        Some(Commands::Sync {
+            groups,
+            to,
+            dry_run,
+            json,
+        }) => {
+            let target = StagingRepository::new(staging_dir(&to)?);
+            let groups: Vec<GroupId> = groups.into_iter().map(GroupId::from).collect();
+            let plan = sync::plan(&resolver, &target, &groups).await?;
+            if !dry_run {
+                sync::apply(
+                    &resolver,
+                    &target,
+                    &plan,
+                    &deploy::timestamp(SystemTime::now()),
+                )
+                .await?;
+            }
+            if json {
+                serde_json::to_writer_pretty(std::io::stdout(), &plan)?;
+                println!();
+                return Ok(());
+            }
+            for entry in &plan.transfers {
+                let reason = match entry.reason {
+                    SyncReason::Missing => "missing",
+                    SyncReason::Differs => "differs",
+                };
+                println!("{:<8} {}", reason, entry.path.display());
+            }
+            for missing in &plan.versions {
+                let versions: Vec<&str> = missing.versions.iter().map(|v| v.as_ref()).collect();
+                println!("metadata {} + {}", missing.artifact, versions.join(", "));
+            }
+            let verb = if dry_run { "to copy" } else { "copied" };
+            println!(
+                "{} file(s) {}, {} unchanged",
+                plan.transfers.len(),
+                verb,
+                plan.unchanged
+            );
+            Ok(())
+        }
+        Some(Commands::Modules {
+            path,
+            remote_parents,
+            json,
+        }) => {
+            let workspace = if remote_parents {
+                Workspace::load_with(&path, &resolver).await?
+            } else {
+                Workspace::load(&path)?
+            };
+            let dependencies = workspace.dependencies();
+            let order = workspace.build_order()?;
+            if json {
+                serde_json::to_writer_pretty(
+                    std::io::stdout(),
+                    &json!({
+                        "modules": workspace.modules,
+                        "dependencies": dependencies,
+                        "buildOrder": order.iter().map(|m| m.coordinates().to_string()).collect::<Vec<_>>(),
+                    }),
+                )?;
+                println!();
+                return Ok(());
+            }
+            for module in order {
+                let index = workspace
+                    .find(&module.group_id, &module.artifact_id)
+                    .expect("module is part of the workspace");
+                println!("{}:{}", module.coordinates(), module.version);
+                for dependency in dependencies.iter().filter(|d| d.from == index) {
+                    let target = &workspace.modules[dependency.to];
+                    let relation = match (&dependency.scope, dependency.parent) {
+                        (_, true) => "parent",
+                        (Some(scope), false) => scope.as_str(),
+                        (None, false) => "compile",
+                    };
+                    println!("  -> {} ({})", target.coordinates(), relation);
+                }
+            }
+            Ok(())
+        }
+        Some(Commands::VerifyDir {
+            path,
+            root,
+            wait,
+            json,
+        }) => {
+            let root = root.unwrap_or_else(|| path.clone());
+            let files = layout::walk(&root, &path)
+                .with_context(|| format!("Unable to read {}", path.display()))?;
+            let deadline = wait.map(|secs| Instant::now() + Duration::from_secs(secs));
+            let mut drifted = 0;
+            let mut pending = 0;
+            let mut report = vec![];
+            for (file, artifact) in files {
+                let check = match (&artifact, deadline) {
+                    (Some(artifact), Some(deadline)) => {
+                        let options = PollOptions {
+                            timeout: deadline.saturating_duration_since(Instant::now()),
+                            ..PollOptions::default()
+                        };
+                        Some(resolver.await_published(artifact, &file, &options).await?)
+                    }
+                    (Some(artifact), None) => Some(resolver.check_file(artifact, &file).await?),
+                    (None, _) => None,
+                };
+                match check {
+                    Some(FileCheck::Mismatch { .. }) => drifted += 1,
+                    Some(FileCheck::NotFound | FileCheck::NoChecksum) => pending += 1,
+                    _ => (),
+                }
+                if json {
+                    report.push(json!({
+                        "file": file,
+                        "coordinates": artifact.map(|a| Artifact::from(a).to_string()),
+                        "check": check,
+                    }));
+                    continue;
+                }
+                match check {
+                    Some(FileCheck::Match { algorithm, .. }) => {
+                        println!("ok        {} ({})", file.display(), algorithm.extension())
+                    }
+                    Some(FileCheck::Mismatch {
+                        algorithm,
+                        expected,
+                        actual,
+                        ..
+                    }) => println!(
+                        "drift     {} ({} expected {}, local {})",
+                        file.display(),
+                        algorithm.extension(),
+                        expected,
+                        actual
+                    ),
+                    Some(FileCheck::NoChecksum) => {
+                        println!("unchecked {} (no remote checksum)", file.display())
+                    }
+                    Some(FileCheck::NotFound) => {
+                        println!("missing   {} (not in any repository)", file.display())
+                    }
+                    None => println!("skipped   {} (not in repository layout)", file.display()),
+                }
+            }
+            if json {
+                serde_json::to_writer_pretty(std::io::stdout(), &report)?;
+                println!();
+            }
+            if drifted > 0 {
+                Err(Drift(drifted).into())
+            } else if let Some(secs) = wait
+                && pending > 0
+            {
+                Err(NotFound(format!(
+                    "{} file(s) were not published within {}s",
+                    pending, secs
+                ))
+                .into())
+            } else {
+                Ok(())
+            }
+        }
+        Some(Commands::Login {
+            repository,
+            token,
+            username,
+        }) => {
+            let host = repository_host(&repository)?;
+            let auth = match token {
+                Some(token) => Auth::Bearer(token),
+                None => {
+                    let username = match username {
+                        Some(username) => username,
+                        None => prompt("Username: ")?,
+                    };
+                    let password = rpassword::prompt_password("Password: ")?;
+                    Auth::Basic { username, password }
+                }
+            };
+            let mut store = credential_store()?;
+            store.set(host.clone(), auth);
+            store.save().with_context(|| {
+                format!("Unable to write credentials to {}", store.path().display())
+            })?;
+            println!("Stored credentials for {}", host);
+            Ok(())
+        }
+        Some(Commands::Logout { repository }) => {
+            let host = repository_host(&repository)?;
+            let mut store = credential_store()?;
+            if !store.remove(&host) {
+                return Err(NotFound(format!("no stored credentials for {}", host)).into());
+            }
+            store.save().with_context(|| {
+                format!("Unable to write credentials to {}", store.path().display())
+            })?;
+            println!("Removed credentials for {}", host);
+            Ok(())
+        }
+        Some(Commands::Settings { path }) => {
+            let path = path
+                .or_else(Settings::default_path)
+                .context("No home directory, pass the settings.xml to read")?;
+            let settings = Settings::load(&path)?;
+            // passwords are only reported as set or not
+            let mut json = serde_json::to_value(&settings)?;
+            json["path"] = serde_json::json!(path);
+            json["active"] = settings.active().iter().map(|p| p.id.clone()).collect();
+            serde_json::to_writer_pretty(std::io::stdout(), &json)?;
+            println!();
+            Ok(())
+        }
+        #[cfg(feature = "sign")]
+        Some(Commands::Sign { key, files }) => {
+            let signer = maven_artifact::signing::Signer::from_env();
+            let signer = match key {
+                Some(k) => signer.with_key(k),
+                None => signer,
+            };
+            for file in files {
+                let signature = signer.sign(&file)?;
+                println!("{}", signature.display());
+            }
+            Ok(())
+        }
+        #[cfg(feature = "verify-signatures")]
+        Some(Commands::KeyPins { group_id, approve }) => {
+            let pins = key_pins()?;
+            if let (Some(group_id), Some(fingerprint)) = (&group_id, approve)
+                && pins.approve(group_id, &fingerprint)
+            {
+                pins.save()
+                    .with_context(|| format!("Unable to write {}", pins.path().display()))?;
+            }
+            for (group, keys) in pins.groups() {
+                if group_id.as_ref().is_none_or(|g| *g == group) {
+                    for key in keys {
+                        println!("{}\t{}", group, key);
+                    }
+                }
+            }
+            Ok(())
+        }
+        Some(Commands::External(_)) => {
+            unreachable!("external commands run before the resolver is built")
+        }
         None => Ok(()),
     }
 }
 
-fn make_client() -> anyhow::Result<Client> {
-    let client = ClientBuilder::new().user_agent(APP_USER_AGENT);
-    let auth = Authorization::from_env();
-    let c = match auth {
-        None => client,
-        Some(Authorization::Basic { username, password }) => client.default_headers({
-            let mut m = HeaderMap::new();
-            let basic = BASE64_STANDARD.encode(format!("{}:{}", username, password));
-            let value = HeaderValue::from_str(&format!("Basic {}", basic))?;
-            m.insert(AUTHORIZATION, value);
-            m
-        }),
-        Some(Authorization::Token { value }) => client.default_headers({
-            let mut m = HeaderMap::new();
-            let value = HeaderValue::from_str(&format!("Bearer {}", value))?;
-            m.insert(AUTHORIZATION, value);
-            m
-        }),
+// Runs maven-artifact-<name> with the repositories to use in $MAVEN_REPOSITORIES, separated
+// by spaces, and the global options given in $MAVEN_ARTIFACT_OPTIONS, one per line, to pass
+// on when calling back into $MAVEN_ARTIFACT
+fn external(cli: &Cli, args: &[OsString]) -> anyhow::Result<()> {
+    let Some((name, rest)) = args.split_first() else {
+        return Ok(());
+    };
+    let name = name.to_string_lossy();
+    let program = format!("maven-artifact-{}", name);
+    let repositories = match std::env::var("MAVEN_REPOSITORY") {
+        Ok(url) => vec![url],
+        Err(_) => Settings::load_default()
+            .ok()
+            .flatten()
+            .and_then(|settings| settings.repositories().ok())
+            .filter(|repositories| !repositories.is_empty())
+            .unwrap_or_else(|| vec![Repository::maven_central()])
+            .iter()
+            .map(|r| r.url.to_string())
+            .collect(),
+    };
+    // everything between the program and the command name
+    let argv: Vec<OsString> = std::env::args_os().collect();
+    let options = &argv[1..argv.len().saturating_sub(args.len()).max(1)];
+    let options: Vec<_> = options.iter().map(|o| o.to_string_lossy()).collect();
+    let mut command = std::process::Command::new(&program);
+    command
+        .args(rest)
+        .env("MAVEN_REPOSITORY", &repositories[0])
+        .env("MAVEN_REPOSITORIES", repositories.join(" "))
+        .env("MAVEN_ARTIFACT_OPTIONS", options.join("\n"))
+        .env("MAVEN_ARTIFACT_LOG", log_level(cli.verbose, cli.quiet));
+    if let Ok(exe) = std::env::current_exe() {
+        command.env("MAVEN_ARTIFACT", exe);
+    }
+    match command.status() {
+        Ok(status) if status.success() => Ok(()),
+        Ok(status) => std::process::exit(status.code().unwrap_or(Exit::Failure as i32)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            Err(UnknownCommand(name.into_owned()).into())
+        }
+        Err(e) => Err(e).context(format!("Failed to run {}", program)),
+    }
+}
+
+async fn resolve(
+    resolver: &Resolver,
+    artifact: Artifact,
+    path: &Path,
+    dry_run: bool,
+    json: bool,
+) -> anyhow::Result<()> {
+    let coordinates = artifact.to_string();
+    if dry_run {
+        let resolved = resolver.resolve(artifact).await?;
+        let location = resolver.locate(&resolved).await?;
+        let url = location.url;
+        let size = location.content_length;
+        let file = path.join(resolved.artifact.file_name());
+        if json {
+            let value = json!({
+                "coordinates": coordinates,
+                "url": url.as_str(),
+                "version": resolved.resolved_version,
+                "size": size,
+                "file": file,
+            });
+            println!("{}", value);
+        } else {
+            println!("url: {}", url);
+            println!("version: {}", resolved.resolved_version);
+            match size {
+                Some(s) => println!("size: {s}"),
+                None => println!("size: unknown"),
+            }
+            println!("file: {}", file.display());
+        }
+    } else {
+        // Ctrl-C stops the download without leaving a partial file behind
+        let interrupted = async {
+            if tokio::signal::ctrl_c().await.is_err() {
+                std::future::pending::<()>().await;
+            }
+        };
+        let resolved = resolver.download_until(artifact, path, interrupted).await?;
+        let file = resolved.file.unwrap_or_default();
+        if json {
+            let checksums: serde_json::Map<String, serde_json::Value> = resolved
+                .checksums
+                .iter()
+                .map(|c| (c.algorithm().extension().to_string(), json!(c)))
+                .collect();
+            let value = json!({
+                "coordinates": coordinates,
+                "file": file,
+                "url": resolved.url,
+                "repository": resolved.repository,
+                "version": resolved.resolved_version,
+                "checksums": checksums,
+                "verifiedChecksum": resolved.verified_checksum.map(|a| a.extension()),
+                "signature": resolved.signature,
+                "link": resolved.link,
+                "skipped": resolved.skipped,
+            });
+            println!("{}", value);
+        } else {
+            println!("{}", file.as_path().display());
+        }
+    }
+    Ok(())
+}
+
+async fn verify(
+    resolver: &Resolver,
+    coordinates: Artifact,
+    file: Option<PathBuf>,
+    attestations: bool,
+    json: bool,
+) -> anyhow::Result<()> {
+    let requested = coordinates.to_string();
+    let (resolved, check) = match file {
+        Some(file) => {
+            let check = jar::check_gav(&file, &coordinates)
+                .with_context(|| format!("Unable to inspect {}", file.display()));
+            let mut resolved = ResolvedArtifact::new(coordinates.clone(), coordinates.version);
+            if attestations {
+                resolved = resolver.resolve(resolved.artifact).await?;
+                resolved.checksums = checksum::digest_file(&file)
+                    .with_context(|| format!("Unable to checksum {}", file.display()))?;
+            }
+            (resolved, check)
+        }
+        None => {
+            let dir = temp_dir("verify")?;
+            let downloaded = resolver.download(coordinates, &dir).await;
+            let result = downloaded.map(|resolved| {
+                let file = resolved.file.clone().unwrap_or_default();
+                let check = jar::check_gav(&file, &resolved.artifact)
+                    .with_context(|| format!("Unable to inspect {}", file.display()));
+                (resolved, check)
+            });
+            std::fs::remove_dir_all(&dir)?;
+            result?
+        }
+    };
+    let check = check?;
+    let artifact = &resolved.artifact;
+    // each published attestation, and whether one of its subjects is the file
+    let attested = match attestations {
+        true => Some(resolver.attestations(&resolved).await?),
+        false => None,
     };
+    let covered: Option<Vec<_>> = attested.as_ref().map(|found| {
+        found
+            .iter()
+            .map(|a| (a, a.covers(&resolved.checksums)))
+            .collect()
+    });
+    if json {
+        let mut value =
+            json!({ "coordinates": requested, "artifact": artifact.to_string(), "check": check });
+        if let Some(covered) = &covered {
+            value["attestations"] = covered
+                .iter()
+                .map(|(a, covers)| json!({ "kind": a.kind, "url": a.url, "covers": covers }))
+                .collect();
+        }
+        println!("{}", value);
+    } else if let Some(covered) = &covered {
+        if covered.is_empty() {
+            println!("unattested {} (no attestations published)", artifact);
+        }
+        for (attestation, covers) in covered {
+            let status = match covers {
+                Some(true) => "covers",
+                Some(false) => "does not cover",
+                None => "has no comparable digest for",
+            };
+            println!("attestation {} {} {}", attestation.url, status, artifact);
+        }
+    }
+    if let Some((attestation, _)) = covered
+        .iter()
+        .flatten()
+        .find(|(_, covers)| *covers == Some(false))
+    {
+        bail!("{} is not a subject of {}", artifact, attestation.url);
+    }
+    match check {
+        GavCheck::Match => {
+            if !json {
+                println!("ok {}", artifact);
+            }
+            Ok(())
+        }
+        GavCheck::Missing => {
+            if !json {
+                println!("unverified {} (no pom.properties)", artifact);
+            }
+            Ok(())
+        }
+        GavCheck::Mismatch { found } => {
+            let found: Vec<String> = found
+                .iter()
+                .map(|p| format!("{}:{}:{}", p.group_id, p.artifact_id, p.version))
+                .collect();
+            bail!("{} contains {}", artifact, found.join(", "))
+        }
+    }
+}
 
-    let result = c.build()?;
-    Ok(result)
+fn print_tree(graph: &DependencyGraph, node: usize, prefix: &str, visible: &[bool]) {
+    let children: Vec<usize> = graph.nodes[node]
+        .children
+        .iter()
+        .copied()
+        .filter(|&c| visible[c])
+        .collect();
+    for (i, &child) in children.iter().enumerate() {
+        let last = i + 1 == children.len();
+        let node = &graph.nodes[child];
+        let note = match &node.omitted {
+            Some(Omitted::Conflict { winner }) => {
+                format!(" (omitted for conflict with {})", winner)
+            }
+            Some(Omitted::Duplicate) => String::from(" (omitted for duplicate)"),
+            None => String::new(),
+        };
+        println!(
+            "{}{}{}:{}{}",
+            prefix,
+            if last { "\\- " } else { "+- " },
+            node.artifact,
+            node.scope.as_deref().unwrap_or("compile"),
+            note
+        );
+        let prefix = format!("{}{}", prefix, if last { "   " } else { "|  " });
+        print_tree(graph, child, &prefix, visible);
+    }
 }
 
-enum Authorization {
-    Basic { username: String, password: String },
-    Token { value: String },
+fn temp_dir(purpose: &str) -> anyhow::Result<PathBuf> {
+    let dir =
+        std::env::temp_dir().join(format!("maven-artifact-{}-{}", purpose, std::process::id()));
+    std::fs::create_dir_all(&dir).with_context(|| format!("Unable to create {}", dir.display()))?;
+    Ok(dir)
 }
 
-impl Authorization {
-    fn from_env() -> Option<Authorization> {
-        Self::basic().or(Self::token())
+async fn jar_diff(
+    resolver: &Resolver,
+    artifact: Artifact,
+    version: Version,
+    dir: &Path,
+) -> anyhow::Result<(ResolvedArtifact, ResolvedArtifact, JarDiff)> {
+    let other = artifact.with_version(version);
+    let mut downloaded = vec![];
+    for (name, artifact) in [("old", artifact), ("new", other)] {
+        let target = dir.join(name);
+        std::fs::create_dir_all(&target)?;
+        let resolved = resolver.download(artifact, &target).await?;
+        let path = resolved.file.clone().unwrap_or_default();
+        let entries =
+            jar::entries(&path).with_context(|| format!("Unable to read {}", path.display()))?;
+        downloaded.push((resolved, entries));
     }
+    let (new, new_entries) = downloaded.pop().unwrap();
+    let (old, old_entries) = downloaded.pop().unwrap();
+    let diff = jar::diff(&old_entries, &new_entries);
+    Ok((old, new, diff))
+}
 
-    fn basic() -> Option<Authorization> {
-        let username = std::env::var("MAVEN_USERNAME").ok()?;
-        let password = std::env::var("MAVEN_PASSWORD").ok()?;
-        Some(Authorization::Basic { username, password })
+fn repository_host(repository: &str) -> anyhow::Result<String> {
+    let repository =
+        Repository::parse(repository).with_context(|| format!("Unable to parse {}", repository))?;
+    match repository.url.host_str() {
+        Some(host) => Ok(host.to_string()),
+        None => bail!("{} has no host", repository.url),
     }
+}
+
+#[cfg(feature = "verify-signatures")]
+fn key_pins() -> anyhow::Result<maven_artifact::signatures::KeyPins> {
+    let Some(path) = maven_artifact::signatures::KeyPins::default_path() else {
+        bail!("Unable to locate a configuration directory for key pins, set MAVEN_KEY_PINS");
+    };
+    maven_artifact::signatures::KeyPins::load(&path)
+        .with_context(|| format!("Unable to read {}", path.display()))
+}
 
-    fn token() -> Option<Authorization> {
-        let token = std::env::var("MAVEN_TOKEN").ok()?;
-        Some(Authorization::Token { value: token })
+fn credential_store() -> anyhow::Result<CredentialStore> {
+    let Some(path) = CredentialStore::default_path() else {
+        bail!("Unable to locate a configuration directory for stored credentials");
+    };
+    CredentialStore::load(&path).with_context(|| format!("Unable to read {}", path.display()))
+}
+
+fn prompt(label: &str) -> anyhow::Result<String> {
+    eprint!("{}", label);
+    let mut line = String::new();
+    std::io::stdin().read_line(&mut line)?;
+    Ok(line.trim().to_string())
+}
+
+fn log_level(verbose: u8, quiet: bool) -> &'static str {
+    match (quiet, verbose) {
+        (true, _) => "error",
+        (false, 0) => "warn",
+        (false, 1) => "info",
+        (false, 2) => "debug",
+        (false, _) => "trace",
     }
 }
+
+fn init_tracing(verbose: u8, quiet: bool) {
+    let level = log_level(verbose, quiet);
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(level));
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr)
+        .init();
+}
@@ -0,0 +1,46 @@
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use maven_artifact::progress::ProgressListener;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use url::Url;
+
+// A bar on stderr for each download, drawn only when stderr is a terminal. Bars of
+// finished downloads are cleared, those broken off are left where they stopped.
+#[derive(Debug, Default)]
+pub struct ProgressBars {
+    bars: MultiProgress,
+    downloads: Mutex<HashMap<Url, ProgressBar>>,
+}
+
+impl ProgressListener for ProgressBars {
+    fn started(&self, url: &Url, total: Option<u64>) {
+        let pb = self.bars.add(ProgressBar::no_length());
+        if let Some(length) = total {
+            pb.set_length(length)
+        };
+        pb.set_style(
+            ProgressStyle::with_template(
+                "{spinner:.green} [{wide_bar:.cyan/blue}] {bytes}/{total_bytes} ({eta})",
+            )
+            .unwrap()
+            .progress_chars("#>-"),
+        );
+        self.downloads.lock().unwrap().insert(url.clone(), pb);
+    }
+
+    fn received(&self, url: &Url, bytes: u64) {
+        if let Some(pb) = self.downloads.lock().unwrap().get(url) {
+            pb.inc(bytes);
+        }
+    }
+
+    fn completed(&self, url: &Url, complete: bool) {
+        if let Some(pb) = self.downloads.lock().unwrap().remove(url) {
+            if complete {
+                pb.finish_and_clear();
+            } else {
+                pb.abandon();
+            }
+        }
+    }
+}
@@ -0,0 +1,229 @@
+use crate::Version;
+use crate::artifact::Artifact;
+use crate::jar::JarEntry;
+use crate::pom::{Dependency, Project};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionOverride {
+    pub dependency: Dependency,
+    pub declared: Version,
+    pub managed: Version,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManagementReport {
+    pub overridden: Vec<VersionOverride>,
+    pub unused: Vec<Dependency>,
+}
+
+impl ManagementReport {
+    pub fn is_empty(&self) -> bool {
+        self.overridden.is_empty() && self.unused.is_empty()
+    }
+}
+
+pub fn analyze_management(project: &Project) -> ManagementReport {
+    let Some(management) = &project.dependency_management else {
+        return ManagementReport::default();
+    };
+    let interpolate = |v: &Version| Version::from(project.interpolate(v.as_ref()));
+
+    let overridden = project
+        .dependencies
+        .iter()
+        .filter_map(|dependency| {
+            let declared = interpolate(dependency.version.as_ref()?);
+            let managed = interpolate(management.find(dependency)?.version.as_ref()?);
+            (declared != managed).then(|| VersionOverride {
+                dependency: dependency.clone(),
+                declared,
+                managed,
+            })
+        })
+        .collect();
+
+    // imported BOMs are never declared as dependencies, so they are not "unused"
+    let unused = management
+        .dependencies
+        .iter()
+        .filter(|m| m.scope.as_deref() != Some("import"))
+        .filter(|m| !project.dependencies.iter().any(|d| d.same_key(m)))
+        .cloned()
+        .collect();
+
+    ManagementReport { overridden, unused }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DuplicateClass {
+    pub name: String,
+    pub artifacts: Vec<String>,
+    // every copy has the same content, typically a shaded or relocated-but-not-renamed dupe
+    pub identical: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SplitPackage {
+    pub package: String,
+    pub artifacts: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OverlapReport {
+    pub duplicate_classes: Vec<DuplicateClass>,
+    pub split_packages: Vec<SplitPackage>,
+}
+
+impl OverlapReport {
+    pub fn is_empty(&self) -> bool {
+        self.duplicate_classes.is_empty() && self.split_packages.is_empty()
+    }
+}
+
+// META-INF holds multi-release copies and module descriptors, which legitimately repeat
+fn is_class(name: &str) -> bool {
+    name.ends_with(".class")
+        && !name.starts_with("META-INF/")
+        && !name.ends_with("module-info.class")
+}
+
+pub fn analyze_overlap(jars: &[(Artifact, Vec<JarEntry>)]) -> OverlapReport {
+    let mut classes: BTreeMap<&str, Vec<(String, &str)>> = BTreeMap::new();
+    let mut packages: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for (artifact, entries) in jars {
+        let coordinates = artifact.to_string();
+        for entry in entries.iter().filter(|e| is_class(&e.name)) {
+            classes
+                .entry(&entry.name)
+                .or_default()
+                .push((coordinates.clone(), &entry.sha256));
+            let package = entry.name.rsplit_once('/').map_or("", |(p, _)| p);
+            let owners = packages.entry(package).or_default();
+            if !owners.contains(&coordinates) {
+                owners.push(coordinates.clone());
+            }
+        }
+    }
+    let duplicate_classes = classes
+        .into_iter()
+        .filter(|(_, copies)| copies.len() > 1)
+        .map(|(name, copies)| DuplicateClass {
+            name: name.to_string(),
+            identical: copies.iter().all(|(_, sha)| *sha == copies[0].1),
+            artifacts: copies.into_iter().map(|(a, _)| a).collect(),
+        })
+        .collect();
+    let split_packages = packages
+        .into_iter()
+        .filter(|(package, owners)| !package.is_empty() && owners.len() > 1)
+        .map(|(package, artifacts)| SplitPackage {
+            package: package.replace('/', "."),
+            artifacts,
+        })
+        .collect();
+    OverlapReport {
+        duplicate_classes,
+        split_packages,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pom::DependencyManagement;
+    use crate::{ArtifactId, GroupId};
+
+    fn dependency(artifact_id: &str, version: Option<&str>) -> Dependency {
+        Dependency::new(
+            GroupId::from("org.example"),
+            ArtifactId::from(artifact_id),
+            version.map(Version::from),
+        )
+    }
+
+    #[test]
+    fn overrides_and_unused() {
+        let mut project = Project::new(ArtifactId::from("example"));
+        project
+            .properties
+            .insert(String::from("a.version"), String::from("1.0"));
+        let mut bom = dependency("bom", Some("3.0"));
+        bom.type_ = Some(String::from("pom"));
+        bom.scope = Some(String::from("import"));
+        project.dependency_management = Some(DependencyManagement {
+            dependencies: vec![
+                dependency("a", Some("${a.version}")),
+                dependency("b", Some("2.0")),
+                dependency("c", Some("1.0")),
+                bom,
+            ],
+        });
+        project.dependencies = vec![
+            dependency("a", Some("1.0")),
+            dependency("b", Some("2.1")),
+            dependency("d", Some("1.0")),
+        ];
+
+        let report = analyze_management(&project);
+        assert_eq!(
+            report.overridden,
+            vec![VersionOverride {
+                dependency: dependency("b", Some("2.1")),
+                declared: Version::from("2.1"),
+                managed: Version::from("2.0"),
+            }]
+        );
+        assert_eq!(report.unused, vec![dependency("c", Some("1.0"))]);
+        assert!(analyze_management(&Project::new(ArtifactId::from("x"))).is_empty());
+    }
+
+    #[test]
+    fn overlapping_classes() {
+        let entry = |name: &str, sha256: &str| JarEntry {
+            name: name.to_string(),
+            size: 1,
+            sha256: sha256.to_string(),
+        };
+        let jars = vec![
+            (
+                Artifact::parse("org.example:a:1.0").unwrap(),
+                vec![
+                    entry("org/example/Shared.class", "x"),
+                    entry("org/example/A.class", "a"),
+                    entry("META-INF/versions/9/module-info.class", "m"),
+                ],
+            ),
+            (
+                Artifact::parse("org.example:b:1.0").unwrap(),
+                vec![
+                    entry("org/example/Shared.class", "y"),
+                    entry("META-INF/versions/9/module-info.class", "n"),
+                ],
+            ),
+            (
+                Artifact::parse("org.example:c:1.0").unwrap(),
+                vec![entry("org/other/C.class", "c")],
+            ),
+        ];
+        let report = analyze_overlap(&jars);
+        assert_eq!(
+            report.duplicate_classes,
+            vec![DuplicateClass {
+                name: String::from("org/example/Shared.class"),
+                artifacts: vec![
+                    String::from("org.example:a:1.0"),
+                    String::from("org.example:b:1.0")
+                ],
+                identical: false,
+            }]
+        );
+        assert_eq!(report.split_packages.len(), 1);
+        assert_eq!(report.split_packages[0].package, "org.example");
+        assert!(analyze_overlap(&jars[2..]).is_empty());
+    }
+}
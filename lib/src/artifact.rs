@@ -1,5 +1,11 @@
+use crate::checksum::{Checksum, ChecksumAlgorithm};
+use crate::disk::LinkStrategy;
+use crate::metadata::Snapshot;
+use crate::packaging::{classifier_for_type, extension_for_packaging};
 use crate::*;
+use serde::Serialize;
 use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
 use url::Url;
 
 #[derive(Debug, Clone, Error)]
@@ -94,6 +100,32 @@ impl Artifact {
         cloned
     }
 
+    // The form Display and parse agree on: `jar` without a classifier is implied, so
+    // extension is None, while a classifier always carries an explicit extension.
+    pub fn canonicalize(&self) -> Artifact {
+        let trimmed = |value: &str| {
+            Some(value.trim())
+                .filter(|v| !v.is_empty())
+                .map(String::from)
+        };
+        let classifier = self.classifier.as_deref().and_then(trimmed);
+        let extension = self.extension.as_deref().and_then(trimmed);
+        let (extension, classifier) = match (extension, classifier) {
+            (Some(e), None) => (
+                extension_for_packaging(&e).to_string(),
+                classifier_for_type(&e).map(String::from),
+            ),
+            (e, c) => (e.unwrap_or_else(|| String::from("jar")), c),
+        };
+        Artifact {
+            group_id: GroupId::from(self.group_id.trim()),
+            artifact_id: ArtifactId::from(self.artifact_id.trim()),
+            version: Version::from(self.version.trim()),
+            extension: Some(extension).filter(|e| classifier.is_some() || e != "jar"),
+            classifier: classifier.map(Classifier::from),
+        }
+    }
+
     pub fn is_snapshot(&self) -> bool {
         self.version.is_snapshot()
     }
@@ -104,10 +136,15 @@ impl Artifact {
     }
 
     pub fn file_name(&self) -> String {
+        let classifier = match &self.classifier {
+            Some(c) => format!("-{}", c),
+            None => String::new(),
+        };
         format!(
-            "{}-{}.{}",
+            "{}-{}{}.{}",
             self.artifact_id,
             self.version,
+            classifier,
             self.extension.as_deref().unwrap_or("jar")
         )
     }
@@ -116,7 +153,7 @@ impl Artifact {
         let parts: Vec<_> = input.split(":").collect();
         if parts.len() >= 3 {
             let (ga, rest) = parts.split_at(2);
-            match (ga, rest) {
+            let artifact = match (ga, rest) {
                 ([g, a], [v]) => Ok(Artifact {
                     group_id: GroupId(g.to_string()),
                     artifact_id: ArtifactId(a.to_string()),
@@ -128,18 +165,19 @@ impl Artifact {
                     group_id: GroupId(g.to_string()),
                     artifact_id: ArtifactId(a.to_string()),
                     version: Version(v.to_string()),
-                    extension: Some(e.to_string()),
-                    classifier: None,
+                    extension: Some(extension_for_packaging(e).to_string()),
+                    classifier: classifier_for_type(e).map(Classifier::from),
                 }),
                 ([g, a], [e, c, v]) => Ok(Artifact {
                     group_id: GroupId(g.to_string()),
                     artifact_id: ArtifactId(a.to_string()),
                     version: Version(v.to_string()),
-                    extension: Some(e.to_string()),
+                    extension: Some(extension_for_packaging(e).to_string()),
                     classifier: Some(Classifier(c.to_string())),
                 }),
                 _ => Err(ParseArtifactError(String::from("Unable to parse artifact"))),
-            }
+            }?;
+            Ok(artifact.canonicalize())
         } else {
             Err(ParseArtifactError(format!(
                 "Incorrect number of parts. Expected as least 3, but was {}",
@@ -149,6 +187,72 @@ impl Artifact {
     }
 }
 
+// pkg:maven/<groupId>/<artifactId>@<version>?type=<packaging>&classifier=<classifier>
+impl Artifact {
+    pub fn parse_purl(input: &str) -> Result<Artifact, ParseArtifactError> {
+        let error = |reason: &str| ParseArtifactError(format!("{}: {}", reason, input));
+        let rest = input
+            .strip_prefix("pkg:maven/")
+            .ok_or_else(|| error("Not a Maven package URL"))?;
+        let rest = rest.split_once('#').map_or(rest, |(rest, _)| rest);
+        let (path, qualifiers) = match rest.split_once('?') {
+            Some((path, qualifiers)) => (path, qualifiers),
+            None => (rest, ""),
+        };
+        let (path, version) = path
+            .rsplit_once('@')
+            .ok_or_else(|| error("Package URL has no version"))?;
+        let (group_id, artifact_id) = path
+            .trim_matches('/')
+            .rsplit_once('/')
+            .ok_or_else(|| error("Package URL has no namespace"))?;
+        let mut artifact = Artifact::new(
+            GroupId(percent_decode(group_id)),
+            ArtifactId(percent_decode(artifact_id)),
+            Version(percent_decode(version)),
+        );
+        for (key, value) in url::form_urlencoded::parse(qualifiers.as_bytes()) {
+            match key.as_ref() {
+                "type" => {
+                    artifact.extension = Some(extension_for_packaging(&value).to_string());
+                    artifact.classifier = artifact
+                        .classifier
+                        .or_else(|| classifier_for_type(&value).map(Classifier::from));
+                }
+                "classifier" => artifact.classifier = Some(Classifier(value.into_owned())),
+                _ => (),
+            }
+        }
+        if artifact.group_id.is_empty() || artifact.artifact_id.is_empty() || version.is_empty() {
+            return Err(error("Incomplete package URL"));
+        }
+        Ok(artifact.canonicalize())
+    }
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
 impl From<Artifact> for PartialArtifact {
     fn from(value: Artifact) -> Self {
         PartialArtifact::new(value.group_id, value.artifact_id)
@@ -179,13 +283,54 @@ impl Display for Artifact {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SignatureStatus {
+    #[default]
+    Unchecked,
+    Missing,
+    Present,
+    // checked against the configured keyring
+    Verified,
+}
+
+#[derive(Debug, Clone)]
 pub struct ResolvedArtifact {
     pub artifact: Artifact,
     pub resolved_version: Version,
+    pub snapshot: Option<Snapshot>,
+    pub repository: Option<Url>,
+    pub url: Option<Url>,
+    pub file: Option<PathBuf>,
+    // digests of the downloaded file
+    pub checksums: Vec<Checksum>,
+    // the published checksum the download was verified against
+    pub verified_checksum: Option<ChecksumAlgorithm>,
+    pub signature: SignatureStatus,
+    // how the file was placed from the local repository, None when it was downloaded
+    pub link: Option<LinkStrategy>,
+    // the file already at the target matched the published checksum and was kept
+    pub skipped: bool,
 }
 
 impl ResolvedArtifact {
-    fn path(&self) -> String {
+    pub fn new(artifact: Artifact, resolved_version: Version) -> ResolvedArtifact {
+        ResolvedArtifact {
+            artifact,
+            resolved_version,
+            snapshot: None,
+            repository: None,
+            url: None,
+            file: None,
+            checksums: vec![],
+            verified_checksum: None,
+            signature: SignatureStatus::Unchecked,
+            link: None,
+            skipped: false,
+        }
+    }
+
+    pub(crate) fn path(&self) -> String {
         let base = format!(
             "{}/{}",
             self.artifact.group_id.path_string(),
@@ -215,6 +360,16 @@ impl ResolvedArtifact {
             format!(".{}", self.artifact.extension.as_deref().unwrap_or("jar")).as_str();
         repository.url.join(current_path.as_str())
     }
+
+    pub fn sidecar_uri(
+        &self,
+        repository: &Repository,
+        suffix: &str,
+    ) -> Result<Url, url::ParseError> {
+        let uri = self.uri(repository)?;
+        let path = format!("{}{}", uri.path(), suffix);
+        uri.join(&path)
+    }
 }
 
 impl From<ResolvedArtifact> for Artifact {
@@ -240,6 +395,35 @@ mod tests {
         );
         assert_eq!(&result.to_string(), "g:a:v")
     }
+    #[test]
+    fn parse_package_urls() {
+        let parse = |purl: &str| Artifact::parse_purl(purl).map(|a| a.to_string());
+        assert_eq!(
+            parse("pkg:maven/org.apache.commons/commons-lang3@3.12.0").unwrap(),
+            "org.apache.commons:commons-lang3:3.12.0"
+        );
+        assert_eq!(
+            parse("pkg:maven/org.example/lib@1.0%2Bbuild.1?classifier=sources&type=jar").unwrap(),
+            "org.example:lib:jar:sources:1.0+build.1"
+        );
+        assert_eq!(
+            parse("pkg:maven/org.example/lib@1.0?type=test-jar#ignored").unwrap(),
+            "org.example:lib:jar:tests:1.0"
+        );
+        assert_eq!(
+            parse("pkg:maven/org.example/bom@2.0?type=pom").unwrap(),
+            "org.example:bom:pom:2.0"
+        );
+        for invalid in [
+            "pkg:npm/left-pad@1.0",
+            "pkg:maven/lib@1.0",
+            "pkg:maven/org.example/lib",
+            "pkg:maven/org.example/lib@",
+        ] {
+            assert!(Artifact::parse_purl(invalid).is_err(), "{}", invalid);
+        }
+    }
+
     #[test]
     fn parse_full_gav() {
         let input = "groupId:artifact_id:packaging:classifier:version";
@@ -274,6 +458,54 @@ mod tests {
         assert_eq!(result.to_string(), String::from(input))
     }
 
+    #[test]
+    fn canonical_round_trip() {
+        let base = Artifact::new(
+            GroupId::from("org.example"),
+            ArtifactId::from("lib"),
+            Version::from("1.0"),
+        );
+        let variants = vec![
+            base.clone(),
+            base.with_extension(String::from("jar")),
+            base.with_extension(String::from("pom")),
+            base.with_extension(String::from("test-jar")),
+            base.with_classifier(Classifier::from("sources")),
+            base.with_extension(String::from("zip"))
+                .with_classifier(Classifier::from("dist")),
+        ];
+        for artifact in variants {
+            let canonical = artifact.canonicalize();
+            assert_eq!(Artifact::parse(&canonical.to_string()).unwrap(), canonical);
+            assert_eq!(canonical.canonicalize(), canonical);
+        }
+
+        let jar = base.with_extension(String::from("jar")).canonicalize();
+        assert_eq!(jar.extension, None);
+        let sources = base
+            .with_classifier(Classifier::from("sources"))
+            .canonicalize();
+        assert_eq!(sources.extension.as_deref(), Some("jar"));
+
+        let padded = Artifact::parse(" org.example : lib : 1.0 ").unwrap();
+        assert_eq!(padded, base);
+        assert_eq!(Artifact::parse("org.example:lib:jar:1.0").unwrap(), base);
+    }
+
+    #[test]
+    fn parse_test_jar() {
+        let result = Artifact::parse("com.example:fixtures:test-jar:1.0.0").unwrap();
+        assert_eq!(result.extension.as_deref(), Some("jar"));
+        assert_eq!(result.classifier, Some(Classifier::from("tests")));
+        assert_eq!(result.to_string(), "com.example:fixtures:jar:tests:1.0.0");
+        assert_eq!(result.file_name(), "fixtures-1.0.0-tests.jar");
+        let resolved = ResolvedArtifact::new(result, Version::from("1.0.0"));
+        assert_eq!(
+            resolved.uri(&Repository::maven_central()).unwrap().as_str(),
+            "https://repo1.maven.org/maven2/com/example/fixtures/1.0.0/fixtures-1.0.0-tests.jar"
+        );
+    }
+
     #[test]
     fn resolved_uri() {
         let a = Artifact::new(
@@ -281,10 +513,7 @@ mod tests {
             ArtifactId::from("artifact"),
             Version::from("1.0.0"),
         );
-        let resolved = ResolvedArtifact {
-            artifact: a,
-            resolved_version: Version::from("1.0.0"),
-        };
+        let resolved = ResolvedArtifact::new(a, Version::from("1.0.0"));
 
         let base = Repository::maven_central();
         let parsed = resolved.uri(&base).unwrap();
@@ -295,4 +524,21 @@ mod tests {
             .unwrap();
         assert_eq!(parsed, expected)
     }
+
+    #[test]
+    fn resolved_sidecar_uri() {
+        let a = Artifact::new(
+            GroupId::from("com.example"),
+            ArtifactId::from("artifact"),
+            Version::from("1.0.0"),
+        );
+        let resolved = ResolvedArtifact::new(a, Version::from("1.0.0"));
+
+        let base = Repository::maven_central();
+        let parsed = resolved.sidecar_uri(&base, ".sha1").unwrap();
+        assert_eq!(
+            parsed.as_str(),
+            "https://repo1.maven.org/maven2/com/example/artifact/1.0.0/artifact-1.0.0.jar.sha1"
+        )
+    }
 }
@@ -0,0 +1,141 @@
+use crate::checksum::{Checksum, ChecksumAlgorithm};
+use base64::Engine;
+use base64::prelude::BASE64_STANDARD;
+use serde::Serialize;
+use serde_json::Value;
+use url::Url;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum AttestationKind {
+    SigstoreBundle,
+    InToto,
+}
+
+impl AttestationKind {
+    pub fn all() -> [AttestationKind; 2] {
+        [AttestationKind::SigstoreBundle, AttestationKind::InToto]
+    }
+
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            AttestationKind::SigstoreBundle => ".sigstore.json",
+            AttestationKind::InToto => ".intoto.jsonl",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Attestation {
+    pub kind: AttestationKind,
+    pub url: Url,
+    #[serde(skip)]
+    pub content: Vec<u8>,
+}
+
+impl Attestation {
+    // The digests of the subjects of its in-toto statements, which come bare or in DSSE
+    // envelopes, one per line or inside a Sigstore bundle. Digests of algorithms without a
+    // Checksum, or that don't parse, are left out.
+    pub fn subjects(&self) -> Vec<Checksum> {
+        let mut subjects = vec![];
+        let documents = serde_json::Deserializer::from_slice(&self.content).into_iter::<Value>();
+        for document in documents.map_while(Result::ok) {
+            for statement in statements(document) {
+                let digests = statement
+                    .get("subject")
+                    .and_then(Value::as_array)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|s| s.get("digest")?.as_object());
+                for digest in digests {
+                    for algorithm in ChecksumAlgorithm::all() {
+                        if let Some(hex) = digest.get(algorithm.extension()).and_then(Value::as_str)
+                            && let Ok(checksum) = Checksum::parse(algorithm, hex)
+                        {
+                            subjects.push(checksum);
+                        }
+                    }
+                }
+            }
+        }
+        subjects
+    }
+
+    // Whether a subject is the file with `checksums`, None when no subject has a digest of
+    // an algorithm the file was digested with
+    pub fn covers(&self, checksums: &[Checksum]) -> Option<bool> {
+        let comparable: Vec<Checksum> = self
+            .subjects()
+            .into_iter()
+            .filter(|s| checksums.iter().any(|c| c.algorithm() == s.algorithm()))
+            .collect();
+        (!comparable.is_empty()).then(|| comparable.iter().any(|s| checksums.contains(s)))
+    }
+}
+
+fn statements(document: Value) -> Vec<Value> {
+    if document.get("subject").is_some() {
+        return vec![document];
+    }
+    if let Some(envelope) = document.get("dsseEnvelope") {
+        return statements(envelope.clone());
+    }
+    document
+        .get("payload")
+        .and_then(Value::as_str)
+        .and_then(|payload| BASE64_STANDARD.decode(payload).ok())
+        .and_then(|payload| serde_json::from_slice::<Value>(&payload).ok())
+        .filter(|statement| statement.get("subject").is_some())
+        .into_iter()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHA256: &str = "773532d2bc1a7ae88f64aa2a7bcf97312c37d3de4fe216c3e93d891a45c11cf3";
+
+    fn attestation(kind: AttestationKind, content: String) -> Attestation {
+        Attestation {
+            kind,
+            url: Url::parse("https://repo.example.com/lib-1.0.jar.intoto.jsonl").unwrap(),
+            content: content.into_bytes(),
+        }
+    }
+
+    #[test]
+    fn attested_subjects() {
+        let statement = format!(
+            r#"{{"_type":"https://in-toto.io/Statement/v1","subject":[{{"name":"lib-1.0.jar","digest":{{"sha256":"{}","gitCommit":"abc"}}}}]}}"#,
+            SHA256
+        );
+        let envelope = format!(
+            r#"{{"payloadType":"application/vnd.in-toto+json","payload":"{}"}}"#,
+            BASE64_STANDARD.encode(&statement)
+        );
+        let lines = attestation(
+            AttestationKind::InToto,
+            format!("{}\n{}\n", statement, envelope),
+        );
+        let jar = Checksum::parse(ChecksumAlgorithm::Sha256, SHA256).unwrap();
+        assert_eq!(lines.subjects(), vec![jar.clone(), jar.clone()]);
+
+        let bundle = attestation(
+            AttestationKind::SigstoreBundle,
+            format!(
+                r#"{{"mediaType":"application/vnd.dev.sigstore.bundle.v0.3+json","dsseEnvelope":{}}}"#,
+                envelope
+            ),
+        );
+        assert_eq!(bundle.covers(&[jar]), Some(true));
+        let other = Checksum::parse(ChecksumAlgorithm::Sha256, &"0".repeat(64)).unwrap();
+        assert_eq!(bundle.covers(&[other]), Some(false));
+        let sha1 = Checksum::parse(ChecksumAlgorithm::Sha1, &"0".repeat(40)).unwrap();
+        assert_eq!(bundle.covers(&[sha1]), None);
+        assert_eq!(
+            attestation(AttestationKind::InToto, String::from("not json")).subjects(),
+            vec![]
+        );
+    }
+}
@@ -0,0 +1,790 @@
+use crate::Repository;
+use crate::settings::Settings;
+use base64::Engine;
+use base64::prelude::BASE64_STANDARD;
+use reqwest::header::{HeaderValue, InvalidHeaderValue};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Debug, Formatter};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+use url::Url;
+
+pub type Refresh<'a> = Pin<Box<dyn Future<Output = ()> + Send + 'a>>;
+
+pub trait CredentialsProvider: Debug + Send + Sync {
+    fn credentials(&self, url: &Url) -> Option<Auth>;
+
+    fn invalidate(&self, _url: &Url) {}
+
+    // Awaited before credentials(), so providers handing out tokens can fetch them
+    // without blocking the request
+    fn refresh<'a>(&'a self, _url: &'a Url) -> Refresh<'a> {
+        Box::pin(std::future::ready(()))
+    }
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub enum Auth {
+    Basic { username: String, password: String },
+    Bearer(String),
+}
+
+impl Auth {
+    pub fn from_env() -> Option<Auth> {
+        Self::basic_from_env().or_else(Self::bearer_from_env)
+    }
+
+    fn basic_from_env() -> Option<Auth> {
+        let username = std::env::var("MAVEN_USERNAME").ok()?;
+        let password = std::env::var("MAVEN_PASSWORD").ok()?;
+        Some(Auth::Basic { username, password })
+    }
+
+    fn bearer_from_env() -> Option<Auth> {
+        let token = std::env::var("MAVEN_TOKEN").ok()?;
+        Some(Auth::Bearer(token))
+    }
+
+    pub fn header_value(&self) -> Result<HeaderValue, InvalidHeaderValue> {
+        let mut value = match self {
+            Auth::Basic { username, password } => {
+                let basic = BASE64_STANDARD.encode(format!("{}:{}", username, password));
+                HeaderValue::from_str(&format!("Basic {}", basic))?
+            }
+            Auth::Bearer(token) => HeaderValue::from_str(&format!("Bearer {}", token))?,
+        };
+        value.set_sensitive(true);
+        Ok(value)
+    }
+}
+
+impl Debug for Auth {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Auth::Basic { username, .. } => f
+                .debug_struct("Basic")
+                .field("username", username)
+                .field("password", &"***")
+                .finish(),
+            Auth::Bearer(_) => f.debug_tuple("Bearer").field(&"***").finish(),
+        }
+    }
+}
+
+impl CredentialsProvider for Auth {
+    fn credentials(&self, _url: &Url) -> Option<Auth> {
+        Some(self.clone())
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct HostCredentials {
+    hosts: BTreeMap<String, Auth>,
+}
+
+impl HostCredentials {
+    pub fn new() -> HostCredentials {
+        Self::default()
+    }
+
+    pub fn with_host<S: Into<String>>(mut self, host: S, auth: Auth) -> HostCredentials {
+        self.hosts.insert(host.into(), auth);
+        self
+    }
+}
+
+impl CredentialsProvider for HostCredentials {
+    fn credentials(&self, url: &Url) -> Option<Auth> {
+        self.hosts.get(url.host_str()?).cloned()
+    }
+}
+
+// Credentials set with Repository::with_auth, each only sent to urls within its repository
+#[derive(Debug, Default, Clone)]
+pub struct RepositoryCredentials {
+    repositories: Vec<Repository>,
+}
+
+impl RepositoryCredentials {
+    pub fn new<'a, I: IntoIterator<Item = &'a Repository>>(
+        repositories: I,
+    ) -> RepositoryCredentials {
+        RepositoryCredentials {
+            repositories: repositories
+                .into_iter()
+                .filter(|r| r.auth.is_some())
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+impl CredentialsProvider for RepositoryCredentials {
+    fn credentials(&self, url: &Url) -> Option<Auth> {
+        // the most specific repository wins when urls nest
+        self.repositories
+            .iter()
+            .filter(|r| r.contains(url))
+            .max_by_key(|r| r.url.as_str().len())
+            .and_then(|r| r.auth.clone())
+    }
+}
+
+// The <servers> of settings.xml, each only sent to the repositories or mirrors with its id
+#[derive(Debug, Default, Clone)]
+pub struct SettingsServers {
+    repositories: RepositoryCredentials,
+}
+
+impl SettingsServers {
+    pub fn load() -> SettingsServers {
+        match Settings::load_default() {
+            Ok(Some(settings)) => Self::new(&settings),
+            Ok(None) => Self::default(),
+            Err(e) => {
+                debug!("Not using settings.xml servers: {}", e);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn new(settings: &Settings) -> SettingsServers {
+        match settings.repositories() {
+            Ok(repositories) => SettingsServers {
+                repositories: RepositoryCredentials::new(&repositories),
+            },
+            Err(e) => {
+                debug!("Not using settings.xml servers: {}", e);
+                Self::default()
+            }
+        }
+    }
+}
+
+impl CredentialsProvider for SettingsServers {
+    fn credentials(&self, url: &Url) -> Option<Auth> {
+        self.repositories.credentials(url)
+    }
+}
+
+// MAVEN_USERNAME and MAVEN_PASSWORD, or MAVEN_TOKEN, for every url
+#[derive(Debug, Default, Clone, Copy)]
+pub struct EnvCredentials;
+
+impl CredentialsProvider for EnvCredentials {
+    fn credentials(&self, _url: &Url) -> Option<Auth> {
+        Auth::from_env()
+    }
+}
+
+// Credentials of ~/.netrc machines. The default entry is only sent to the hosts it is
+// allowed for, not to every repository asked.
+#[derive(Debug, Default, Clone)]
+pub struct NetrcCredentials {
+    machines: BTreeMap<String, Auth>,
+    default: Option<Auth>,
+    default_hosts: BTreeSet<String>,
+}
+
+impl NetrcCredentials {
+    pub fn with_default_for<I, S>(mut self, hosts: I) -> NetrcCredentials
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.default_hosts.extend(hosts.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn load() -> NetrcCredentials {
+        let path = match std::env::var_os("NETRC") {
+            Some(path) => Some(PathBuf::from(path)),
+            None => std::env::home_dir().map(|home| home.join(".netrc")),
+        };
+        path.and_then(|p| std::fs::read_to_string(p).ok())
+            .map(|content| Self::parse(&content))
+            .unwrap_or_default()
+    }
+
+    pub fn parse(content: &str) -> NetrcCredentials {
+        let mut netrc = NetrcCredentials::default();
+        let mut entries: Vec<(Option<String>, Option<String>, Option<String>)> = vec![];
+        let mut lines = content.lines();
+        while let Some(line) = lines.next() {
+            let mut tokens = line.split_whitespace();
+            while let Some(token) = tokens.next() {
+                match token {
+                    "machine" => entries.push((tokens.next().map(String::from), None, None)),
+                    "default" => entries.push((None, None, None)),
+                    "login" => {
+                        if let Some(entry) = entries.last_mut() {
+                            entry.1 = tokens.next().map(String::from);
+                        }
+                    }
+                    "password" => {
+                        if let Some(entry) = entries.last_mut() {
+                            entry.2 = tokens.next().map(String::from);
+                        }
+                    }
+                    "account" => {
+                        tokens.next();
+                    }
+                    "macdef" => {
+                        // macro bodies run until the next empty line
+                        for line in lines.by_ref() {
+                            if line.trim().is_empty() {
+                                break;
+                            }
+                        }
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        for (machine, login, password) in entries {
+            let (Some(username), Some(password)) = (login, password) else {
+                continue;
+            };
+            let auth = Auth::Basic { username, password };
+            match machine {
+                Some(machine) => {
+                    netrc.machines.entry(machine).or_insert(auth);
+                }
+                None => netrc.default = Some(auth),
+            }
+        }
+        netrc
+    }
+}
+
+impl CredentialsProvider for NetrcCredentials {
+    fn credentials(&self, url: &Url) -> Option<Auth> {
+        let host = url.host_str()?;
+        match self.machines.get(host) {
+            Some(auth) => Some(auth.clone()),
+            None if self.default_hosts.contains(host) => self.default.clone(),
+            None => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum StoredAuth {
+    Basic { username: String, password: String },
+    Bearer { token: String },
+}
+
+impl From<&Auth> for StoredAuth {
+    fn from(auth: &Auth) -> Self {
+        match auth.clone() {
+            Auth::Basic { username, password } => StoredAuth::Basic { username, password },
+            Auth::Bearer(token) => StoredAuth::Bearer { token },
+        }
+    }
+}
+
+impl From<StoredAuth> for Auth {
+    fn from(stored: StoredAuth) -> Self {
+        match stored {
+            StoredAuth::Basic { username, password } => Auth::Basic { username, password },
+            StoredAuth::Bearer { token } => Auth::Bearer(token),
+        }
+    }
+}
+
+// Credentials per host in a JSON file only the user can read. They are not encrypted, there
+// is no OS keyring support.
+#[derive(Debug, Clone)]
+pub struct CredentialStore {
+    path: PathBuf,
+    hosts: BTreeMap<String, Auth>,
+}
+
+impl CredentialStore {
+    pub fn default_path() -> Option<PathBuf> {
+        let config = match std::env::var_os("XDG_CONFIG_HOME") {
+            Some(dir) => PathBuf::from(dir),
+            None => std::env::home_dir()?.join(".config"),
+        };
+        Some(config.join("maven-artifact").join("credentials.json"))
+    }
+
+    pub fn load_default() -> CredentialStore {
+        Self::default_path()
+            .and_then(|path| Self::load(&path).ok())
+            .unwrap_or_else(|| CredentialStore {
+                path: PathBuf::new(),
+                hosts: BTreeMap::new(),
+            })
+    }
+
+    pub fn load(path: &Path) -> std::io::Result<CredentialStore> {
+        let hosts = match std::fs::read(path) {
+            Ok(content) => serde_json::from_slice::<BTreeMap<String, StoredAuth>>(&content)?
+                .into_iter()
+                .map(|(host, auth)| (host, Auth::from(auth)))
+                .collect(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(e),
+        };
+        Ok(CredentialStore {
+            path: path.to_path_buf(),
+            hosts,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn get(&self, host: &str) -> Option<&Auth> {
+        self.hosts.get(host)
+    }
+
+    pub fn set(&mut self, host: String, auth: Auth) {
+        self.hosts.insert(host, auth);
+    }
+
+    pub fn remove(&mut self, host: &str) -> bool {
+        self.hosts.remove(host).is_some()
+    }
+
+    pub fn save(&self) -> std::io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let stored: BTreeMap<&String, StoredAuth> = self
+            .hosts
+            .iter()
+            .map(|(host, auth)| (host, StoredAuth::from(auth)))
+            .collect();
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let file = options.open(&self.path)?;
+        // mode only applies when creating the file, so tighten one that already existed
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+        }
+        serde_json::to_writer_pretty(file, &stored)?;
+        Ok(())
+    }
+}
+
+impl CredentialsProvider for CredentialStore {
+    fn credentials(&self, url: &Url) -> Option<Auth> {
+        self.get(url.host_str()?).cloned()
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct CredentialsChain {
+    providers: Vec<Arc<dyn CredentialsProvider>>,
+}
+
+impl CredentialsChain {
+    pub fn new() -> CredentialsChain {
+        Self::default()
+    }
+
+    // settings.xml servers, then credentials keyed by host. EnvCredentials is left out since
+    // it would send the same credentials everywhere, ResolverBuilder::from_env ties them to
+    // $MAVEN_REPOSITORY instead.
+    pub fn standard() -> CredentialsChain {
+        Self::standard_for(&[])
+    }
+
+    // the standard chain, with the netrc default entry sent to the hosts of `repositories`
+    pub fn standard_for(repositories: &[Repository]) -> CredentialsChain {
+        let hosts = repositories.iter().filter_map(|r| r.url.host_str());
+        Self::new()
+            .with(SettingsServers::load())
+            .with(NetrcCredentials::load().with_default_for(hosts))
+            .with(CredentialStore::load_default())
+    }
+
+    pub fn with<P: CredentialsProvider + 'static>(mut self, provider: P) -> CredentialsChain {
+        self.providers.push(Arc::new(provider));
+        self
+    }
+
+    pub fn with_shared(mut self, provider: Arc<dyn CredentialsProvider>) -> CredentialsChain {
+        self.providers.push(provider);
+        self
+    }
+}
+
+impl CredentialsProvider for CredentialsChain {
+    fn credentials(&self, url: &Url) -> Option<Auth> {
+        self.providers.iter().find_map(|p| p.credentials(url))
+    }
+
+    fn invalidate(&self, url: &Url) {
+        for provider in &self.providers {
+            provider.invalidate(url);
+        }
+    }
+
+    fn refresh<'a>(&'a self, url: &'a Url) -> Refresh<'a> {
+        Box::pin(async move {
+            for provider in &self.providers {
+                provider.refresh(url).await;
+            }
+        })
+    }
+}
+
+pub type TokenError = Box<dyn std::error::Error + Send + Sync>;
+
+#[derive(Clone)]
+pub struct Token {
+    pub value: String,
+    pub expires_at: Option<Instant>,
+}
+
+impl Token {
+    pub fn new<S: Into<String>>(value: S) -> Token {
+        Token {
+            value: value.into(),
+            expires_at: None,
+        }
+    }
+
+    pub fn expires_in(mut self, ttl: Duration) -> Token {
+        self.expires_at = Some(Instant::now() + ttl);
+        self
+    }
+
+    fn is_fresh(&self, margin: Duration) -> bool {
+        match self.expires_at {
+            Some(expires_at) => Instant::now() + margin < expires_at,
+            None => true,
+        }
+    }
+}
+
+type FetchToken =
+    dyn Fn() -> Pin<Box<dyn Future<Output = Result<Token, TokenError>> + Send>> + Send + Sync;
+
+// A bearer token for one host, fetched again when it is about to expire or was rejected
+pub struct RefreshingToken {
+    host: String,
+    fetch: Box<FetchToken>,
+    margin: Duration,
+    current: Mutex<Option<Token>>,
+    // held across the fetch so concurrent requests wait for one token
+    fetching: tokio::sync::Mutex<()>,
+}
+
+impl RefreshingToken {
+    pub fn new<S, F, T>(host: S, fetch: F) -> RefreshingToken
+    where
+        S: Into<String>,
+        F: Fn() -> T + Send + Sync + 'static,
+        T: Future<Output = Result<Token, TokenError>> + Send + 'static,
+    {
+        RefreshingToken {
+            host: host.into(),
+            fetch: Box::new(move || Box::pin(fetch())),
+            margin: Duration::from_secs(30),
+            current: Mutex::new(None),
+            fetching: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    pub fn refresh_before(mut self, margin: Duration) -> RefreshingToken {
+        self.margin = margin;
+        self
+    }
+
+    fn applies_to(&self, url: &Url) -> bool {
+        url.host_str() == Some(self.host.as_str())
+    }
+
+    fn is_fresh(&self) -> bool {
+        let current = self.current.lock().unwrap();
+        current.as_ref().is_some_and(|t| t.is_fresh(self.margin))
+    }
+}
+
+impl CredentialsProvider for RefreshingToken {
+    fn credentials(&self, url: &Url) -> Option<Auth> {
+        if !self.applies_to(url) {
+            return None;
+        }
+        let current = self.current.lock().unwrap();
+        current
+            .as_ref()
+            .filter(|token| token.is_fresh(Duration::ZERO))
+            .map(|token| Auth::Bearer(token.value.clone()))
+    }
+
+    fn invalidate(&self, url: &Url) {
+        if self.applies_to(url) {
+            *self.current.lock().unwrap() = None;
+        }
+    }
+
+    fn refresh<'a>(&'a self, url: &'a Url) -> Refresh<'a> {
+        Box::pin(async move {
+            if !self.applies_to(url) || self.is_fresh() {
+                return;
+            }
+            let _fetching = self.fetching.lock().await;
+            // another request may have fetched one while this one waited
+            if self.is_fresh() {
+                return;
+            }
+            debug!(host = %self.host, "fetching bearer token");
+            let token = match (self.fetch)().await {
+                Ok(token) => Some(token),
+                Err(e) => {
+                    warn!(error = %e, "unable to fetch bearer token");
+                    None
+                }
+            };
+            *self.current.lock().unwrap() = token;
+        })
+    }
+}
+
+impl Debug for RefreshingToken {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RefreshingToken")
+            .field("host", &self.host)
+            .field("margin", &self.margin)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn basic_header() {
+        let auth = Auth::Basic {
+            username: String::from("user"),
+            password: String::from("pass"),
+        };
+        assert_eq!(auth.header_value().unwrap(), "Basic dXNlcjpwYXNz");
+        assert!(!format!("{:?}", auth).contains("pass\""));
+    }
+
+    #[test]
+    fn bearer_header() {
+        let auth = Auth::Bearer(String::from("token"));
+        assert_eq!(auth.header_value().unwrap(), "Bearer token");
+    }
+
+    #[test]
+    fn settings_servers() {
+        let settings: Settings = r#"<settings>
+  <servers>
+    <server><id>corporate</id><username>alice</username><password>secret</password></server>
+    <server><id>unused</id><username>bob</username><password>hunter2</password></server>
+  </servers>
+  <mirrors>
+    <mirror><id>corporate</id><url>https://nexus.example.org/maven/</url><mirrorOf>*</mirrorOf></mirror>
+  </mirrors>
+</settings>"#
+            .parse()
+            .unwrap();
+        let servers = SettingsServers::new(&settings);
+        let url = |s: &str| Url::parse(s).unwrap();
+        assert_eq!(
+            servers.credentials(&url("https://nexus.example.org/maven/org/example/a.pom")),
+            Some(Auth::Basic {
+                username: String::from("alice"),
+                password: String::from("secret"),
+            })
+        );
+        assert_eq!(
+            servers.credentials(&url("https://nexus.example.org/other/a.pom")),
+            None
+        );
+        assert_eq!(
+            servers.credentials(&url("https://repo1.maven.org/maven2/a.pom")),
+            None
+        );
+    }
+
+    #[test]
+    fn netrc_lookup() {
+        let netrc = NetrcCredentials::parse(
+            "machine repo.example.com\n  login alice\n  password secret\n\n\
+             macdef init\ncd /tmp\n\n\
+             machine other.example.com login bob password hunter2\n\
+             default login anonymous password guest\n",
+        );
+        let url = |s: &str| Url::parse(s).unwrap();
+        assert_eq!(
+            netrc.credentials(&url("https://repo.example.com/maven2")),
+            Some(Auth::Basic {
+                username: String::from("alice"),
+                password: String::from("secret"),
+            })
+        );
+        assert_eq!(
+            netrc.credentials(&url("https://other.example.com/")),
+            Some(Auth::Basic {
+                username: String::from("bob"),
+                password: String::from("hunter2"),
+            })
+        );
+        // the default entry only goes to hosts it is allowed for
+        assert_eq!(
+            netrc.credentials(&url("https://unknown.example.com/")),
+            None
+        );
+        let netrc = netrc.with_default_for(["unknown.example.com"]);
+        assert_eq!(
+            netrc.credentials(&url("https://unknown.example.com/")),
+            Some(Auth::Basic {
+                username: String::from("anonymous"),
+                password: String::from("guest"),
+            })
+        );
+        assert_eq!(netrc.credentials(&url("https://repo1.maven.org/")), None);
+    }
+
+    #[test]
+    fn chain_order() {
+        let chain = CredentialsChain::new()
+            .with(
+                HostCredentials::new()
+                    .with_host("repo.example.com", Auth::Bearer(String::from("first"))),
+            )
+            .with(Auth::Bearer(String::from("fallback")));
+        let url = |s: &str| Url::parse(s).unwrap();
+        assert_eq!(
+            chain.credentials(&url("https://repo.example.com/maven2")),
+            Some(Auth::Bearer(String::from("first")))
+        );
+        assert_eq!(
+            chain.credentials(&url("https://other.example.com/")),
+            Some(Auth::Bearer(String::from("fallback")))
+        );
+        assert_eq!(
+            CredentialsChain::new().credentials(&url("https://x/")),
+            None
+        );
+    }
+
+    #[test]
+    fn repository_credentials() {
+        let url = |s: &str| Url::parse(s).unwrap();
+        let internal = Repository::both(url("https://repo.example.com/internal/"))
+            .with_auth(Auth::Bearer(String::from("internal")));
+        let releases = Repository::both(url("https://repo.example.com/internal/releases"))
+            .with_auth(Auth::Bearer(String::from("releases")));
+        let credentials =
+            RepositoryCredentials::new([&Repository::maven_central(), &internal, &releases]);
+        let auth = |s| credentials.credentials(&url(s));
+        assert_eq!(
+            auth("https://repo.example.com/internal/org/a/1.0/a-1.0.pom"),
+            Some(Auth::Bearer(String::from("internal")))
+        );
+        assert_eq!(
+            auth("https://repo.example.com/internal/releases/org/a/1.0/a-1.0.pom"),
+            Some(Auth::Bearer(String::from("releases")))
+        );
+        assert_eq!(auth("https://repo.example.com/internal2/org/a/"), None);
+        assert_eq!(auth("https://repo1.maven.org/maven2/org/a/"), None);
+    }
+
+    #[tokio::test]
+    async fn refreshing_token() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let provider = RefreshingToken::new("repo.example.com", move || {
+            let n = counter.fetch_add(1, Ordering::SeqCst);
+            let token = Token::new(format!("token-{}", n));
+            if n == 0 {
+                std::future::ready(Ok(token.expires_in(Duration::from_secs(3600))))
+            } else {
+                std::future::ready(Ok(token.expires_in(Duration::from_secs(10))))
+            }
+        });
+        let url = Url::parse("https://repo.example.com/").unwrap();
+        let other = Url::parse("https://other.example.com/").unwrap();
+        let bearer = |s: &str| Some(Auth::Bearer(String::from(s)));
+        let credentials = async |url: &Url| {
+            provider.refresh(url).await;
+            provider.credentials(url)
+        };
+
+        assert_eq!(provider.credentials(&url), None);
+        assert_eq!(credentials(&url).await, bearer("token-0"));
+        assert_eq!(credentials(&url).await, bearer("token-0"));
+        // other hosts neither get the token nor cause a fetch
+        assert_eq!(credentials(&other).await, None);
+        provider.invalidate(&other);
+        assert_eq!(credentials(&url).await, bearer("token-0"));
+        provider.invalidate(&url);
+        assert_eq!(credentials(&url).await, bearer("token-1"));
+        // expires within the refresh margin, so every call refreshes
+        assert_eq!(credentials(&url).await, bearer("token-2"));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn credential_store_round_trip() {
+        let dir = std::env::temp_dir().join(format!("maven-artifact-store-{}", std::process::id()));
+        let path = dir.join("credentials.json");
+        let mut store = CredentialStore::load(&path).unwrap();
+        assert_eq!(store.get("repo.example.com"), None);
+        store.set(
+            String::from("repo.example.com"),
+            Auth::Basic {
+                username: String::from("alice"),
+                password: String::from("secret"),
+            },
+        );
+        store.set(
+            String::from("other.example.com"),
+            Auth::Bearer(String::from("token")),
+        );
+        store.save().unwrap();
+
+        let mut loaded = CredentialStore::load(&path).unwrap();
+        let url = Url::parse("https://other.example.com/maven2").unwrap();
+        assert_eq!(
+            loaded.credentials(&url),
+            Some(Auth::Bearer(String::from("token")))
+        );
+        assert!(loaded.remove("repo.example.com"));
+        assert!(!loaded.remove("repo.example.com"));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = |path: &Path| std::fs::metadata(path).unwrap().permissions().mode() & 0o777;
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+            loaded.save().unwrap();
+            assert_eq!(mode(&path), 0o600);
+        }
+        loaded.save().unwrap();
+        assert_eq!(
+            CredentialStore::load(&path)
+                .unwrap()
+                .get("repo.example.com"),
+            None
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,98 @@
+use crate::Repository;
+use crate::auth::{Auth, HostCredentials};
+use url::Url;
+
+static HOST: &str = "pkgs.dev.azure.com";
+
+#[derive(Clone, Debug)]
+pub struct AzureArtifacts {
+    organization: String,
+    project: Option<String>,
+    feed: String,
+}
+
+impl AzureArtifacts {
+    pub fn new(organization: String, feed: String) -> AzureArtifacts {
+        AzureArtifacts {
+            organization,
+            project: None,
+            feed,
+        }
+    }
+
+    pub fn with_project(mut self, project: String) -> AzureArtifacts {
+        self.project = Some(project);
+        self
+    }
+
+    pub fn url(&self) -> Result<Url, url::ParseError> {
+        let scope = match &self.project {
+            Some(project) => format!("{}/{}", self.organization, project),
+            None => self.organization.clone(),
+        };
+        Url::parse(&format!(
+            "https://{}/{}/_packaging/{}/maven/v1/",
+            HOST, scope, self.feed
+        ))
+    }
+
+    pub fn repository(&self) -> Result<Repository, url::ParseError> {
+        self.url().map(Repository::both)
+    }
+
+    // Azure ignores the username for PATs, but it has to be non-empty;
+    // the organization name is what the feed's "connect" page suggests
+    pub fn auth(&self, personal_access_token: String) -> Auth {
+        Auth::Basic {
+            username: self.organization.clone(),
+            password: personal_access_token,
+        }
+    }
+
+    pub fn credentials(&self, personal_access_token: String) -> HostCredentials {
+        HostCredentials::new().with_host(HOST, self.auth(personal_access_token))
+    }
+
+    pub fn credentials_from_env(&self) -> Option<HostCredentials> {
+        let token = std::env::var("AZURE_DEVOPS_EXT_PAT")
+            .or_else(|_| std::env::var("SYSTEM_ACCESSTOKEN"))
+            .ok()?;
+        Some(self.credentials(token))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::CredentialsProvider;
+
+    #[test]
+    fn feed_urls() {
+        let org = AzureArtifacts::new(String::from("contoso"), String::from("libs"));
+        assert_eq!(
+            org.url().unwrap().as_str(),
+            "https://pkgs.dev.azure.com/contoso/_packaging/libs/maven/v1/"
+        );
+        let project = org.with_project(String::from("platform"));
+        assert_eq!(
+            project.url().unwrap().as_str(),
+            "https://pkgs.dev.azure.com/contoso/platform/_packaging/libs/maven/v1/"
+        );
+    }
+
+    #[test]
+    fn pat_credentials() {
+        let feed = AzureArtifacts::new(String::from("contoso"), String::from("libs"));
+        let credentials = feed.credentials(String::from("pat"));
+        let url = feed.url().unwrap();
+        assert_eq!(
+            credentials.credentials(&url),
+            Some(Auth::Basic {
+                username: String::from("contoso"),
+                password: String::from("pat"),
+            })
+        );
+        let other = Url::parse("https://repo1.maven.org/maven2/").unwrap();
+        assert_eq!(credentials.credentials(&other), None);
+    }
+}
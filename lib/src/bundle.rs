@@ -0,0 +1,159 @@
+use crate::artifact::Artifact;
+use crate::checksum::{Checksum, ChecksumAlgorithm, digest_file};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use zip::ZipWriter;
+use zip::write::SimpleFileOptions;
+
+#[derive(Debug, Error)]
+pub enum BundleError {
+    #[error("Bundle is incomplete, missing: {}", .0.join(", "))]
+    Incomplete(Vec<String>),
+    #[error("IO operation failed, {0}")]
+    IO(#[from] std::io::Error),
+    #[error("Zip error {0}")]
+    Zip(#[from] zip::result::ZipError),
+    #[error("Unexpected entry {0} in bundle")]
+    InvalidEntry(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BundleEntry {
+    File { source: PathBuf, name: String },
+    Generated { name: String, content: String },
+}
+
+impl BundleEntry {
+    pub fn name(&self) -> &str {
+        match self {
+            BundleEntry::File { name, .. } => name,
+            BundleEntry::Generated { name, .. } => name,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Bundle {
+    pub artifact: Artifact,
+    pub entries: Vec<BundleEntry>,
+}
+
+impl Bundle {
+    pub fn required_files(artifact: &Artifact) -> Vec<String> {
+        let base = format!("{}-{}", artifact.artifact_id, artifact.version);
+        let extension = artifact.extension.as_deref().unwrap_or("jar");
+        let mut files = vec![format!("{}.pom", base)];
+        if extension != "pom" {
+            files.push(format!("{}.{}", base, extension));
+            files.push(format!("{}-sources.jar", base));
+            files.push(format!("{}-javadoc.jar", base));
+        }
+        files
+    }
+
+    pub fn collect(artifact: &Artifact, dir: &Path) -> Result<Bundle, BundleError> {
+        let prefix = artifact.path();
+        let mut missing = Vec::new();
+        let mut entries = Vec::new();
+        for file in Self::required_files(artifact) {
+            let source = dir.join(&file);
+            if !source.is_file() {
+                missing.push(file);
+                continue;
+            }
+            let signature = format!("{}.asc", file);
+            if dir.join(&signature).is_file() {
+                entries.push(BundleEntry::File {
+                    source: dir.join(&signature),
+                    name: format!("{}/{}", prefix, signature),
+                });
+            } else {
+                missing.push(signature);
+            }
+            let mut computed: Option<Vec<Checksum>> = None;
+            for algorithm in ChecksumAlgorithm::all() {
+                let checksum = format!("{}.{}", file, algorithm.extension());
+                let name = format!("{}/{}", prefix, checksum);
+                if dir.join(&checksum).is_file() {
+                    entries.push(BundleEntry::File {
+                        source: dir.join(&checksum),
+                        name,
+                    });
+                } else {
+                    if computed.is_none() {
+                        computed = Some(digest_file(&source)?);
+                    }
+                    let content = computed
+                        .iter()
+                        .flatten()
+                        .find(|c| c.algorithm() == algorithm)
+                        .map(Checksum::to_string)
+                        .unwrap_or_default();
+                    entries.push(BundleEntry::Generated { name, content });
+                }
+            }
+            entries.push(BundleEntry::File {
+                source,
+                name: format!("{}/{}", prefix, file),
+            });
+        }
+        if missing.is_empty() {
+            entries.sort_by(|a, b| a.name().cmp(b.name()));
+            Ok(Bundle {
+                artifact: artifact.clone(),
+                entries,
+            })
+        } else {
+            Err(BundleError::Incomplete(missing))
+        }
+    }
+
+    pub fn write_zip(&self, output: &Path) -> Result<(), BundleError> {
+        let mut zip = ZipWriter::new(BufWriter::new(File::create(output)?));
+        let options = SimpleFileOptions::default();
+        for entry in &self.entries {
+            zip.start_file(entry.name(), options)?;
+            match entry {
+                BundleEntry::File { source, .. } => {
+                    let mut reader = BufReader::new(File::open(source)?);
+                    std::io::copy(&mut reader, &mut zip)?;
+                }
+                BundleEntry::Generated { content, .. } => {
+                    zip.write_all(content.as_bytes())?;
+                }
+            }
+        }
+        zip.finish()?.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn required_files_for_jar() {
+        let artifact = Artifact::parse("com.example:lib:1.0.0").unwrap();
+        assert_eq!(
+            Bundle::required_files(&artifact),
+            vec![
+                String::from("lib-1.0.0.pom"),
+                String::from("lib-1.0.0.jar"),
+                String::from("lib-1.0.0-sources.jar"),
+                String::from("lib-1.0.0-javadoc.jar"),
+            ]
+        )
+    }
+
+    #[test]
+    fn required_files_for_pom() {
+        let artifact = Artifact::parse("com.example:bom:pom:1.0.0").unwrap();
+        assert_eq!(
+            Bundle::required_files(&artifact),
+            vec![String::from("bom-1.0.0.pom")]
+        )
+    }
+}
@@ -0,0 +1,390 @@
+use crate::Repository;
+use crate::artifact::Artifact;
+use crate::bundle::BundleError;
+use crate::local::repository_id;
+use crate::paths;
+use crate::pom::{PomFlavor, Project};
+use crate::resolver::Descriptor;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+use tracing::debug;
+use url::Url;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+#[derive(Debug, Clone, Error)]
+#[error("Invalid update policy {0}, expected always, daily, never or interval:MINUTES")]
+pub struct ParsePolicyError(String);
+
+// Maven's <updatePolicy>: how long something fetched from a repository is used before
+// it is fetched again
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpdatePolicy {
+    Always,
+    Daily,
+    Interval(Duration),
+    Never,
+}
+
+impl UpdatePolicy {
+    // daily entries go stale at midnight UTC, not 24 hours after they were fetched
+    pub fn is_stale(&self, fetched: SystemTime, now: SystemTime) -> bool {
+        let secs = |at: SystemTime| at.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+        match self {
+            UpdatePolicy::Always => true,
+            UpdatePolicy::Daily => secs(fetched) / 86400 < secs(now) / 86400,
+            UpdatePolicy::Interval(interval) => {
+                now.duration_since(fetched).unwrap_or_default() >= *interval
+            }
+            UpdatePolicy::Never => false,
+        }
+    }
+}
+
+impl FromStr for UpdatePolicy {
+    type Err = ParsePolicyError;
+
+    fn from_str(input: &str) -> Result<UpdatePolicy, ParsePolicyError> {
+        match input {
+            "always" => Ok(UpdatePolicy::Always),
+            "daily" => Ok(UpdatePolicy::Daily),
+            "never" => Ok(UpdatePolicy::Never),
+            _ => input
+                .strip_prefix("interval:")
+                .and_then(|minutes| minutes.parse::<u64>().ok())
+                .map(|minutes| UpdatePolicy::Interval(Duration::from_secs(minutes * 60)))
+                .ok_or_else(|| ParsePolicyError(input.to_string())),
+        }
+    }
+}
+
+// What the caching layers of a Resolver, its local repository and descriptor cache, spared
+// the network. Misses are only counted where a cache was asked first.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheStats {
+    pub hits: u64,
+    // cached files a repository answered 304 Not Modified for
+    pub not_modified: u64,
+    pub misses: u64,
+    // size of the hits and of the files not modified, parsed POMs count as nothing
+    pub bytes_saved: u64,
+}
+
+impl CacheStats {
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+impl Display for CacheStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} hits, {} not modified, {} misses, {} bytes saved",
+            self.hits, self.not_modified, self.misses, self.bytes_saved
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    fetched: u64,
+    url: Url,
+    flavor: PomFlavor,
+    project: Project,
+}
+
+// Parsed POMs on disk, one JSON file per repository and coordinates, so building the
+// same graph again needs neither the network nor the XML parser. Released POMs don't
+// change and are kept according to the release policy, which by default never expires
+// them; snapshots are fetched again once the snapshot policy says so.
+#[derive(Debug, Clone)]
+pub struct DescriptorCache {
+    pub root: PathBuf,
+    releases: UpdatePolicy,
+    snapshots: UpdatePolicy,
+}
+
+impl DescriptorCache {
+    pub fn new(root: impl Into<PathBuf>) -> DescriptorCache {
+        DescriptorCache {
+            root: root.into(),
+            releases: UpdatePolicy::Never,
+            snapshots: UpdatePolicy::Daily,
+        }
+    }
+
+    // $MAVEN_DESCRIPTOR_CACHE, or maven-artifact/descriptors in the user's cache directory
+    pub fn from_env() -> Option<DescriptorCache> {
+        if let Some(root) = std::env::var_os("MAVEN_DESCRIPTOR_CACHE") {
+            return Some(DescriptorCache::new(root));
+        }
+        let cache = match std::env::var_os("XDG_CACHE_HOME") {
+            Some(dir) => PathBuf::from(dir),
+            None => std::env::home_dir()?.join(".cache"),
+        };
+        Some(DescriptorCache::new(
+            cache.join("maven-artifact").join("descriptors"),
+        ))
+    }
+
+    pub fn with_release_policy(mut self, policy: UpdatePolicy) -> DescriptorCache {
+        self.releases = policy;
+        self
+    }
+
+    pub fn with_snapshot_policy(mut self, policy: UpdatePolicy) -> DescriptorCache {
+        self.snapshots = policy;
+        self
+    }
+
+    // `artifact` has the version as requested, 1.0-SNAPSHOT rather than a timestamp
+    pub fn path(&self, artifact: &Artifact, repository: &Repository) -> PathBuf {
+        paths::long_path(
+            self.root
+                .join(repository_id(repository))
+                .join(artifact.path())
+                .join(format!(
+                    "{}-{}.json",
+                    artifact.artifact_id, artifact.version
+                )),
+        )
+    }
+
+    pub fn get(&self, artifact: &Artifact, repository: &Repository) -> Option<Descriptor> {
+        let path = self.path(artifact, repository);
+        let content = std::fs::read(&path).ok()?;
+        let entry: Entry = match serde_json::from_slice(&content) {
+            Ok(entry) => entry,
+            Err(e) => {
+                debug!(path = %path.display(), "ignoring unreadable cache entry: {}", e);
+                return None;
+            }
+        };
+        let policy = if artifact.is_snapshot() {
+            self.snapshots
+        } else {
+            self.releases
+        };
+        let fetched = UNIX_EPOCH + Duration::from_secs(entry.fetched);
+        if policy.is_stale(fetched, SystemTime::now()) {
+            debug!(path = %path.display(), "cache entry is stale");
+            return None;
+        }
+        Some(Descriptor {
+            url: entry.url,
+            project: entry.project,
+            flavor: entry.flavor,
+        })
+    }
+
+    pub fn put(
+        &self,
+        artifact: &Artifact,
+        repository: &Repository,
+        descriptor: &Descriptor,
+    ) -> std::io::Result<()> {
+        let path = self.path(artifact, repository);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let entry = Entry {
+            fetched: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            url: descriptor.url.clone(),
+            flavor: descriptor.flavor,
+            project: descriptor.project.clone(),
+        };
+        // written aside and renamed, so concurrent builds never read half an entry
+        let partial = path.with_extension("json.part");
+        std::fs::write(&partial, serde_json::to_vec(&entry)?)?;
+        std::fs::rename(partial, path)
+    }
+
+    // Every entry in the cache as a deflated zip, for building graphs offline elsewhere.
+    // Fill an empty cache with a single graph build to export just that closure; version
+    // ranges in it still need maven-metadata.xml from a repository.
+    // Returns the number of descriptors written.
+    pub fn export(&self, output: &Path) -> Result<usize, BundleError> {
+        let mut names = vec![];
+        collect_entries(&self.root, &self.root, &mut names)?;
+        names.sort();
+        let mut zip = ZipWriter::new(BufWriter::new(File::create(output)?));
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        for name in &names {
+            zip.start_file(name.as_str(), options)?;
+            let mut reader = BufReader::new(File::open(self.root.join(name))?);
+            std::io::copy(&mut reader, &mut zip)?;
+        }
+        zip.finish()?.flush()?;
+        Ok(names.len())
+    }
+
+    // Adds the descriptors of an exported bundle, keeping the time they were fetched.
+    // With `repository` they are filed under it instead of where they were exported from,
+    // for machines resolving through a mirror. Returns the number of descriptors read.
+    pub fn import(
+        &self,
+        input: &Path,
+        repository: Option<&Repository>,
+    ) -> Result<usize, BundleError> {
+        let mut archive = ZipArchive::new(BufReader::new(File::open(input)?))?;
+        let mut imported = 0;
+        for index in 0..archive.len() {
+            let mut file = archive.by_index(index)?;
+            if file.is_dir() {
+                continue;
+            }
+            let entry_name = file.name().to_string();
+            let invalid = || BundleError::InvalidEntry(entry_name.clone());
+            let name = file.enclosed_name().ok_or_else(invalid)?;
+            let mut components = name.components();
+            let (Some(Component::Normal(_)), Some("json")) =
+                (components.next(), name.extension().and_then(|e| e.to_str()))
+            else {
+                return Err(invalid());
+            };
+            let path = match repository {
+                Some(repository) => self
+                    .root
+                    .join(repository_id(repository))
+                    .join(components.as_path()),
+                None => self.root.join(&name),
+            };
+            let mut content = vec![];
+            std::io::copy(&mut file, &mut content)?;
+            if serde_json::from_slice::<Entry>(&content).is_err() {
+                return Err(invalid());
+            }
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(path, content)?;
+            imported += 1;
+        }
+        Ok(imported)
+    }
+}
+
+// relative paths of the entries below `dir`, with / as separator like in the zip
+fn collect_entries(root: &Path, dir: &Path, names: &mut Vec<String>) -> std::io::Result<()> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    for entry in entries {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_entries(root, &path, names)?;
+        } else if path.extension().is_some_and(|e| e == "json")
+            && let Ok(relative) = path.strip_prefix(root)
+        {
+            let parts: Vec<_> = relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect();
+            names.push(parts.join("/"));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pom::PomParser;
+    use std::io::Cursor;
+
+    #[test]
+    fn update_policies() {
+        let day = |d: u64, h: u64| UNIX_EPOCH + Duration::from_secs(d * 86400 + h * 3600);
+        assert!(UpdatePolicy::Always.is_stale(day(1, 0), day(1, 0)));
+        assert!(!UpdatePolicy::Never.is_stale(day(1, 0), day(100, 0)));
+        assert!(!UpdatePolicy::Daily.is_stale(day(1, 1), day(1, 23)));
+        assert!(UpdatePolicy::Daily.is_stale(day(1, 23), day(2, 1)));
+        let hourly = UpdatePolicy::from_str("interval:60").unwrap();
+        assert_eq!(hourly, UpdatePolicy::Interval(Duration::from_secs(3600)));
+        assert!(!hourly.is_stale(day(1, 1), day(1, 1)));
+        assert!(hourly.is_stale(day(1, 1), day(1, 2)));
+        assert!(UpdatePolicy::from_str("weekly").is_err());
+    }
+
+    #[test]
+    fn cache_stats() {
+        assert!(CacheStats::default().is_empty());
+        let stats = CacheStats {
+            hits: 3,
+            not_modified: 1,
+            misses: 2,
+            bytes_saved: 4096,
+        };
+        assert_eq!(
+            stats.to_string(),
+            "3 hits, 1 not modified, 2 misses, 4096 bytes saved"
+        );
+        assert_eq!(serde_json::to_value(stats).unwrap()["bytesSaved"], 4096);
+    }
+
+    #[test]
+    fn round_trip() {
+        let dir = std::env::temp_dir().join(format!("maven-artifact-cache-{}", std::process::id()));
+        let pom = r#"<project>
+  <groupId>org.example</groupId>
+  <artifactId>lib</artifactId>
+  <version>1.0-SNAPSHOT</version>
+  <properties><java.version>17</java.version></properties>
+  <dependencies>
+    <dependency>
+      <groupId>org.example</groupId>
+      <artifactId>util</artifactId>
+      <version>2.0</version>
+      <optional>true</optional>
+      <exclusions><exclusion><groupId>*</groupId><artifactId>*</artifactId></exclusion></exclusions>
+    </dependency>
+  </dependencies>
+</project>"#;
+        let descriptor = Descriptor {
+            url: Url::parse("https://repo1.maven.org/maven2/org/example/lib/1.0-SNAPSHOT/lib-1.0-20240102.030405-1.pom").unwrap(),
+            project: PomParser::new().parse(Cursor::new(pom)).unwrap(),
+            flavor: PomFlavor::Consumer,
+        };
+        let central = Repository::maven_central();
+        let artifact = Artifact::parse("org.example:lib:pom:1.0-SNAPSHOT").unwrap();
+        let cache = DescriptorCache::new(&dir);
+        assert!(cache.get(&artifact, &central).is_none());
+        cache.put(&artifact, &central, &descriptor).unwrap();
+        assert_eq!(
+            cache.path(&artifact, &central),
+            dir.join("central/org/example/lib/1.0-SNAPSHOT/lib-1.0-SNAPSHOT.json")
+        );
+        let cached = cache.get(&artifact, &central).unwrap();
+        assert_eq!(cached.project, descriptor.project);
+        assert_eq!(cached.url, descriptor.url);
+        // keyed by repository too
+        let other = Repository::parse("https://repo.example.org/maven2/").unwrap();
+        assert!(cache.get(&artifact, &other).is_none());
+        let always = cache.clone().with_snapshot_policy(UpdatePolicy::Always);
+        assert!(always.get(&artifact, &central).is_none());
+
+        let bundle = dir.join("descriptors.zip");
+        assert_eq!(cache.export(&bundle).unwrap(), 1);
+        let mirror = DescriptorCache::new(dir.join("mirror"));
+        assert_eq!(mirror.import(&bundle, Some(&other)).unwrap(), 1);
+        assert_eq!(
+            mirror.get(&artifact, &other).unwrap().project,
+            descriptor.project
+        );
+        assert!(mirror.get(&artifact, &central).is_none());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,154 @@
+use crate::Version;
+use crate::listing::{PublishedVersion, parse_listing_timestamp};
+use serde::Serialize;
+use std::time::{Duration, SystemTime};
+
+const DAY: f64 = 86400.0;
+const YEAR: f64 = 365.25 * DAY;
+
+// How often an artifact is released and how long ago, for dependency health reviews.
+// Snapshots are left out, and so are releases the listings give no timestamp for, which
+// only count towards `releases`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReleaseStats {
+    pub releases: usize,
+    pub dated: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first: Option<PublishedVersion>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latest: Option<PublishedVersion>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latest_age_days: Option<u64>,
+    // over the time from the first dated release to the latest
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub releases_per_year: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub median_gap_days: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current: Option<VersionAge>,
+}
+
+// the version in use, and how far behind it is
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionAge {
+    pub version: Version,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub published: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub age_days: Option<u64>,
+    // newer releases, by version order
+    pub releases_since: usize,
+}
+
+pub fn release_stats(
+    versions: &[PublishedVersion],
+    current: Option<&Version>,
+    now: SystemTime,
+) -> ReleaseStats {
+    let releases: Vec<&PublishedVersion> = versions
+        .iter()
+        .filter(|p| !p.version.is_snapshot())
+        .collect();
+    let mut dated: Vec<(SystemTime, &PublishedVersion)> = releases
+        .iter()
+        .filter_map(|p| Some((parse_listing_timestamp(p.published.as_deref()?)?, *p)))
+        .collect();
+    dated.sort_by_key(|(time, _)| *time);
+    let age_days =
+        |time: SystemTime| now.duration_since(time).unwrap_or_default().as_secs() / 86400;
+    let first = dated.first();
+    let latest = dated.last();
+    let span = match (first, latest) {
+        (Some((first, _)), Some((latest, _))) => latest.duration_since(*first).unwrap_or_default(),
+        _ => Duration::ZERO,
+    };
+    let mut gaps: Vec<f64> = dated
+        .windows(2)
+        .map(|w| {
+            w[1].0
+                .duration_since(w[0].0)
+                .unwrap_or_default()
+                .as_secs_f64()
+                / DAY
+        })
+        .collect();
+    gaps.sort_by(f64::total_cmp);
+    let median_gap_days = match gaps.len() {
+        0 => None,
+        n if n % 2 == 1 => Some(gaps[n / 2]),
+        n => Some((gaps[n / 2 - 1] + gaps[n / 2]) / 2.0),
+    };
+    let current = current.map(|version| {
+        let published = versions
+            .iter()
+            .find(|p| &p.version == version)
+            .and_then(|p| p.published.clone());
+        VersionAge {
+            version: version.clone(),
+            age_days: published
+                .as_deref()
+                .and_then(parse_listing_timestamp)
+                .map(age_days),
+            published,
+            releases_since: releases
+                .iter()
+                .filter(|p| p.version.compare(version).is_gt())
+                .count(),
+        }
+    });
+    ReleaseStats {
+        releases: releases.len(),
+        dated: dated.len(),
+        first: first.map(|(_, p)| (*p).clone()),
+        latest: latest.map(|(_, p)| (*p).clone()),
+        latest_age_days: latest.map(|(time, _)| age_days(*time)),
+        releases_per_year: (!span.is_zero())
+            .then(|| (dated.len() - 1) as f64 / (span.as_secs_f64() / YEAR)),
+        median_gap_days,
+        current,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cadence() {
+        let published = |version: &str, date: Option<&str>| PublishedVersion {
+            version: Version::from(version),
+            published: date.map(String::from),
+        };
+        let versions = vec![
+            published("1.0", Some("2021-01-01 00:00")),
+            published("1.1", Some("2021-03-02 00:00")),
+            published("1.2-SNAPSHOT", Some("2021-04-01 00:00")),
+            published("1.2", Some("2021-07-01 12:00")),
+            published("1.3", None),
+            published("2.0", Some("2023-01-01 00:00")),
+        ];
+        let now = parse_listing_timestamp("2024-01-01 00:00").unwrap();
+        let stats = release_stats(&versions, Some(&Version::from("1.1")), now);
+        assert_eq!(stats.releases, 5);
+        assert_eq!(stats.dated, 4);
+        assert_eq!(stats.first.unwrap().version, Version::from("1.0"));
+        assert_eq!(stats.latest.unwrap().version, Version::from("2.0"));
+        assert_eq!(stats.latest_age_days, Some(365));
+        // three gaps in two years, of 60, 121.5 and 548.5 days
+        assert_eq!(stats.median_gap_days, Some(121.5));
+        let per_year = stats.releases_per_year.unwrap();
+        assert!((per_year - 1.5).abs() < 0.01, "{}", per_year);
+        let current = stats.current.unwrap();
+        assert_eq!(current.published.as_deref(), Some("2021-03-02 00:00"));
+        assert_eq!(current.age_days, Some(1035));
+        assert_eq!(current.releases_since, 3);
+
+        let undated = release_stats(&[published("1.0", None)], None, now);
+        assert_eq!(undated.releases, 1);
+        assert_eq!(undated.latest, None);
+        assert_eq!(undated.releases_per_year, None);
+        assert_eq!(undated.median_gap_days, None);
+    }
+}
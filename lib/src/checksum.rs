@@ -0,0 +1,310 @@
+use md5::Md5;
+use serde::{Serialize, Serializer};
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha512};
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::{BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum ChecksumAlgorithm {
+    Md5,
+    Sha1,
+    Sha256,
+    Sha512,
+}
+
+impl ChecksumAlgorithm {
+    pub fn all() -> [ChecksumAlgorithm; 4] {
+        [
+            ChecksumAlgorithm::Md5,
+            ChecksumAlgorithm::Sha1,
+            ChecksumAlgorithm::Sha256,
+            ChecksumAlgorithm::Sha512,
+        ]
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ChecksumAlgorithm::Md5 => "md5",
+            ChecksumAlgorithm::Sha1 => "sha1",
+            ChecksumAlgorithm::Sha256 => "sha256",
+            ChecksumAlgorithm::Sha512 => "sha512",
+        }
+    }
+
+    pub fn from_extension(extension: &str) -> Option<ChecksumAlgorithm> {
+        Self::all()
+            .into_iter()
+            .find(|a| a.extension().eq_ignore_ascii_case(extension))
+    }
+
+    pub fn digest_len(&self) -> usize {
+        match self {
+            ChecksumAlgorithm::Md5 => 16,
+            ChecksumAlgorithm::Sha1 => 20,
+            ChecksumAlgorithm::Sha256 => 32,
+            ChecksumAlgorithm::Sha512 => 64,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Error)]
+#[error("Invalid {algorithm:?} checksum '{value}'")]
+pub struct ParseChecksumError {
+    algorithm: ChecksumAlgorithm,
+    value: String,
+}
+
+#[derive(Debug, Clone, Error)]
+#[error("Invalid checksum policy {0}, expected fail, warn or ignore")]
+pub struct ParseChecksumPolicyError(String);
+
+// Maven's <checksumPolicy>: what a mismatch between a download and its published
+// checksum does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChecksumPolicy {
+    #[default]
+    Fail,
+    // the download is kept and the mismatch logged
+    Warn,
+    // published checksums aren't fetched at all
+    Ignore,
+}
+
+impl FromStr for ChecksumPolicy {
+    type Err = ParseChecksumPolicyError;
+
+    fn from_str(input: &str) -> Result<ChecksumPolicy, ParseChecksumPolicyError> {
+        match input {
+            "fail" => Ok(ChecksumPolicy::Fail),
+            "warn" => Ok(ChecksumPolicy::Warn),
+            "ignore" => Ok(ChecksumPolicy::Ignore),
+            _ => Err(ParseChecksumPolicyError(input.to_string())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Checksum {
+    Md5([u8; 16]),
+    Sha1([u8; 20]),
+    Sha256([u8; 32]),
+    Sha512([u8; 64]),
+}
+
+impl Checksum {
+    // Case-insensitive hex, the way sidecar files and verification metadata carry them.
+    pub fn parse(algorithm: ChecksumAlgorithm, hex: &str) -> Result<Checksum, ParseChecksumError> {
+        let error = || ParseChecksumError {
+            algorithm,
+            value: hex.to_string(),
+        };
+        if hex.len() != algorithm.digest_len() * 2 || !hex.is_ascii() {
+            return Err(error());
+        }
+        let bytes = (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+            .collect::<Option<Vec<u8>>>()
+            .ok_or_else(error)?;
+        Ok(Self::from_bytes(algorithm, &bytes))
+    }
+
+    fn from_bytes(algorithm: ChecksumAlgorithm, bytes: &[u8]) -> Checksum {
+        match algorithm {
+            ChecksumAlgorithm::Md5 => Checksum::Md5(bytes.try_into().unwrap()),
+            ChecksumAlgorithm::Sha1 => Checksum::Sha1(bytes.try_into().unwrap()),
+            ChecksumAlgorithm::Sha256 => Checksum::Sha256(bytes.try_into().unwrap()),
+            ChecksumAlgorithm::Sha512 => Checksum::Sha512(bytes.try_into().unwrap()),
+        }
+    }
+
+    pub fn algorithm(&self) -> ChecksumAlgorithm {
+        match self {
+            Checksum::Md5(_) => ChecksumAlgorithm::Md5,
+            Checksum::Sha1(_) => ChecksumAlgorithm::Sha1,
+            Checksum::Sha256(_) => ChecksumAlgorithm::Sha256,
+            Checksum::Sha512(_) => ChecksumAlgorithm::Sha512,
+        }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            Checksum::Md5(bytes) => bytes,
+            Checksum::Sha1(bytes) => bytes,
+            Checksum::Sha256(bytes) => bytes,
+            Checksum::Sha512(bytes) => bytes,
+        }
+    }
+}
+
+impl Display for Checksum {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for byte in self.as_bytes() {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for Checksum {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[derive(Default, Clone)]
+pub struct Digester {
+    md5: Md5,
+    sha1: Sha1,
+    sha256: Sha256,
+    sha512: Sha512,
+}
+
+impl Digester {
+    pub fn new() -> Digester {
+        Digester::default()
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        self.md5.update(data);
+        self.sha1.update(data);
+        self.sha256.update(data);
+        self.sha512.update(data);
+    }
+
+    pub fn finish(self) -> Vec<Checksum> {
+        vec![
+            Checksum::Md5(self.md5.finalize().into()),
+            Checksum::Sha1(self.sha1.finalize().into()),
+            Checksum::Sha256(self.sha256.finalize().into()),
+            Checksum::Sha512(self.sha512.finalize().into()),
+        ]
+    }
+}
+
+impl Write for Digester {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+pub fn digest_file(path: &Path) -> std::io::Result<Vec<Checksum>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut digester = Digester::new();
+    std::io::copy(&mut reader, &mut digester)?;
+    Ok(digester.finish())
+}
+
+pub fn sidecar_path(path: &Path, algorithm: ChecksumAlgorithm) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".");
+    name.push(algorithm.extension());
+    PathBuf::from(name)
+}
+
+pub fn write_sidecars(path: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut written = Vec::new();
+    for checksum in digest_file(path)? {
+        let sidecar = sidecar_path(path, checksum.algorithm());
+        std::fs::write(&sidecar, checksum.to_string())?;
+        written.push(sidecar);
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn digest_known_values() {
+        let mut digester = Digester::new();
+        digester.update(b"abc");
+        let hex: Vec<(ChecksumAlgorithm, String)> = digester
+            .finish()
+            .iter()
+            .map(|c| (c.algorithm(), c.to_string()))
+            .collect();
+        assert_eq!(
+            hex,
+            vec![
+                (
+                    ChecksumAlgorithm::Md5,
+                    String::from("900150983cd24fb0d6963f7d28e17f72")
+                ),
+                (
+                    ChecksumAlgorithm::Sha1,
+                    String::from("a9993e364706816aba3e25717850c26c9cd0d89d")
+                ),
+                (
+                    ChecksumAlgorithm::Sha256,
+                    String::from(
+                        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+                    )
+                ),
+                (
+                    ChecksumAlgorithm::Sha512,
+                    String::from(
+                        "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"
+                    )
+                ),
+            ]
+        )
+    }
+
+    #[test]
+    fn parse_checksums() {
+        let sha1 = Checksum::parse(
+            ChecksumAlgorithm::Sha1,
+            "A9993E364706816ABA3E25717850C26C9CD0D89D",
+        )
+        .unwrap();
+        assert_eq!(sha1.algorithm(), ChecksumAlgorithm::Sha1);
+        assert_eq!(sha1.to_string(), "a9993e364706816aba3e25717850c26c9cd0d89d");
+        assert_eq!(sha1.as_bytes().len(), 20);
+        for invalid in ["a9993e36", "g9993e364706816aba3e25717850c26c9cd0d89d", ""] {
+            assert!(Checksum::parse(ChecksumAlgorithm::Sha1, invalid).is_err());
+        }
+        // an md5 is not a sha1
+        assert!(
+            Checksum::parse(ChecksumAlgorithm::Sha1, "900150983cd24fb0d6963f7d28e17f72").is_err()
+        );
+        assert_eq!(
+            ChecksumAlgorithm::from_extension("SHA512"),
+            Some(ChecksumAlgorithm::Sha512)
+        );
+    }
+
+    #[test]
+    fn parse_checksum_policies() {
+        assert_eq!(
+            ChecksumPolicy::from_str("warn").unwrap(),
+            ChecksumPolicy::Warn
+        );
+        assert_eq!(
+            ChecksumPolicy::from_str("ignore").unwrap(),
+            ChecksumPolicy::Ignore
+        );
+        assert_eq!(ChecksumPolicy::default(), ChecksumPolicy::Fail);
+        assert!(ChecksumPolicy::from_str("strict").is_err());
+    }
+
+    #[test]
+    fn sidecar_path_appends_extension() {
+        let path = Path::new("dir/artifact-1.0.jar");
+        assert_eq!(
+            sidecar_path(path, ChecksumAlgorithm::Sha1),
+            PathBuf::from("dir/artifact-1.0.jar.sha1")
+        )
+    }
+}
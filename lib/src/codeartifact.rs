@@ -0,0 +1,244 @@
+use crate::Repository;
+use crate::auth::{Auth, CredentialsProvider, Refresh, RefreshingToken, Token};
+use crate::deploy::from_civil;
+use std::process::Stdio;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+use url::Url;
+
+#[derive(Debug, Error)]
+pub enum CodeArtifactError {
+    #[error("Failed to run {executable}: {source}")]
+    Spawn {
+        executable: String,
+        source: std::io::Error,
+    },
+    #[error("Fetching CodeArtifact token failed with {status}: {stderr}")]
+    Failed {
+        status: std::process::ExitStatus,
+        stderr: String,
+    },
+}
+
+// Tokens come from `aws codeartifact get-authorization-token`, so the aws CLI has to be on
+// PATH (or set with CodeArtifact::with_executable) and configured for the domain owner.
+#[derive(Clone, Debug)]
+pub struct CodeArtifact {
+    domain: String,
+    domain_owner: String,
+    region: String,
+    repository: String,
+    executable: String,
+    duration: Duration,
+}
+
+impl CodeArtifact {
+    pub fn new(domain: String, domain_owner: String, region: String, repository: String) -> Self {
+        CodeArtifact {
+            domain,
+            domain_owner,
+            region,
+            repository,
+            executable: String::from("aws"),
+            duration: Duration::from_secs(12 * 60 * 60),
+        }
+    }
+
+    pub fn with_executable(mut self, executable: String) -> CodeArtifact {
+        self.executable = executable;
+        self
+    }
+
+    pub fn with_duration(mut self, duration: Duration) -> CodeArtifact {
+        self.duration = duration;
+        self
+    }
+
+    pub fn host(&self) -> String {
+        format!(
+            "{}-{}.d.codeartifact.{}.amazonaws.com",
+            self.domain, self.domain_owner, self.region
+        )
+    }
+
+    pub fn url(&self) -> Result<Url, url::ParseError> {
+        Url::parse(&format!(
+            "https://{}/maven/{}/",
+            self.host(),
+            self.repository
+        ))
+    }
+
+    pub fn repository(&self) -> Result<Repository, url::ParseError> {
+        self.url().map(Repository::both)
+    }
+
+    // The token is valid until the expiration the CLI reports, or for the requested duration
+    // when that can't be read
+    pub async fn token(&self) -> Result<Token, CodeArtifactError> {
+        let output = tokio::process::Command::new(&self.executable)
+            .args(["codeartifact", "get-authorization-token"])
+            .arg("--domain")
+            .arg(&self.domain)
+            .arg("--domain-owner")
+            .arg(&self.domain_owner)
+            .arg("--region")
+            .arg(&self.region)
+            .arg("--duration-seconds")
+            .arg(self.duration.as_secs().to_string())
+            .args([
+                "--query",
+                "[authorizationToken,expiration]",
+                "--output",
+                "text",
+            ])
+            .stdin(Stdio::null())
+            .kill_on_drop(true)
+            .output()
+            .await
+            .map_err(|source| CodeArtifactError::Spawn {
+                executable: self.executable.clone(),
+                source,
+            })?;
+        if output.status.success() {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            let (token, expiration) = match stdout.trim().split_once('\t') {
+                Some((token, expiration)) => (token, parse_expiration(expiration.trim())),
+                None => (stdout.trim(), None),
+            };
+            let ttl = match expiration {
+                Some(at) => at.duration_since(SystemTime::now()).unwrap_or_default(),
+                None => self.duration,
+            };
+            Ok(Token::new(token).expires_in(ttl))
+        } else {
+            Err(CodeArtifactError::Failed {
+                status: output.status,
+                stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            })
+        }
+    }
+
+    pub fn credentials(&self) -> CodeArtifactCredentials {
+        let this = self.clone();
+        CodeArtifactCredentials {
+            token: RefreshingToken::new(self.host(), move || {
+                let this = this.clone();
+                async move { Ok(this.token().await?) }
+            }),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct CodeArtifactCredentials {
+    token: RefreshingToken,
+}
+
+impl CredentialsProvider for CodeArtifactCredentials {
+    fn credentials(&self, url: &Url) -> Option<Auth> {
+        match self.token.credentials(url)? {
+            Auth::Bearer(token) => Some(Auth::Basic {
+                username: String::from("aws"),
+                password: token,
+            }),
+            other => Some(other),
+        }
+    }
+
+    fn invalidate(&self, url: &Url) {
+        self.token.invalidate(url);
+    }
+
+    fn refresh<'a>(&'a self, url: &'a Url) -> Refresh<'a> {
+        self.token.refresh(url)
+    }
+}
+
+// aws CLI v2 prints 2024-01-02T03:04:05+01:00, v1 seconds since the epoch
+fn parse_expiration(value: &str) -> Option<SystemTime> {
+    if let Ok(secs) = value.parse::<f64>() {
+        return (secs >= 0.0).then(|| UNIX_EPOCH + Duration::from_secs_f64(secs));
+    }
+    let (date, time) = value.split_once('T')?;
+    let mut date = date.splitn(3, '-').map(|n| n.parse::<i64>().ok());
+    let (year, month, day) = (date.next()??, date.next()??, date.next()??);
+    let (time, offset) = match time.find(['Z', '+', '-']) {
+        Some(i) => time.split_at(i),
+        None => (time, "Z"),
+    };
+    let mut time = time
+        .split('.')
+        .next()?
+        .splitn(3, ':')
+        .map(|n| n.parse::<u64>().ok());
+    let secs = time.next()?? * 3600 + time.next()?? * 60 + time.next().flatten().unwrap_or(0);
+    let at = from_civil(year, month, day, secs)?;
+    if offset == "Z" {
+        return Some(at);
+    }
+    let (sign, offset) = offset.split_at(1);
+    let (hours, minutes) = offset.split_once(':').unwrap_or((offset, "0"));
+    let offset =
+        Duration::from_secs(hours.parse::<u64>().ok()? * 3600 + minutes.parse::<u64>().ok()? * 60);
+    // local time ahead of UTC by the offset
+    match sign {
+        "+" => at.checked_sub(offset),
+        _ => at.checked_add(offset),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> CodeArtifact {
+        CodeArtifact::new(
+            String::from("acme"),
+            String::from("123456789012"),
+            String::from("eu-west-1"),
+            String::from("releases"),
+        )
+    }
+
+    #[test]
+    fn endpoint() {
+        assert_eq!(
+            example().url().unwrap().as_str(),
+            "https://acme-123456789012.d.codeartifact.eu-west-1.amazonaws.com/maven/releases/"
+        );
+    }
+
+    #[test]
+    fn expiration() {
+        let at = |secs: u64| Some(UNIX_EPOCH + Duration::from_secs(secs));
+        assert_eq!(parse_expiration("1704164645"), at(1704164645));
+        assert_eq!(parse_expiration("2024-01-02T03:04:05Z"), at(1704164645));
+        assert_eq!(
+            parse_expiration("2024-01-02T04:04:05.123+01:00"),
+            at(1704164645)
+        );
+        assert_eq!(
+            parse_expiration("2024-01-01T22:04:05-05:00"),
+            at(1704164645)
+        );
+        assert_eq!(parse_expiration("tomorrow"), None);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn credentials_scoped_to_host() {
+        let codeartifact = example().with_executable(String::from("echo"));
+        let credentials = codeartifact.credentials();
+        let url = codeartifact.url().unwrap().join("com/example").unwrap();
+        credentials.refresh(&url).await;
+        let Some(Auth::Basic { username, password }) = credentials.credentials(&url) else {
+            panic!("expected basic credentials");
+        };
+        assert_eq!(username, "aws");
+        assert!(password.starts_with("codeartifact get-authorization-token --domain acme"));
+        let other = Url::parse("https://repo1.maven.org/maven2/").unwrap();
+        credentials.refresh(&other).await;
+        assert_eq!(credentials.credentials(&other), None);
+    }
+}
@@ -0,0 +1,293 @@
+use crate::checksum::Checksum;
+use crate::deploy::{civil, from_civil};
+use reqwest::header::{
+    ETAG, HeaderMap, HeaderValue, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED,
+};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// The ETag and Last-Modified a server sent with a file. Sent back as If-None-Match and
+// If-Modified-Since when the file is fetched again, so an unchanged one is answered with
+// 304 Not Modified and no body.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Validators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+impl Validators {
+    pub fn from_headers(headers: &HeaderMap) -> Validators {
+        let value = |name| {
+            headers
+                .get(name)
+                .and_then(|v: &HeaderValue| v.to_str().ok())
+                .map(String::from)
+        };
+        Validators {
+            etag: value(ETAG),
+            last_modified: value(LAST_MODIFIED),
+        }
+    }
+
+    // for a file written at `time` whose headers weren't kept, since it can't be older
+    // than the copy on the server was then
+    pub fn modified_at(time: SystemTime) -> Validators {
+        Validators {
+            etag: None,
+            last_modified: Some(http_date(time)),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+
+    pub fn headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in [
+            (IF_NONE_MATCH, &self.etag),
+            (IF_MODIFIED_SINCE, &self.last_modified),
+        ] {
+            if let Some(value) = value.as_deref().and_then(|v| HeaderValue::from_str(v).ok()) {
+                headers.insert(name, value);
+            }
+        }
+        headers
+    }
+
+    // kept next to the file they validate, one `name=value` per line
+    pub fn sidecar(file: &Path) -> PathBuf {
+        let mut name = file.as_os_str().to_owned();
+        name.push(".validators");
+        PathBuf::from(name)
+    }
+
+    pub fn load(file: &Path) -> Option<Validators> {
+        Some(Self::parse(
+            &std::fs::read_to_string(Self::sidecar(file)).ok()?,
+        ))
+    }
+
+    fn parse(content: &str) -> Validators {
+        let mut validators = Validators::default();
+        for (name, value) in content.lines().filter_map(|l| l.split_once('=')) {
+            match name {
+                "etag" => validators.etag = Some(value.to_string()),
+                "last-modified" => validators.last_modified = Some(value.to_string()),
+                _ => {}
+            }
+        }
+        validators
+    }
+
+    fn lines(&self) -> String {
+        let mut content = String::new();
+        if let Some(etag) = &self.etag {
+            content.push_str(&format!("etag={}\n", etag));
+        }
+        if let Some(last_modified) = &self.last_modified {
+            content.push_str(&format!("last-modified={}\n", last_modified));
+        }
+        content
+    }
+
+    // a file without validators loses any stale sidecar
+    pub fn store(&self, file: &Path) -> std::io::Result<()> {
+        let sidecar = Self::sidecar(file);
+        if self.is_empty() {
+            return match std::fs::remove_file(sidecar) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+                _ => Ok(()),
+            };
+        }
+        std::fs::write(sidecar, self.lines())
+    }
+}
+
+// Validators of downloaded files, kept in a cache of their own rather than next to the files
+// in whatever directory they were downloaded to. An entry is named after a hash of the file's
+// path and records the size and modification time the download left it with, so a file
+// changed since is fetched in full.
+#[derive(Debug, Clone)]
+pub struct ValidatorCache {
+    pub root: PathBuf,
+}
+
+impl ValidatorCache {
+    pub fn new(root: impl Into<PathBuf>) -> ValidatorCache {
+        ValidatorCache { root: root.into() }
+    }
+
+    // maven-artifact/validators in the user's cache directory
+    pub fn from_env() -> Option<ValidatorCache> {
+        let cache = match std::env::var_os("XDG_CACHE_HOME") {
+            Some(dir) => PathBuf::from(dir),
+            None => std::env::home_dir()?.join(".cache"),
+        };
+        Some(ValidatorCache::new(
+            cache.join("maven-artifact").join("validators"),
+        ))
+    }
+
+    fn entry(&self, file: &Path) -> PathBuf {
+        let digest = Sha256::digest(file.as_os_str().as_encoded_bytes());
+        self.root.join(Checksum::Sha256(digest.into()).to_string())
+    }
+
+    // none when the file wasn't written by a download that had validators, or was since
+    pub fn load(&self, file: &Path) -> Option<Validators> {
+        let file = std::path::absolute(file).ok()?;
+        let content = std::fs::read_to_string(self.entry(&file)).ok()?;
+        let mut lines = content.splitn(3, '\n');
+        if lines.next()? != file.to_string_lossy() || lines.next()? != stamp(&file)? {
+            return None;
+        }
+        Some(Validators::parse(lines.next()?)).filter(|v| !v.is_empty())
+    }
+
+    // called once `file` is written, an empty one removes the entry
+    pub fn store(&self, file: &Path, validators: &Validators) -> std::io::Result<()> {
+        let file = std::path::absolute(file)?;
+        let entry = self.entry(&file);
+        let stamp = stamp(&file);
+        if validators.is_empty() || stamp.is_none() {
+            return match std::fs::remove_file(entry) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+                _ => Ok(()),
+            };
+        }
+        std::fs::create_dir_all(&self.root)?;
+        let content = format!(
+            "{}\n{}\n{}",
+            file.to_string_lossy(),
+            stamp.unwrap_or_default(),
+            validators.lines()
+        );
+        std::fs::write(entry, content)
+    }
+}
+
+// size and modification time, which any rewrite of a file changes
+fn stamp(file: &Path) -> Option<String> {
+    let metadata = std::fs::metadata(file).ok()?;
+    let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    Some(format!("{} {}", metadata.len(), modified.as_nanos()))
+}
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+// IMF-fixdate, like Sun, 06 Nov 1994 08:49:37 GMT
+pub fn http_date(time: SystemTime) -> String {
+    const DAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+    let days = time
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() / 86400)
+        .unwrap_or_default();
+    let (year, month, day, rem) = civil(time);
+    format!(
+        "{}, {:02} {} {:04} {:02}:{:02}:{:02} GMT",
+        DAYS[(days % 7) as usize],
+        day,
+        MONTHS[(month - 1) as usize],
+        year,
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    )
+}
+
+// IMF-fixdate only, the obsolete formats servers no longer send are not understood
+pub fn parse_http_date(value: &str) -> Option<SystemTime> {
+    let (_, date) = value.trim().split_once(", ")?;
+    let [day, month, year, time, "GMT"] = date.split_whitespace().collect::<Vec<_>>()[..] else {
+        return None;
+    };
+    let month = MONTHS.iter().position(|m| *m == month)? as i64 + 1;
+    let mut clock = time.split(':').map(str::parse::<u64>);
+    let (Some(Ok(hours)), Some(Ok(minutes)), Some(Ok(seconds)), None) =
+        (clock.next(), clock.next(), clock.next(), clock.next())
+    else {
+        return None;
+    };
+    from_civil(
+        year.parse().ok()?,
+        month,
+        day.parse().ok()?,
+        hours * 3600 + minutes * 60 + seconds,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn format_http_dates() {
+        let at = |secs: u64| http_date(UNIX_EPOCH + Duration::from_secs(secs));
+        assert_eq!(at(0), "Thu, 01 Jan 1970 00:00:00 GMT");
+        assert_eq!(at(784111777), "Sun, 06 Nov 1994 08:49:37 GMT");
+        for secs in [0, 784111777, 951782400, 1704164645] {
+            assert_eq!(
+                parse_http_date(&at(secs)),
+                Some(UNIX_EPOCH + Duration::from_secs(secs))
+            );
+        }
+        assert_eq!(parse_http_date("Sunday, 06-Nov-94 08:49:37 GMT"), None);
+        assert_eq!(parse_http_date("Sun, 06 Nov 1994 08:49 GMT"), None);
+    }
+
+    #[test]
+    fn store_and_send() {
+        let dir =
+            std::env::temp_dir().join(format!("maven-artifact-validators-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("maven-metadata-central.xml");
+        let validators = Validators {
+            etag: Some(String::from("\"5f2b-1a\"")),
+            last_modified: Some(String::from("Sun, 06 Nov 1994 08:49:37 GMT")),
+        };
+        validators.store(&file).unwrap();
+        assert_eq!(Validators::load(&file), Some(validators.clone()));
+        let headers = validators.headers();
+        assert_eq!(headers[IF_NONE_MATCH], "\"5f2b-1a\"");
+        assert_eq!(headers[IF_MODIFIED_SINCE], "Sun, 06 Nov 1994 08:49:37 GMT");
+
+        Validators::default().store(&file).unwrap();
+        assert_eq!(Validators::load(&file), None);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn cache_download_validators() {
+        let dir = std::env::temp_dir().join(format!(
+            "maven-artifact-validator-cache-{}",
+            std::process::id()
+        ));
+        let cache = ValidatorCache::new(dir.join("cache"));
+        let downloads = dir.join("downloads");
+        std::fs::create_dir_all(&downloads).unwrap();
+        let file = downloads.join("lib-1.0.jar");
+        let validators = Validators {
+            etag: Some(String::from("\"v1\"")),
+            last_modified: None,
+        };
+        assert_eq!(cache.load(&file), None);
+        std::fs::write(&file, "jar").unwrap();
+        cache.store(&file, &validators).unwrap();
+        assert_eq!(cache.load(&file), Some(validators.clone()));
+        // nothing is written where the file was downloaded to
+        assert_eq!(std::fs::read_dir(&downloads).unwrap().count(), 1);
+
+        std::fs::write(&file, "changed").unwrap();
+        assert_eq!(cache.load(&file), None);
+        cache.store(&file, &validators).unwrap();
+        cache.store(&file, &Validators::default()).unwrap();
+        assert_eq!(cache.load(&file), None);
+        assert_eq!(std::fs::read_dir(dir.join("cache")).unwrap().count(), 0);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
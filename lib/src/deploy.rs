@@ -0,0 +1,640 @@
+use crate::artifact::{Artifact, PartialArtifact};
+use crate::auth::{Auth, CredentialsChain, CredentialsProvider, RepositoryCredentials};
+use crate::checksum::{Digester, write_sidecars};
+use crate::metadata::{MetadataError, VersionedMetadata};
+use crate::paths::UnsafePath;
+use crate::pom::PomError;
+use crate::{Classifier, Repository, Version};
+use reqwest::header::{AUTHORIZATION, InvalidHeaderValue};
+use reqwest::{Client, Method, RequestBuilder, Response, StatusCode};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+use tracing::{debug, info};
+use url::Url;
+
+#[derive(Debug, Error)]
+pub enum DeployError {
+    #[error("Unable to write {path}: {source}")]
+    IO {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Metadata error: {0}")]
+    Metadata(#[from] MetadataError),
+    #[error("{0} is already deployed with different content")]
+    Immutable(PathBuf),
+    #[error("POM error: {0}")]
+    Pom(#[from] PomError),
+    #[error("Error using reqwest {0}")]
+    Reqwest(#[from] reqwest::Error),
+    #[error("Failed to parse url {0}")]
+    Url(#[from] url::ParseError),
+    #[error("Invalid credentials {0}")]
+    InvalidCredentials(#[from] InvalidHeaderValue),
+    #[error("Http error, url={url}, status={status}")]
+    Rejected { url: Url, status: u16 },
+    #[error("Repository policy of {url} does not allow {version}")]
+    PolicyViolation { url: Url, version: Version },
+    #[error(transparent)]
+    UnsafePath(#[from] UnsafePath),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeployFile {
+    pub classifier: Option<Classifier>,
+    pub extension: String,
+    pub source: PathBuf,
+}
+
+// A repository on the local file system, such as a staging directory that is reviewed
+// before being synced to the real repository.
+#[derive(Debug, Clone)]
+pub struct StagingRepository {
+    pub root: PathBuf,
+}
+
+impl StagingRepository {
+    pub fn new(root: impl Into<PathBuf>) -> StagingRepository {
+        StagingRepository { root: root.into() }
+    }
+
+    // Copies the files into repository layout next to their checksums and updates both
+    // maven-metadata.xml files. Snapshot files get the timestamped name of a new build;
+    // `timestamp` is yyyyMMdd.HHmmss in UTC. Returns every file written, relative to root.
+    pub fn deploy(
+        &self,
+        artifact: &Artifact,
+        files: &[DeployFile],
+        timestamp: &str,
+    ) -> Result<Vec<PathBuf>, DeployError> {
+        let version_dir = PathBuf::from(artifact.path());
+        let mut written = vec![];
+        let resolved = if artifact.is_snapshot() {
+            let path = version_dir.join("maven-metadata.xml");
+            let mut metadata = self.read_metadata(&path)?.unwrap_or_else(|| {
+                VersionedMetadata::for_snapshot(
+                    artifact.group_id.clone(),
+                    artifact.artifact_id.clone(),
+                    artifact.version.clone(),
+                )
+            });
+            let deployed: Vec<(Option<Classifier>, String)> = files
+                .iter()
+                .map(|f| (f.classifier.clone(), f.extension.clone()))
+                .collect();
+            let resolved = metadata.merge_snapshot_build(timestamp, &deployed)?;
+            self.write_metadata(&path, &metadata, &mut written)?;
+            resolved
+        } else {
+            artifact.version.clone()
+        };
+
+        let targets: Vec<(&DeployFile, PathBuf)> = files
+            .iter()
+            .map(|file| (file, version_dir.join(file_name(artifact, &resolved, file))))
+            .collect();
+        // releases never change once deployed, so check everything before copying anything
+        if !artifact.is_snapshot() {
+            for (file, target) in &targets {
+                let existing = self.root.join(target);
+                if existing.is_file() && !same_content(&file.source, &existing)? {
+                    return Err(DeployError::Immutable(target.clone()));
+                }
+            }
+        }
+        for (file, target) in targets {
+            written.extend(self.store(&target, &file.source)?);
+        }
+        let coordinates =
+            PartialArtifact::new(artifact.group_id.clone(), artifact.artifact_id.clone());
+        written.extend(self.add_versions(
+            &coordinates,
+            std::slice::from_ref(&artifact.version),
+            timestamp,
+        )?);
+        Ok(written)
+    }
+
+    // Copies `source` to `target`, relative to root, replacing what was there, and writes
+    // its checksums. Returns the files written, relative to root.
+    pub fn store(&self, target: &Path, source: &Path) -> Result<Vec<PathBuf>, DeployError> {
+        let path = self.root.join(target);
+        self.create_parent(&path)?;
+        std::fs::copy(source, &path).map_err(|e| DeployError::IO {
+            path: source.to_path_buf(),
+            source: e,
+        })?;
+        let mut written = vec![target.to_path_buf()];
+        written.extend(self.sidecars(&path)?);
+        Ok(written)
+    }
+
+    pub fn metadata(
+        &self,
+        artifact: &PartialArtifact,
+    ) -> Result<Option<VersionedMetadata>, DeployError> {
+        self.read_metadata(&Self::metadata_path(artifact))
+    }
+
+    // Lists the versions in the artifact's maven-metadata.xml, creating it when needed.
+    pub fn add_versions(
+        &self,
+        artifact: &PartialArtifact,
+        versions: &[Version],
+        timestamp: &str,
+    ) -> Result<Vec<PathBuf>, DeployError> {
+        let path = Self::metadata_path(artifact);
+        let mut metadata = self
+            .read_metadata(&path)?
+            .unwrap_or_else(|| VersionedMetadata {
+                group_id: artifact.group_id.clone(),
+                artifact_id: artifact.artifact_id.clone(),
+                version: None,
+                versioning: Default::default(),
+            });
+        add_versions(&mut metadata, versions, timestamp);
+        let mut written = vec![];
+        self.write_metadata(&path, &metadata, &mut written)?;
+        Ok(written)
+    }
+
+    fn metadata_path(artifact: &PartialArtifact) -> PathBuf {
+        Path::new(&artifact.path()).join("maven-metadata.xml")
+    }
+
+    fn read_metadata(&self, path: &Path) -> Result<Option<VersionedMetadata>, DeployError> {
+        let path = self.root.join(path);
+        match std::fs::read_to_string(&path) {
+            Ok(content) => Ok(Some(VersionedMetadata::from_str(&content)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(source) => Err(DeployError::IO { path, source }),
+        }
+    }
+
+    fn write_metadata(
+        &self,
+        path: &Path,
+        metadata: &VersionedMetadata,
+        written: &mut Vec<PathBuf>,
+    ) -> Result<(), DeployError> {
+        let full = self.root.join(path);
+        self.create_parent(&full)?;
+        let file = File::create(&full).map_err(|source| DeployError::IO {
+            path: full.clone(),
+            source,
+        })?;
+        metadata.write(file)?;
+        written.push(path.to_path_buf());
+        written.extend(self.sidecars(&full)?);
+        Ok(())
+    }
+
+    fn create_parent(&self, path: &Path) -> Result<(), DeployError> {
+        let parent = path.parent().unwrap_or(&self.root);
+        std::fs::create_dir_all(parent).map_err(|source| DeployError::IO {
+            path: parent.to_path_buf(),
+            source,
+        })
+    }
+
+    fn sidecars(&self, path: &Path) -> Result<Vec<PathBuf>, DeployError> {
+        let written = write_sidecars(path).map_err(|source| DeployError::IO {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Ok(written
+            .into_iter()
+            .map(|p| {
+                p.strip_prefix(&self.root)
+                    .map(Path::to_path_buf)
+                    .unwrap_or(p)
+            })
+            .collect())
+    }
+}
+
+// Uploads to a remote repository with PUT, like maven-deploy-plugin: every file gets its
+// checksums next to it, and maven-metadata.xml is downloaded, updated and uploaded again
+// once the files are in place.
+#[derive(Debug, Clone)]
+pub struct Deployer {
+    client: Client,
+    repository: Repository,
+    credentials: Arc<dyn CredentialsProvider>,
+}
+
+impl Deployer {
+    pub fn new(client: Client, repository: Repository) -> Deployer {
+        Deployer {
+            client,
+            credentials: Arc::new(
+                CredentialsChain::new()
+                    .with(RepositoryCredentials::new([&repository]))
+                    .with(CredentialsChain::standard_for(std::slice::from_ref(
+                        &repository,
+                    ))),
+            ),
+            repository,
+        }
+    }
+
+    // asked when the repository has no credentials of its own
+    pub fn credentials<P: CredentialsProvider + 'static>(self, provider: P) -> Deployer {
+        self.shared_credentials(Arc::new(provider))
+    }
+
+    pub(crate) fn shared_credentials(mut self, provider: Arc<dyn CredentialsProvider>) -> Deployer {
+        self.credentials = Arc::new(
+            CredentialsChain::new()
+                .with(RepositoryCredentials::new([&self.repository]))
+                .with_shared(provider),
+        );
+        self
+    }
+
+    pub fn repository(&self) -> &Repository {
+        &self.repository
+    }
+
+    // Like StagingRepository::deploy, returning the url of every file uploaded
+    pub async fn deploy(
+        &self,
+        artifact: &Artifact,
+        files: &[DeployFile],
+        timestamp: &str,
+    ) -> Result<Vec<Url>, DeployError> {
+        let allowed = if artifact.is_snapshot() {
+            self.repository.snapshots
+        } else {
+            self.repository.releases
+        };
+        if !allowed {
+            return Err(DeployError::PolicyViolation {
+                url: self.repository.url.clone(),
+                version: artifact.version.clone(),
+            });
+        }
+        let version_dir = artifact.path();
+        let mut snapshot_metadata = None;
+        let resolved = if artifact.is_snapshot() {
+            let path = format!("{}/maven-metadata.xml", version_dir);
+            let mut metadata = self.metadata(&path).await?.unwrap_or_else(|| {
+                VersionedMetadata::for_snapshot(
+                    artifact.group_id.clone(),
+                    artifact.artifact_id.clone(),
+                    artifact.version.clone(),
+                )
+            });
+            // some repository managers leave out <version> in version-level metadata
+            metadata
+                .version
+                .get_or_insert_with(|| artifact.version.clone());
+            let deployed: Vec<(Option<Classifier>, String)> = files
+                .iter()
+                .map(|f| (f.classifier.clone(), f.extension.clone()))
+                .collect();
+            let resolved = metadata.merge_snapshot_build(timestamp, &deployed)?;
+            snapshot_metadata = Some((path, metadata));
+            resolved
+        } else {
+            artifact.version.clone()
+        };
+
+        let mut uploaded = vec![];
+        for file in files {
+            let content = std::fs::read(&file.source).map_err(|source| DeployError::IO {
+                path: file.source.clone(),
+                source,
+            })?;
+            let path = format!("{}/{}", version_dir, file_name(artifact, &resolved, file));
+            uploaded.extend(self.upload(&path, content).await?);
+        }
+        if let Some((path, metadata)) = snapshot_metadata {
+            uploaded.extend(self.upload(&path, metadata.to_xml()?.into_bytes()).await?);
+        }
+        let coordinates =
+            PartialArtifact::new(artifact.group_id.clone(), artifact.artifact_id.clone());
+        let path = format!("{}/maven-metadata.xml", coordinates.path());
+        let mut metadata = self
+            .metadata(&path)
+            .await?
+            .unwrap_or_else(|| VersionedMetadata {
+                group_id: artifact.group_id.clone(),
+                artifact_id: artifact.artifact_id.clone(),
+                version: None,
+                versioning: Default::default(),
+            });
+        add_versions(
+            &mut metadata,
+            std::slice::from_ref(&artifact.version),
+            timestamp,
+        );
+        uploaded.extend(self.upload(&path, metadata.to_xml()?.into_bytes()).await?);
+        Ok(uploaded)
+    }
+
+    // PUTs the content and its checksums to `path`, relative to the repository
+    pub async fn upload(&self, path: &str, content: Vec<u8>) -> Result<Vec<Url>, DeployError> {
+        let mut digester = Digester::new();
+        digester.update(&content);
+        let mut uploaded = vec![self.put(path, content).await?];
+        for checksum in digester.finish() {
+            let sidecar = format!("{}.{}", path, checksum.algorithm().extension());
+            uploaded.push(
+                self.put(&sidecar, checksum.to_string().into_bytes())
+                    .await?,
+            );
+        }
+        Ok(uploaded)
+    }
+
+    async fn metadata(&self, path: &str) -> Result<Option<VersionedMetadata>, DeployError> {
+        let url = self.url(path)?;
+        let response = self.send(Method::GET, &url, None).await?;
+        match response.status() {
+            StatusCode::NOT_FOUND => Ok(None),
+            status if status.is_success() => {
+                Ok(Some(VersionedMetadata::from_str(&response.text().await?)?))
+            }
+            status => Err(DeployError::Rejected {
+                url,
+                status: status.as_u16(),
+            }),
+        }
+    }
+
+    async fn put(&self, path: &str, content: Vec<u8>) -> Result<Url, DeployError> {
+        let url = self.url(path)?;
+        info!(%url, "uploading");
+        let response = self.send(Method::PUT, &url, Some(content)).await?;
+        if response.status().is_success() {
+            Ok(url)
+        } else {
+            Err(DeployError::Rejected {
+                url,
+                status: response.status().as_u16(),
+            })
+        }
+    }
+
+    fn url(&self, path: &str) -> Result<Url, url::ParseError> {
+        let base = &self.repository.url;
+        base.join(&format!("{}/{}", base.path(), path))
+    }
+
+    fn request(
+        &self,
+        method: Method,
+        url: &Url,
+        auth: Option<&Auth>,
+        content: Option<&Vec<u8>>,
+    ) -> Result<RequestBuilder, DeployError> {
+        let mut request = self.client.request(method, url.clone());
+        if let Some(auth) = auth {
+            request = request.header(AUTHORIZATION, auth.header_value()?);
+        }
+        if let Some(content) = content {
+            request = request.body(content.clone());
+        }
+        Ok(request)
+    }
+
+    async fn send(
+        &self,
+        method: Method,
+        url: &Url,
+        content: Option<Vec<u8>>,
+    ) -> Result<Response, DeployError> {
+        self.credentials.refresh(url).await;
+        let auth = self.credentials.credentials(url);
+        let response = self
+            .request(method.clone(), url, auth.as_ref(), content.as_ref())?
+            .send()
+            .await?;
+        if response.status() == StatusCode::UNAUTHORIZED {
+            // credentials may have expired, retry once if the provider hands out new ones
+            self.credentials.invalidate(url);
+            self.credentials.refresh(url).await;
+            let refreshed = self.credentials.credentials(url);
+            if refreshed.is_some() && refreshed != auth {
+                debug!(%url, "retrying with refreshed credentials");
+                return Ok(self
+                    .request(method, url, refreshed.as_ref(), content.as_ref())?
+                    .send()
+                    .await?);
+            }
+        }
+        Ok(response)
+    }
+}
+
+fn file_name(artifact: &Artifact, resolved: &Version, file: &DeployFile) -> String {
+    let classifier = match &file.classifier {
+        Some(c) => format!("-{}", c),
+        None => String::new(),
+    };
+    format!(
+        "{}-{}{}.{}",
+        artifact.artifact_id, resolved, classifier, file.extension
+    )
+}
+
+fn same_content(a: &Path, b: &Path) -> Result<bool, DeployError> {
+    let read = |path: &Path| {
+        std::fs::read(path).map_err(|source| DeployError::IO {
+            path: path.to_path_buf(),
+            source,
+        })
+    };
+    Ok(read(a)? == read(b)?)
+}
+
+pub(crate) fn add_versions(metadata: &mut VersionedMetadata, added: &[Version], timestamp: &str) {
+    let versioning = &mut metadata.versioning;
+    let versions = versioning.versions.get_or_insert_default();
+    for version in added {
+        if !versions.contains(version) {
+            versions.push(version.clone());
+        }
+    }
+    let newest = |snapshots: bool| {
+        versions
+            .iter()
+            .filter(|v| snapshots || !v.is_snapshot())
+            .max_by(|a, b| a.compare(b))
+            .cloned()
+    };
+    versioning.latest = newest(true);
+    versioning.release = newest(false);
+    versioning.last_updated = Some(timestamp.replacen('.', "", 1));
+}
+
+// yyyyMMdd.HHmmss in UTC, the form snapshot builds are stamped with
+pub fn timestamp(time: SystemTime) -> String {
+    let (year, month, day, rem) = civil(time);
+    format!(
+        "{:04}{:02}{:02}.{:02}{:02}{:02}",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    )
+}
+
+// year, month, day and seconds into the day in UTC
+pub(crate) fn civil(time: SystemTime) -> (i64, i64, i64, u64) {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let (days, rem) = ((secs / 86400) as i64, secs % 86400);
+    // civil date from days since 1970-01-01, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day, rem)
+}
+
+// the inverse of civil, None before 1970
+pub(crate) fn from_civil(year: i64, month: i64, day: i64, secs: u64) -> Option<SystemTime> {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = u64::try_from(era * 146097 + doe - 719468).ok()?;
+    Some(UNIX_EPOCH + Duration::from_secs(days * 86400 + secs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn format_timestamp() {
+        let at = |secs: u64| timestamp(UNIX_EPOCH + Duration::from_secs(secs));
+        assert_eq!(at(0), "19700101.000000");
+        assert_eq!(at(1704164645), "20240102.030405");
+        assert_eq!(at(951868799), "20000229.235959");
+    }
+
+    #[test]
+    fn deployer_urls() {
+        let deployer = Deployer::new(
+            Client::new(),
+            Repository::parse("https://repo.example.org/releases").unwrap(),
+        )
+        .credentials(CredentialsChain::new());
+        assert_eq!(
+            deployer
+                .url("org/example/lib/1.0/lib-1.0.jar")
+                .unwrap()
+                .as_str(),
+            "https://repo.example.org/releases/org/example/lib/1.0/lib-1.0.jar"
+        );
+        let artifact = Artifact::parse("org.example:lib:1.0-SNAPSHOT").unwrap();
+        let sources = DeployFile {
+            classifier: Some(Classifier::from("sources")),
+            extension: String::from("jar"),
+            source: PathBuf::from("lib-sources.jar"),
+        };
+        assert_eq!(
+            file_name(&artifact, &Version::from("1.0-20240102.030405-1"), &sources),
+            "lib-1.0-20240102.030405-1-sources.jar"
+        );
+    }
+
+    #[test]
+    fn deployer_follows_repository_policy() {
+        use futures_util::FutureExt;
+        let url = Url::parse("https://repo.example.org/releases").unwrap();
+        let deployer = Deployer::new(Client::new(), Repository::releases(url))
+            .credentials(CredentialsChain::new());
+        let snapshot = Artifact::parse("org.example:lib:1.0-SNAPSHOT").unwrap();
+        // refused before anything is sent
+        let result = deployer
+            .deploy(&snapshot, &[], "20240102.030405")
+            .now_or_never()
+            .expect("no request made");
+        assert!(
+            matches!(result, Err(DeployError::PolicyViolation { ref version, .. }) if *version == snapshot.version),
+            "{:?}",
+            result
+        );
+    }
+
+    #[test]
+    fn deploy_to_staging() {
+        let dir = std::env::temp_dir().join(format!("maven-artifact-stage-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let jar = dir.join("lib.jar");
+        std::fs::write(&jar, b"jar").unwrap();
+        let pom = dir.join("pom.xml");
+        std::fs::write(&pom, b"<project/>").unwrap();
+        let files = vec![
+            DeployFile {
+                classifier: None,
+                extension: String::from("jar"),
+                source: jar.clone(),
+            },
+            DeployFile {
+                classifier: None,
+                extension: String::from("pom"),
+                source: pom,
+            },
+        ];
+        let staging = StagingRepository::new(dir.join("repo"));
+
+        let snapshot = Artifact::parse("org.example:lib:1.0-SNAPSHOT").unwrap();
+        staging
+            .deploy(&snapshot, &files, "20240102.030405")
+            .unwrap();
+        let written = staging
+            .deploy(&snapshot, &files, "20240103.030405")
+            .unwrap();
+        assert!(written.contains(&PathBuf::from(
+            "org/example/lib/1.0-SNAPSHOT/lib-1.0-20240103.030405-2.jar"
+        )));
+        assert!(written.contains(&PathBuf::from(
+            "org/example/lib/1.0-SNAPSHOT/lib-1.0-20240103.030405-2.jar.sha1"
+        )));
+
+        let release = Artifact::parse("org.example:lib:1.0").unwrap();
+        let written = staging.deploy(&release, &files, "20240104.030405").unwrap();
+        // jar, pom and artifact metadata, each with four checksums
+        assert_eq!(written.len(), 15);
+        let metadata = staging
+            .metadata(&PartialArtifact::parse("org.example:lib").unwrap())
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            metadata.versioning.versions,
+            Some(vec![Version::from("1.0-SNAPSHOT"), Version::from("1.0")])
+        );
+        assert_eq!(metadata.versioning.release, Some(Version::from("1.0")));
+        assert_eq!(metadata.versioning.latest, Some(Version::from("1.0")));
+
+        // the same content can be staged again, different content can not
+        staging.deploy(&release, &files, "20240104.030405").unwrap();
+        std::fs::write(&jar, b"changed").unwrap();
+        assert!(matches!(
+            staging.deploy(&release, &files, "20240105.030405"),
+            Err(DeployError::Immutable(_))
+        ));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,200 @@
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use thiserror::Error;
+use tracing::debug;
+
+#[derive(Debug, Clone, Error)]
+#[error("Invalid link strategy {0}, expected copy, hardlink or symlink")]
+pub struct ParseLinkStrategyError(String);
+
+// How a file already in the local repository is placed where it was asked for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkStrategy {
+    #[default]
+    Copy,
+    Hardlink,
+    Symlink,
+}
+
+impl FromStr for LinkStrategy {
+    type Err = ParseLinkStrategyError;
+
+    fn from_str(input: &str) -> Result<LinkStrategy, ParseLinkStrategyError> {
+        match input {
+            "copy" => Ok(LinkStrategy::Copy),
+            "hardlink" => Ok(LinkStrategy::Hardlink),
+            "symlink" => Ok(LinkStrategy::Symlink),
+            _ => Err(ParseLinkStrategyError(input.to_string())),
+        }
+    }
+}
+
+// Bytes the current user can still write to the file system holding `path`, None on
+// platforms where that isn't known
+#[cfg(unix)]
+pub fn available_space(path: &Path) -> std::io::Result<Option<u64>> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+    // SAFETY: path is a valid C string and statvfs only writes to the struct it is given
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    #[allow(clippy::useless_conversion)]
+    Ok(Some(
+        u64::from(stat.f_bavail).saturating_mul(u64::from(stat.f_frsize)),
+    ))
+}
+
+#[cfg(not(unix))]
+pub fn available_space(_path: &Path) -> std::io::Result<Option<u64>> {
+    Ok(None)
+}
+
+// a rename, or a copy when the staging directory is on another file system, never through
+// a link left at `to`
+pub fn move_file(from: &Path, to: &Path) -> std::io::Result<()> {
+    if std::fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    if to.symlink_metadata().is_ok() {
+        std::fs::remove_file(to)?;
+    }
+    std::fs::copy(from, to)?;
+    std::fs::remove_file(from)
+}
+
+// A download in progress, removed when dropped if it is still there. Moved into place once
+// complete, and otherwise gone however the download ended: failed, timed out, or dropped
+// by whoever awaited it.
+#[derive(Debug)]
+pub struct PartialFile {
+    path: PathBuf,
+}
+
+impl PartialFile {
+    pub fn new(path: PathBuf) -> PartialFile {
+        PartialFile { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for PartialFile {
+    fn drop(&mut self) {
+        match std::fs::remove_file(&self.path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                debug!(path = %self.path.display(), "unable to remove partial download, {}", e);
+            }
+            _ => {}
+        }
+    }
+}
+
+// Places `from` at `to` with `strategy`, falling back to a hard link and then a copy when
+// the file system refuses, say a symlink on Windows without the privilege or a hard link
+// across devices. Returns the strategy that worked. A link shares the file of the local
+// repository, so whatever is at `to` is removed first rather than written through.
+pub fn link_file(from: &Path, to: &Path, strategy: LinkStrategy) -> std::io::Result<LinkStrategy> {
+    if to.symlink_metadata().is_ok() {
+        std::fs::remove_file(to)?;
+    }
+    if strategy == LinkStrategy::Symlink {
+        match symlink(&std::fs::canonicalize(from)?, to) {
+            Ok(()) => return Ok(LinkStrategy::Symlink),
+            Err(e) => debug!(path = %to.display(), "symlink refused, {}", e),
+        }
+    }
+    if strategy != LinkStrategy::Copy {
+        match std::fs::hard_link(from, to) {
+            Ok(()) => return Ok(LinkStrategy::Hardlink),
+            Err(e) => debug!(path = %to.display(), "hard link refused, {}", e),
+        }
+    }
+    std::fs::copy(from, to)?;
+    Ok(LinkStrategy::Copy)
+}
+
+#[cfg(unix)]
+fn symlink(original: &Path, link: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(original, link)
+}
+
+#[cfg(windows)]
+fn symlink(original: &Path, link: &Path) -> std::io::Result<()> {
+    std::os::windows::fs::symlink_file(original, link)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn symlink(_original: &Path, _link: &Path) -> std::io::Result<()> {
+    Err(std::io::ErrorKind::Unsupported.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn space_and_moves() {
+        let dir = std::env::temp_dir().join(format!("maven-artifact-disk-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        if cfg!(unix) {
+            assert!(available_space(&dir).unwrap().unwrap() > 0);
+        }
+
+        let staged = dir.join("lib-1.0.jar.part");
+        std::fs::write(&staged, b"jar").unwrap();
+        move_file(&staged, &dir.join("lib-1.0.jar")).unwrap();
+        assert!(!staged.exists());
+        assert_eq!(std::fs::read(dir.join("lib-1.0.jar")).unwrap(), b"jar");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn partial_files() {
+        let dir = std::env::temp_dir().join(format!("maven-artifact-part-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let partial = PartialFile::new(dir.join("lib-1.0.jar.part"));
+        std::fs::write(partial.path(), b"ja").unwrap();
+        drop(partial);
+        assert!(!dir.join("lib-1.0.jar.part").exists());
+
+        let partial = PartialFile::new(dir.join("lib-1.0.jar.part"));
+        std::fs::write(partial.path(), b"jar").unwrap();
+        move_file(partial.path(), &dir.join("lib-1.0.jar")).unwrap();
+        drop(partial);
+        assert_eq!(std::fs::read(dir.join("lib-1.0.jar")).unwrap(), b"jar");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn link_strategies() {
+        let dir = std::env::temp_dir().join(format!("maven-artifact-link-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let cached = dir.join("cached.jar");
+        std::fs::write(&cached, b"jar").unwrap();
+        let out = dir.join("lib-1.0.jar");
+        for strategy in ["copy", "hardlink", "symlink"] {
+            let strategy = strategy.parse().unwrap();
+            assert_eq!(link_file(&cached, &out, strategy).unwrap(), strategy);
+            assert_eq!(std::fs::read(&out).unwrap(), b"jar");
+        }
+        if cfg!(unix) {
+            assert!(out.symlink_metadata().unwrap().file_type().is_symlink());
+        }
+        // replacing the output leaves the file linked before alone
+        link_file(&cached, &out, LinkStrategy::Hardlink).unwrap();
+        link_file(&dir.join("cached.jar"), &out, LinkStrategy::Copy).unwrap();
+        std::fs::write(&out, b"changed").unwrap();
+        assert_eq!(std::fs::read(&cached).unwrap(), b"jar");
+        assert!("link".parse::<LinkStrategy>().is_err());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,209 @@
+use crate::artifact::Artifact;
+use crate::checksum::{self, Checksum, ChecksumAlgorithm, ParseChecksumError};
+use crate::layout;
+use serde::{Serialize, Serializer};
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use xml::EventReader;
+use xml::attribute::OwnedAttribute;
+use xml::reader::XmlEvent;
+
+#[derive(Debug, Error)]
+pub enum GradleError {
+    #[error("{0} XML error while parsing")]
+    Xml(#[from] xml::reader::Error),
+    #[error("{0}")]
+    Checksum(#[from] ParseChecksumError),
+    #[error("<{0}> is missing the {1} attribute")]
+    MissingAttribute(&'static str, &'static str),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct VerifiedArtifact {
+    #[serde(rename = "coordinates", serialize_with = "coordinates")]
+    pub artifact: Artifact,
+    pub file: String,
+    pub checksums: Vec<Checksum>,
+}
+
+fn coordinates<S: Serializer>(artifact: &Artifact, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(artifact)
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChecksumMismatch {
+    pub algorithm: ChecksumAlgorithm,
+    pub expected: String,
+    pub actual: String,
+}
+
+impl VerifiedArtifact {
+    // Every recorded checksum that `path` does not have, empty when it is the file Gradle saw.
+    pub fn mismatches(&self, path: &Path) -> std::io::Result<Vec<ChecksumMismatch>> {
+        let digests = checksum::digest_file(path)?;
+        Ok(self
+            .checksums
+            .iter()
+            .filter_map(|expected| {
+                let actual = digests
+                    .iter()
+                    .find(|d| d.algorithm() == expected.algorithm())?;
+                (actual != expected).then(|| ChecksumMismatch {
+                    algorithm: expected.algorithm(),
+                    expected: expected.to_string(),
+                    actual: actual.to_string(),
+                })
+            })
+            .collect())
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct VerificationMetadata {
+    pub artifacts: Vec<VerifiedArtifact>,
+    // files that don't follow Maven naming, like Gradle module metadata of renamed artifacts
+    pub skipped: Vec<String>,
+}
+
+fn attribute<'a>(attributes: &'a [OwnedAttribute], name: &str) -> Option<&'a str> {
+    attributes
+        .iter()
+        .find(|a| a.name.local_name == name)
+        .map(|a| a.value.as_str())
+}
+
+// Reads gradle/verification-metadata.xml. Only checksums of the artifact itself are kept:
+// <also-trust> alternatives and PGP keys are ignored.
+pub fn parse_verification_metadata<R: Read>(input: R) -> Result<VerificationMetadata, GradleError> {
+    let mut parser = EventReader::new(BufReader::new(input));
+    let mut metadata = VerificationMetadata::default();
+    let mut component: Option<PathBuf> = None;
+    let mut current: Option<VerifiedArtifact> = None;
+    loop {
+        match parser.next()? {
+            XmlEvent::EndDocument => break Ok(metadata),
+            XmlEvent::StartElement {
+                name, attributes, ..
+            } => match name.local_name.as_str() {
+                "component" => {
+                    let get = |attr| {
+                        attribute(&attributes, attr)
+                            .ok_or(GradleError::MissingAttribute("component", attr))
+                    };
+                    let group = get("group")?.replace('.', "/");
+                    component = Some(Path::new(&group).join(get("name")?).join(get("version")?));
+                }
+                "artifact" => {
+                    if let Some(dir) = &component {
+                        let file = attribute(&attributes, "name")
+                            .ok_or(GradleError::MissingAttribute("artifact", "name"))?;
+                        match layout::parse_path(&dir.join(file)) {
+                            Some(resolved) => {
+                                current = Some(VerifiedArtifact {
+                                    artifact: resolved.artifact,
+                                    file: file.to_string(),
+                                    checksums: vec![],
+                                })
+                            }
+                            None => metadata.skipped.push(file.to_string()),
+                        }
+                    }
+                }
+                local => {
+                    let Some(algorithm) = ChecksumAlgorithm::from_extension(local) else {
+                        continue;
+                    };
+                    if let Some(artifact) = &mut current {
+                        let value = attribute(&attributes, "value")
+                            .ok_or(GradleError::MissingAttribute("checksum", "value"))?;
+                        artifact.checksums.push(Checksum::parse(algorithm, value)?);
+                    }
+                }
+            },
+            XmlEvent::EndElement { name } => match name.local_name.as_str() {
+                "component" => component = None,
+                "artifact" => metadata.artifacts.extend(current.take()),
+                _ => (),
+            },
+            _ => continue,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn parse_gradle_verification_metadata() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<verification-metadata xmlns="https://schema.gradle.org/dependency-verification">
+   <configuration>
+      <verify-metadata>true</verify-metadata>
+      <trusted-artifacts>
+         <trust group="org.example" name="lib"/>
+      </trusted-artifacts>
+   </configuration>
+   <components>
+      <component group="com.google.guava" name="guava" version="32.1.2-jre">
+         <artifact name="guava-32.1.2-jre.jar">
+            <sha256 value="bc65dea7cfd9e4dacf8419d8af0e741655857d27885bb35d943d7187fc3a8fce" origin="Generated by Gradle">
+               <also-trust value="0000000000000000000000000000000000000000000000000000000000000000"/>
+            </sha256>
+            <sha512 value="ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"/>
+         </artifact>
+         <artifact name="guava-32.1.2-jre-sources.jar">
+            <sha1 value="3cd7d6a4a4d3e3e2e1d3b2d7b0f7d4a8f3bdb6b7"/>
+         </artifact>
+         <artifact name="guava-32.1.2-jre.module">
+            <sha256 value="a2f3b0e8b2a5b6f1a7f3bdc7e0e9cbd8e7f1a5c4b6d3e2f1a0b9c8d7e6f5a4b3"/>
+         </artifact>
+      </component>
+      <component group="org.example" name="renamed" version="1.0">
+         <artifact name="something-else.jar">
+            <sha256 value="00"/>
+         </artifact>
+      </component>
+   </components>
+</verification-metadata>"#;
+        let metadata = parse_verification_metadata(Cursor::new(xml)).unwrap();
+        let coordinates: Vec<String> = metadata
+            .artifacts
+            .iter()
+            .map(|a| a.artifact.to_string())
+            .collect();
+        assert_eq!(
+            coordinates,
+            vec![
+                "com.google.guava:guava:32.1.2-jre",
+                "com.google.guava:guava:jar:sources:32.1.2-jre",
+                "com.google.guava:guava:module:32.1.2-jre",
+            ]
+        );
+        let checksums: Vec<(ChecksumAlgorithm, String)> = metadata.artifacts[0]
+            .checksums
+            .iter()
+            .map(|c| (c.algorithm(), c.to_string()))
+            .collect();
+        assert_eq!(
+            checksums,
+            vec![
+                (
+                    ChecksumAlgorithm::Sha256,
+                    String::from(
+                        "bc65dea7cfd9e4dacf8419d8af0e741655857d27885bb35d943d7187fc3a8fce"
+                    )
+                ),
+                (
+                    ChecksumAlgorithm::Sha512,
+                    String::from(
+                        "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"
+                    )
+                ),
+            ]
+        );
+        assert_eq!(metadata.skipped, vec!["something-else.jar"]);
+    }
+}
@@ -0,0 +1,898 @@
+use crate::Version;
+use crate::artifact::{Artifact, PartialArtifact};
+use crate::pom::{
+    Dependency, DependencyManagement, Exclusion, PomError, Project, dependencies_xml,
+};
+use crate::resolver::{ResolveError, Resolver};
+use crate::version::DynamicVersion;
+use futures_util::stream::{self, Stream};
+use serde::{Serialize, Serializer};
+use std::collections::{HashMap, VecDeque};
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "reason", rename_all = "camelCase")]
+pub enum Omitted {
+    Conflict { winner: Version },
+    Duplicate,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DependencyNode {
+    #[serde(serialize_with = "coordinates")]
+    pub artifact: Artifact,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub optional: bool,
+    pub depth: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent: Option<usize>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub omitted: Option<Omitted>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SkipReason {
+    Excluded,
+    Optional,
+    // the scope is not passed on to dependents, like test or provided
+    Scope,
+    // beyond the configured GraphLimits
+    Limit,
+}
+
+// Bounds on the graphs a Resolver builds, so pathological or hostile POMs can't make it
+// walk forever. The root is at depth 0 and counts as a node; omitted nodes count too.
+#[derive(Debug, Clone, Default)]
+pub struct GraphLimits {
+    pub max_depth: Option<usize>,
+    pub max_nodes: Option<usize>,
+    // stop at a limit and return what was resolved so far instead of failing
+    pub partial: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "limit", content = "max", rename_all = "camelCase")]
+pub enum Limit {
+    Depth(usize),
+    Nodes(usize),
+}
+
+impl Display for Limit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Limit::Depth(max) => write!(f, "max depth of {}", max),
+            Limit::Nodes(max) => write!(f, "max of {} nodes", max),
+        }
+    }
+}
+
+// A decision taken while building a DependencyGraph, in the order it was taken. Displayed
+// with the wording of Maven Resolver's debug output, so a trace can be compared with
+// `mvn -X dependency:tree -Dverbose`. Artifacts are groupId:artifactId:type[:classifier]:version.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "camelCase")]
+pub enum TraceEvent {
+    #[serde(rename_all = "camelCase")]
+    Descriptor { artifact: String, url: String },
+    #[serde(rename_all = "camelCase")]
+    Managed {
+        artifact: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        premanaged_version: Option<Version>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        premanaged_scope: Option<String>,
+    },
+    // by a rule of the resolver's Substitutions
+    #[serde(rename_all = "camelCase")]
+    Substituted {
+        artifact: String,
+        replacement: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    RangeResolved {
+        artifact: String,
+        constraint: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    Skipped {
+        artifact: String,
+        scope: String,
+        declared_by: String,
+        reason: SkipReason,
+    },
+    #[serde(rename_all = "camelCase")]
+    Selected {
+        artifact: String,
+        scope: String,
+        depth: usize,
+    },
+    #[serde(rename_all = "camelCase")]
+    Omitted {
+        artifact: String,
+        scope: String,
+        depth: usize,
+        #[serde(flatten)]
+        omitted: Omitted,
+    },
+}
+
+impl Display for TraceEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TraceEvent::Descriptor { artifact, url } => {
+                write!(f, "Reading descriptor of {} from {}", artifact, url)
+            }
+            TraceEvent::Managed {
+                artifact,
+                premanaged_version,
+                premanaged_scope,
+            } => {
+                let notes: Vec<String> = premanaged_version
+                    .iter()
+                    .map(|v| format!("version managed from {}", v))
+                    .chain(
+                        premanaged_scope
+                            .iter()
+                            .map(|s| format!("scope managed from {}", s)),
+                    )
+                    .collect();
+                write!(f, "{} ({})", artifact, notes.join("; "))
+            }
+            TraceEvent::Substituted {
+                artifact,
+                replacement,
+            } => write!(f, "{} (substituted by {})", artifact, replacement),
+            TraceEvent::RangeResolved {
+                artifact,
+                constraint,
+            } => write!(
+                f,
+                "{} (version selected from constraint {})",
+                artifact, constraint
+            ),
+            TraceEvent::Skipped {
+                artifact,
+                scope,
+                declared_by,
+                reason,
+            } => {
+                let reason = match reason {
+                    SkipReason::Excluded => "excluded",
+                    SkipReason::Optional => "optional",
+                    SkipReason::Scope => "scope not transitive",
+                    SkipReason::Limit => "beyond graph limits",
+                };
+                write!(
+                    f,
+                    "({}:{} - {} from {})",
+                    artifact, scope, reason, declared_by
+                )
+            }
+            TraceEvent::Selected {
+                artifact, scope, ..
+            } => write!(f, "{}:{}", artifact, scope),
+            TraceEvent::Omitted {
+                artifact,
+                scope,
+                omitted,
+                ..
+            } => match omitted {
+                Omitted::Conflict { winner } => write!(
+                    f,
+                    "({}:{} - omitted for conflict with {})",
+                    artifact, scope, winner
+                ),
+                Omitted::Duplicate => {
+                    write!(f, "({}:{} - omitted for duplicate)", artifact, scope)
+                }
+            },
+        }
+    }
+}
+
+// groupId:artifactId:type[:classifier]:version, the way Maven Resolver prints a node
+fn resolver_id(dependency: &Dependency) -> String {
+    let mut id = format!(
+        "{}:{}:{}",
+        dependency.group_id,
+        dependency.artifact_id,
+        dependency.type_()
+    );
+    if let Some(classifier) = &dependency.classifier {
+        id = format!("{}:{}", id, classifier);
+    }
+    match &dependency.version {
+        Some(version) => format!("{}:{}", id, version),
+        None => id,
+    }
+}
+
+fn coordinates<S: Serializer>(artifact: &Artifact, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(artifact)
+}
+
+impl DependencyNode {
+    pub fn is_omitted(&self) -> bool {
+        self.omitted.is_some()
+    }
+
+    pub fn is(&self, artifact: &PartialArtifact) -> bool {
+        self.artifact.group_id == artifact.group_id
+            && self.artifact.artifact_id == artifact.artifact_id
+    }
+}
+
+// A dependency tree as Maven builds it: breadth first, where the nearest declaration of
+// a groupId:artifactId:type:classifier wins and later ones are kept as omitted leaves.
+// nodes[0] is the root.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DependencyGraph {
+    pub nodes: Vec<DependencyNode>,
+    // the limit that cut the graph short, with GraphLimits::partial
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub truncated: Option<Limit>,
+}
+
+impl DependencyGraph {
+    pub fn root(&self) -> &DependencyNode {
+        &self.nodes[0]
+    }
+
+    pub fn resolved(&self) -> impl Iterator<Item = &DependencyNode> {
+        self.nodes.iter().skip(1).filter(|n| !n.is_omitted())
+    }
+
+    // The resolved closure flattened into declarations with their effective scope
+    pub fn dependencies(&self) -> Vec<Dependency> {
+        (1..self.nodes.len())
+            .filter(|&i| !self.nodes[i].is_omitted())
+            .map(|i| self.declaration(i))
+            .collect()
+    }
+
+    pub fn path_to(&self, node: usize) -> Vec<usize> {
+        let mut path = vec![node];
+        let mut current = node;
+        while let Some(parent) = self.nodes[current].parent {
+            path.push(parent);
+            current = parent;
+        }
+        path.reverse();
+        path
+    }
+
+    // Every path from the root to the artifact, omitted occurrences included, like
+    // `mvn dependency:tree -Dverbose -Dincludes=groupId:artifactId`.
+    pub fn who_depends_on(&self, artifact: &PartialArtifact) -> Vec<Vec<&DependencyNode>> {
+        (1..self.nodes.len())
+            .filter(|&i| self.nodes[i].is(artifact))
+            .map(|i| {
+                self.path_to(i)
+                    .into_iter()
+                    .map(|n| &self.nodes[n])
+                    .collect()
+            })
+            .collect()
+    }
+
+    // The smallest change to the root's direct dependencies that makes `version` of the
+    // artifact win mediation. Nodes are stored in mediation order, so every occurrence
+    // before the first reachable one with the wanted version has to be cut, and a POM can
+    // only exclude on its direct dependencies. If the version is not in the tree at all,
+    // declaring it directly is enough since the nearest declaration always wins.
+    pub fn suggest_exclusions(
+        &self,
+        artifact: &PartialArtifact,
+        version: &Version,
+    ) -> ExclusionSuggestion {
+        let occurrences: Vec<Vec<usize>> = (1..self.nodes.len())
+            .filter(|&i| self.nodes[i].is(artifact))
+            .map(|i| self.path_to(i))
+            .collect();
+        let mut suggestion = ExclusionSuggestion::default();
+        if !occurrences
+            .iter()
+            .any(|path| self.nodes[path[path.len() - 1]].artifact.version == *version)
+        {
+            let mut dependency = Dependency::new(
+                artifact.group_id.clone(),
+                artifact.artifact_id.clone(),
+                Some(version.clone()),
+            );
+            dependency.scope = occurrences
+                .first()
+                .and_then(|path| self.nodes[path[1]].scope.clone())
+                .filter(|scope| scope != "compile");
+            suggestion.dependencies.push(dependency);
+            return suggestion;
+        }
+        let mut excluded: Vec<usize> = vec![];
+        for path in &occurrences {
+            let direct = path[1];
+            if excluded.contains(&direct) {
+                continue;
+            }
+            if self.nodes[path[path.len() - 1]].artifact.version == *version {
+                break;
+            }
+            let mut dependency = self.declaration(direct);
+            if path.len() == 2 {
+                // declared directly, only the declaration itself can change
+                dependency.version = Some(version.clone());
+                suggestion.dependencies.push(dependency);
+                break;
+            }
+            dependency.exclusions.push(Exclusion {
+                group_id: artifact.group_id.clone(),
+                artifact_id: artifact.artifact_id.clone(),
+            });
+            suggestion.dependencies.push(dependency);
+            excluded.push(direct);
+        }
+        suggestion
+    }
+
+    fn declaration(&self, node: usize) -> Dependency {
+        let node = &self.nodes[node];
+        let mut dependency = Dependency::from_artifact(&node.artifact);
+        dependency.scope = node.scope.clone().filter(|scope| scope != "compile");
+        dependency.optional = node.optional;
+        dependency
+    }
+
+    pub async fn resolve(
+        resolver: &Resolver,
+        artifact: Artifact,
+    ) -> Result<DependencyGraph, ResolveError> {
+        Ok(Self::resolve_traced(resolver, artifact).await?.0)
+    }
+
+    // resolve, also returning every decision taken on the way
+    pub async fn resolve_traced(
+        resolver: &Resolver,
+        artifact: Artifact,
+    ) -> Result<(DependencyGraph, Vec<TraceEvent>), ResolveError> {
+        let mut walk = GraphWalk::start(resolver, artifact).await?;
+        while walk.next().await?.is_some() {}
+        let graph = DependencyGraph {
+            nodes: walk.nodes,
+            truncated: walk.truncated,
+        };
+        Ok((graph, walk.trace))
+    }
+
+    // The nodes of resolve in the same order, each yielded as soon as it is added, which
+    // for a selected node is after its POM is fetched. Children of a node are not known
+    // when it is yielded, a tree is built by attaching every node to its parent.
+    // The stream ends after the first error.
+    pub fn stream(
+        resolver: &Resolver,
+        artifact: Artifact,
+    ) -> impl Stream<Item = Result<ResolvedNode, ResolveError>> + '_ {
+        enum State<'a> {
+            Start(Artifact),
+            Walking(Box<GraphWalk<'a>>),
+            Done,
+        }
+        stream::unfold(State::Start(artifact), move |state| async move {
+            let mut walk = match state {
+                State::Start(artifact) => {
+                    return match GraphWalk::start(resolver, artifact).await {
+                        Ok(walk) => Some((Ok(walk.resolved(0)), State::Walking(Box::new(walk)))),
+                        Err(e) => Some((Err(e), State::Done)),
+                    };
+                }
+                State::Walking(walk) => walk,
+                State::Done => return None,
+            };
+            match walk.next().await {
+                Ok(Some(index)) => Some((Ok(walk.resolved(index)), State::Walking(walk))),
+                Ok(None) => None,
+                Err(e) => Some((Err(e), State::Done)),
+            }
+        })
+    }
+
+    // dependencyManagement with scope=import BOMs expanded; entries declared directly
+    // win over imported ones, earlier imports over later ones
+    async fn management(
+        resolver: &Resolver,
+        project: &Project,
+    ) -> Result<DependencyManagement, ResolveError> {
+        let mut result = DependencyManagement::default();
+        let mut imported: Vec<Artifact> = vec![];
+        let mut pending = VecDeque::from([project.clone()]);
+        while let Some(project) = pending.pop_front() {
+            let Some(management) = &project.dependency_management else {
+                continue;
+            };
+            for declared in &management.dependencies {
+                let dependency = declared.interpolated(&project);
+                if dependency.scope.as_deref() == Some("import") && dependency.type_() == "pom" {
+                    if let Some(bom) = dependency.to_artifact()
+                        && !imported.contains(&bom)
+                    {
+                        imported.push(bom.clone());
+                        pending.push_back(resolver.effective_project(bom).await?);
+                    }
+                } else if result.find(&dependency).is_none() {
+                    result.dependencies.push(dependency);
+                }
+            }
+        }
+        Ok(result)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ExclusionSuggestion {
+    // root declarations to replace, with exclusions added or the version changed
+    pub dependencies: Vec<Dependency>,
+}
+
+impl ExclusionSuggestion {
+    pub fn is_empty(&self) -> bool {
+        self.dependencies.is_empty()
+    }
+
+    pub fn to_xml(&self) -> Result<String, PomError> {
+        dependencies_xml(&self.dependencies)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ResolvedNode {
+    pub index: usize,
+    #[serde(flatten)]
+    pub node: DependencyNode,
+}
+
+// The breadth first walk of DependencyGraph::resolve, adding one node per step
+struct GraphWalk<'a> {
+    resolver: &'a Resolver,
+    // the root's, forced on every transitive dependency
+    management: DependencyManagement,
+    nodes: Vec<DependencyNode>,
+    winners: HashMap<String, usize>,
+    pending: VecDeque<(usize, Project, Vec<Exclusion>)>,
+    current: Option<Visit>,
+    trace: Vec<TraceEvent>,
+    truncated: Option<Limit>,
+}
+
+// the node whose declared dependencies are being added
+struct Visit {
+    parent: usize,
+    project: Project,
+    exclusions: Vec<Exclusion>,
+    own: DependencyManagement,
+    next: usize,
+}
+
+impl<'a> GraphWalk<'a> {
+    async fn start(
+        resolver: &'a Resolver,
+        artifact: Artifact,
+    ) -> Result<GraphWalk<'a>, ResolveError> {
+        let descriptor = resolver.effective_descriptor(artifact.clone()).await?;
+        let trace = vec![TraceEvent::Descriptor {
+            artifact: resolver_id(&Dependency::from_artifact(&artifact)),
+            url: descriptor.url.to_string(),
+        }];
+        let root = descriptor.project;
+        let management = DependencyGraph::management(resolver, &root).await?;
+        Ok(GraphWalk {
+            resolver,
+            management,
+            nodes: vec![DependencyNode {
+                artifact: artifact.canonicalize(),
+                scope: None,
+                optional: false,
+                depth: 0,
+                parent: None,
+                children: vec![],
+                omitted: None,
+            }],
+            winners: HashMap::new(),
+            pending: VecDeque::from([(0, root, vec![])]),
+            current: None,
+            trace,
+            truncated: None,
+        })
+    }
+
+    fn resolved(&self, index: usize) -> ResolvedNode {
+        ResolvedNode {
+            index,
+            node: self.nodes[index].clone(),
+        }
+    }
+
+    // the index of the next node added, None when the walk is complete
+    async fn next(&mut self) -> Result<Option<usize>, ResolveError> {
+        loop {
+            if let Some(Limit::Nodes(_)) = self.truncated {
+                return Ok(None);
+            }
+            let Some(mut visit) = self.current.take() else {
+                let Some((parent, project, exclusions)) = self.pending.pop_front() else {
+                    return Ok(None);
+                };
+                let own = if parent == 0 {
+                    self.management.clone()
+                } else {
+                    DependencyGraph::management(self.resolver, &project).await?
+                };
+                self.current = Some(Visit {
+                    parent,
+                    project,
+                    exclusions,
+                    own,
+                    next: 0,
+                });
+                continue;
+            };
+            let Some(declared) = visit.project.dependencies.get(visit.next) else {
+                continue;
+            };
+            let dependency = declared.interpolated(&visit.project);
+            visit.next += 1;
+            let added = self.add(&visit, dependency).await;
+            self.current = Some(visit);
+            if let Some(index) = added? {
+                return Ok(Some(index));
+            }
+        }
+    }
+
+    async fn add(
+        &mut self,
+        visit: &Visit,
+        mut dependency: Dependency,
+    ) -> Result<Option<usize>, ResolveError> {
+        let parent = visit.parent;
+        if dependency.scope.as_deref() == Some("import") {
+            return Ok(None);
+        }
+        let skipped = |dependency: &Dependency, reason| TraceEvent::Skipped {
+            artifact: resolver_id(dependency),
+            scope: dependency.scope.clone().unwrap_or_else(|| "compile".into()),
+            declared_by: resolver_id(&Dependency::from_artifact(&self.nodes[parent].artifact)),
+            reason,
+        };
+        if parent != 0 && dependency.optional {
+            self.trace.push(skipped(&dependency, SkipReason::Optional));
+            return Ok(None);
+        }
+        if visit.exclusions.iter().any(|e| excludes(e, &dependency)) {
+            self.trace.push(skipped(&dependency, SkipReason::Excluded));
+            return Ok(None);
+        }
+        let premanaged_version = dependency.version.clone();
+        let premanaged_scope = dependency.scope.clone();
+        if parent != 0 {
+            force(&self.management, &mut dependency);
+        }
+        visit.own.apply(std::slice::from_mut(&mut dependency));
+        // a missing version filled in by management is not an override
+        let premanaged_version =
+            premanaged_version.filter(|v| dependency.version.as_ref() != Some(v));
+        let premanaged_scope = premanaged_scope
+            .or_else(|| Some(String::from("compile")))
+            .filter(|s| s != dependency.scope.as_deref().unwrap_or("compile"));
+        if premanaged_version.is_some() || premanaged_scope.is_some() {
+            self.trace.push(TraceEvent::Managed {
+                artifact: resolver_id(&dependency),
+                premanaged_version,
+                premanaged_scope,
+            });
+        }
+        let substitutions = self.resolver.substitutions();
+        if !substitutions.is_empty() {
+            let declared = resolver_id(&dependency);
+            if substitutions.apply(&mut dependency) {
+                self.trace.push(TraceEvent::Substituted {
+                    artifact: declared,
+                    replacement: resolver_id(&dependency),
+                });
+            }
+        }
+        let declared_scope = dependency.scope.as_deref().unwrap_or("compile");
+        let scope = match &self.nodes[parent].scope {
+            None => declared_scope.to_string(),
+            Some(inherited) => match transitive_scope(inherited, declared_scope) {
+                Some(scope) => scope.to_string(),
+                None => {
+                    self.trace.push(skipped(&dependency, SkipReason::Scope));
+                    return Ok(None);
+                }
+            },
+        };
+        let depth = self.nodes[parent].depth + 1;
+        let limits = self.resolver.graph_limits();
+        let exceeded = match (limits.max_depth, limits.max_nodes) {
+            (Some(max), _) if depth > max => Some(Limit::Depth(max)),
+            (_, Some(max)) if self.nodes.len() >= max => Some(Limit::Nodes(max)),
+            _ => None,
+        };
+        if let Some(limit) = exceeded {
+            if !limits.partial {
+                return Err(ResolveError::LimitExceeded {
+                    limit,
+                    dependency: resolver_id(&dependency),
+                });
+            }
+            self.trace.push(skipped(&dependency, SkipReason::Limit));
+            // a node limit ends the walk, a depth limit only this branch
+            if self.truncated.is_none() || matches!(limit, Limit::Nodes(_)) {
+                self.truncated = Some(limit);
+            }
+            return Ok(None);
+        }
+        let Some(mut artifact) = dependency.to_artifact() else {
+            return Err(ResolveError::Message(format!(
+                "No version for {}:{} declared by {}",
+                dependency.group_id, dependency.artifact_id, self.nodes[parent].artifact
+            )));
+        };
+        if artifact.version.is_range() || DynamicVersion::parse(&artifact.version).is_some() {
+            let constraint = artifact.version.to_string();
+            artifact = self.resolver.resolve(artifact).await?.artifact;
+            dependency.version = Some(artifact.version.clone());
+            self.trace.push(TraceEvent::RangeResolved {
+                artifact: resolver_id(&dependency),
+                constraint,
+            });
+        }
+        let key = format!(
+            "{}:{}:{}:{}",
+            dependency.group_id,
+            dependency.artifact_id,
+            dependency.type_(),
+            dependency.classifier.as_deref().unwrap_or("")
+        );
+        let index = self.nodes.len();
+        let omitted = self.winners.get(&key).map(|&winner| {
+            let winner = &self.nodes[winner].artifact.version;
+            if *winner == artifact.version {
+                Omitted::Duplicate
+            } else {
+                Omitted::Conflict {
+                    winner: winner.clone(),
+                }
+            }
+        });
+        let id = resolver_id(&dependency);
+        if let Some(omitted) = &omitted {
+            self.trace.push(TraceEvent::Omitted {
+                artifact: id,
+                scope: scope.clone(),
+                depth,
+                omitted: omitted.clone(),
+            });
+        } else {
+            self.trace.push(TraceEvent::Selected {
+                artifact: id.clone(),
+                scope: scope.clone(),
+                depth,
+            });
+            self.winners.insert(key, index);
+            let descriptor = self.resolver.effective_descriptor(artifact.clone()).await?;
+            self.trace.push(TraceEvent::Descriptor {
+                artifact: id,
+                url: descriptor.url.to_string(),
+            });
+            let mut inherited = visit.exclusions.clone();
+            inherited.extend(dependency.exclusions.iter().cloned());
+            self.pending
+                .push_back((index, descriptor.project, inherited));
+        }
+        self.nodes[parent].children.push(index);
+        self.nodes.push(DependencyNode {
+            artifact: artifact.canonicalize(),
+            scope: Some(scope),
+            optional: dependency.optional,
+            depth,
+            parent: Some(parent),
+            children: vec![],
+            omitted,
+        });
+        Ok(Some(index))
+    }
+}
+
+// the root's dependencyManagement overrides versions and scopes of transitive dependencies
+fn force(management: &DependencyManagement, dependency: &mut Dependency) {
+    if let Some(managed) = management.find(dependency) {
+        if managed.version.is_some() {
+            dependency.version = managed.version.clone();
+        }
+        if managed.scope.is_some() {
+            dependency.scope = managed.scope.clone();
+        }
+    }
+}
+
+fn excludes(exclusion: &Exclusion, dependency: &Dependency) -> bool {
+    let matches = |pattern: &str, value: &str| pattern == "*" || pattern == value;
+    matches(&exclusion.group_id, &dependency.group_id)
+        && matches(&exclusion.artifact_id, &dependency.artifact_id)
+}
+
+// https://maven.apache.org/guides/introduction/introduction-to-dependency-mechanism.html#dependency-scope
+pub fn transitive_scope(inherited: &str, declared: &str) -> Option<&'static str> {
+    match (inherited, declared) {
+        (_, "provided" | "test" | "system" | "import") => None,
+        ("compile", "runtime") => Some("runtime"),
+        ("compile", _) => Some("compile"),
+        ("provided", _) => Some("provided"),
+        ("runtime", _) => Some("runtime"),
+        ("test", _) => Some("test"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ArtifactId;
+
+    fn node(coordinates: &str, parent: Option<usize>, scope: &str) -> DependencyNode {
+        DependencyNode {
+            artifact: Artifact::parse(coordinates).unwrap(),
+            scope: Some(scope.to_string()).filter(|s| !s.is_empty()),
+            optional: false,
+            depth: 0,
+            parent,
+            children: vec![],
+            omitted: None,
+        }
+    }
+
+    // root -> a -> c:1.0, root -> b -> c:2.0 (omitted)
+    fn sample() -> DependencyGraph {
+        let mut nodes = vec![
+            node("org.example:app:1.0", None, ""),
+            node("org.example:a:1.0", Some(0), "compile"),
+            node("org.example:b:1.0", Some(0), "compile"),
+            node("org.example:c:1.0", Some(1), "compile"),
+            node("org.example:c:2.0", Some(2), "compile"),
+        ];
+        nodes[0].children = vec![1, 2];
+        nodes[1].children = vec![3];
+        nodes[2].children = vec![4];
+        nodes[4].omitted = Some(Omitted::Conflict {
+            winner: Version::from("1.0"),
+        });
+        DependencyGraph {
+            nodes,
+            truncated: None,
+        }
+    }
+
+    #[test]
+    fn paths_to_artifact() {
+        let graph = sample();
+        let paths = graph.who_depends_on(&PartialArtifact::parse("org.example:c").unwrap());
+        let rendered: Vec<Vec<String>> = paths
+            .iter()
+            .map(|p| p.iter().map(|n| n.artifact.to_string()).collect())
+            .collect();
+        assert_eq!(
+            rendered,
+            vec![
+                vec![
+                    "org.example:app:1.0",
+                    "org.example:a:1.0",
+                    "org.example:c:1.0"
+                ],
+                vec![
+                    "org.example:app:1.0",
+                    "org.example:b:1.0",
+                    "org.example:c:2.0"
+                ],
+            ]
+        );
+        assert_eq!(graph.resolved().count(), 3);
+    }
+
+    #[test]
+    fn suggest_exclusions() {
+        let graph = sample();
+        let c = PartialArtifact::parse("org.example:c").unwrap();
+
+        let suggestion = graph.suggest_exclusions(&c, &Version::from("2.0"));
+        assert_eq!(
+            suggestion.to_xml().unwrap(),
+            "<dependencies>
+  <dependency>
+    <groupId>org.example</groupId>
+    <artifactId>a</artifactId>
+    <version>1.0</version>
+    <exclusions>
+      <exclusion>
+        <groupId>org.example</groupId>
+        <artifactId>c</artifactId>
+      </exclusion>
+    </exclusions>
+  </dependency>
+</dependencies>"
+        );
+        assert!(
+            graph
+                .suggest_exclusions(&c, &Version::from("1.0"))
+                .is_empty()
+        );
+
+        // no path reaches 3.0, declaring it directly is enough
+        let forced = graph.suggest_exclusions(&c, &Version::from("3.0"));
+        assert_eq!(forced.dependencies.len(), 1);
+        assert_eq!(forced.dependencies[0].artifact_id, ArtifactId::from("c"));
+        assert_eq!(forced.dependencies[0].version, Some(Version::from("3.0")));
+    }
+
+    #[test]
+    fn scopes() {
+        assert_eq!(transitive_scope("compile", "runtime"), Some("runtime"));
+        assert_eq!(transitive_scope("runtime", "compile"), Some("runtime"));
+        assert_eq!(transitive_scope("test", "compile"), Some("test"));
+        assert_eq!(transitive_scope("compile", "test"), None);
+        assert_eq!(transitive_scope("provided", "provided"), None);
+    }
+
+    #[test]
+    fn trace_wording() {
+        let mut dependency =
+            Dependency::from_artifact(&Artifact::parse("org.example:c:jar:tests:1.0").unwrap());
+        assert_eq!(resolver_id(&dependency), "org.example:c:jar:tests:1.0");
+        dependency.version = None;
+        assert_eq!(resolver_id(&dependency), "org.example:c:jar:tests");
+
+        let managed = TraceEvent::Managed {
+            artifact: String::from("org.example:c:jar:2.0"),
+            premanaged_version: Some(Version::from("1.0")),
+            premanaged_scope: Some(String::from("runtime")),
+        };
+        assert_eq!(
+            managed.to_string(),
+            "org.example:c:jar:2.0 (version managed from 1.0; scope managed from runtime)"
+        );
+        let substituted = TraceEvent::Substituted {
+            artifact: String::from("org.example:c:jar:1.0"),
+            replacement: String::from("org.example.patched:c:jar:1.0-patched"),
+        };
+        assert_eq!(
+            substituted.to_string(),
+            "org.example:c:jar:1.0 (substituted by org.example.patched:c:jar:1.0-patched)"
+        );
+        let omitted = TraceEvent::Omitted {
+            artifact: String::from("org.example:c:jar:2.0"),
+            scope: String::from("compile"),
+            depth: 2,
+            omitted: Omitted::Conflict {
+                winner: Version::from("1.0"),
+            },
+        };
+        assert_eq!(
+            omitted.to_string(),
+            "(org.example:c:jar:2.0:compile - omitted for conflict with 1.0)"
+        );
+        assert_eq!(
+            serde_json::to_value(&omitted).unwrap(),
+            serde_json::json!({
+                "event": "omitted",
+                "artifact": "org.example:c:jar:2.0",
+                "scope": "compile",
+                "depth": 2,
+                "reason": "conflict",
+                "winner": "1.0",
+            })
+        );
+    }
+}
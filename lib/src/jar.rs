@@ -0,0 +1,382 @@
+use crate::artifact::Artifact;
+use crate::pom::{PomError, PomParser, Project};
+use crate::{ArtifactId, GroupId, Version};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use thiserror::Error;
+use zip::ZipArchive;
+use zip::result::{ZipError, ZipResult};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PomProperties {
+    pub group_id: GroupId,
+    pub artifact_id: ArtifactId,
+    pub version: Version,
+}
+
+impl PomProperties {
+    pub fn parse(content: &str) -> Option<PomProperties> {
+        let mut group_id = None;
+        let mut artifact_id = None;
+        let mut version = None;
+        for line in content.lines().map(str::trim) {
+            if line.starts_with(['#', '!']) {
+                continue;
+            }
+            let Some((key, value)) = line.split_once(['=', ':']) else {
+                continue;
+            };
+            let value = value.trim();
+            match key.trim() {
+                "groupId" => group_id = Some(GroupId::from(value)),
+                "artifactId" => artifact_id = Some(ArtifactId::from(value)),
+                "version" => version = Some(Version::from(value)),
+                _ => (),
+            }
+        }
+        Some(PomProperties {
+            group_id: group_id?,
+            artifact_id: artifact_id?,
+            version: version?,
+        })
+    }
+
+    pub fn matches(&self, artifact: &Artifact) -> bool {
+        self.group_id == artifact.group_id
+            && self.artifact_id == artifact.artifact_id
+            && self.version == artifact.version
+    }
+}
+
+fn is_pom_properties(name: &str) -> bool {
+    name.strip_prefix("META-INF/maven/")
+        .and_then(|rest| rest.strip_suffix("/pom.properties"))
+        .is_some_and(|ga| ga.split('/').count() == 2)
+}
+
+// shaded jars carry one entry per bundled module, so all of them are returned
+pub fn pom_properties(path: &Path) -> ZipResult<Vec<PomProperties>> {
+    let mut archive = ZipArchive::new(File::open(path)?)?;
+    let names: Vec<String> = archive
+        .file_names()
+        .filter(|name| is_pom_properties(name))
+        .map(String::from)
+        .collect();
+    let mut found = vec![];
+    for name in names {
+        let mut content = String::new();
+        archive.by_name(&name)?.read_to_string(&mut content)?;
+        if let Some(properties) = PomProperties::parse(&content) {
+            found.push(properties);
+        }
+    }
+    Ok(found)
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum GavCheck {
+    Match,
+    Mismatch { found: Vec<PomProperties> },
+    Missing,
+}
+
+pub fn check_gav(path: &Path, artifact: &Artifact) -> ZipResult<GavCheck> {
+    let found = pom_properties(path)?;
+    Ok(if found.is_empty() {
+        GavCheck::Missing
+    } else if found.iter().any(|p| p.matches(artifact)) {
+        GavCheck::Match
+    } else {
+        GavCheck::Mismatch { found }
+    })
+}
+
+#[derive(Debug, Error)]
+pub enum EmbeddedPomError {
+    #[error("Unable to read jar: {0}")]
+    Zip(#[from] ZipError),
+    #[error("Unable to read jar: {0}")]
+    IO(#[from] std::io::Error),
+    #[error("Embedded POM error: {0}")]
+    Pom(#[from] PomError),
+}
+
+pub fn embedded_pom_path(group_id: &GroupId, artifact_id: &ArtifactId) -> String {
+    format!("META-INF/maven/{}/{}/pom.xml", group_id, artifact_id)
+}
+
+pub fn embedded_pom(
+    path: &Path,
+    group_id: &GroupId,
+    artifact_id: &ArtifactId,
+) -> Result<Option<Project>, EmbeddedPomError> {
+    let mut archive = ZipArchive::new(File::open(path)?)?;
+    let mut entry = match archive.by_name(&embedded_pom_path(group_id, artifact_id)) {
+        Ok(entry) => entry,
+        Err(ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let mut content = vec![];
+    entry.read_to_end(&mut content)?;
+    Ok(Some(PomParser::new().parse(content.as_slice())?))
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct JarEntry {
+    pub name: String,
+    pub size: u64,
+    pub sha256: String,
+}
+
+pub fn entries(path: &Path) -> ZipResult<Vec<JarEntry>> {
+    let mut archive = ZipArchive::new(File::open(path)?)?;
+    let mut entries = Vec::with_capacity(archive.len());
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i)?;
+        if file.is_dir() {
+            continue;
+        }
+        let mut hasher = Sha256::new();
+        std::io::copy(&mut file, &mut hasher)?;
+        entries.push(JarEntry {
+            name: file.name().to_string(),
+            size: file.size(),
+            sha256: format!("{:x}", hasher.finalize()),
+        });
+    }
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(entries)
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChangedEntry {
+    pub name: String,
+    pub old_size: u64,
+    pub new_size: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct JarDiff {
+    pub added: Vec<JarEntry>,
+    pub removed: Vec<JarEntry>,
+    pub changed: Vec<ChangedEntry>,
+}
+
+impl JarDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+pub fn diff(old: &[JarEntry], new: &[JarEntry]) -> JarDiff {
+    let find = |entries: &[JarEntry], name: &str| entries.iter().find(|e| e.name == name).cloned();
+    let mut result = JarDiff::default();
+    for entry in old {
+        match find(new, &entry.name) {
+            None => result.removed.push(entry.clone()),
+            Some(other) if other.sha256 != entry.sha256 => result.changed.push(ChangedEntry {
+                name: entry.name.clone(),
+                old_size: entry.size,
+                new_size: other.size,
+            }),
+            Some(_) => (),
+        }
+    }
+    result.added = new
+        .iter()
+        .filter(|e| find(old, &e.name).is_none())
+        .cloned()
+        .collect();
+    result
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ClassSource {
+    // the entry of the sources jar
+    pub path: String,
+    pub content: String,
+}
+
+static SOURCE_EXTENSIONS: [&str; 4] = ["java", "kt", "groovy", "scala"];
+
+// Entries of a sources jar that may hold a class given by its fully qualified name, most
+// likely first. Nested classes, written Outer$Inner or Outer.Inner, are in the file of
+// the outermost class, and other JVM languages are looked for after Java.
+pub fn source_paths(class_name: &str) -> Vec<String> {
+    let outer = class_name.split('$').next().unwrap_or(class_name);
+    let segments: Vec<&str> = outer.split('.').filter(|s| !s.is_empty()).collect();
+    let mut paths = vec![];
+    for end in (1..=segments.len()).rev() {
+        let path = segments[..end].join("/");
+        for extension in SOURCE_EXTENSIONS {
+            paths.push(format!("{}.{}", path, extension));
+        }
+        // only classes enclose classes, and class names start upper case by convention
+        if end < 2 || !segments[end - 2].starts_with(|c: char| c.is_uppercase()) {
+            break;
+        }
+    }
+    paths
+}
+
+pub fn class_source(path: &Path, class_name: &str) -> ZipResult<Option<ClassSource>> {
+    let mut archive = ZipArchive::new(File::open(path)?)?;
+    for name in source_paths(class_name) {
+        let mut entry = match archive.by_name(&name) {
+            Ok(entry) => entry,
+            Err(ZipError::FileNotFound) => continue,
+            Err(e) => return Err(e),
+        };
+        let mut content = vec![];
+        entry.read_to_end(&mut content)?;
+        return Ok(Some(ClassSource {
+            path: name,
+            content: String::from_utf8_lossy(&content).into_owned(),
+        }));
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+
+    #[test]
+    fn parse_properties() {
+        let content = "#Generated by Maven\n#Tue Jan 02 10:00:00 UTC 2024\ngroupId=org.example\nartifactId = lib\nversion=1.0\n";
+        assert_eq!(
+            PomProperties::parse(content),
+            Some(PomProperties {
+                group_id: GroupId::from("org.example"),
+                artifact_id: ArtifactId::from("lib"),
+                version: Version::from("1.0"),
+            })
+        );
+        assert_eq!(PomProperties::parse("groupId=org.example"), None);
+    }
+
+    #[test]
+    fn check_embedded_gav() {
+        let path =
+            std::env::temp_dir().join(format!("maven-artifact-gav-{}.jar", std::process::id()));
+        let mut zip = zip::ZipWriter::new(File::create(&path).unwrap());
+        zip.start_file(
+            "META-INF/maven/org.example/lib/pom.properties",
+            SimpleFileOptions::default(),
+        )
+        .unwrap();
+        zip.write_all(b"groupId=org.example\nartifactId=lib\nversion=1.0\n")
+            .unwrap();
+        zip.finish().unwrap();
+
+        let artifact = Artifact::parse("org.example:lib:1.0").unwrap();
+        assert_eq!(check_gav(&path, &artifact).unwrap(), GavCheck::Match);
+        let other = Artifact::parse("org.example:other:1.0").unwrap();
+        assert!(matches!(
+            check_gav(&path, &other).unwrap(),
+            GavCheck::Mismatch { found } if found.len() == 1
+        ));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn find_class_source() {
+        assert_eq!(
+            source_paths("org.example.Outer$Inner")[..2],
+            ["org/example/Outer.java", "org/example/Outer.kt"]
+        );
+        let nested = source_paths("org.example.Outer.Inner");
+        assert_eq!(nested.len(), 8);
+        assert_eq!(nested[0], "org/example/Outer/Inner.java");
+        assert_eq!(nested[4], "org/example/Outer.java");
+
+        let path =
+            std::env::temp_dir().join(format!("maven-artifact-sources-{}.jar", std::process::id()));
+        let mut zip = zip::ZipWriter::new(File::create(&path).unwrap());
+        zip.start_file("org/example/Outer.java", SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(b"package org.example;\n\npublic class Outer {}\n")
+            .unwrap();
+        zip.finish().unwrap();
+
+        let source = class_source(&path, "org.example.Outer.Inner")
+            .unwrap()
+            .unwrap();
+        assert_eq!(source.path, "org/example/Outer.java");
+        assert!(source.content.contains("public class Outer"));
+        assert_eq!(class_source(&path, "org.example.Other").unwrap(), None);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn diff_entries() {
+        let entry = |name: &str, size: u64, sha256: &str| JarEntry {
+            name: name.to_string(),
+            size,
+            sha256: sha256.to_string(),
+        };
+        let old = vec![
+            entry("A.class", 10, "a"),
+            entry("B.class", 20, "b"),
+            entry("C.class", 5, "c"),
+        ];
+        let new = vec![
+            entry("A.class", 10, "a"),
+            entry("B.class", 22, "b2"),
+            entry("D.class", 7, "d"),
+        ];
+        let result = diff(&old, &new);
+        assert_eq!(result.added, vec![entry("D.class", 7, "d")]);
+        assert_eq!(result.removed, vec![entry("C.class", 5, "c")]);
+        assert_eq!(
+            result.changed,
+            vec![ChangedEntry {
+                name: String::from("B.class"),
+                old_size: 20,
+                new_size: 22,
+            }]
+        );
+        assert!(diff(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn extract_embedded_pom() {
+        let path =
+            std::env::temp_dir().join(format!("maven-artifact-pom-{}.jar", std::process::id()));
+        let mut zip = zip::ZipWriter::new(File::create(&path).unwrap());
+        zip.start_file(
+            "META-INF/maven/com.example/example-lib/pom.xml",
+            SimpleFileOptions::default(),
+        )
+        .unwrap();
+        let pom =
+            std::fs::read("test-files/pom/com/example/example-lib/1.0.0/example-lib-1.0.0.pom")
+                .unwrap();
+        zip.write_all(&pom).unwrap();
+        zip.finish().unwrap();
+
+        let project = embedded_pom(
+            &path,
+            &GroupId::from("com.example"),
+            &ArtifactId::from("example-lib"),
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(project.artifact_id, ArtifactId::from("example-lib"));
+        let missing = embedded_pom(
+            &path,
+            &GroupId::from("com.example"),
+            &ArtifactId::from("other"),
+        )
+        .unwrap();
+        assert!(missing.is_none());
+        std::fs::remove_file(path).unwrap();
+    }
+}
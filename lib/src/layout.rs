@@ -0,0 +1,159 @@
+use crate::artifact::{Artifact, ResolvedArtifact};
+use crate::{ArtifactId, Classifier, GroupId, Version};
+use std::path::{Path, PathBuf};
+
+static SIDECAR_SUFFIXES: [&str; 7] = [
+    ".md5",
+    ".sha1",
+    ".sha256",
+    ".sha512",
+    ".asc",
+    ".sigstore.json",
+    ".lastUpdated",
+];
+
+fn is_bookkeeping(file_name: &str) -> bool {
+    SIDECAR_SUFFIXES.iter().any(|s| file_name.ends_with(s))
+        || file_name.starts_with("maven-metadata")
+        || file_name == "_remote.repositories"
+        || file_name == "resolver-status.properties"
+}
+
+// Maps <group path>/<artifactId>/<version>/<file> back to coordinates, including
+// timestamped snapshot files like lib-1.0-20240101.120000-1.jar in 1.0-SNAPSHOT/.
+pub fn parse_path(relative: &Path) -> Option<ResolvedArtifact> {
+    let components: Vec<&str> = relative
+        .components()
+        .map(|c| c.as_os_str().to_str())
+        .collect::<Option<Vec<&str>>>()?;
+    let [group @ .., artifact_id, version, file_name] = components.as_slice() else {
+        return None;
+    };
+    if group.is_empty() || is_bookkeeping(file_name) {
+        return None;
+    }
+    let prefix = format!("{}-", artifact_id);
+    let rest = file_name.strip_prefix(prefix.as_str())?;
+    let (resolved_version, rest) = match version.strip_suffix("SNAPSHOT") {
+        Some(base) if !rest.starts_with(version) => {
+            let after_base = rest.strip_prefix(base)?;
+            let end = timestamp_len(after_base)?;
+            (
+                Version::from(format!("{}{}", base, &after_base[..end])),
+                &after_base[end..],
+            )
+        }
+        _ => (Version::from(*version), rest.strip_prefix(version)?),
+    };
+    let (classifier, extension) = match rest.strip_prefix('-') {
+        Some(classified) => {
+            let (classifier, extension) = classified.split_once('.')?;
+            (Some(Classifier::from(classifier)), extension)
+        }
+        None => (None, rest.strip_prefix('.')?),
+    };
+    if extension.is_empty() {
+        return None;
+    }
+    let mut artifact = Artifact::new(
+        GroupId::from(group.join(".")),
+        ArtifactId::from(*artifact_id),
+        Version::from(*version),
+    )
+    .with_extension(extension.to_string());
+    artifact.classifier = classifier;
+    Some(ResolvedArtifact::new(artifact, resolved_version))
+}
+
+// yyyyMMdd.HHmmss-buildNumber
+fn timestamp_len(value: &str) -> Option<usize> {
+    let bytes = value.as_bytes();
+    let digits = |range: std::ops::Range<usize>| {
+        bytes
+            .get(range)
+            .is_some_and(|b| b.iter().all(u8::is_ascii_digit))
+    };
+    if !(digits(0..8)
+        && bytes.get(8) == Some(&b'.')
+        && digits(9..15)
+        && bytes.get(15) == Some(&b'-'))
+    {
+        return None;
+    }
+    let build = bytes[16..]
+        .iter()
+        .take_while(|b| b.is_ascii_digit())
+        .count();
+    (build > 0).then_some(16 + build)
+}
+
+pub fn walk(root: &Path, dir: &Path) -> std::io::Result<Vec<(PathBuf, Option<ResolvedArtifact>)>> {
+    let mut found = vec![];
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        for entry in std::fs::read_dir(&current)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                pending.push(path);
+                continue;
+            }
+            let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            if is_bookkeeping(name) {
+                continue;
+            }
+            let artifact = path.strip_prefix(root).ok().and_then(parse_path);
+            found.push((path, artifact));
+        }
+    }
+    found.sort_by(|(a, _), (b, _)| a.cmp(b));
+    Ok(found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn release_paths() {
+        let resolved = parse_path(Path::new("org/example/lib/1.0/lib-1.0.jar")).unwrap();
+        assert_eq!(resolved.artifact.to_string(), "org.example:lib:1.0");
+        assert_eq!(resolved.resolved_version, Version::from("1.0"));
+
+        let sources = parse_path(Path::new("org/example/lib/1.0/lib-1.0-sources.jar")).unwrap();
+        assert_eq!(
+            sources.artifact.to_string(),
+            "org.example:lib:jar:sources:1.0"
+        );
+
+        let tarball = parse_path(Path::new("org/example/lib/1.0/lib-1.0-dist.tar.gz")).unwrap();
+        assert_eq!(tarball.artifact.extension.as_deref(), Some("tar.gz"));
+
+        assert!(parse_path(Path::new("org/example/lib/1.0/lib-1.0.jar.sha1")).is_none());
+        assert!(parse_path(Path::new("org/example/lib/1.0/other-1.0.jar")).is_none());
+        assert!(parse_path(Path::new("lib/1.0/lib-1.0.jar")).is_none());
+    }
+
+    #[test]
+    fn snapshot_paths() {
+        let timestamped = parse_path(Path::new(
+            "org/example/lib/1.0-SNAPSHOT/lib-1.0-20240101.120000-3-tests.jar",
+        ))
+        .unwrap();
+        assert_eq!(
+            timestamped.resolved_version,
+            Version::from("1.0-20240101.120000-3")
+        );
+        assert_eq!(timestamped.artifact.version, Version::from("1.0-SNAPSHOT"));
+        assert_eq!(
+            timestamped.artifact.classifier,
+            Some(Classifier::from("tests"))
+        );
+
+        let plain = parse_path(Path::new(
+            "org/example/lib/1.0-SNAPSHOT/lib-1.0-SNAPSHOT.pom",
+        ))
+        .unwrap();
+        assert_eq!(plain.resolved_version, Version::from("1.0-SNAPSHOT"));
+        assert_eq!(plain.artifact.extension.as_deref(), Some("pom"));
+    }
+}
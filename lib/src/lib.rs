@@ -1,14 +1,57 @@
-use serde::Serialize;
+use crate::auth::Auth;
+use crate::cache::UpdatePolicy;
+use crate::checksum::ChecksumPolicy;
+use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
 use std::ops::Deref;
 use thiserror::Error;
 use url::Url;
 
+pub mod analysis;
 pub mod artifact;
-mod metadata;
+pub mod attestation;
+pub mod auth;
+pub mod azure;
+pub mod bundle;
+pub mod cache;
+pub mod cadence;
+pub mod checksum;
+#[cfg(feature = "codeartifact")]
+pub mod codeartifact;
+pub mod conditional;
+pub mod deploy;
+pub mod disk;
+pub mod gradle;
+pub mod graph;
+pub mod jar;
+pub mod layout;
+pub mod listing;
+pub mod local;
+pub mod metadata;
+pub mod packaging;
+pub mod paths;
+pub mod policy;
+pub mod pom;
+pub mod progress;
+pub mod proxy;
+pub mod ratelimit;
 pub mod resolver;
-
-#[derive(Ord, PartialOrd, Eq, PartialEq, Clone, Hash, Debug, Serialize)]
+pub mod retry;
+pub mod sbom;
+pub mod settings;
+#[cfg(feature = "signatures")]
+pub mod signatures;
+#[cfg(feature = "signing")]
+pub mod signing;
+pub mod substitution;
+pub mod sync;
+pub mod validation;
+pub mod version;
+pub mod warning;
+pub mod withdrawn;
+pub mod workspace;
+
+#[derive(Ord, PartialOrd, Eq, PartialEq, Clone, Hash, Debug, Serialize, Deserialize)]
 pub struct GroupId(String);
 
 impl GroupId {
@@ -52,7 +95,7 @@ impl Display for GroupId {
     }
 }
 
-#[derive(Ord, PartialOrd, Eq, PartialEq, Clone, Hash, Debug, Serialize)]
+#[derive(Ord, PartialOrd, Eq, PartialEq, Clone, Hash, Debug, Serialize, Deserialize)]
 pub struct ArtifactId(String);
 impl ArtifactId {
     pub fn into_string(self) -> String {
@@ -92,7 +135,7 @@ impl Display for ArtifactId {
     }
 }
 
-#[derive(Ord, PartialOrd, Eq, PartialEq, Clone, Hash, Debug, Serialize)]
+#[derive(Ord, PartialOrd, Eq, PartialEq, Clone, Hash, Debug, Serialize, Deserialize)]
 pub struct Version(String);
 impl Version {
     pub fn into_string(self) -> String {
@@ -149,7 +192,7 @@ impl Display for Version {
     }
 }
 
-#[derive(Ord, PartialOrd, Eq, PartialEq, Clone, Hash, Debug, Serialize)]
+#[derive(Ord, PartialOrd, Eq, PartialEq, Clone, Hash, Debug, Serialize, Deserialize)]
 pub struct Classifier(String);
 impl Classifier {
     pub fn into_string(self) -> String {
@@ -189,11 +232,16 @@ impl Display for Classifier {
     }
 }
 
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub struct Repository {
     pub url: Url,
     pub snapshots: bool,
     pub releases: bool,
+    // sent to this repository only, ahead of any other credentials
+    pub auth: Option<Auth>,
+    // how long its maven-metadata.xml is used from a local repository
+    pub update_policy: UpdatePolicy,
+    pub checksum_policy: ChecksumPolicy,
 }
 
 impl Repository {
@@ -206,6 +254,57 @@ impl Repository {
         )
     }
 
+    pub fn google() -> Repository {
+        Self::releases(Url::parse("https://maven.google.com/").unwrap())
+    }
+
+    pub fn apache_snapshots() -> Repository {
+        Self::snapshots(
+            Url::parse("https://repository.apache.org/content/repositories/snapshots/").unwrap(),
+        )
+    }
+
+    pub fn jitpack() -> Repository {
+        Self::both(Url::parse("https://jitpack.io/").unwrap())
+    }
+
+    pub fn gradle_plugin_portal() -> Repository {
+        Self::releases(Url::parse("https://plugins.gradle.org/m2/").unwrap())
+    }
+
+    pub fn well_known(name: &str) -> Option<Repository> {
+        match name {
+            "central" => Some(Self::maven_central()),
+            "central-snapshots" => Some(Self::maven_central_snapshots()),
+            "google" => Some(Self::google()),
+            "apache-snapshots" => Some(Self::apache_snapshots()),
+            "jitpack" => Some(Self::jitpack()),
+            "gradle-plugins" => Some(Self::gradle_plugin_portal()),
+            _ => None,
+        }
+    }
+
+    // the name well_known knows the repository by
+    pub fn well_known_name(&self) -> Option<&'static str> {
+        [
+            "central",
+            "central-snapshots",
+            "google",
+            "apache-snapshots",
+            "jitpack",
+            "gradle-plugins",
+        ]
+        .into_iter()
+        .find(|name| Self::well_known(name).is_some_and(|known| known.url == self.url))
+    }
+
+    pub fn parse(value: &str) -> Result<Repository, url::ParseError> {
+        match Self::well_known(value) {
+            Some(repository) => Ok(repository),
+            None => Url::parse(value).map(Self::both),
+        }
+    }
+
     fn new(url: Url, snapshots: bool, releases: bool) -> Repository {
         let new_base = if url.path().ends_with("/") {
             let mut new_base = url.clone();
@@ -218,6 +317,9 @@ impl Repository {
             url: new_base,
             snapshots,
             releases,
+            auth: None,
+            update_policy: UpdatePolicy::Daily,
+            checksum_policy: ChecksumPolicy::Fail,
         }
     }
 
@@ -231,4 +333,111 @@ impl Repository {
     pub fn snapshots(url: Url) -> Repository {
         Self::new(url, true, false)
     }
+
+    pub fn with_auth(mut self, auth: Auth) -> Repository {
+        self.auth = Some(auth);
+        self
+    }
+
+    pub fn with_update_policy(mut self, policy: UpdatePolicy) -> Repository {
+        self.update_policy = policy;
+        self
+    }
+
+    pub fn with_checksum_policy(mut self, policy: ChecksumPolicy) -> Repository {
+        self.checksum_policy = policy;
+        self
+    }
+
+    // true for urls of files in this repository
+    pub fn contains(&self, url: &Url) -> bool {
+        let base = self.url.as_str();
+        url.as_str()
+            .strip_prefix(base)
+            .is_some_and(|rest| base.ends_with('/') || rest.is_empty() || rest.starts_with('/'))
+    }
+
+    pub fn allows(&self, version: &Version) -> bool {
+        if version.is_snapshot() {
+            self.snapshots
+        } else if version.is_latest() {
+            self.snapshots || self.releases
+        } else {
+            self.releases
+        }
+    }
+}
+
+// Policies forced onto every repository of a Resolver, whatever configured them, for a
+// single run that needs snapshots, tolerates a broken mirror or wants fresh metadata
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RepositoryOverrides {
+    pub snapshots: Option<bool>,
+    pub checksum_policy: Option<ChecksumPolicy>,
+    pub update_policy: Option<UpdatePolicy>,
+}
+
+impl RepositoryOverrides {
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    pub fn apply(&self, mut repository: Repository) -> Repository {
+        if let Some(snapshots) = self.snapshots {
+            repository.snapshots = snapshots;
+        }
+        if let Some(policy) = self.checksum_policy {
+            repository.checksum_policy = policy;
+        }
+        if let Some(policy) = self.update_policy {
+            repository.update_policy = policy;
+        }
+        repository
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repository_policy() {
+        let releases = Repository::maven_central();
+        let snapshots = Repository::maven_central_snapshots();
+        let release = Version::from("1.0.0");
+        let snapshot = Version::from("1.0.0-SNAPSHOT");
+        assert!(releases.allows(&release));
+        assert!(!releases.allows(&snapshot));
+        assert!(releases.allows(&Version::from("RELEASE")));
+        assert!(snapshots.allows(&snapshot));
+        assert!(!snapshots.allows(&release));
+        assert!(!snapshots.allows(&Version::from("RELEASE")));
+        assert!(snapshots.allows(&Version::from("LATEST")));
+    }
+
+    #[test]
+    fn repository_parse() {
+        let google = Repository::parse("google").unwrap();
+        assert_eq!(google.url, Repository::google().url);
+        assert!(!google.snapshots);
+        let custom = Repository::parse("https://repo.example.com/maven/").unwrap();
+        assert_eq!(custom.url.as_str(), "https://repo.example.com/maven");
+        assert!(custom.snapshots && custom.releases);
+        assert!(Repository::parse("not a repository").is_err());
+    }
+
+    #[test]
+    fn repository_overrides() {
+        let overrides = RepositoryOverrides {
+            snapshots: Some(true),
+            checksum_policy: Some(ChecksumPolicy::Warn),
+            ..RepositoryOverrides::default()
+        };
+        let central = overrides.apply(Repository::maven_central());
+        assert!(central.allows(&Version::from("1.0.0-SNAPSHOT")));
+        assert!(central.releases);
+        assert_eq!(central.checksum_policy, ChecksumPolicy::Warn);
+        assert_eq!(central.update_policy, UpdatePolicy::Daily);
+        assert!(RepositoryOverrides::default().is_empty());
+    }
 }
@@ -0,0 +1,259 @@
+use crate::Version;
+use crate::deploy;
+use serde::Serialize;
+use std::time::SystemTime;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ListingEntry {
+    pub name: String,
+    pub modified: Option<String>,
+    pub directory: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PublishedVersion {
+    pub version: Version,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub published: Option<String>,
+}
+
+static MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+// Directory listings as served by Central ("2005-09-20 05:53") and Artifactory ("20-Sep-2005 05:53").
+// Timestamps are normalized to "YYYY-MM-DD HH:MM" so they sort as strings.
+pub fn parse_listing(html: &str) -> Vec<ListingEntry> {
+    let mut entries = vec![];
+    let mut rest = html;
+    while let Some(start) = rest.find("<a href=\"") {
+        rest = &rest[start + 9..];
+        let Some(end) = rest.find('"') else {
+            break;
+        };
+        let href = &rest[..end];
+        rest = &rest[end..];
+        let after = match rest.find("</a>") {
+            Some(close) => &rest[close + 4..],
+            None => rest,
+        };
+        let line = after.split(['\n', '<']).next().unwrap_or("");
+        let name = href.trim_end_matches('/');
+        if name.is_empty() || name.starts_with(['.', '?', '/']) || name.contains(':') {
+            continue;
+        }
+        entries.push(ListingEntry {
+            name: name.to_string(),
+            modified: parse_modified(line),
+            directory: href.ends_with('/'),
+        });
+    }
+    entries
+}
+
+// JSON listings from the Artifactory storage API ({"children": [{"uri", "folder"}]}) and
+// Nexus 2 content listings ({"data": [{"text", "leaf", "lastModified"}]}).
+pub fn parse_json_listing(json: &str) -> Vec<ListingEntry> {
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(json) else {
+        return vec![];
+    };
+    let str_field =
+        |item: &serde_json::Value, name: &str| item.get(name)?.as_str().map(String::from);
+    let mut entries = vec![];
+    if let Some(children) = value.get("children").and_then(|c| c.as_array()) {
+        for child in children {
+            let Some(uri) = str_field(child, "uri") else {
+                continue;
+            };
+            entries.push(ListingEntry {
+                name: uri.trim_matches('/').to_string(),
+                modified: None,
+                directory: child
+                    .get("folder")
+                    .and_then(|f| f.as_bool())
+                    .unwrap_or(false),
+            });
+        }
+    } else if let Some(data) = value.get("data").and_then(|d| d.as_array()) {
+        for item in data {
+            let Some(name) = str_field(item, "text") else {
+                continue;
+            };
+            entries.push(ListingEntry {
+                name,
+                modified: str_field(item, "lastModified").and_then(|m| parse_modified(&m)),
+                directory: item.get("leaf").and_then(|l| l.as_bool()) == Some(false),
+            });
+        }
+    }
+    entries.retain(|e| !e.name.is_empty());
+    entries
+}
+
+// Picks the parser by content since repositories rarely label listings correctly
+pub fn parse_listing_body(body: &str) -> Vec<ListingEntry> {
+    if body.trim_start().starts_with('{') {
+        parse_json_listing(body)
+    } else {
+        parse_listing(body)
+    }
+}
+
+fn parse_modified(text: &str) -> Option<String> {
+    let mut parts = text.split_whitespace();
+    let date = parts.next()?;
+    let time = parts.next().filter(|t| is_time(t));
+    let date = iso_date(date)?;
+    Some(match time {
+        Some(time) => format!("{} {}", date, &time[..5]),
+        None => date,
+    })
+}
+
+fn is_time(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    bytes.len() >= 5
+        && bytes[2] == b':'
+        && bytes[..2].iter().all(u8::is_ascii_digit)
+        && bytes[3..5].iter().all(u8::is_ascii_digit)
+}
+
+fn iso_date(date: &str) -> Option<String> {
+    let parts: Vec<&str> = date.split('-').collect();
+    let [first, month, last] = parts.as_slice() else {
+        return None;
+    };
+    let numeric = |s: &str, len: usize| s.len() == len && s.bytes().all(|b| b.is_ascii_digit());
+    if numeric(first, 4) && numeric(month, 2) && numeric(last, 2) {
+        Some(date.to_string())
+    } else if numeric(first, 2) && numeric(last, 4) {
+        let month = MONTHS.iter().position(|m| m == month)? + 1;
+        Some(format!("{}-{:02}-{}", last, month, first))
+    } else {
+        None
+    }
+}
+
+pub fn published_versions(versions: &[Version], listing: &[ListingEntry]) -> Vec<PublishedVersion> {
+    versions
+        .iter()
+        .map(|version| PublishedVersion {
+            version: version.clone(),
+            published: listing
+                .iter()
+                .find(|entry| entry.name == version.as_ref())
+                .and_then(|entry| entry.modified.clone()),
+        })
+        .collect()
+}
+
+// A point in time as ListingEntry::modified has it, in UTC like the listings of Central
+pub fn listing_timestamp(time: SystemTime) -> String {
+    let stamp = deploy::timestamp(time);
+    format!(
+        "{}-{}-{} {}:{}",
+        &stamp[..4],
+        &stamp[4..6],
+        &stamp[6..8],
+        &stamp[9..11],
+        &stamp[11..13]
+    )
+}
+
+// back from "YYYY-MM-DD HH:MM", or a date alone
+pub fn parse_listing_timestamp(value: &str) -> Option<SystemTime> {
+    let (date, time) = value.split_once(' ').unwrap_or((value, "00:00"));
+    let mut date = date.split('-').map(str::parse::<i64>);
+    let (Some(Ok(year)), Some(Ok(month)), Some(Ok(day)), None) =
+        (date.next(), date.next(), date.next(), date.next())
+    else {
+        return None;
+    };
+    let (hours, minutes) = time.split_once(':')?;
+    let secs = hours.parse::<u64>().ok()? * 3600 + minutes.parse::<u64>().ok()? * 60;
+    deploy::from_civil(year, month, day, secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn central_listing() {
+        let html = r#"<html><body><pre id="contents">
+<a href="../">../</a>
+<a href="4.12/" title="4.12/">4.12/</a>                                             2014-12-04 16:17         -
+<a href="4.13/" title="4.13/">4.13/</a>                                             2020-01-21 19:21         -
+<a href="maven-metadata.xml" title="maven-metadata.xml">maven-metadata.xml</a>                                2020-02-13 14:20      1234
+</pre></body></html>"#;
+        let entries = parse_listing(html);
+        assert_eq!(entries.len(), 3);
+        assert_eq!(
+            entries[0],
+            ListingEntry {
+                name: String::from("4.12"),
+                modified: Some(String::from("2014-12-04 16:17")),
+                directory: true,
+            }
+        );
+        let versions = vec![Version::from("4.12"), Version::from("4.14")];
+        let published = published_versions(&versions, &entries);
+        assert_eq!(published[0].published.as_deref(), Some("2014-12-04 16:17"));
+        assert_eq!(published[1].published, None);
+    }
+
+    #[test]
+    fn artifactory_listing() {
+        let html = r#"<pre><a href="1.0.0/">1.0.0/</a>  20-Sep-2023 10:11    -
+<a href="1.1.0/">1.1.0/</a>  03-Jan-2024 08:00:12    -
+</pre>"#;
+        let entries = parse_listing(html);
+        assert_eq!(entries[0].modified.as_deref(), Some("2023-09-20 10:11"));
+        assert_eq!(entries[1].modified.as_deref(), Some("2024-01-03 08:00"));
+    }
+
+    #[test]
+    fn json_listings() {
+        let artifactory = r#"{"repo": "libs-release", "path": "/org/example", "children": [
+            {"uri": "/lib", "folder": true},
+            {"uri": "/maven-metadata.xml", "folder": false}
+        ]}"#;
+        let entries = parse_listing_body(artifactory);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].name, "lib");
+        assert!(entries[0].directory);
+        assert!(!entries[1].directory);
+
+        let nexus = r#"{"data": [
+            {"resourceURI": "https://repo/org/example/lib/", "text": "lib", "leaf": false, "lastModified": "2024-01-03 08:00:12.0 UTC"}
+        ]}"#;
+        let entries = parse_listing_body(nexus);
+        assert_eq!(
+            entries,
+            vec![ListingEntry {
+                name: String::from("lib"),
+                modified: Some(String::from("2024-01-03 08:00")),
+                directory: true,
+            }]
+        );
+        assert!(parse_listing_body("{not json").is_empty());
+    }
+
+    #[test]
+    fn timestamps_sort_with_listings() {
+        let at = listing_timestamp(UNIX_EPOCH + Duration::from_secs(1704164645));
+        assert_eq!(at, "2024-01-02 03:04");
+        assert!(*"2024-01-02" < *at);
+        assert!(*"2024-01-02 03:05" > *at);
+        assert_eq!(
+            parse_listing_timestamp(&at),
+            Some(UNIX_EPOCH + Duration::from_secs(1704164640))
+        );
+        assert_eq!(
+            parse_listing_timestamp("2024-01-02"),
+            Some(UNIX_EPOCH + Duration::from_secs(1704153600))
+        );
+        assert_eq!(parse_listing_timestamp("02-Jan-2024"), None);
+    }
+}
@@ -0,0 +1,456 @@
+use crate::Repository;
+use crate::artifact::{Artifact, PartialArtifact, ResolvedArtifact};
+use crate::checksum::write_sidecars;
+use crate::conditional::Validators;
+use crate::deploy::{self, DeployError, DeployFile};
+use crate::metadata::VersionedMetadata;
+use crate::paths;
+use crate::pom::Project;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::SystemTime;
+
+const MARKER: &str = "_remote.repositories";
+
+// A local repository in the layout of ~/.m2/repository. Like Maven Resolver, every file
+// is recorded in _remote.repositories next to it with the repository it came from, so a
+// file downloaded from one repository is not handed out when resolving from another.
+#[derive(Debug, Clone)]
+pub struct LocalRepository {
+    pub root: PathBuf,
+}
+
+impl LocalRepository {
+    pub fn new(root: impl Into<PathBuf>) -> LocalRepository {
+        LocalRepository { root: root.into() }
+    }
+
+    // $MAVEN_REPO_LOCAL, or ~/.m2/repository
+    pub fn from_env() -> Option<LocalRepository> {
+        let root = match std::env::var_os("MAVEN_REPO_LOCAL") {
+            Some(root) => PathBuf::from(root),
+            None => std::env::home_dir()?.join(".m2").join("repository"),
+        };
+        Some(LocalRepository::new(root))
+    }
+
+    // snapshots are kept under their timestamped name
+    pub fn path(&self, artifact: &ResolvedArtifact) -> PathBuf {
+        paths::long_path(
+            self.root
+                .join(artifact.path())
+                .join(Artifact::from(artifact.clone()).file_name()),
+        )
+    }
+
+    // coordinates no file system could hold are refused before anything is written
+    fn checked_path(&self, artifact: &ResolvedArtifact) -> std::io::Result<PathBuf> {
+        paths::check_artifact(&Artifact::from(artifact.clone()))
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+        Ok(self.path(artifact))
+    }
+
+    // The cached file when one of the repositories, or an install, put it there. Files
+    // without a _remote.repositories entry are trusted, they predate the marker.
+    pub fn find(
+        &self,
+        artifact: &ResolvedArtifact,
+        repositories: &[&Repository],
+    ) -> Option<PathBuf> {
+        let path = self.checked_path(artifact).ok()?;
+        if !path.is_file() {
+            return None;
+        }
+        let name = path.file_name()?.to_str()?;
+        let Ok(marker) = std::fs::read_to_string(path.with_file_name(MARKER)) else {
+            return Some(path);
+        };
+        let ids: Vec<&str> = marker
+            .lines()
+            .filter_map(|line| line.strip_suffix('=')?.split_once('>'))
+            .filter(|(file, _)| *file == name)
+            .map(|(_, id)| id)
+            .collect();
+        let trusted = ids.is_empty()
+            || ids.contains(&"")
+            || repositories
+                .iter()
+                .any(|repository| ids.contains(&repository_id(repository).as_str()));
+        trusted.then_some(path)
+    }
+
+    // Metadata of a remote repository is kept as maven-metadata-<repository id>.xml, like
+    // Maven does, and used until the update policy of the repository has it go stale.
+    // `path` is the directory of the metadata, relative to the repository root.
+    pub fn metadata_path(&self, path: &str, repository: &Repository) -> PathBuf {
+        self.root
+            .join(path)
+            .join(format!("maven-metadata-{}.xml", repository_id(repository)))
+    }
+
+    pub fn cached_metadata(&self, path: &str, repository: &Repository) -> Option<Vec<u8>> {
+        let file = self.metadata_path(path, repository);
+        let fetched = std::fs::metadata(&file).and_then(|m| m.modified()).ok()?;
+        if repository
+            .update_policy
+            .is_stale(fetched, SystemTime::now())
+        {
+            return None;
+        }
+        std::fs::read(file).ok()
+    }
+
+    // Cached metadata whatever its age, with what to send to learn whether it changed.
+    // Metadata cached before validators were kept is checked against when it was fetched.
+    pub fn stale_metadata(
+        &self,
+        path: &str,
+        repository: &Repository,
+    ) -> Option<(Vec<u8>, Validators)> {
+        let file = self.metadata_path(path, repository);
+        let content = std::fs::read(&file).ok()?;
+        let validators = match Validators::load(&file) {
+            Some(validators) => validators,
+            None => {
+                Validators::modified_at(std::fs::metadata(&file).and_then(|m| m.modified()).ok()?)
+            }
+        };
+        Some((content, validators))
+    }
+
+    pub fn store_metadata(
+        &self,
+        path: &str,
+        repository: &Repository,
+        content: &[u8],
+        validators: &Validators,
+    ) -> std::io::Result<PathBuf> {
+        let file = self.metadata_path(path, repository);
+        if let Some(parent) = file.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&file, content)?;
+        validators.store(&file)?;
+        Ok(file)
+    }
+
+    // the server answered 304, so the cached metadata is fresh for another update interval
+    pub fn touch_metadata(&self, path: &str, repository: &Repository) -> std::io::Result<()> {
+        File::options()
+            .write(true)
+            .open(self.metadata_path(path, repository))?
+            .set_modified(SystemTime::now())
+    }
+
+    // Copies the file into place with its checksums. `repository` is where it was
+    // downloaded from, None for an install.
+    pub fn store(
+        &self,
+        artifact: &ResolvedArtifact,
+        source: &Path,
+        repository: Option<&Repository>,
+    ) -> std::io::Result<PathBuf> {
+        let path = self.checked_path(artifact)?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        if source != path {
+            std::fs::copy(source, &path)?;
+        }
+        self.finish(&path, repository)?;
+        Ok(path)
+    }
+
+    pub fn store_content(
+        &self,
+        artifact: &ResolvedArtifact,
+        content: &[u8],
+        repository: Option<&Repository>,
+    ) -> std::io::Result<PathBuf> {
+        let path = self.checked_path(artifact)?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, content)?;
+        self.finish(&path, repository)?;
+        Ok(path)
+    }
+
+    // Like mvn install: the files are placed under their plain names, snapshots included,
+    // and the version is listed in maven-metadata-local.xml. A POM with just the
+    // coordinates is generated when none is among the files. `timestamp` is yyyyMMdd.HHmmss
+    // in UTC. Returns every file written, relative to root.
+    pub fn install(
+        &self,
+        artifact: &Artifact,
+        files: &[DeployFile],
+        timestamp: &str,
+    ) -> Result<Vec<PathBuf>, DeployError> {
+        paths::check_artifact(artifact)?;
+        let target = |classifier, extension: &str| {
+            let installed = Artifact {
+                classifier,
+                extension: Some(extension.to_string()),
+                ..artifact.clone()
+            };
+            ResolvedArtifact::new(installed, artifact.version.clone())
+        };
+        let mut written = vec![];
+        for file in files {
+            let installed = target(file.classifier.clone(), &file.extension);
+            let path = self
+                .store(&installed, &file.source, None)
+                .map_err(|source| DeployError::IO {
+                    path: file.source.clone(),
+                    source,
+                })?;
+            written.push(path);
+        }
+        if !files
+            .iter()
+            .any(|f| f.classifier.is_none() && f.extension == "pom")
+        {
+            let packaging = files
+                .iter()
+                .find(|f| f.classifier.is_none())
+                .map(|f| f.extension.clone());
+            let project = Project::skeleton(&Artifact {
+                classifier: None,
+                extension: packaging,
+                ..artifact.clone()
+            });
+            let pom = target(None, "pom");
+            let path = self
+                .store_content(&pom, project.to_xml()?.as_bytes(), None)
+                .map_err(|source| DeployError::IO {
+                    path: self.path(&pom),
+                    source,
+                })?;
+            written.push(path);
+        }
+        let coordinates =
+            PartialArtifact::new(artifact.group_id.clone(), artifact.artifact_id.clone());
+        written.push(self.add_local_version(&coordinates, artifact, timestamp)?);
+        Ok(written
+            .into_iter()
+            .map(|p| {
+                p.strip_prefix(&self.root)
+                    .map(Path::to_path_buf)
+                    .unwrap_or(p)
+            })
+            .collect())
+    }
+
+    fn add_local_version(
+        &self,
+        coordinates: &PartialArtifact,
+        artifact: &Artifact,
+        timestamp: &str,
+    ) -> Result<PathBuf, DeployError> {
+        let path = self
+            .root
+            .join(coordinates.path())
+            .join("maven-metadata-local.xml");
+        let io = |source| DeployError::IO {
+            path: path.clone(),
+            source,
+        };
+        let mut metadata = match std::fs::read_to_string(&path) {
+            Ok(content) => VersionedMetadata::from_str(&content)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => VersionedMetadata {
+                group_id: artifact.group_id.clone(),
+                artifact_id: artifact.artifact_id.clone(),
+                version: None,
+                versioning: Default::default(),
+            },
+            Err(e) => return Err(io(e)),
+        };
+        deploy::add_versions(
+            &mut metadata,
+            std::slice::from_ref(&artifact.version),
+            timestamp,
+        );
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(io)?;
+        }
+        metadata.write(File::create(&path).map_err(io)?)?;
+        Ok(path)
+    }
+
+    fn finish(&self, path: &Path, repository: Option<&Repository>) -> std::io::Result<()> {
+        write_sidecars(path)?;
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+            return Ok(());
+        };
+        let marker = path.with_file_name(MARKER);
+        let id = repository.map(repository_id).unwrap_or_default();
+        let entry = format!("{}>{}=", name, id);
+        let existing = std::fs::read_to_string(&marker).unwrap_or_default();
+        if existing.lines().any(|line| line == entry) {
+            return Ok(());
+        }
+        let mut lines: Vec<&str> = existing
+            .lines()
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .collect();
+        lines.push(&entry);
+        lines.sort();
+        let content = format!(
+            "#NOTE: This is a Maven Resolver internal implementation file, its format can be changed without prior notice.\n{}\n",
+            lines.join("\n")
+        );
+        std::fs::write(marker, content)
+    }
+}
+
+// Repositories have no configured id, so the well-known name is used when the url is one,
+// otherwise the url with everything that can't go in a properties key replaced
+pub fn repository_id(repository: &Repository) -> String {
+    if let Some(name) = repository.well_known_name() {
+        return String::from(name);
+    }
+    let url = repository.url.as_str().trim_end_matches('/');
+    url.split_once("://")
+        .map_or(url, |(_, rest)| rest)
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || "._-".contains(c) {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Version;
+
+    #[test]
+    fn store_and_find() {
+        let dir = std::env::temp_dir().join(format!("maven-artifact-local-{}", std::process::id()));
+        let local = LocalRepository::new(dir.join("repository"));
+        let source = dir.join("lib.jar");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(&source, b"jar").unwrap();
+        let central = Repository::maven_central();
+        let other = Repository::parse("https://repo.example.org:8443/maven2/").unwrap();
+        assert_eq!(repository_id(&central), "central");
+        assert_eq!(repository_id(&other), "repo.example.org-8443-maven2");
+
+        let artifact = ResolvedArtifact::new(
+            Artifact::parse("org.example:lib:1.0-SNAPSHOT").unwrap(),
+            Version::from("1.0-20240102.030405-2"),
+        );
+        assert_eq!(local.find(&artifact, &[&central]), None);
+        let path = local.store(&artifact, &source, Some(&central)).unwrap();
+        assert_eq!(
+            path,
+            dir.join("repository/org/example/lib/1.0-SNAPSHOT/lib-1.0-20240102.030405-2.jar")
+        );
+        assert!(path.with_extension("jar.sha1").is_file());
+        assert_eq!(local.find(&artifact, &[&central]), Some(path.clone()));
+        assert_eq!(local.find(&artifact, &[&other]), None);
+
+        // installed files are available whatever the repositories
+        let pom = ResolvedArtifact::new(
+            Artifact::parse("org.example:lib:pom:1.0-SNAPSHOT").unwrap(),
+            Version::from("1.0-20240102.030405-2"),
+        );
+        local.store_content(&pom, b"<project/>", None).unwrap();
+        assert!(local.find(&pom, &[&other]).is_some());
+        let marker = std::fs::read_to_string(path.with_file_name(MARKER)).unwrap();
+        assert!(marker.ends_with(
+            "lib-1.0-20240102.030405-2.jar>central=\nlib-1.0-20240102.030405-2.pom>=\n"
+        ));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn cached_metadata() {
+        use crate::cache::UpdatePolicy;
+
+        let dir = std::env::temp_dir().join(format!("maven-artifact-meta-{}", std::process::id()));
+        let local = LocalRepository::new(&dir);
+        let central = Repository::maven_central();
+        let validators = Validators {
+            etag: Some(String::from("\"abc\"")),
+            last_modified: None,
+        };
+        let written = local
+            .store_metadata("org/example/lib", &central, b"<metadata/>", &validators)
+            .unwrap();
+        assert_eq!(
+            written,
+            dir.join("org/example/lib/maven-metadata-central.xml")
+        );
+        assert_eq!(
+            local.cached_metadata("org/example/lib", &central),
+            Some(b"<metadata/>".to_vec())
+        );
+        let always = central.clone().with_update_policy(UpdatePolicy::Always);
+        assert_eq!(local.cached_metadata("org/example/lib", &always), None);
+        assert_eq!(
+            local.stale_metadata("org/example/lib", &always),
+            Some((b"<metadata/>".to_vec(), validators))
+        );
+        // metadata from other repositories is kept apart
+        let google = Repository::google().with_update_policy(UpdatePolicy::Never);
+        assert_eq!(local.cached_metadata("org/example/lib", &google), None);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn install_with_generated_pom() {
+        let dir =
+            std::env::temp_dir().join(format!("maven-artifact-install-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let war = dir.join("app.war");
+        std::fs::write(&war, b"war").unwrap();
+        let local = LocalRepository::new(dir.join("repository"));
+        let artifact = Artifact::parse("org.example:app:1.0-SNAPSHOT").unwrap();
+        let files = vec![DeployFile {
+            classifier: None,
+            extension: String::from("war"),
+            source: war,
+        }];
+        let written = local.install(&artifact, &files, "20240102.030405").unwrap();
+        assert_eq!(
+            written,
+            vec![
+                PathBuf::from("org/example/app/1.0-SNAPSHOT/app-1.0-SNAPSHOT.war"),
+                PathBuf::from("org/example/app/1.0-SNAPSHOT/app-1.0-SNAPSHOT.pom"),
+                PathBuf::from("org/example/app/maven-metadata-local.xml"),
+            ]
+        );
+        let pom = std::fs::read_to_string(local.root.join(&written[1])).unwrap();
+        assert!(pom.contains("<packaging>war</packaging>"), "{}", pom);
+        local
+            .install(
+                &artifact.with_version(Version::from("1.0")),
+                &files,
+                "20240103.030405",
+            )
+            .unwrap();
+        let metadata = std::fs::read_to_string(local.root.join(&written[2])).unwrap();
+        let metadata = VersionedMetadata::from_str(&metadata).unwrap();
+        assert_eq!(
+            metadata.versioning.versions,
+            Some(vec![Version::from("1.0-SNAPSHOT"), Version::from("1.0")])
+        );
+        // installed files are found whatever the repositories
+        let installed = ResolvedArtifact::new(
+            Artifact::parse("org.example:app:war:1.0").unwrap(),
+            Version::from("1.0"),
+        );
+        assert!(
+            local
+                .find(&installed, &[&Repository::maven_central()])
+                .is_some()
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,11 +1,16 @@
 use crate::metadata::MetadataError::Unexpected;
+use crate::pom::NamespaceCheck;
+use crate::warning::{Warning, Warnings};
 pub use crate::{ArtifactId, Classifier, GroupId, Version};
 use serde::Serialize;
-use std::io::{BufReader, Cursor, Read, Seek};
+use std::io::{BufReader, Cursor, Read, Seek, Write};
 use std::num::ParseIntError;
+use std::str::FromStr;
 use thiserror::Error;
-use xml::EventReader;
+use xml::common::XmlVersion;
 use xml::reader::XmlEvent;
+use xml::writer::XmlEvent as WriteEvent;
+use xml::{EmitterConfig, EventReader, EventWriter};
 
 #[derive(Error, Debug)]
 pub enum MetadataError {
@@ -15,16 +20,24 @@ pub enum MetadataError {
     Xml(#[from] xml::reader::Error),
     #[error("{0} Failed to parse integer")]
     IntParse(#[from] ParseIntError),
+    #[error("{0} XML error while writing")]
+    XmlWrite(#[from] xml::writer::Error),
     #[error("{0} Unexpected XML error while parsing")]
     Unexpected(String),
+    #[error("{0} is not a Maven namespace")]
+    Namespace(String),
 }
 
+static METADATA_NAMESPACE_PREFIX: &str = "http://maven.apache.org/METADATA/";
+
 #[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct VersionedMetadata {
     #[serde(rename = "groupId")]
     pub group_id: GroupId,
     #[serde(rename = "artifactId")]
     pub artifact_id: ArtifactId,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<Version>,
     pub versioning: Versioning,
 }
 
@@ -77,16 +90,114 @@ impl SnapshotVersion {
     }
 }
 
-impl VersionedMetadata {
-    pub fn from_str(input: &str) -> Result<VersionedMetadata, MetadataError> {
+#[derive(Default, Debug, Clone, PartialEq, Serialize)]
+pub struct GroupMetadata {
+    pub plugins: Vec<PluginMapping>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PluginMapping {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub prefix: String,
+    #[serde(rename = "artifactId")]
+    pub artifact_id: ArtifactId,
+}
+
+impl FromStr for GroupMetadata {
+    type Err = MetadataError;
+
+    fn from_str(input: &str) -> Result<GroupMetadata, MetadataError> {
+        Self::parse(Cursor::new(input))
+    }
+}
+
+impl GroupMetadata {
+    pub fn parse<R: Read + Seek>(input: R) -> Result<GroupMetadata, MetadataError> {
+        let mut parser = EventReader::new(BufReader::new(input));
+        let mut metadata = GroupMetadata::default();
+        loop {
+            match &parser.next()? {
+                XmlEvent::EndDocument => break Ok(metadata),
+                XmlEvent::StartElement { name, .. } if name.local_name == "plugin" => {
+                    metadata.plugins.push(Self::parse_plugin(&mut parser)?);
+                }
+                _ => continue,
+            }
+        }
+    }
+
+    fn parse_plugin<R: Read + Seek>(
+        parser: &mut EventReader<BufReader<R>>,
+    ) -> Result<PluginMapping, MetadataError> {
+        let mut name: Option<String> = None;
+        let mut prefix: Option<String> = None;
+        let mut artifact_id: Option<ArtifactId> = None;
+        loop {
+            match parser.next()? {
+                XmlEvent::EndElement { name: end, .. } if end.local_name == "plugin" => {
+                    break match (prefix, artifact_id) {
+                        (Some(prefix), Some(artifact_id)) => Ok(PluginMapping {
+                            name,
+                            prefix,
+                            artifact_id,
+                        }),
+                        (None, _) => Err(Unexpected(String::from("Plugin prefix is missing"))),
+                        (_, None) => Err(Unexpected(String::from("Plugin artifactId is missing"))),
+                    };
+                }
+                XmlEvent::StartElement { name: start, .. } if start.local_name == "name" => {
+                    name = Some(VersionedMetadata::string_element(parser)?);
+                }
+                XmlEvent::StartElement { name: start, .. } if start.local_name == "prefix" => {
+                    prefix = Some(VersionedMetadata::string_element(parser)?);
+                }
+                XmlEvent::StartElement { name: start, .. } if start.local_name == "artifactId" => {
+                    artifact_id =
+                        Some(ArtifactId::from(VersionedMetadata::string_element(parser)?));
+                }
+                _ => continue,
+            }
+        }
+    }
+
+    pub fn find_prefix(&self, prefix: &str) -> Option<&PluginMapping> {
+        self.plugins.iter().find(|p| p.prefix == prefix)
+    }
+}
+
+impl FromStr for VersionedMetadata {
+    type Err = MetadataError;
+
+    fn from_str(input: &str) -> Result<VersionedMetadata, MetadataError> {
         Self::parse(Cursor::new(input))
     }
+}
 
+impl VersionedMetadata {
     pub fn parse<R: Read + Seek>(input: R) -> Result<VersionedMetadata, MetadataError> {
+        Self::parse_with_warnings(input).map(|(metadata, _)| metadata)
+    }
+
+    pub fn parse_with_warnings<R: Read + Seek>(
+        input: R,
+    ) -> Result<(VersionedMetadata, Warnings), MetadataError> {
+        Self::parse_with_namespace_check(input, NamespaceCheck::Ignore)
+    }
+
+    pub fn parse_with_namespace_check<R: Read + Seek>(
+        input: R,
+        namespace: NamespaceCheck,
+    ) -> Result<(VersionedMetadata, Warnings), MetadataError> {
+        let mut warnings = Warnings::default();
         let buffer = BufReader::new(input);
         let mut parser = EventReader::new(buffer);
+        if namespace != NamespaceCheck::Ignore {
+            Self::check_root(&mut parser, namespace)?;
+        }
         let mut group_id: Option<GroupId> = None;
         let mut artifact_id: Option<ArtifactId> = None;
+        let mut version: Option<Version> = None;
         let mut versioning: Option<Versioning> = None;
 
         loop {
@@ -94,11 +205,13 @@ impl VersionedMetadata {
             match event {
                 XmlEvent::EndDocument => match (&group_id, &artifact_id, &versioning) {
                     (Some(g), Some(a), Some(v)) => {
-                        break Ok(VersionedMetadata {
+                        let metadata = VersionedMetadata {
                             group_id: g.clone(),
                             artifact_id: a.clone(),
+                            version: version.clone(),
                             versioning: v.clone(),
-                        });
+                        };
+                        break Ok((metadata, warnings));
                     }
                     (None, _, _) => {
                         break Err(Unexpected(String::from("Missing groupId")));
@@ -118,10 +231,17 @@ impl VersionedMetadata {
                     let id = Self::string_element(&mut parser)?;
                     artifact_id = Some(ArtifactId::from(id));
                 }
+                XmlEvent::StartElement { name, .. } if name.local_name == "version" => {
+                    let v = Self::string_element(&mut parser)?;
+                    version = Some(Version::from(v));
+                }
                 XmlEvent::StartElement { name, .. } if name.local_name == "versioning" => {
-                    let v = Self::parse_versionining(&mut parser)?;
+                    let v = Self::parse_versionining(&mut parser, &mut warnings)?;
                     versioning = Some(v);
                 }
+                XmlEvent::StartElement { name, .. } if name.local_name != "metadata" => {
+                    Self::unknown(&mut parser, &mut warnings, "metadata", &name.local_name)?;
+                }
                 _ => continue,
             }
         }
@@ -129,6 +249,7 @@ impl VersionedMetadata {
 
     fn parse_versionining<R: Read + Seek>(
         parser: &mut EventReader<BufReader<R>>,
+        warnings: &mut Warnings,
     ) -> Result<Versioning, MetadataError> {
         let mut parsed: Versioning = Versioning::default();
         let mut versions: Vec<Version> = Vec::new();
@@ -137,6 +258,12 @@ impl VersionedMetadata {
             let event = &parser.next()?;
             match event {
                 XmlEvent::EndElement { name, .. } if name.local_name == "versioning" => {
+                    if parsed.last_updated.is_none() {
+                        warnings.push(Warning::MissingField {
+                            path: String::from("metadata/versioning"),
+                            field: "lastUpdated",
+                        });
+                    }
                     break Ok(parsed.clone());
                 }
                 XmlEvent::StartElement { name, .. } if name.local_name == "latest" => {
@@ -153,22 +280,29 @@ impl VersionedMetadata {
                 }
                 XmlEvent::StartElement { name, .. } if name.local_name == "lastUpdated" => {
                     let updated = Self::string_element(parser)?;
+                    Self::check_timestamp(warnings, "metadata/versioning/lastUpdated", &updated);
                     parsed.last_updated = Some(updated);
                 }
                 XmlEvent::EndElement { name, .. } if name.local_name == "versions" => {
                     parsed.versions = Some(versions.clone());
                 }
                 XmlEvent::StartElement { name, .. } if name.local_name == "snapshot" => {
-                    let snapshot = Self::parse_snapshot(parser)?;
+                    let snapshot = Self::parse_snapshot(parser, warnings)?;
                     parsed.snapshot = Some(snapshot);
                 }
                 XmlEvent::StartElement { name, .. } if name.local_name == "snapshotVersion" => {
-                    let version = Self::parse_snapshot_version(parser)?;
+                    let version = Self::parse_snapshot_version(parser, warnings)?;
                     snapshots.push(version);
                 }
                 XmlEvent::EndElement { name, .. } if name.local_name == "snapshotVersions" => {
                     parsed.snapshot_versions = Some(snapshots.clone());
                 }
+                XmlEvent::StartElement { name, .. }
+                    if name.local_name != "versions" && name.local_name != "snapshotVersions" =>
+                {
+                    let path = "metadata/versioning";
+                    Self::unknown(parser, warnings, path, &name.local_name)?;
+                }
                 _ => continue,
             }
         }
@@ -176,6 +310,7 @@ impl VersionedMetadata {
 
     fn parse_snapshot<R: Read + Seek>(
         parser: &mut EventReader<BufReader<R>>,
+        warnings: &mut Warnings,
     ) -> Result<Snapshot, MetadataError> {
         let mut timestamp: Option<String> = None;
         let mut build_number: Option<i32> = None;
@@ -201,12 +336,17 @@ impl VersionedMetadata {
                     let parsed = updated.parse::<i32>()?;
                     build_number = Some(parsed);
                 }
+                XmlEvent::StartElement { name, .. } if name.local_name != "localCopy" => {
+                    let path = "metadata/versioning/snapshot";
+                    Self::unknown(parser, warnings, path, &name.local_name)?;
+                }
                 _ => continue,
             }
         }
     }
     fn parse_snapshot_version<R: Read + Seek>(
         parser: &mut EventReader<BufReader<R>>,
+        warnings: &mut Warnings,
     ) -> Result<SnapshotVersion, MetadataError> {
         let mut value: Option<Version> = None;
         let mut updated: Option<String> = None;
@@ -233,6 +373,8 @@ impl VersionedMetadata {
                 }
                 XmlEvent::StartElement { name, .. } if name.local_name == "updated" => {
                     let up = Self::string_element(parser)?;
+                    let path = "metadata/versioning/snapshotVersions/snapshotVersion/updated";
+                    Self::check_timestamp(warnings, path, &up);
                     updated = Some(up);
                 }
                 XmlEvent::StartElement { name, .. } if name.local_name == "extension" => {
@@ -243,11 +385,237 @@ impl VersionedMetadata {
                     let updated = Self::string_element(parser)?;
                     classifier = Some(Classifier::from(updated));
                 }
+                XmlEvent::StartElement { name, .. } => {
+                    let path = "metadata/versioning/snapshotVersions/snapshotVersion";
+                    Self::unknown(parser, warnings, path, &name.local_name)?;
+                }
                 _ => continue,
             }
         }
     }
 
+    // Metadata for a snapshot version that has not been deployed yet
+    pub fn for_snapshot(
+        group_id: GroupId,
+        artifact_id: ArtifactId,
+        version: Version,
+    ) -> VersionedMetadata {
+        VersionedMetadata {
+            group_id,
+            artifact_id,
+            version: Some(version),
+            versioning: Versioning::default(),
+        }
+    }
+
+    // Records a new build of the snapshot deployed at `timestamp` (yyyyMMdd.HHmmss). The
+    // build number is bumped and each deployed (classifier, extension) gets a
+    // snapshotVersion, replacing the one from an earlier build. Returns the timestamped
+    // version the files of this build are named with.
+    pub fn merge_snapshot_build(
+        &mut self,
+        timestamp: &str,
+        files: &[(Option<Classifier>, String)],
+    ) -> Result<Version, MetadataError> {
+        let base = match &self.version {
+            Some(version) if version.is_snapshot() => {
+                version.strip_suffix("-SNAPSHOT").unwrap_or_default()
+            }
+            other => {
+                return Err(Unexpected(format!(
+                    "{} is not a snapshot version",
+                    other.as_deref().unwrap_or("<no version>")
+                )));
+            }
+        };
+        let updated = timestamp.replacen('.', "", 1);
+        if !timestamp.contains('.')
+            || updated.len() != 14
+            || !updated.bytes().all(|b| b.is_ascii_digit())
+        {
+            return Err(Unexpected(format!(
+                "{} is not a yyyyMMdd.HHmmss timestamp",
+                timestamp
+            )));
+        }
+        let versioning = &mut self.versioning;
+        let build_number = versioning.snapshot.as_ref().map_or(0, |s| s.buildNumber) + 1;
+        let value = Version::from(format!("{}-{}-{}", base, timestamp, build_number));
+        versioning.snapshot = Some(Snapshot {
+            timestamp: timestamp.to_string(),
+            buildNumber: build_number,
+        });
+        versioning.last_updated = Some(updated.clone());
+        let snapshot_versions = versioning.snapshot_versions.get_or_insert_default();
+        for (classifier, extension) in files {
+            let entry = SnapshotVersion::new(
+                value.clone(),
+                updated.clone(),
+                classifier.clone(),
+                Some(extension.clone()),
+            );
+            match snapshot_versions
+                .iter_mut()
+                .find(|v| &v.classifier == classifier && v.extension.as_ref() == Some(extension))
+            {
+                Some(existing) => *existing = entry,
+                None => snapshot_versions.push(entry),
+            }
+        }
+        Ok(value)
+    }
+
+    pub fn to_xml(&self) -> Result<String, MetadataError> {
+        let mut out = Vec::new();
+        self.write(&mut out)?;
+        String::from_utf8(out).map_err(|e| Unexpected(e.to_string()))
+    }
+
+    pub fn write<W: Write>(&self, output: W) -> Result<(), MetadataError> {
+        let mut writer = EmitterConfig::new()
+            .perform_indent(true)
+            .indent_string("  ")
+            .create_writer(output);
+        writer.write(WriteEvent::StartDocument {
+            version: XmlVersion::Version10,
+            encoding: Some("UTF-8"),
+            standalone: None,
+        })?;
+        let metadata = WriteEvent::start_element("metadata");
+        let metadata = if self.versioning.snapshot_versions.is_some() {
+            metadata.attr("modelVersion", "1.1.0")
+        } else {
+            metadata
+        };
+        writer.write(metadata)?;
+        Self::write_element(&mut writer, "groupId", &self.group_id)?;
+        Self::write_element(&mut writer, "artifactId", &self.artifact_id)?;
+        if let Some(version) = &self.version {
+            Self::write_element(&mut writer, "version", version)?;
+        }
+        Self::write_versioning(&mut writer, &self.versioning)?;
+        writer.write(WriteEvent::end_element())?;
+        Ok(())
+    }
+
+    fn write_versioning<W: Write>(
+        writer: &mut EventWriter<W>,
+        versioning: &Versioning,
+    ) -> Result<(), MetadataError> {
+        writer.write(WriteEvent::start_element("versioning"))?;
+        if let Some(latest) = &versioning.latest {
+            Self::write_element(writer, "latest", latest)?;
+        }
+        if let Some(release) = &versioning.release {
+            Self::write_element(writer, "release", release)?;
+        }
+        if let Some(versions) = &versioning.versions {
+            writer.write(WriteEvent::start_element("versions"))?;
+            for version in versions {
+                Self::write_element(writer, "version", version)?;
+            }
+            writer.write(WriteEvent::end_element())?;
+        }
+        if let Some(last_updated) = &versioning.last_updated {
+            Self::write_element(writer, "lastUpdated", last_updated)?;
+        }
+        if let Some(snapshot) = &versioning.snapshot {
+            writer.write(WriteEvent::start_element("snapshot"))?;
+            Self::write_element(writer, "timestamp", &snapshot.timestamp)?;
+            Self::write_element(writer, "buildNumber", &snapshot.buildNumber.to_string())?;
+            writer.write(WriteEvent::end_element())?;
+        }
+        if let Some(snapshot_versions) = &versioning.snapshot_versions {
+            writer.write(WriteEvent::start_element("snapshotVersions"))?;
+            for snapshot_version in snapshot_versions {
+                writer.write(WriteEvent::start_element("snapshotVersion"))?;
+                if let Some(classifier) = &snapshot_version.classifier {
+                    Self::write_element(writer, "classifier", classifier)?;
+                }
+                if let Some(extension) = &snapshot_version.extension {
+                    Self::write_element(writer, "extension", extension)?;
+                }
+                Self::write_element(writer, "value", &snapshot_version.value)?;
+                Self::write_element(writer, "updated", &snapshot_version.updated)?;
+                writer.write(WriteEvent::end_element())?;
+            }
+            writer.write(WriteEvent::end_element())?;
+        }
+        writer.write(WriteEvent::end_element())?;
+        Ok(())
+    }
+
+    fn write_element<W: Write>(
+        writer: &mut EventWriter<W>,
+        name: &str,
+        value: &str,
+    ) -> Result<(), MetadataError> {
+        writer.write(WriteEvent::start_element(name))?;
+        writer.write(WriteEvent::characters(value))?;
+        writer.write(WriteEvent::end_element())?;
+        Ok(())
+    }
+
+    fn check_root<R: Read + Seek>(
+        parser: &mut EventReader<BufReader<R>>,
+        namespace: NamespaceCheck,
+    ) -> Result<(), MetadataError> {
+        loop {
+            match parser.next()? {
+                XmlEvent::StartElement { name, .. } if name.local_name == "metadata" => {
+                    break namespace
+                        .check(name.namespace.as_deref(), |ns| {
+                            ns.starts_with(METADATA_NAMESPACE_PREFIX)
+                        })
+                        .map_err(MetadataError::Namespace);
+                }
+                XmlEvent::StartElement { name, .. } => {
+                    break Err(Unexpected(format!(
+                        "Expected <metadata> root element, found <{}>",
+                        name.local_name
+                    )));
+                }
+                XmlEvent::EndDocument => {
+                    break Err(Unexpected(String::from("Missing <metadata> element")));
+                }
+                _ => continue,
+            }
+        }
+    }
+
+    // yyyyMMddHHmmss
+    fn check_timestamp(warnings: &mut Warnings, path: &str, value: &str) {
+        if value.len() != 14 || !value.bytes().all(|b| b.is_ascii_digit()) {
+            warnings.push(Warning::Malformed {
+                path: path.to_string(),
+                value: value.to_string(),
+            });
+        }
+    }
+
+    fn unknown<R: Read + Seek>(
+        parser: &mut EventReader<BufReader<R>>,
+        warnings: &mut Warnings,
+        parent: &str,
+        name: &str,
+    ) -> Result<(), MetadataError> {
+        warnings.push(Warning::UnknownElement {
+            path: format!("{}/{}", parent, name),
+        });
+        let mut depth = 1;
+        while depth > 0 {
+            match parser.next()? {
+                XmlEvent::StartElement { .. } => depth += 1,
+                XmlEvent::EndElement { .. } => depth -= 1,
+                XmlEvent::EndDocument => {
+                    return Err(Unexpected(String::from("Unexpected end of document")));
+                }
+                _ => continue,
+            }
+        }
+        Ok(())
+    }
+
     fn string_element<R: Read + Seek>(
         parser: &mut EventReader<BufReader<R>>,
     ) -> Result<String, MetadataError> {
@@ -264,6 +632,35 @@ impl VersionedMetadata {
 mod test {
     use super::*;
 
+    #[test]
+    fn parse_group_plugins() {
+        let meta = r##"<?xml version="1.0" encoding="UTF-8"?>
+<metadata>
+  <plugins>
+    <plugin>
+      <name>Apache Maven Compiler Plugin</name>
+      <prefix>compiler</prefix>
+      <artifactId>maven-compiler-plugin</artifactId>
+    </plugin>
+    <plugin>
+      <prefix>surefire</prefix>
+      <artifactId>maven-surefire-plugin</artifactId>
+    </plugin>
+  </plugins>
+</metadata>"##;
+        let metadata = GroupMetadata::from_str(meta).unwrap();
+        assert_eq!(metadata.plugins.len(), 2);
+        assert_eq!(
+            metadata.find_prefix("surefire"),
+            Some(&PluginMapping {
+                name: None,
+                prefix: String::from("surefire"),
+                artifact_id: ArtifactId::from("maven-surefire-plugin"),
+            })
+        );
+        assert_eq!(metadata.find_prefix("jar"), None);
+    }
+
     #[test]
     fn parse_simple() {
         let meta = r##"<?xml version="1.0" encoding="UTF-8"?><metadata><groupId>com.example</groupId><artifactId>example-cli</artifactId><versioning><latest>3.0.0</latest><release>3.0.0</release><versions><version>3.0.0</version></versions><lastUpdated>20250427133131</lastUpdated></versioning></metadata>"##;
@@ -274,6 +671,7 @@ mod test {
             VersionedMetadata {
                 group_id: GroupId::from("com.example"),
                 artifact_id: ArtifactId::from("example-cli"),
+                version: None,
                 versioning: Versioning {
                     latest: Some(Version::from("3.0.0")),
                     release: Some(Version::from("3.0.0")),
@@ -334,6 +732,7 @@ mod test {
         let expected = VersionedMetadata {
             group_id: GroupId::from("org.pac4j"),
             artifact_id: ArtifactId::from("pac4j-http"),
+            version: Some(Version::from("6.1.4-SNAPSHOT")),
             versioning: Versioning {
                 last_updated: Some(String::from("20250607033109")),
                 snapshot: Some(Snapshot {
@@ -355,4 +754,128 @@ mod test {
 
         assert_eq!(metadata, expected)
     }
+
+    #[test]
+    fn merge_snapshot_builds() {
+        let mut metadata = VersionedMetadata::for_snapshot(
+            GroupId::from("org.example"),
+            ArtifactId::from("lib"),
+            Version::from("1.0-SNAPSHOT"),
+        );
+        let jar = (None, String::from("jar"));
+        let pom = (None, String::from("pom"));
+        let sources = (Some(Classifier::from("sources")), String::from("jar"));
+        let first = metadata
+            .merge_snapshot_build("20240102.030405", &[pom.clone(), jar.clone(), sources])
+            .unwrap();
+        assert_eq!(first, Version::from("1.0-20240102.030405-1"));
+
+        let second = metadata
+            .merge_snapshot_build("20240103.101010", &[pom, jar])
+            .unwrap();
+        assert_eq!(second, Version::from("1.0-20240103.101010-2"));
+        let versioning = &metadata.versioning;
+        assert_eq!(versioning.snapshot.as_ref().unwrap().buildNumber, 2);
+        assert_eq!(versioning.last_updated.as_deref(), Some("20240103101010"));
+        let values: Vec<_> = versioning
+            .snapshot_versions
+            .iter()
+            .flatten()
+            .map(|v| v.value.to_string())
+            .collect();
+        // sources were not deployed again, so they still point at the first build
+        assert_eq!(
+            values,
+            vec![
+                "1.0-20240103.101010-2",
+                "1.0-20240103.101010-2",
+                "1.0-20240102.030405-1"
+            ]
+        );
+        let parsed = VersionedMetadata::from_str(&metadata.to_xml().unwrap()).unwrap();
+        assert_eq!(parsed, metadata);
+
+        assert!(metadata.merge_snapshot_build("2024-01-03", &[]).is_err());
+        metadata.version = Some(Version::from("1.0"));
+        assert!(
+            metadata
+                .merge_snapshot_build("20240103.101010", &[])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn namespace_check() {
+        let html = "<html><body><h1>502 Bad Gateway</h1></body></html>";
+        let result = VersionedMetadata::parse_with_namespace_check(
+            Cursor::new(html),
+            NamespaceCheck::Validate,
+        );
+        assert!(matches!(result, Err(Unexpected(message)) if message.contains("found <html>")));
+
+        let meta = r#"<metadata xmlns="http://maven.apache.org/METADATA/1.1.0">
+  <groupId>org.example</groupId>
+  <artifactId>lib</artifactId>
+  <versioning><lastUpdated>20240101120000</lastUpdated></versioning>
+</metadata>"#;
+        let (metadata, _) = VersionedMetadata::parse_with_namespace_check(
+            Cursor::new(meta),
+            NamespaceCheck::Require,
+        )
+        .unwrap();
+        assert_eq!(metadata.artifact_id, ArtifactId::from("lib"));
+
+        let bare = "<metadata><groupId>g</groupId></metadata>";
+        assert!(matches!(
+            VersionedMetadata::parse_with_namespace_check(
+                Cursor::new(bare),
+                NamespaceCheck::Require
+            ),
+            Err(MetadataError::Namespace(_))
+        ));
+    }
+
+    #[test]
+    fn parse_warnings() {
+        let meta = r##"<metadata>
+  <groupId>org.example</groupId>
+  <artifactId>lib</artifactId>
+  <versioning>
+    <release>1.0</release>
+    <versions><version>1.0</version></versions>
+    <lastUpdated>2024-01-01</lastUpdated>
+    <mirror>central</mirror>
+  </versioning>
+</metadata>"##;
+        let (metadata, warnings) =
+            VersionedMetadata::parse_with_warnings(Cursor::new(meta)).unwrap();
+        assert_eq!(metadata.versioning.release, Some(Version::from("1.0")));
+        assert_eq!(
+            warnings.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec![
+                "<metadata/versioning/lastUpdated> has malformed value '2024-01-01'",
+                "skipped unknown element <metadata/versioning/mirror>",
+            ]
+        );
+    }
+
+    fn assert_round_trip(path: &str) {
+        let input = std::fs::read_to_string(path).unwrap();
+        let metadata = VersionedMetadata::from_str(&input).unwrap();
+        let xml = metadata.to_xml().unwrap();
+        let reparsed = VersionedMetadata::from_str(&xml).unwrap();
+        assert_eq!(metadata, reparsed);
+        assert_eq!(xml, reparsed.to_xml().unwrap());
+    }
+
+    #[test]
+    fn round_trip_fixtures() {
+        assert_round_trip(
+            "test-files/metadata/org/openapitools/openapi-generator-cli/maven-metadata.xml",
+        );
+        assert_round_trip("test-files/metadata/org/pac4j/pac4j-http/maven-metadata.xml");
+        assert_round_trip(
+            "test-files/metadata/org/pac4j/pac4j-http/6.1.4-SNAPSHOT/maven-metadata.xml",
+        );
+    }
 }
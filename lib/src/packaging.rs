@@ -0,0 +1,172 @@
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+use std::io::Read;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ContentKind {
+    Zip,
+    Xml,
+    Html,
+    Json,
+}
+
+impl Display for ContentKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ContentKind::Zip => f.write_str("zip"),
+            ContentKind::Xml => f.write_str("xml"),
+            ContentKind::Html => f.write_str("html"),
+            ContentKind::Json => f.write_str("json"),
+        }
+    }
+}
+
+impl ContentKind {
+    pub fn for_extension(extension: &str) -> Option<ContentKind> {
+        match extension {
+            "jar" | "war" | "ear" | "aar" | "rar" | "zip" | "hpi" | "jpi" | "nbm" => {
+                Some(ContentKind::Zip)
+            }
+            "pom" | "xml" => Some(ContentKind::Xml),
+            "module" | "json" => Some(ContentKind::Json),
+            _ => None,
+        }
+    }
+
+    pub fn detect(head: &[u8]) -> Option<ContentKind> {
+        if head.starts_with(b"PK\x03\x04") || head.starts_with(b"PK\x05\x06") {
+            return Some(ContentKind::Zip);
+        }
+        let head = head.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(head);
+        let start = head.iter().position(|b| !b.is_ascii_whitespace())?;
+        let text = String::from_utf8_lossy(&head[start..]).to_lowercase();
+        if text.starts_with("<!doctype html") || text.starts_with("<html") {
+            Some(ContentKind::Html)
+        } else if text.starts_with('<') {
+            Some(ContentKind::Xml)
+        } else if text.starts_with('{') || text.starts_with('[') {
+            Some(ContentKind::Json)
+        } else {
+            None
+        }
+    }
+
+    pub fn detect_file(path: &Path) -> std::io::Result<Option<ContentKind>> {
+        let mut head = Vec::with_capacity(512);
+        std::fs::File::open(path)?
+            .take(512)
+            .read_to_end(&mut head)?;
+        Ok(Self::detect(&head))
+    }
+}
+
+pub const PLUGIN_DESCRIPTOR: &str = "META-INF/maven/plugin.xml";
+
+pub fn required_entries(packaging: &str) -> &'static [&'static str] {
+    match packaging {
+        "maven-plugin" => &[PLUGIN_DESCRIPTOR],
+        _ => &[],
+    }
+}
+
+pub fn missing_entries(path: &Path, packaging: &str) -> zip::result::ZipResult<Vec<&'static str>> {
+    let required = required_entries(packaging);
+    if required.is_empty() {
+        return Ok(vec![]);
+    }
+    let archive = zip::ZipArchive::new(std::fs::File::open(path)?)?;
+    Ok(required
+        .iter()
+        .filter(|entry| archive.index_for_name(entry).is_none())
+        .copied()
+        .collect())
+}
+
+pub fn classifier_for_type(type_: &str) -> Option<&'static str> {
+    match type_ {
+        "test-jar" => Some("tests"),
+        "java-source" => Some("sources"),
+        "javadoc" => Some("javadoc"),
+        "ejb-client" => Some("client"),
+        _ => None,
+    }
+}
+
+pub fn extension_for_packaging(packaging: &str) -> &str {
+    match packaging {
+        "maven-plugin" | "bundle" | "ejb" | "ejb-client" | "eclipse-plugin" | "java-source"
+        | "javadoc" | "test-jar" => "jar",
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_kinds() {
+        assert_eq!(
+            ContentKind::detect(b"PK\x03\x04rest"),
+            Some(ContentKind::Zip)
+        );
+        assert_eq!(
+            ContentKind::detect(b"\xEF\xBB\xBF<?xml version=\"1.0\"?><project/>"),
+            Some(ContentKind::Xml)
+        );
+        assert_eq!(
+            ContentKind::detect(b"\n  <!DOCTYPE HTML><html>"),
+            Some(ContentKind::Html)
+        );
+        assert_eq!(
+            ContentKind::detect(b"{\"formatVersion\": \"1.1\"}"),
+            Some(ContentKind::Json)
+        );
+        assert_eq!(ContentKind::detect(b""), None);
+    }
+
+    #[test]
+    fn packaging_extensions() {
+        assert_eq!(extension_for_packaging("maven-plugin"), "jar");
+        assert_eq!(extension_for_packaging("war"), "war");
+        assert_eq!(extension_for_packaging("test-jar"), "jar");
+        assert_eq!(classifier_for_type("test-jar"), Some("tests"));
+        assert_eq!(classifier_for_type("jar"), None);
+        assert_eq!(
+            ContentKind::for_extension(extension_for_packaging("bundle")),
+            Some(ContentKind::Zip)
+        );
+    }
+
+    #[test]
+    fn plugin_descriptor_entries() {
+        use std::io::Write;
+        use zip::write::SimpleFileOptions;
+
+        let dir =
+            std::env::temp_dir().join(format!("maven-artifact-plugin-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let write_jar = |name: &str, entries: &[&str]| {
+            let path = dir.join(name);
+            let mut zip = zip::ZipWriter::new(std::fs::File::create(&path).unwrap());
+            for entry in entries {
+                zip.start_file(*entry, SimpleFileOptions::default())
+                    .unwrap();
+                zip.write_all(b"<plugin/>").unwrap();
+            }
+            zip.finish().unwrap();
+            path
+        };
+        let plugin = write_jar("plugin.jar", &[PLUGIN_DESCRIPTOR]);
+        let broken = write_jar("broken.jar", &["META-INF/MANIFEST.MF"]);
+
+        assert!(missing_entries(&plugin, "maven-plugin").unwrap().is_empty());
+        assert_eq!(
+            missing_entries(&broken, "maven-plugin").unwrap(),
+            vec![PLUGIN_DESCRIPTOR]
+        );
+        assert!(missing_entries(&broken, "jar").unwrap().is_empty());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,120 @@
+use crate::artifact::Artifact;
+use std::path::PathBuf;
+use thiserror::Error;
+
+#[derive(Debug, Clone, PartialEq, Error)]
+#[error("{value:?} can't be used as a file name, {reason}")]
+pub struct UnsafePath {
+    pub value: String,
+    pub reason: String,
+}
+
+const RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+// A file or directory name that is safe on every platform. Checked everywhere, so that
+// coordinates which happen to work on Linux don't fail later on a Windows machine, and
+// renaming is left to whoever published them: a sanitized name could clash with another.
+pub fn check_name(name: &str) -> Result<(), UnsafePath> {
+    let unsafe_name = |reason: String| {
+        Err(UnsafePath {
+            value: name.to_string(),
+            reason,
+        })
+    };
+    if name.is_empty() || name == "." || name == ".." {
+        return unsafe_name(String::from("it names no file"));
+    }
+    if let Some(c) = name
+        .chars()
+        .find(|c| c.is_control() || r#"<>:"/\|?*"#.contains(*c))
+    {
+        return unsafe_name(format!("it contains {:?}", c));
+    }
+    if name.ends_with(['.', ' ']) {
+        return unsafe_name(String::from("Windows drops a trailing dot or space"));
+    }
+    let stem = name.split('.').next().unwrap_or(name).trim_end();
+    if RESERVED_NAMES.iter().any(|r| r.eq_ignore_ascii_case(stem)) {
+        return unsafe_name(String::from("it is a device name on Windows"));
+    }
+    Ok(())
+}
+
+// every directory and the file name an artifact is stored under
+pub fn check_artifact(artifact: &Artifact) -> Result<(), UnsafePath> {
+    for name in artifact.path().split('/') {
+        check_name(name)?;
+    }
+    check_name(&artifact.file_name())
+}
+
+// Windows refuses absolute paths of MAX_PATH characters or more unless they are given
+// as \\?\C:\..., elsewhere paths are returned as they are
+pub fn long_path(path: PathBuf) -> PathBuf {
+    #[cfg(windows)]
+    {
+        use std::path::{Component, Prefix};
+
+        const MAX_PATH: usize = 260;
+        let plain = path
+            .components()
+            .all(|c| !matches!(c, Component::CurDir | Component::ParentDir));
+        if let (true, true, Some(Component::Prefix(prefix)), Some(value)) = (
+            path.as_os_str().len() >= MAX_PATH,
+            plain,
+            path.components().next(),
+            path.to_str(),
+        ) {
+            let value = value.replace('/', "\\");
+            match prefix.kind() {
+                Prefix::Disk(_) => return PathBuf::from(format!(r"\\?\{}", value)),
+                Prefix::UNC(..) => {
+                    return PathBuf::from(format!(r"\\?\UNC\{}", &value[2..]));
+                }
+                _ => {}
+            }
+        }
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unsafe_names() {
+        for name in [
+            "lib-1.0.jar",
+            "lib-1.0-sources.jar",
+            "con-utils",
+            "auxiliary.jar",
+        ] {
+            assert_eq!(check_name(name), Ok(()), "{}", name);
+        }
+        let reason = |name| check_name(name).unwrap_err().reason;
+        assert_eq!(reason(".."), "it names no file");
+        assert_eq!(reason("lib-1.0-x:y.jar"), "it contains ':'");
+        assert_eq!(reason("lib\t1.0"), "it contains '\\t'");
+        assert_eq!(reason("1.0."), "Windows drops a trailing dot or space");
+        assert_eq!(reason("nul.jar"), "it is a device name on Windows");
+        assert_eq!(reason("COM1"), "it is a device name on Windows");
+
+        let artifact = |coordinates| Artifact::parse(coordinates).unwrap();
+        assert!(check_artifact(&artifact("org.example:lib:jar:sources:1.0")).is_ok());
+        assert!(check_artifact(&artifact("org..example:lib:1.0")).is_err());
+        assert!(check_artifact(&artifact("org.example:aux:1.0")).is_err());
+        assert!(check_artifact(&artifact("org.example:lib:jar:a|b:1.0")).is_err());
+        assert!(check_artifact(&artifact("org.example:lib:1.0/../../x")).is_err());
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn long_paths_elsewhere() {
+        let path = PathBuf::from(format!("/{}", "a/".repeat(200)));
+        assert_eq!(long_path(path.clone()), path);
+    }
+}
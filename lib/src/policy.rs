@@ -0,0 +1,288 @@
+use crate::artifact::Artifact;
+use crate::pom::Project;
+use crate::substitution::{Pattern, glob};
+use crate::version::VersionRange;
+use serde::{Serialize, Serializer};
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum PolicyError {
+    #[error("Unable to read {path}: {source}")]
+    IO {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Invalid rule on line {line}: {message}")]
+    Parse { line: usize, message: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Enforcement {
+    // resolution fails with ResolveError::Banned
+    Deny,
+    // resolution goes on, the violation is kept for Resolver::violations
+    Warn,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Ban {
+    // all versions when there is no range
+    Artifact {
+        pattern: Pattern,
+        versions: Option<VersionRange>,
+    },
+    // license names, * as a wildcard, case insensitive
+    License(String),
+}
+
+impl Display for Ban {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Ban::Artifact {
+                pattern,
+                versions: None,
+            } => write!(f, "{}", pattern),
+            Ban::Artifact {
+                pattern,
+                versions: Some(versions),
+            } => write!(f, "{} {}", pattern, versions),
+            Ban::License(name) => write!(f, "license {}", name),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rule {
+    pub enforcement: Enforcement,
+    pub ban: Ban,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Violation {
+    // groupId:artifactId:version
+    pub artifact: String,
+    pub enforcement: Enforcement,
+    #[serde(serialize_with = "display")]
+    pub ban: Ban,
+    // the offending license, for license bans
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+}
+
+fn display<S: Serializer>(ban: &Ban, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(ban)
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.license {
+            Some(license) => write!(
+                f,
+                "{} is licensed under {}, banned by `{}`",
+                self.artifact, license, self.ban
+            ),
+            None => write!(f, "{} is banned by `{}`", self.artifact, self.ban),
+        }
+    }
+}
+
+// Artifacts platform teams don't want in builds. Checked by the Resolver whenever it
+// resolves coordinates or reads a descriptor, so a ban holds for downloads and for every
+// node of a dependency graph alike.
+#[derive(Debug, Clone, Default)]
+pub struct BanPolicy {
+    rules: Vec<Rule>,
+}
+
+impl BanPolicy {
+    pub fn new() -> BanPolicy {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    pub fn with(mut self, enforcement: Enforcement, ban: Ban) -> BanPolicy {
+        self.rules.push(Rule { enforcement, ban });
+        self
+    }
+
+    // One rule per line, # starts a comment:
+    //   deny org.apache.logging.log4j:log4j-core (,2.17.1)
+    //   warn commons-collections:commons-collections
+    //   deny license GNU Affero*
+    pub fn parse(content: &str) -> Result<BanPolicy, PolicyError> {
+        let mut rules = vec![];
+        for (index, line) in content.lines().enumerate() {
+            let invalid = |message: String| PolicyError::Parse {
+                line: index + 1,
+                message,
+            };
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (enforcement, rest) = match line.split_once(char::is_whitespace) {
+                Some(("deny", rest)) => (Enforcement::Deny, rest.trim()),
+                Some(("warn", rest)) => (Enforcement::Warn, rest.trim()),
+                _ => return Err(invalid(String::from("expected deny or warn and a ban"))),
+            };
+            let ban = match rest.split_once(char::is_whitespace) {
+                Some(("license", name)) => Ban::License(name.trim().to_string()),
+                _ => {
+                    let mut words = rest.split_whitespace();
+                    let pattern = words
+                        .next()
+                        .and_then(Pattern::parse)
+                        .ok_or_else(|| invalid(String::from("expected groupId:artifactId")))?;
+                    let versions = words
+                        .next()
+                        .map(VersionRange::parse)
+                        .transpose()
+                        .map_err(|e| invalid(e.to_string()))?;
+                    if words.next().is_some() {
+                        return Err(invalid(String::from(
+                            "a version range can't contain spaces",
+                        )));
+                    }
+                    Ban::Artifact { pattern, versions }
+                }
+            };
+            rules.push(Rule { enforcement, ban });
+        }
+        Ok(BanPolicy { rules })
+    }
+
+    pub fn load(path: &Path) -> Result<BanPolicy, PolicyError> {
+        let content = std::fs::read_to_string(path).map_err(|source| PolicyError::IO {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::parse(&content)
+    }
+
+    // Bans of the coordinates, denials first
+    pub fn check(&self, artifact: &Artifact) -> Vec<Violation> {
+        self.violations(artifact, |ban| match ban {
+            Ban::Artifact { pattern, versions } => {
+                let banned = pattern.matches(&artifact.group_id, &artifact.artifact_id)
+                    && versions
+                        .as_ref()
+                        .is_none_or(|range| range.contains(&artifact.version));
+                banned.then_some(None)
+            }
+            Ban::License(_) => None,
+        })
+    }
+
+    // Bans of the licenses declared by an effective POM, denials first
+    pub fn check_licenses(&self, artifact: &Artifact, project: &Project) -> Vec<Violation> {
+        self.violations(artifact, |ban| match ban {
+            Ban::License(name) => {
+                let pattern = name.to_lowercase();
+                project
+                    .licenses
+                    .iter()
+                    .filter_map(|l| l.name.as_deref())
+                    .find(|l| glob(&pattern, &l.to_lowercase()))
+                    .map(|l| Some(l.to_string()))
+            }
+            Ban::Artifact { .. } => None,
+        })
+    }
+
+    // matches gives the offending license, if any, of a violated ban
+    fn violations<F>(&self, artifact: &Artifact, matches: F) -> Vec<Violation>
+    where
+        F: Fn(&Ban) -> Option<Option<String>>,
+    {
+        let coordinates = format!(
+            "{}:{}:{}",
+            artifact.group_id, artifact.artifact_id, artifact.version
+        );
+        let mut violations: Vec<Violation> = self
+            .rules
+            .iter()
+            .filter_map(|rule| {
+                matches(&rule.ban).map(|license| Violation {
+                    artifact: coordinates.clone(),
+                    enforcement: rule.enforcement,
+                    ban: rule.ban.clone(),
+                    license,
+                })
+            })
+            .collect();
+        violations.sort_by_key(|v| v.enforcement != Enforcement::Deny);
+        violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pom::{License, PomParser};
+
+    #[test]
+    fn parse_and_check() {
+        let policy = BanPolicy::parse(
+            "# CVE-2021-44228 and friends
+deny org.apache.logging.log4j:log4j-core (,2.17.1)
+warn commons-collections:commons-collections
+deny license GNU Affero*",
+        )
+        .unwrap();
+        assert_eq!(policy.rules().len(), 3);
+
+        let log4j = |version| {
+            Artifact::parse(&format!("org.apache.logging.log4j:log4j-core:{}", version)).unwrap()
+        };
+        let violations = policy.check(&log4j("2.14.1"));
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].enforcement, Enforcement::Deny);
+        assert_eq!(
+            violations[0].to_string(),
+            "org.apache.logging.log4j:log4j-core:2.14.1 is banned by `org.apache.logging.log4j:log4j-core (,2.17.1)`"
+        );
+        assert!(policy.check(&log4j("2.17.1")).is_empty());
+
+        let collections = Artifact::parse("commons-collections:commons-collections:3.2.1").unwrap();
+        let mut project = PomParser::new()
+            .from_str("<project><artifactId>commons-collections</artifactId></project>")
+            .unwrap();
+        project.licenses.push(License {
+            name: Some(String::from("GNU Affero General Public License v3")),
+            ..License::default()
+        });
+        let violations = policy.check(&collections);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].enforcement, Enforcement::Warn);
+        let violations = policy.check_licenses(&collections, &project);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].enforcement, Enforcement::Deny);
+        assert_eq!(
+            violations[0].to_string(),
+            "commons-collections:commons-collections:3.2.1 is licensed under GNU Affero General Public License v3, banned by `license GNU Affero*`"
+        );
+    }
+
+    #[test]
+    fn invalid_rules() {
+        let line = |content| match BanPolicy::parse(content) {
+            Err(PolicyError::Parse { line, .. }) => line,
+            other => panic!("{:?}", other),
+        };
+        assert_eq!(line("ban a:b"), 1);
+        assert_eq!(line("deny a:b\ndeny a:b [1.0"), 2);
+        assert_eq!(line("warn a:b [1.0, 2.0]"), 1);
+        assert_eq!(line("deny license"), 1);
+    }
+}
@@ -0,0 +1,1726 @@
+use crate::artifact::Artifact;
+use crate::packaging::{classifier_for_type, extension_for_packaging};
+use crate::pom::PomError::Unexpected;
+use crate::warning::{Warning, Warnings};
+pub use crate::{ArtifactId, Classifier, GroupId, Version};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufReader, Cursor, Read, Write};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use xml::reader::XmlEvent;
+use xml::writer::XmlEvent as WriteEvent;
+use xml::{EmitterConfig, EventReader, EventWriter};
+
+static MODEL_NAMESPACE_PREFIX: &str = "http://maven.apache.org/POM/";
+static MODEL_VERSIONS: [&str; 2] = ["4.0.0", "4.1.0"];
+
+// valid model elements that Project does not represent, skipped without a warning
+static PROJECT_ELEMENTS: [&str; 9] = [
+    "build",
+    "profiles",
+    "repositories",
+    "pluginRepositories",
+    "reporting",
+    "reports",
+    "issueManagement",
+    "mailingLists",
+    "contributors",
+];
+
+static DISTRIBUTION_ELEMENTS: [&str; 5] = [
+    "repository",
+    "snapshotRepository",
+    "site",
+    "downloadUrl",
+    "status",
+];
+
+static SCOPES: [&str; 6] = ["compile", "provided", "runtime", "test", "system", "import"];
+
+#[derive(Error, Debug)]
+pub enum PomError {
+    #[error("{0} IO error while parsing")]
+    IO(#[from] std::io::Error),
+    #[error("{0} XML error while parsing")]
+    Xml(#[from] xml::reader::Error),
+    #[error("{0} Unexpected XML error while parsing")]
+    Unexpected(String),
+    #[error("{0} is not a Maven namespace")]
+    Namespace(String),
+    #[error("{0} XML error while writing")]
+    XmlWrite(#[from] xml::writer::Error),
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NamespaceCheck {
+    #[default]
+    Ignore,
+    // a declared namespace must be a Maven one, but it may be omitted
+    Validate,
+    Require,
+}
+
+impl NamespaceCheck {
+    pub(crate) fn check(
+        &self,
+        namespace: Option<&str>,
+        valid: impl Fn(&str) -> bool,
+    ) -> Result<(), String> {
+        match (self, namespace) {
+            (NamespaceCheck::Ignore, _) | (NamespaceCheck::Validate, None) => Ok(()),
+            (NamespaceCheck::Require, None) => Err(String::from("<no namespace>")),
+            (_, Some(namespace)) if valid(namespace) => Ok(()),
+            (_, Some(namespace)) => Err(namespace.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Project {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_version: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub root: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent: Option<Parent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group_id: Option<GroupId>,
+    pub artifact_id: ArtifactId,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<Version>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub packaging: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inception_year: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub organization: Option<Organization>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prerequisites: Option<Prerequisites>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ci_management: Option<CiManagement>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub licenses: Vec<License>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub developers: Vec<Developer>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scm: Option<Scm>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub modules: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub subprojects: Vec<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub properties: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dependency_management: Option<DependencyManagement>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<Dependency>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub relocation: Option<Relocation>,
+}
+
+impl Project {
+    pub fn new(artifact_id: ArtifactId) -> Project {
+        Project {
+            model_version: None,
+            root: false,
+            parent: None,
+            group_id: None,
+            artifact_id,
+            version: None,
+            packaging: None,
+            name: None,
+            description: None,
+            url: None,
+            inception_year: None,
+            organization: None,
+            prerequisites: None,
+            ci_management: None,
+            licenses: vec![],
+            developers: vec![],
+            scm: None,
+            modules: vec![],
+            subprojects: vec![],
+            properties: BTreeMap::new(),
+            dependency_management: None,
+            dependencies: vec![],
+            relocation: None,
+        }
+    }
+
+    pub fn model_version(&self) -> &str {
+        self.model_version.as_deref().unwrap_or("4.0.0")
+    }
+
+    pub fn is_maven4_model(&self) -> bool {
+        self.model_version() != "4.0.0"
+    }
+
+    pub fn flavor(&self) -> PomFlavor {
+        // consumer POMs are always flattened to 4.0.0 without build-only structure
+        if self.is_maven4_model() || self.root || !self.subprojects.is_empty() {
+            PomFlavor::Build
+        } else {
+            PomFlavor::Consumer
+        }
+    }
+
+    pub fn dependency_on(&self, artifact: &Artifact) -> Option<Dependency> {
+        let declared = self.dependencies.iter().find(|d| {
+            d.group_id == artifact.group_id
+                && d.artifact_id == artifact.artifact_id
+                && (artifact.classifier.is_none() || d.classifier == artifact.classifier)
+        })?;
+        let mut dependency = declared.clone();
+        if let Some(management) = &self.dependency_management {
+            management.manage(&mut dependency);
+        }
+        Some(dependency)
+    }
+
+    pub fn child_projects(&self) -> impl Iterator<Item = &String> {
+        self.modules.iter().chain(self.subprojects.iter())
+    }
+
+    pub fn effective_group_id(&self) -> Option<&GroupId> {
+        self.group_id
+            .as_ref()
+            .or(self.parent.as_ref().map(|p| &p.group_id))
+    }
+
+    pub fn effective_version(&self) -> Option<&Version> {
+        self.version
+            .as_ref()
+            .or(self.parent.as_ref().map(|p| &p.version))
+    }
+
+    pub fn packaging(&self) -> &str {
+        self.packaging.as_deref().unwrap_or("jar")
+    }
+
+    // Fills in what the child does not declare itself. artifactId, name, packaging and
+    // modules are never inherited.
+    pub fn inherit(&mut self, parent: &Project) {
+        fn fill<T: Clone>(value: &mut Option<T>, inherited: &Option<T>) {
+            if value.is_none() {
+                value.clone_from(inherited);
+            }
+        }
+        fn fill_list<T: Clone>(values: &mut Vec<T>, inherited: &[T]) {
+            if values.is_empty() {
+                values.extend_from_slice(inherited);
+            }
+        }
+        if self.group_id.is_none() {
+            self.group_id = parent.effective_group_id().cloned();
+        }
+        if self.version.is_none() {
+            self.version = parent.effective_version().cloned();
+        }
+        fill(&mut self.description, &parent.description);
+        fill(&mut self.url, &parent.url);
+        fill(&mut self.inception_year, &parent.inception_year);
+        fill(&mut self.organization, &parent.organization);
+        fill(&mut self.ci_management, &parent.ci_management);
+        fill(&mut self.scm, &parent.scm);
+        fill_list(&mut self.licenses, &parent.licenses);
+        fill_list(&mut self.developers, &parent.developers);
+        for (key, value) in &parent.properties {
+            self.properties
+                .entry(key.clone())
+                .or_insert_with(|| value.clone());
+        }
+        if let Some(inherited) = &parent.dependency_management {
+            let management = self.dependency_management.get_or_insert_default();
+            for dependency in &inherited.dependencies {
+                if management.find(dependency).is_none() {
+                    management.dependencies.push(dependency.clone());
+                }
+            }
+        }
+        for dependency in &parent.dependencies {
+            if !self.dependencies.iter().any(|d| d.same_key(dependency)) {
+                self.dependencies.push(dependency.clone());
+            }
+        }
+    }
+
+    // A project with just the coordinates of the artifact, to fill in before to_xml
+    pub fn skeleton(artifact: &Artifact) -> Project {
+        let mut project = Project::new(artifact.artifact_id.clone());
+        project.model_version = Some(String::from("4.0.0"));
+        project.group_id = Some(artifact.group_id.clone());
+        project.version = Some(artifact.version.clone());
+        project.packaging = artifact.extension.clone().filter(|e| e != "jar");
+        project
+    }
+
+    // A pom-packaged project managing the given dependencies, ready to be imported
+    pub fn bom(coordinates: &Artifact, dependencies: Vec<Dependency>) -> Project {
+        let mut project = Project::skeleton(coordinates);
+        project.packaging = Some(String::from("pom"));
+        project.dependency_management = Some(DependencyManagement { dependencies });
+        project
+    }
+
+    // Writes the coordinates, properties, modules and dependency sections. Descriptive
+    // metadata such as licenses and developers is left out.
+    pub fn to_xml(&self) -> Result<String, PomError> {
+        let model_version = self.model_version.as_deref().unwrap_or("4.0.0");
+        let namespace = format!("{}{}", MODEL_NAMESPACE_PREFIX, model_version);
+        let schema = format!(
+            "{} https://maven.apache.org/xsd/maven-{}.xsd",
+            namespace, model_version
+        );
+        let mut out = Vec::new();
+        let mut writer = EmitterConfig::new()
+            .perform_indent(true)
+            .indent_string("  ")
+            .create_writer(&mut out);
+        writer.write(
+            WriteEvent::start_element("project")
+                .default_ns(namespace.as_str())
+                .ns("xsi", "http://www.w3.org/2001/XMLSchema-instance")
+                .attr("xsi:schemaLocation", &schema),
+        )?;
+        write_element(&mut writer, "modelVersion", model_version)?;
+        if let Some(parent) = &self.parent {
+            writer.write(WriteEvent::start_element("parent"))?;
+            write_element(&mut writer, "groupId", &parent.group_id)?;
+            write_element(&mut writer, "artifactId", &parent.artifact_id)?;
+            write_element(&mut writer, "version", &parent.version)?;
+            if let Some(relative_path) = &parent.relative_path {
+                write_element(&mut writer, "relativePath", relative_path)?;
+            }
+            writer.write(WriteEvent::end_element())?;
+        }
+        if let Some(group_id) = &self.group_id {
+            write_element(&mut writer, "groupId", group_id)?;
+        }
+        write_element(&mut writer, "artifactId", &self.artifact_id)?;
+        if let Some(version) = &self.version {
+            write_element(&mut writer, "version", version)?;
+        }
+        if let Some(packaging) = &self.packaging {
+            write_element(&mut writer, "packaging", packaging)?;
+        }
+        if let Some(name) = &self.name {
+            write_element(&mut writer, "name", name)?;
+        }
+        if let Some(description) = &self.description {
+            write_element(&mut writer, "description", description)?;
+        }
+        if let Some(url) = &self.url {
+            write_element(&mut writer, "url", url)?;
+        }
+        if !self.modules.is_empty() {
+            writer.write(WriteEvent::start_element("modules"))?;
+            for module in &self.modules {
+                write_element(&mut writer, "module", module)?;
+            }
+            writer.write(WriteEvent::end_element())?;
+        }
+        if !self.properties.is_empty() {
+            writer.write(WriteEvent::start_element("properties"))?;
+            for (key, value) in &self.properties {
+                write_element(&mut writer, key, value)?;
+            }
+            writer.write(WriteEvent::end_element())?;
+        }
+        if let Some(management) = &self.dependency_management {
+            writer.write(WriteEvent::start_element("dependencyManagement"))?;
+            write_dependencies(&mut writer, &management.dependencies)?;
+            writer.write(WriteEvent::end_element())?;
+        }
+        if !self.dependencies.is_empty() {
+            write_dependencies(&mut writer, &self.dependencies)?;
+        }
+        writer.write(WriteEvent::end_element())?;
+        String::from_utf8(out).map_err(|e| Unexpected(e.to_string()))
+    }
+
+    pub fn relocation_target(&self) -> Option<Artifact> {
+        let relocation = self.relocation.as_ref()?;
+        let group_id = relocation
+            .group_id
+            .clone()
+            .or(self.effective_group_id().cloned())?;
+        let artifact_id = relocation
+            .artifact_id
+            .clone()
+            .unwrap_or(self.artifact_id.clone());
+        let version = relocation
+            .version
+            .clone()
+            .or(self.effective_version().cloned())?;
+        Some(Artifact::new(group_id, artifact_id, version))
+    }
+
+    pub fn property(&self, name: &str) -> Option<String> {
+        match name {
+            "project.groupId" | "pom.groupId" => self.effective_group_id().map(|g| g.to_string()),
+            "project.artifactId" | "pom.artifactId" => Some(self.artifact_id.to_string()),
+            "project.version" | "pom.version" => self.effective_version().map(|v| v.to_string()),
+            "project.parent.version" => self.parent.as_ref().map(|p| p.version.to_string()),
+            _ => self.properties.get(name).cloned(),
+        }
+    }
+
+    // Properties referenced from dependency versions, managed ones included, in the order
+    // they are first used. Dependencies are interpolated except for the version, so it
+    // still shows where the property goes.
+    pub fn version_properties(&self) -> Vec<VersionProperty> {
+        let managed = self
+            .dependency_management
+            .iter()
+            .flat_map(|m| m.dependencies.iter());
+        let mut found: Vec<VersionProperty> = vec![];
+        for dependency in managed.chain(self.dependencies.iter()) {
+            let Some(version) = &dependency.version else {
+                continue;
+            };
+            for name in property_references(version) {
+                if name.starts_with("project.") || name.starts_with("pom.") {
+                    continue;
+                }
+                let index = match found.iter().position(|p| p.name == name) {
+                    Some(index) => index,
+                    None => {
+                        found.push(VersionProperty {
+                            name: name.to_string(),
+                            value: self.property(name).map(|v| self.interpolate(&v)),
+                            dependencies: vec![],
+                        });
+                        found.len() - 1
+                    }
+                };
+                let mut dependency = dependency.interpolated(self);
+                dependency.version = Some(version.clone());
+                if !found[index].dependencies.contains(&dependency) {
+                    found[index].dependencies.push(dependency);
+                }
+            }
+        }
+        found
+    }
+
+    pub fn interpolate(&self, value: &str) -> String {
+        let mut out = String::new();
+        let mut rest = value;
+        while let Some(start) = rest.find("${") {
+            let Some(end) = rest[start..].find('}') else {
+                break;
+            };
+            let name = &rest[start + 2..start + end];
+            out.push_str(&rest[..start]);
+            match self.property(name) {
+                Some(v) => out.push_str(&v),
+                None => out.push_str(&rest[start..start + end + 1]),
+            }
+            rest = &rest[start + end + 1..];
+        }
+        out.push_str(rest);
+        out
+    }
+}
+
+fn property_references(value: &str) -> impl Iterator<Item = &str> {
+    value
+        .split("${")
+        .skip(1)
+        .filter_map(|s| s.split_once('}').map(|(name, _)| name))
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionProperty {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    pub dependencies: Vec<Dependency>,
+}
+
+impl VersionProperty {
+    // The property value that makes the dependency resolve to `version`, e.g. 33.0.0 for
+    // 33.0.0-jre when declared as ${guava.version}-jre. Versions built from several
+    // properties can't be taken apart and give None.
+    pub fn value_for(&self, dependency: &Dependency, version: &Version) -> Option<String> {
+        let declared = dependency.version.as_ref()?;
+        let reference = format!("${{{}}}", self.name);
+        let (prefix, suffix) = declared.split_once(&reference)?;
+        if prefix.contains("${") || suffix.contains("${") {
+            return None;
+        }
+        version
+            .strip_prefix(prefix)?
+            .strip_suffix(suffix)
+            .filter(|value| !value.is_empty())
+            .map(String::from)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PomFlavor {
+    Consumer,
+    Build,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Relocation {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group_id: Option<GroupId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub artifact_id: Option<ArtifactId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<Version>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Parent {
+    pub group_id: GroupId,
+    pub artifact_id: ArtifactId,
+    pub version: Version,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub relative_path: Option<String>,
+}
+
+impl Parent {
+    pub fn artifact(&self) -> Artifact {
+        Artifact::new(
+            self.group_id.clone(),
+            self.artifact_id.clone(),
+            self.version.clone(),
+        )
+    }
+
+    // an empty <relativePath/> disables the lookup, an absent one means ../pom.xml
+    pub fn relative_path(&self) -> Option<&str> {
+        match self.relative_path.as_deref() {
+            None => Some("../pom.xml"),
+            Some("") => None,
+            Some(path) => Some(path),
+        }
+    }
+
+    pub fn matches(&self, project: &Project) -> bool {
+        project.effective_group_id() == Some(&self.group_id)
+            && project.artifact_id == self.artifact_id
+            && project.effective_version() == Some(&self.version)
+    }
+
+    // Maven only uses the POM at relativePath if its coordinates match, otherwise the
+    // parent comes from a repository
+    pub fn find_local(&self, base_dir: &Path) -> Option<(PathBuf, Project)> {
+        let mut path = base_dir.join(self.relative_path()?);
+        if path.is_dir() {
+            path.push("pom.xml");
+        }
+        let file = File::open(&path).ok()?;
+        let project = PomParser::new().parse(file).ok()?;
+        self.matches(&project).then_some((path, project))
+    }
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Organization {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Prerequisites {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maven: Option<String>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CiManagement {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct License {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub distribution: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comments: Option<String>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Developer {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub organization: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub organization_url: Option<String>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Scm {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub connection: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub developer_connection: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DependencyManagement {
+    pub dependencies: Vec<Dependency>,
+}
+
+impl DependencyManagement {
+    pub fn find(&self, dependency: &Dependency) -> Option<&Dependency> {
+        self.dependencies.iter().find(|m| m.same_key(dependency))
+    }
+
+    pub fn apply(&self, dependencies: &mut [Dependency]) {
+        for dependency in dependencies.iter_mut() {
+            self.manage(dependency);
+        }
+    }
+
+    // values declared on the dependency itself always win over managed ones
+    fn manage(&self, dependency: &mut Dependency) {
+        let Some(managed) = self.find(dependency) else {
+            return;
+        };
+        if dependency.version.is_none() {
+            dependency.version = managed.version.clone();
+        }
+        if dependency.scope.is_none() {
+            dependency.scope = managed.scope.clone();
+        }
+        for exclusion in &managed.exclusions {
+            if !dependency.exclusions.contains(exclusion) {
+                dependency.exclusions.push(exclusion.clone());
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Dependency {
+    pub group_id: GroupId,
+    pub artifact_id: ArtifactId,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<Version>,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub type_: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub classifier: Option<Classifier>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    pub optional: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclusions: Vec<Exclusion>,
+}
+
+impl Dependency {
+    pub fn new(group_id: GroupId, artifact_id: ArtifactId, version: Option<Version>) -> Dependency {
+        Dependency {
+            group_id,
+            artifact_id,
+            version,
+            type_: None,
+            classifier: None,
+            scope: None,
+            optional: false,
+            exclusions: vec![],
+        }
+    }
+
+    pub fn type_(&self) -> &str {
+        self.type_.as_deref().unwrap_or("jar")
+    }
+
+    pub fn same_key(&self, other: &Dependency) -> bool {
+        self.group_id == other.group_id
+            && self.artifact_id == other.artifact_id
+            && self.type_() == other.type_()
+            && self.classifier == other.classifier
+    }
+
+    pub fn from_artifact(artifact: &Artifact) -> Dependency {
+        let mut dependency = Dependency::new(
+            artifact.group_id.clone(),
+            artifact.artifact_id.clone(),
+            Some(artifact.version.clone()),
+        );
+        dependency.type_ = artifact.extension.clone().filter(|e| e != "jar");
+        dependency.classifier = artifact.classifier.clone();
+        dependency
+    }
+
+    pub fn write<W: Write>(&self, writer: &mut EventWriter<W>) -> Result<(), PomError> {
+        writer.write(WriteEvent::start_element("dependency"))?;
+        write_element(writer, "groupId", &self.group_id)?;
+        write_element(writer, "artifactId", &self.artifact_id)?;
+        if let Some(version) = &self.version {
+            write_element(writer, "version", version)?;
+        }
+        if let Some(type_) = &self.type_ {
+            write_element(writer, "type", type_)?;
+        }
+        if let Some(classifier) = &self.classifier {
+            write_element(writer, "classifier", classifier)?;
+        }
+        if let Some(scope) = &self.scope {
+            write_element(writer, "scope", scope)?;
+        }
+        if self.optional {
+            write_element(writer, "optional", "true")?;
+        }
+        if !self.exclusions.is_empty() {
+            writer.write(WriteEvent::start_element("exclusions"))?;
+            for exclusion in &self.exclusions {
+                writer.write(WriteEvent::start_element("exclusion"))?;
+                write_element(writer, "groupId", &exclusion.group_id)?;
+                write_element(writer, "artifactId", &exclusion.artifact_id)?;
+                writer.write(WriteEvent::end_element())?;
+            }
+            writer.write(WriteEvent::end_element())?;
+        }
+        writer.write(WriteEvent::end_element())?;
+        Ok(())
+    }
+
+    pub fn interpolated(&self, project: &Project) -> Dependency {
+        let interpolate = |value: &str| project.interpolate(value);
+        Dependency {
+            group_id: GroupId::from(interpolate(&self.group_id)),
+            artifact_id: ArtifactId::from(interpolate(&self.artifact_id)),
+            version: self.version.as_deref().map(interpolate).map(Version::from),
+            type_: self.type_.as_deref().map(interpolate),
+            classifier: self
+                .classifier
+                .as_deref()
+                .map(interpolate)
+                .map(Classifier::from),
+            scope: self.scope.as_deref().map(interpolate),
+            optional: self.optional,
+            exclusions: self.exclusions.clone(),
+        }
+    }
+
+    pub fn to_artifact(&self) -> Option<Artifact> {
+        let version = self.version.clone()?;
+        let mut artifact = Artifact::new(self.group_id.clone(), self.artifact_id.clone(), version)
+            .with_extension(extension_for_packaging(self.type_()).to_string());
+        artifact.classifier = self
+            .classifier
+            .clone()
+            .or_else(|| classifier_for_type(self.type_()).map(Classifier::from));
+        Some(artifact)
+    }
+
+    pub fn to_artifact_with_management(
+        &self,
+        management: &DependencyManagement,
+    ) -> Option<Artifact> {
+        match &self.version {
+            Some(_) => self.to_artifact(),
+            None => {
+                let mut managed = self.clone();
+                managed.version = management.find(self)?.version.clone();
+                managed.to_artifact()
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Exclusion {
+    pub group_id: GroupId,
+    pub artifact_id: ArtifactId,
+}
+
+// A <dependencies> block without an XML declaration, for pasting into an existing POM
+pub fn dependencies_xml(dependencies: &[Dependency]) -> Result<String, PomError> {
+    let mut out = Vec::new();
+    let mut writer = EmitterConfig::new()
+        .perform_indent(true)
+        .indent_string("  ")
+        .write_document_declaration(false)
+        .create_writer(&mut out);
+    write_dependencies(&mut writer, dependencies)?;
+    String::from_utf8(out).map_err(|e| Unexpected(e.to_string()))
+}
+
+fn write_dependencies<W: Write>(
+    writer: &mut EventWriter<W>,
+    dependencies: &[Dependency],
+) -> Result<(), PomError> {
+    writer.write(WriteEvent::start_element("dependencies"))?;
+    for dependency in dependencies {
+        dependency.write(writer)?;
+    }
+    writer.write(WriteEvent::end_element())?;
+    Ok(())
+}
+
+fn write_element<W: Write>(
+    writer: &mut EventWriter<W>,
+    name: &str,
+    value: &str,
+) -> Result<(), PomError> {
+    writer.write(WriteEvent::start_element(name))?;
+    writer.write(WriteEvent::characters(value))?;
+    writer.write(WriteEvent::end_element())?;
+    Ok(())
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct PomParser {
+    namespace: NamespaceCheck,
+}
+
+// Tracks the element path while parsing so warnings can say where they came from
+struct Events<R: Read> {
+    reader: EventReader<R>,
+    path: Vec<String>,
+    warnings: Warnings,
+}
+
+impl<R: Read> Events<R> {
+    fn new(reader: EventReader<R>) -> Events<R> {
+        Events {
+            reader,
+            path: Vec::new(),
+            warnings: Warnings::default(),
+        }
+    }
+
+    fn next(&mut self) -> xml::reader::Result<XmlEvent> {
+        self.reader.next()
+    }
+
+    fn path(&self) -> String {
+        self.path.join("/")
+    }
+
+    fn missing(&mut self, field: &'static str) {
+        let path = self.path();
+        self.warnings.push(Warning::MissingField { path, field });
+    }
+
+    // property references are resolved later, so only literal values are checked
+    fn malformed(&mut self, value: &str) {
+        if !value.contains("${") {
+            let path = self.path();
+            self.warnings.push(Warning::Malformed {
+                path,
+                value: value.to_string(),
+            });
+        }
+    }
+}
+
+impl PomParser {
+    pub fn new() -> PomParser {
+        PomParser::default()
+    }
+
+    pub fn with_namespace_check(mut self, namespace: NamespaceCheck) -> PomParser {
+        self.namespace = namespace;
+        self
+    }
+
+    pub fn from_str(&self, input: &str) -> Result<Project, PomError> {
+        self.parse(Cursor::new(input))
+    }
+
+    pub fn parse<R: Read>(&self, input: R) -> Result<Project, PomError> {
+        self.parse_with_warnings(input).map(|(project, _)| project)
+    }
+
+    pub fn parse_with_warnings<R: Read>(&self, input: R) -> Result<(Project, Warnings), PomError> {
+        let mut parser = Events::new(EventReader::new(BufReader::new(input)));
+        loop {
+            match parser.next()? {
+                XmlEvent::StartElement {
+                    name, attributes, ..
+                } if name.local_name == "project" => {
+                    self.namespace
+                        .check(name.namespace.as_deref(), |ns| {
+                            ns.strip_prefix(MODEL_NAMESPACE_PREFIX)
+                                .is_some_and(|version| MODEL_VERSIONS.contains(&version))
+                        })
+                        .map_err(PomError::Namespace)?;
+                    parser.path.push(name.local_name);
+                    let mut project = Self::parse_project(&mut parser)?;
+                    project.root = attributes
+                        .iter()
+                        .any(|a| a.name.local_name == "root" && a.value == "true");
+                    if project.model_version.is_none() {
+                        // Maven 4 allows omitting modelVersion, the namespace carries it
+                        project.model_version = name
+                            .namespace
+                            .as_deref()
+                            .and_then(|ns| ns.strip_prefix(MODEL_NAMESPACE_PREFIX))
+                            .map(String::from);
+                    }
+                    if project.model_version.is_none() {
+                        parser.missing("modelVersion");
+                    }
+                    break Ok((project, parser.warnings));
+                }
+                XmlEvent::StartElement { name, .. } => {
+                    break Err(Unexpected(format!(
+                        "Expected <project> root element, found <{}>",
+                        name.local_name
+                    )));
+                }
+                XmlEvent::EndDocument => {
+                    break Err(Unexpected(String::from("Missing <project> element")));
+                }
+                _ => continue,
+            }
+        }
+    }
+
+    fn parse_project<R: Read>(parser: &mut Events<R>) -> Result<Project, PomError> {
+        let mut project = Project::new(ArtifactId::from(""));
+        let mut artifact_id: Option<ArtifactId> = None;
+        Self::children(parser, |parser, name| {
+            match name {
+                "modelVersion" => project.model_version = Some(Self::text(parser)?),
+                "parent" => project.parent = Some(Self::parse_parent(parser)?),
+                "groupId" => project.group_id = Some(GroupId::from(Self::text(parser)?)),
+                "artifactId" => artifact_id = Some(ArtifactId::from(Self::text(parser)?)),
+                "version" => project.version = Some(Version::from(Self::text(parser)?)),
+                "packaging" => project.packaging = Some(Self::text(parser)?),
+                "name" => project.name = Some(Self::text(parser)?),
+                "description" => project.description = Some(Self::text(parser)?),
+                "url" => project.url = Some(Self::text(parser)?),
+                "inceptionYear" => project.inception_year = Some(Self::text(parser)?),
+                "organization" => project.organization = Some(Self::parse_organization(parser)?),
+                "prerequisites" => {
+                    let mut prerequisites = Prerequisites::default();
+                    Self::children(parser, |parser, name| match name {
+                        "maven" => {
+                            prerequisites.maven = Some(Self::text(parser)?);
+                            Ok(())
+                        }
+                        other => Self::ignore(parser, other, &[]),
+                    })?;
+                    project.prerequisites = Some(prerequisites);
+                }
+                "ciManagement" => project.ci_management = Some(Self::parse_ci_management(parser)?),
+                "licenses" => {
+                    project.licenses = Self::list(parser, "license", Self::parse_license)?
+                }
+                "developers" => {
+                    project.developers = Self::list(parser, "developer", Self::parse_developer)?
+                }
+                "scm" => project.scm = Some(Self::parse_scm(parser)?),
+                "modules" => project.modules = Self::list(parser, "module", Self::text)?,
+                "subprojects" => {
+                    project.subprojects = Self::list(parser, "subproject", Self::text)?
+                }
+                "properties" => project.properties = Self::parse_properties(parser)?,
+                "dependencyManagement" => {
+                    let mut management = DependencyManagement::default();
+                    Self::children(parser, |parser, name| match name {
+                        "dependencies" => {
+                            management.dependencies = Self::parse_dependencies(parser)?;
+                            Ok(())
+                        }
+                        other => Self::ignore(parser, other, &[]),
+                    })?;
+                    project.dependency_management = Some(management);
+                }
+                "dependencies" => project.dependencies = Self::parse_dependencies(parser)?,
+                "distributionManagement" => {
+                    Self::children(parser, |parser, name| match name {
+                        "relocation" => {
+                            project.relocation = Some(Self::parse_relocation(parser)?);
+                            Ok(())
+                        }
+                        other => Self::ignore(parser, other, &DISTRIBUTION_ELEMENTS),
+                    })?;
+                }
+                other => Self::ignore(parser, other, &PROJECT_ELEMENTS)?,
+            };
+            Ok(())
+        })?;
+        match artifact_id {
+            Some(a) => {
+                project.artifact_id = a;
+                Ok(project)
+            }
+            None => Err(Unexpected(String::from("Missing artifactId"))),
+        }
+    }
+
+    fn parse_parent<R: Read>(parser: &mut Events<R>) -> Result<Parent, PomError> {
+        let mut group_id: Option<GroupId> = None;
+        let mut artifact_id: Option<ArtifactId> = None;
+        let mut version: Option<Version> = None;
+        let mut relative_path: Option<String> = None;
+        Self::children(parser, |parser, name| {
+            match name {
+                "groupId" => group_id = Some(GroupId::from(Self::text(parser)?)),
+                "artifactId" => artifact_id = Some(ArtifactId::from(Self::text(parser)?)),
+                "version" => version = Some(Version::from(Self::text(parser)?)),
+                "relativePath" => relative_path = Some(Self::text(parser)?),
+                other => Self::ignore(parser, other, &[])?,
+            };
+            Ok(())
+        })?;
+        match (group_id, artifact_id, version) {
+            (Some(g), Some(a), Some(v)) => Ok(Parent {
+                group_id: g,
+                artifact_id: a,
+                version: v,
+                relative_path,
+            }),
+            (None, _, _) => Err(Unexpected(String::from("Parent is missing groupId"))),
+            (_, None, _) => Err(Unexpected(String::from("Parent is missing artifactId"))),
+            (_, _, None) => Err(Unexpected(String::from("Parent is missing version"))),
+        }
+    }
+
+    fn parse_relocation<R: Read>(parser: &mut Events<R>) -> Result<Relocation, PomError> {
+        let mut relocation = Relocation::default();
+        Self::children(parser, |parser, name| {
+            match name {
+                "groupId" => relocation.group_id = Some(GroupId::from(Self::text(parser)?)),
+                "artifactId" => {
+                    relocation.artifact_id = Some(ArtifactId::from(Self::text(parser)?))
+                }
+                "version" => relocation.version = Some(Version::from(Self::text(parser)?)),
+                "message" => relocation.message = Some(Self::text(parser)?),
+                other => Self::ignore(parser, other, &[])?,
+            };
+            Ok(())
+        })?;
+        Ok(relocation)
+    }
+
+    fn parse_organization<R: Read>(parser: &mut Events<R>) -> Result<Organization, PomError> {
+        let mut organization = Organization::default();
+        Self::children(parser, |parser, name| {
+            match name {
+                "name" => organization.name = Some(Self::text(parser)?),
+                "url" => organization.url = Some(Self::text(parser)?),
+                other => Self::ignore(parser, other, &[])?,
+            };
+            Ok(())
+        })?;
+        Ok(organization)
+    }
+
+    fn parse_ci_management<R: Read>(parser: &mut Events<R>) -> Result<CiManagement, PomError> {
+        let mut ci = CiManagement::default();
+        Self::children(parser, |parser, name| {
+            match name {
+                "system" => ci.system = Some(Self::text(parser)?),
+                "url" => ci.url = Some(Self::text(parser)?),
+                other => Self::ignore(parser, other, &["notifiers"])?,
+            };
+            Ok(())
+        })?;
+        Ok(ci)
+    }
+
+    fn parse_license<R: Read>(parser: &mut Events<R>) -> Result<License, PomError> {
+        let mut license = License::default();
+        Self::children(parser, |parser, name| {
+            match name {
+                "name" => license.name = Some(Self::text(parser)?),
+                "url" => license.url = Some(Self::text(parser)?),
+                "distribution" => license.distribution = Some(Self::text(parser)?),
+                "comments" => license.comments = Some(Self::text(parser)?),
+                other => Self::ignore(parser, other, &[])?,
+            };
+            Ok(())
+        })?;
+        if license.name.is_none() {
+            parser.missing("name");
+        }
+        Ok(license)
+    }
+
+    fn parse_developer<R: Read>(parser: &mut Events<R>) -> Result<Developer, PomError> {
+        let mut developer = Developer::default();
+        Self::children(parser, |parser, name| {
+            match name {
+                "id" => developer.id = Some(Self::text(parser)?),
+                "name" => developer.name = Some(Self::text(parser)?),
+                "email" => developer.email = Some(Self::text(parser)?),
+                "url" => developer.url = Some(Self::text(parser)?),
+                "organization" => developer.organization = Some(Self::text(parser)?),
+                "organizationUrl" => developer.organization_url = Some(Self::text(parser)?),
+                other => Self::ignore(parser, other, &["roles", "timezone", "properties"])?,
+            };
+            Ok(())
+        })?;
+        Ok(developer)
+    }
+
+    fn parse_scm<R: Read>(parser: &mut Events<R>) -> Result<Scm, PomError> {
+        let mut scm = Scm::default();
+        Self::children(parser, |parser, name| {
+            match name {
+                "connection" => scm.connection = Some(Self::text(parser)?),
+                "developerConnection" => scm.developer_connection = Some(Self::text(parser)?),
+                "url" => scm.url = Some(Self::text(parser)?),
+                "tag" => scm.tag = Some(Self::text(parser)?),
+                other => Self::ignore(parser, other, &[])?,
+            };
+            Ok(())
+        })?;
+        Ok(scm)
+    }
+
+    fn parse_properties<R: Read>(
+        parser: &mut Events<R>,
+    ) -> Result<BTreeMap<String, String>, PomError> {
+        let mut properties = BTreeMap::new();
+        Self::children(parser, |parser, name| {
+            properties.insert(name.to_string(), Self::text(parser)?);
+            Ok(())
+        })?;
+        Ok(properties)
+    }
+
+    fn parse_dependencies<R: Read>(parser: &mut Events<R>) -> Result<Vec<Dependency>, PomError> {
+        Self::list(parser, "dependency", Self::parse_dependency)
+    }
+
+    fn parse_dependency<R: Read>(parser: &mut Events<R>) -> Result<Dependency, PomError> {
+        let mut group_id: Option<GroupId> = None;
+        let mut artifact_id: Option<ArtifactId> = None;
+        let mut dependency = Dependency::new(GroupId::from(""), ArtifactId::from(""), None);
+        Self::children(parser, |parser, name| {
+            match name {
+                "groupId" => group_id = Some(GroupId::from(Self::text(parser)?)),
+                "artifactId" => artifact_id = Some(ArtifactId::from(Self::text(parser)?)),
+                "version" => dependency.version = Some(Version::from(Self::text(parser)?)),
+                "type" => dependency.type_ = Some(Self::text(parser)?),
+                "classifier" => dependency.classifier = Some(Classifier::from(Self::text(parser)?)),
+                "scope" => {
+                    let scope = Self::text(parser)?;
+                    if !SCOPES.contains(&scope.as_str()) {
+                        parser.malformed(&scope);
+                    }
+                    dependency.scope = Some(scope);
+                }
+                "optional" => {
+                    let optional = Self::text(parser)?;
+                    if optional != "true" && optional != "false" {
+                        parser.malformed(&optional);
+                    }
+                    dependency.optional = optional == "true";
+                }
+                "exclusions" => {
+                    dependency.exclusions = Self::list(parser, "exclusion", Self::parse_exclusion)?
+                }
+                other => Self::ignore(parser, other, &["systemPath"])?,
+            };
+            Ok(())
+        })?;
+        match (group_id, artifact_id) {
+            (Some(g), Some(a)) => {
+                dependency.group_id = g;
+                dependency.artifact_id = a;
+                Ok(dependency)
+            }
+            (None, _) => Err(Unexpected(String::from("Dependency is missing groupId"))),
+            (_, None) => Err(Unexpected(String::from("Dependency is missing artifactId"))),
+        }
+    }
+
+    fn parse_exclusion<R: Read>(parser: &mut Events<R>) -> Result<Exclusion, PomError> {
+        let mut group_id: Option<GroupId> = None;
+        let mut artifact_id: Option<ArtifactId> = None;
+        Self::children(parser, |parser, name| {
+            match name {
+                "groupId" => group_id = Some(GroupId::from(Self::text(parser)?)),
+                "artifactId" => artifact_id = Some(ArtifactId::from(Self::text(parser)?)),
+                other => Self::ignore(parser, other, &[])?,
+            };
+            Ok(())
+        })?;
+        match (group_id, artifact_id) {
+            (Some(g), Some(a)) => Ok(Exclusion {
+                group_id: g,
+                artifact_id: a,
+            }),
+            (None, _) => Err(Unexpected(String::from("Exclusion is missing groupId"))),
+            (_, None) => Err(Unexpected(String::from("Exclusion is missing artifactId"))),
+        }
+    }
+
+    fn list<R: Read, T>(
+        parser: &mut Events<R>,
+        element: &str,
+        parse: fn(&mut Events<R>) -> Result<T, PomError>,
+    ) -> Result<Vec<T>, PomError> {
+        let mut items = Vec::new();
+        Self::children(parser, |parser, name| {
+            if name == element {
+                items.push(parse(parser)?);
+                Ok(())
+            } else {
+                Self::ignore(parser, name, &[])
+            }
+        })?;
+        Ok(items)
+    }
+
+    fn children<R: Read, F>(parser: &mut Events<R>, mut f: F) -> Result<(), PomError>
+    where
+        F: FnMut(&mut Events<R>, &str) -> Result<(), PomError>,
+    {
+        loop {
+            match parser.next()? {
+                XmlEvent::StartElement { name, .. } => {
+                    parser.path.push(name.local_name.clone());
+                    f(parser, &name.local_name)?;
+                    parser.path.pop();
+                }
+                XmlEvent::EndElement { .. } => break Ok(()),
+                XmlEvent::EndDocument => {
+                    break Err(Unexpected(String::from("Unexpected end of document")));
+                }
+                _ => continue,
+            }
+        }
+    }
+
+    fn text<R: Read>(parser: &mut Events<R>) -> Result<String, PomError> {
+        let mut out = String::new();
+        loop {
+            match parser.next()? {
+                XmlEvent::Characters(chars) | XmlEvent::CData(chars) => out.push_str(&chars),
+                XmlEvent::Whitespace(chars) => out.push_str(&chars),
+                XmlEvent::EndElement { .. } => break Ok(out.trim().to_string()),
+                XmlEvent::StartElement { name, .. } => {
+                    break Err(Unexpected(format!(
+                        "Expected text, found <{}>",
+                        name.local_name
+                    )));
+                }
+                XmlEvent::EndDocument => {
+                    break Err(Unexpected(String::from("Unexpected end of document")));
+                }
+                _ => continue,
+            }
+        }
+    }
+
+    fn ignore<R: Read>(parser: &mut Events<R>, name: &str, known: &[&str]) -> Result<(), PomError> {
+        if !known.contains(&name) {
+            let path = parser.path();
+            parser.warnings.push(Warning::UnknownElement { path });
+        }
+        Self::skip(parser)
+    }
+
+    fn skip<R: Read>(parser: &mut Events<R>) -> Result<(), PomError> {
+        let mut depth = 1;
+        while depth > 0 {
+            match parser.next()? {
+                XmlEvent::StartElement { .. } => depth += 1,
+                XmlEvent::EndElement { .. } => depth -= 1,
+                XmlEvent::EndDocument => {
+                    return Err(Unexpected(String::from("Unexpected end of document")));
+                }
+                _ => continue,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_minimal() {
+        let pom = r##"<project><modelVersion>4.0.0</modelVersion><groupId>com.example</groupId><artifactId>example</artifactId><version>1.0.0</version></project>"##;
+        let project = PomParser::new().from_str(pom).unwrap();
+        let mut expected = Project::new(ArtifactId::from("example"));
+        expected.model_version = Some(String::from("4.0.0"));
+        expected.group_id = Some(GroupId::from("com.example"));
+        expected.version = Some(Version::from("1.0.0"));
+        assert_eq!(project, expected);
+        assert_eq!(project.packaging(), "jar");
+    }
+
+    #[test]
+    fn interpolate_properties() {
+        let mut project = Project::new(ArtifactId::from("example"));
+        project.version = Some(Version::from("1.0.0"));
+        project
+            .properties
+            .insert(String::from("foo.version"), String::from("2.0"));
+        assert_eq!(project.interpolate("${foo.version}"), "2.0");
+        assert_eq!(project.interpolate("${project.version}-x"), "1.0.0-x");
+        assert_eq!(project.interpolate("${missing}"), "${missing}");
+        assert_eq!(project.interpolate("plain"), "plain");
+    }
+
+    #[test]
+    fn parse_relocation() {
+        let input =
+            std::fs::read_to_string("test-files/pom/com/example/old-lib/1.0.0/old-lib-1.0.0.pom")
+                .unwrap();
+        let project = PomParser::new().from_str(&input).unwrap();
+        assert_eq!(
+            project.relocation,
+            Some(Relocation {
+                group_id: Some(GroupId::from("org.example")),
+                artifact_id: None,
+                version: None,
+                message: Some(String::from("Moved to org.example")),
+            })
+        );
+        assert_eq!(
+            project.relocation_target(),
+            Some(Artifact::new(
+                GroupId::from("org.example"),
+                ArtifactId::from("old-lib"),
+                Version::from("1.0.0")
+            ))
+        );
+    }
+
+    #[test]
+    fn parse_maven4_model() {
+        let pom = r##"<project xmlns="http://maven.apache.org/POM/4.1.0" root="true">
+            <groupId>com.example</groupId>
+            <artifactId>aggregator</artifactId>
+            <version>2.0.0</version>
+            <packaging>pom</packaging>
+            <subprojects>
+                <subproject>core</subproject>
+                <subproject>cli</subproject>
+            </subprojects>
+        </project>"##;
+        let project = PomParser::new().from_str(pom).unwrap();
+        assert_eq!(project.model_version(), "4.1.0");
+        assert!(project.is_maven4_model());
+        assert!(project.root);
+        assert_eq!(project.subprojects, vec!["core", "cli"]);
+        assert_eq!(project.child_projects().count(), 2);
+        assert_eq!(project.flavor(), PomFlavor::Build);
+
+        let pom = r##"<project xmlns="http://maven.apache.org/POM/4.0.0"><artifactId>a</artifactId></project>"##;
+        let project = PomParser::new().from_str(pom).unwrap();
+        assert_eq!(project.model_version(), "4.0.0");
+        assert!(!project.is_maven4_model());
+        assert!(!project.root);
+        assert_eq!(project.flavor(), PomFlavor::Consumer);
+    }
+
+    #[test]
+    fn dependency_with_management() {
+        let mut project = Project::new(ArtifactId::from("example"));
+        let mut managed = Dependency::new(
+            GroupId::from("org.example"),
+            ArtifactId::from("lib"),
+            Some(Version::from("1.2.3")),
+        );
+        managed.scope = Some(String::from("runtime"));
+        let management = DependencyManagement {
+            dependencies: vec![managed],
+        };
+        let declared = Dependency::new(GroupId::from("org.example"), ArtifactId::from("lib"), None);
+        let mut fixtures = declared.clone();
+        fixtures.classifier = Some(Classifier::from("fixtures"));
+
+        assert_eq!(declared.to_artifact(), None);
+        let artifact = declared.to_artifact_with_management(&management).unwrap();
+        assert_eq!(artifact.to_string(), "org.example:lib:1.2.3");
+        assert_eq!(fixtures.to_artifact_with_management(&management), None);
+        let mut tests = Dependency::new(
+            GroupId::from("org.example"),
+            ArtifactId::from("lib"),
+            Some(Version::from("1.0")),
+        );
+        tests.type_ = Some(String::from("test-jar"));
+        assert_eq!(
+            tests.to_artifact().unwrap().to_string(),
+            "org.example:lib:jar:tests:1.0"
+        );
+
+        project.dependencies = vec![declared];
+        project.dependency_management = Some(management);
+        let found = project.dependency_on(&artifact).unwrap();
+        assert_eq!(found.version, Some(Version::from("1.2.3")));
+        assert_eq!(found.scope.as_deref(), Some("runtime"));
+        assert_eq!(
+            project.dependency_on(&Artifact::new(
+                GroupId::from("org.example"),
+                ArtifactId::from("other"),
+                Version::from("1.0")
+            )),
+            None
+        );
+    }
+
+    #[test]
+    fn apply_management() {
+        let excluded = Exclusion {
+            group_id: GroupId::from("org.example"),
+            artifact_id: ArtifactId::from("excluded"),
+        };
+        let mut managed = Dependency::new(
+            GroupId::from("org.example"),
+            ArtifactId::from("lib"),
+            Some(Version::from("1.2.3")),
+        );
+        managed.scope = Some(String::from("runtime"));
+        managed.exclusions = vec![excluded.clone()];
+        let mut shadowed = managed.clone();
+        shadowed.version = Some(Version::from("0.0.1"));
+        let management = DependencyManagement {
+            dependencies: vec![managed, shadowed],
+        };
+
+        let mut pinned = Dependency::new(
+            GroupId::from("org.example"),
+            ArtifactId::from("lib"),
+            Some(Version::from("2.0.0")),
+        );
+        pinned.scope = Some(String::from("test"));
+        pinned.exclusions = vec![excluded.clone()];
+        let bare = Dependency::new(GroupId::from("org.example"), ArtifactId::from("lib"), None);
+        let unmanaged = Dependency::new(GroupId::from("org.other"), ArtifactId::from("x"), None);
+        let mut dependencies = vec![pinned.clone(), bare, unmanaged.clone()];
+        management.apply(&mut dependencies);
+
+        assert_eq!(dependencies[0], pinned);
+        assert_eq!(dependencies[1].version, Some(Version::from("1.2.3")));
+        assert_eq!(dependencies[1].scope.as_deref(), Some("runtime"));
+        assert_eq!(dependencies[1].exclusions, vec![excluded]);
+        assert_eq!(dependencies[2], unmanaged);
+    }
+
+    #[test]
+    fn parse_not_a_pom() {
+        let html = "<html><body>Not found</body></html>";
+        assert!(PomParser::new().from_str(html).is_err());
+    }
+
+    #[test]
+    fn namespace_check() {
+        let bare =
+            "<project><modelVersion>4.0.0</modelVersion><artifactId>lib</artifactId></project>";
+        let foreign =
+            r#"<project xmlns="http://example.org/project"><artifactId>lib</artifactId></project>"#;
+        let maven = r#"<project xmlns="http://maven.apache.org/POM/4.0.0"><artifactId>lib</artifactId></project>"#;
+
+        let lenient = PomParser::new();
+        assert!(lenient.from_str(foreign).is_ok());
+
+        let validate = PomParser::new().with_namespace_check(NamespaceCheck::Validate);
+        assert!(validate.from_str(bare).is_ok());
+        assert!(validate.from_str(maven).is_ok());
+        assert!(matches!(
+            validate.from_str(foreign),
+            Err(PomError::Namespace(ns)) if ns == "http://example.org/project"
+        ));
+
+        let require = PomParser::new().with_namespace_check(NamespaceCheck::Require);
+        assert!(matches!(
+            require.from_str(bare),
+            Err(PomError::Namespace(_))
+        ));
+        assert!(require.from_str(maven).is_ok());
+    }
+
+    #[test]
+    fn inherit_from_local_parent() {
+        let dir =
+            std::env::temp_dir().join(format!("maven-artifact-parent-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("module")).unwrap();
+        std::fs::write(
+            dir.join("pom.xml"),
+            r#"<project>
+  <modelVersion>4.0.0</modelVersion>
+  <groupId>org.example</groupId>
+  <artifactId>parent</artifactId>
+  <version>2.0-SNAPSHOT</version>
+  <packaging>pom</packaging>
+  <url>https://example.org</url>
+  <properties><junit.version>5.10.0</junit.version><java.version>17</java.version></properties>
+  <dependencyManagement><dependencies>
+    <dependency><groupId>org.junit</groupId><artifactId>junit</artifactId><version>${junit.version}</version></dependency>
+  </dependencies></dependencyManagement>
+</project>"#,
+        )
+        .unwrap();
+        let child = PomParser::new()
+            .from_str(
+                r#"<project>
+  <modelVersion>4.0.0</modelVersion>
+  <parent>
+    <groupId>org.example</groupId>
+    <artifactId>parent</artifactId>
+    <version>2.0-SNAPSHOT</version>
+  </parent>
+  <artifactId>module</artifactId>
+  <properties><java.version>21</java.version></properties>
+</project>"#,
+            )
+            .unwrap();
+        let parent = child.parent.clone().unwrap();
+        assert_eq!(parent.relative_path(), Some("../pom.xml"));
+
+        let (path, model) = parent.find_local(&dir.join("module")).unwrap();
+        assert_eq!(path, dir.join("module/../pom.xml"));
+        let mut effective = child.clone();
+        effective.inherit(&model);
+        assert_eq!(effective.group_id, Some(GroupId::from("org.example")));
+        assert_eq!(effective.packaging(), "jar");
+        assert_eq!(effective.url.as_deref(), Some("https://example.org"));
+        assert_eq!(effective.property("java.version").as_deref(), Some("21"));
+        assert_eq!(effective.interpolate("${junit.version}"), "5.10.0");
+        assert_eq!(
+            effective.dependency_management.unwrap().dependencies.len(),
+            1
+        );
+
+        let mut other = parent.clone();
+        other.version = Version::from("1.0");
+        assert!(other.find_local(&dir.join("module")).is_none());
+        other.relative_path = Some(String::new());
+        assert_eq!(other.relative_path(), None);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn parse_warnings() {
+        let input = r#"<project xmlns="http://maven.apache.org/POM/4.0.0">
+  <modelVersion>4.0.0</modelVersion>
+  <groupId>org.example</groupId>
+  <artifactId>lib</artifactId>
+  <version>1.0</version>
+  <build><plugins/></build>
+  <liscenses/>
+  <licenses><license><url>https://example.org/license</url></license></licenses>
+  <dependencies>
+    <dependency>
+      <groupId>org.example</groupId>
+      <artifactId>dep</artifactId>
+      <scope>compiel</scope>
+      <optional>${dep.optional}</optional>
+    </dependency>
+  </dependencies>
+</project>"#;
+        let (project, warnings) = PomParser::new()
+            .parse_with_warnings(Cursor::new(input))
+            .unwrap();
+        assert_eq!(project.dependencies.len(), 1);
+        assert_eq!(
+            warnings.into_iter().collect::<Vec<_>>(),
+            vec![
+                Warning::UnknownElement {
+                    path: String::from("project/liscenses"),
+                },
+                Warning::MissingField {
+                    path: String::from("project/licenses/license"),
+                    field: "name",
+                },
+                Warning::Malformed {
+                    path: String::from("project/dependencies/dependency/scope"),
+                    value: String::from("compiel"),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn write_skeleton() {
+        let mut project = Project::skeleton(&Artifact::parse("org.example:bom:pom:1.0").unwrap());
+        project
+            .properties
+            .insert("slf4j.version".into(), "2.0.9".into());
+        let mut managed = Dependency::from_artifact(
+            &Artifact::parse("org.slf4j:slf4j-api:${slf4j.version}").unwrap(),
+        );
+        managed.scope = Some(String::from("runtime"));
+        project.dependency_management = Some(DependencyManagement {
+            dependencies: vec![managed],
+        });
+        project.dependencies = vec![Dependency::from_artifact(
+            &Artifact::parse("org.example:lib:zip:dist:1.0").unwrap(),
+        )];
+
+        let xml = project.to_xml().unwrap();
+        assert!(xml.starts_with("<?xml"));
+        let (parsed, warnings) = PomParser::new()
+            .with_namespace_check(NamespaceCheck::Require)
+            .parse_with_warnings(xml.as_bytes())
+            .unwrap();
+        assert!(warnings.is_empty());
+        assert_eq!(parsed, project);
+        assert_eq!(
+            dependencies_xml(&project.dependencies).unwrap(),
+            "<dependencies>
+  <dependency>
+    <groupId>org.example</groupId>
+    <artifactId>lib</artifactId>
+    <version>1.0</version>
+    <type>zip</type>
+    <classifier>dist</classifier>
+  </dependency>
+</dependencies>"
+        );
+    }
+
+    #[test]
+    fn version_properties() {
+        let pom = r#"<project>
+  <groupId>org.example</groupId>
+  <artifactId>app</artifactId>
+  <version>1.0</version>
+  <properties>
+    <jackson.version>2.15.0</jackson.version>
+    <guava.version>32.1.0</guava.version>
+  </properties>
+  <dependencyManagement><dependencies>
+    <dependency><groupId>com.fasterxml.jackson.core</groupId><artifactId>jackson-core</artifactId><version>${jackson.version}</version></dependency>
+  </dependencies></dependencyManagement>
+  <dependencies>
+    <dependency><groupId>com.fasterxml.jackson.core</groupId><artifactId>jackson-databind</artifactId><version>${jackson.version}</version></dependency>
+    <dependency><groupId>com.google.guava</groupId><artifactId>guava</artifactId><version>${guava.version}-jre</version></dependency>
+    <dependency><groupId>org.example</groupId><artifactId>core</artifactId><version>${project.version}</version></dependency>
+    <dependency><groupId>org.example</groupId><artifactId>util</artifactId><version>${util.version}</version></dependency>
+  </dependencies>
+</project>"#;
+        let project = PomParser::new().from_str(pom).unwrap();
+        let properties = project.version_properties();
+        let names: Vec<_> = properties.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["jackson.version", "guava.version", "util.version"]
+        );
+
+        let jackson = &properties[0];
+        assert_eq!(jackson.value.as_deref(), Some("2.15.0"));
+        let artifacts: Vec<_> = jackson
+            .dependencies
+            .iter()
+            .map(|d| d.artifact_id.to_string())
+            .collect();
+        assert_eq!(artifacts, vec!["jackson-core", "jackson-databind"]);
+        let guava = &properties[1];
+        assert_eq!(
+            guava.value_for(&guava.dependencies[0], &Version::from("33.0.0-jre")),
+            Some(String::from("33.0.0"))
+        );
+        assert_eq!(
+            guava.value_for(&guava.dependencies[0], &Version::from("33.0.0-android")),
+            None
+        );
+        assert_eq!(properties[2].value, None);
+    }
+
+    #[test]
+    fn parse_full() {
+        let input = std::fs::read_to_string(
+            "test-files/pom/com/example/example-lib/1.0.0/example-lib-1.0.0.pom",
+        )
+        .unwrap();
+        let project = PomParser::new().from_str(&input).unwrap();
+        assert_eq!(
+            project.effective_group_id(),
+            Some(&GroupId::from("com.example"))
+        );
+        assert_eq!(project.effective_version(), Some(&Version::from("1.0.0")));
+        assert_eq!(project.name.as_deref(), Some("Example library"));
+        assert_eq!(project.inception_year.as_deref(), Some("2019"));
+        assert_eq!(
+            project.organization,
+            Some(Organization {
+                name: Some(String::from("Example Org")),
+                url: Some(String::from("https://example.com")),
+            })
+        );
+        assert_eq!(
+            project.prerequisites.and_then(|p| p.maven).as_deref(),
+            Some("3.6.3")
+        );
+        assert_eq!(
+            project.ci_management.and_then(|c| c.system).as_deref(),
+            Some("GitHub Actions")
+        );
+        assert_eq!(project.licenses.len(), 1);
+        assert_eq!(
+            project.licenses[0].name.as_deref(),
+            Some("Apache License, Version 2.0")
+        );
+        assert_eq!(project.developers[0].id.as_deref(), Some("jdoe"));
+        assert_eq!(
+            project.scm.as_ref().and_then(|s| s.url.as_deref()),
+            Some("https://github.com/example/example-lib")
+        );
+        assert_eq!(
+            project.properties.get("slf4j.version"),
+            Some(&String::from("2.0.17"))
+        );
+        let managed = project.dependency_management.unwrap().dependencies;
+        assert_eq!(managed.len(), 1);
+        assert_eq!(managed[0].scope.as_deref(), Some("import"));
+
+        let mut junit = Dependency::new(
+            GroupId::from("org.junit.jupiter"),
+            ArtifactId::from("junit-jupiter"),
+            None,
+        );
+        junit.scope = Some(String::from("test"));
+        let mut slf4j = Dependency::new(
+            GroupId::from("org.slf4j"),
+            ArtifactId::from("slf4j-api"),
+            Some(Version::from("${slf4j.version}")),
+        );
+        slf4j.exclusions = vec![Exclusion {
+            group_id: GroupId::from("org.example"),
+            artifact_id: ArtifactId::from("excluded"),
+        }];
+        assert_eq!(project.dependencies, vec![slf4j, junit]);
+    }
+}
@@ -0,0 +1,55 @@
+use std::fmt::Debug;
+use url::Url;
+
+// Told how the downloads of a Resolver go, for callers to show progress however they
+// like. Downloads can run at the same time, each is told apart by its url.
+pub trait ProgressListener: Debug + Send + Sync {
+    // `total` is the length the server gave, if it gave one
+    fn started(&self, url: &Url, total: Option<u64>);
+
+    // `bytes` more of the file were received
+    fn received(&self, url: &Url, bytes: u64);
+
+    // the whole file arrived, or with `complete` false the transfer was broken off
+    fn completed(&self, url: &Url, complete: bool);
+}
+
+// The listener's view of one transfer, which is told it was broken off when this is
+// dropped before `finish`, as happens when the download is cancelled
+pub(crate) struct Transfer<'a> {
+    listener: Option<&'a dyn ProgressListener>,
+    url: &'a Url,
+}
+
+impl<'a> Transfer<'a> {
+    pub(crate) fn start(
+        listener: Option<&'a dyn ProgressListener>,
+        url: &'a Url,
+        total: Option<u64>,
+    ) -> Transfer<'a> {
+        if let Some(listener) = listener {
+            listener.started(url, total);
+        }
+        Transfer { listener, url }
+    }
+
+    pub(crate) fn received(&self, bytes: u64) {
+        if let Some(listener) = self.listener {
+            listener.received(self.url, bytes);
+        }
+    }
+
+    pub(crate) fn finish(mut self, complete: bool) {
+        if let Some(listener) = self.listener.take() {
+            listener.completed(self.url, complete);
+        }
+    }
+}
+
+impl Drop for Transfer<'_> {
+    fn drop(&mut self) {
+        if let Some(listener) = self.listener.take() {
+            listener.completed(self.url, false);
+        }
+    }
+}
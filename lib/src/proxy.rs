@@ -0,0 +1,157 @@
+use crate::auth::Auth;
+use crate::resolver::ResolveError;
+use reqwest::{NoProxy, Proxy};
+use url::Url;
+
+// A proxy for the requests of a Resolver. Proxies apply in the order they are added to
+// the ResolverBuilder, the first one covering the scheme of a url and not excluded by its
+// non-proxy hosts is used.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProxyConfig {
+    pub url: Url,
+    // http or https traffic only, both when None
+    pub scheme: Option<String>,
+    pub auth: Option<Auth>,
+    // host names, domains as *.example.com or .example.com, ip addresses or subnets
+    pub non_proxy_hosts: Vec<String>,
+}
+
+impl ProxyConfig {
+    pub fn new(url: Url) -> ProxyConfig {
+        ProxyConfig {
+            url,
+            scheme: None,
+            auth: None,
+            non_proxy_hosts: vec![],
+        }
+    }
+
+    pub fn for_scheme<S: Into<String>>(mut self, scheme: S) -> ProxyConfig {
+        self.scheme = Some(scheme.into());
+        self
+    }
+
+    pub fn with_auth(mut self, auth: Auth) -> ProxyConfig {
+        self.auth = Some(auth);
+        self
+    }
+
+    pub fn with_non_proxy_hosts<I, S>(mut self, hosts: I) -> ProxyConfig
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.non_proxy_hosts
+            .extend(hosts.into_iter().map(Into::into));
+        self
+    }
+
+    // HTTPS_PROXY, HTTP_PROXY and ALL_PROXY, in lower case too, all excluding NO_PROXY
+    pub fn from_env() -> Vec<ProxyConfig> {
+        Self::from_vars(|name| std::env::var(name).ok())
+    }
+
+    fn from_vars<F: Fn(&str) -> Option<String>>(var: F) -> Vec<ProxyConfig> {
+        let lookup = |name: &str| {
+            var(name)
+                .or_else(|| var(&name.to_lowercase()))
+                .filter(|v| !v.trim().is_empty())
+        };
+        let non_proxy_hosts: Vec<String> = lookup("NO_PROXY")
+            .map(|v| split_hosts(&v))
+            .unwrap_or_default();
+        [
+            ("HTTPS_PROXY", Some("https")),
+            ("HTTP_PROXY", Some("http")),
+            ("ALL_PROXY", None),
+        ]
+        .into_iter()
+        .filter_map(|(name, scheme)| {
+            let value = lookup(name)?;
+            // curl and friends accept a bare host:port
+            let url = Url::parse(&value)
+                .ok()
+                .filter(|u| u.has_host())
+                .or_else(|| Url::parse(&format!("http://{}", value)).ok())?;
+            Some(ProxyConfig {
+                url,
+                scheme: scheme.map(String::from),
+                auth: None,
+                non_proxy_hosts: non_proxy_hosts.clone(),
+            })
+        })
+        .collect()
+    }
+
+    pub(crate) fn to_proxy(&self) -> Result<Proxy, ResolveError> {
+        let proxy = match self.scheme.as_deref() {
+            Some("http") => Proxy::http(self.url.clone())?,
+            Some("https") => Proxy::https(self.url.clone())?,
+            _ => Proxy::all(self.url.clone())?,
+        };
+        let proxy = match &self.auth {
+            Some(Auth::Basic { username, password }) => proxy.basic_auth(username, password),
+            Some(auth @ Auth::Bearer(_)) => proxy.custom_http_auth(auth.header_value()?),
+            None => proxy,
+        };
+        Ok(proxy.no_proxy(NoProxy::from_string(&no_proxy_list(&self.non_proxy_hosts))))
+    }
+}
+
+// Maven separates nonProxyHosts with |, NO_PROXY with commas
+pub(crate) fn split_hosts(value: &str) -> Vec<String> {
+    value
+        .split(['|', ','])
+        .map(str::trim)
+        .filter(|h| !h.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+// reqwest matches .example.com and example.com alike, subdomains included
+fn no_proxy_list(hosts: &[String]) -> String {
+    hosts
+        .iter()
+        .map(|h| match h.strip_prefix("*.") {
+            Some(domain) => domain,
+            None => h,
+        })
+        .collect::<Vec<&str>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn proxies_from_env() {
+        let vars = |name: &str| match name {
+            "https_proxy" => Some(String::from("http://proxy.corp:3128")),
+            "HTTP_PROXY" => Some(String::from("proxy.corp:8080")),
+            "ALL_PROXY" => Some(String::new()),
+            "NO_PROXY" => Some(String::from("localhost, .corp.example.com,10.0.0.0/8")),
+            _ => None,
+        };
+        let proxies = ProxyConfig::from_vars(vars);
+        assert_eq!(
+            proxies,
+            vec![
+                ProxyConfig::new(Url::parse("http://proxy.corp:3128").unwrap())
+                    .for_scheme("https")
+                    .with_non_proxy_hosts(["localhost", ".corp.example.com", "10.0.0.0/8"]),
+                ProxyConfig::new(Url::parse("http://proxy.corp:8080").unwrap())
+                    .for_scheme("http")
+                    .with_non_proxy_hosts(["localhost", ".corp.example.com", "10.0.0.0/8"]),
+            ]
+        );
+        assert!(proxies.iter().all(|p| p.to_proxy().is_ok()));
+    }
+
+    #[test]
+    fn maven_non_proxy_hosts() {
+        let hosts = split_hosts("*.example.com|localhost| 127.0.0.1");
+        assert_eq!(hosts, vec!["*.example.com", "localhost", "127.0.0.1"]);
+        assert_eq!(no_proxy_list(&hosts), "example.com,localhost,127.0.0.1");
+    }
+}
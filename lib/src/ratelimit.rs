@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::debug;
+use url::Url;
+
+// A token bucket per host: each request takes a token, tokens come back at `per_second`
+// and at most `burst` are saved up. Requests finding the bucket empty wait their turn in
+// the order they came, so a batch of hundreds of artifacts is spread out instead of
+// hitting a repository manager all at once.
+#[derive(Debug)]
+pub struct RateLimiter {
+    per_second: f64,
+    burst: f64,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    // below zero when requests are waiting for tokens not yet come back
+    tokens: f64,
+    updated: Instant,
+}
+
+impl RateLimiter {
+    // a burst of one second's worth of requests, and at least one
+    pub fn new(per_second: f64) -> RateLimiter {
+        assert!(per_second > 0.0, "rate limit must be above zero");
+        RateLimiter {
+            per_second,
+            burst: per_second.max(1.0),
+            buckets: Mutex::default(),
+        }
+    }
+
+    pub fn with_burst(mut self, burst: u32) -> RateLimiter {
+        self.burst = f64::from(burst.max(1));
+        self
+    }
+
+    pub fn per_second(&self) -> f64 {
+        self.per_second
+    }
+
+    // takes a token for `host`, returning how long to wait before it may be used
+    fn reserve(&self, host: &str, now: Instant) -> Duration {
+        let mut buckets = self.buckets.lock().unwrap();
+        let bucket = buckets.entry(host.to_string()).or_insert(Bucket {
+            tokens: self.burst,
+            updated: now,
+        });
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.per_second).min(self.burst) - 1.0;
+        bucket.updated = now;
+        if bucket.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-bucket.tokens / self.per_second)
+        }
+    }
+
+    pub async fn acquire(&self, url: &Url) {
+        let wait = self.reserve(url.host_str().unwrap_or_default(), Instant::now());
+        if !wait.is_zero() {
+            debug!(%url, ?wait, "rate limited");
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_buckets() {
+        let limiter = RateLimiter::new(2.0);
+        let start = Instant::now();
+        let at = |millis| start + Duration::from_millis(millis);
+        let waits: Vec<u128> = (0..5)
+            .map(|_| limiter.reserve("repo.example.com", start).as_millis())
+            .collect();
+        assert_eq!(waits, vec![0, 0, 500, 1000, 1500]);
+        // other hosts have buckets of their own
+        assert_eq!(limiter.reserve("mirror.example.com", start), Duration::ZERO);
+        // the queue drains at the rate and the bucket never holds more than the burst
+        assert_eq!(
+            limiter.reserve("repo.example.com", at(1000)).as_millis(),
+            1000
+        );
+        assert_eq!(
+            limiter.reserve("repo.example.com", at(60000)),
+            Duration::ZERO
+        );
+        assert_eq!(
+            limiter.reserve("repo.example.com", at(60000)),
+            Duration::ZERO
+        );
+        assert_eq!(
+            limiter.reserve("repo.example.com", at(60000)).as_millis(),
+            500
+        );
+
+        let slow = RateLimiter::new(0.5).with_burst(3);
+        let waits: Vec<u128> = (0..4)
+            .map(|_| slow.reserve("repo.example.com", start).as_millis())
+            .collect();
+        assert_eq!(waits, vec![0, 0, 0, 2000]);
+    }
+}
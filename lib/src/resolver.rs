@@ -1,12 +1,52 @@
-use crate::artifact::{Artifact, ParseArtifactError, PartialArtifact, ResolvedArtifact};
-use crate::metadata::VersionedMetadata;
-use crate::{Repository, Version, metadata};
-use reqwest::{Client, Response};
+use crate::artifact::{
+    Artifact, ParseArtifactError, PartialArtifact, ResolvedArtifact, SignatureStatus,
+};
+use crate::attestation::{Attestation, AttestationKind};
+use crate::auth::{Auth, CredentialsChain, CredentialsProvider, RepositoryCredentials};
+use crate::cache::{CacheStats, DescriptorCache};
+use crate::checksum::{Checksum, ChecksumAlgorithm, ChecksumPolicy, Digester};
+use crate::conditional::{ValidatorCache, Validators};
+use crate::deploy::Deployer;
+use crate::disk::{self, LinkStrategy};
+use crate::graph::{GraphLimits, Limit};
+use crate::jar::{self, ClassSource};
+use crate::layout;
+use crate::listing::{
+    ListingEntry, PublishedVersion, listing_timestamp, parse_listing_body, published_versions,
+};
+use crate::local::LocalRepository;
+use crate::metadata::{GroupMetadata, VersionedMetadata};
+use crate::packaging::ContentKind;
+use crate::paths::{self, UnsafePath};
+use crate::policy::{BanPolicy, Enforcement, Violation};
+use crate::pom::{NamespaceCheck, PomError, PomFlavor, PomParser, Project};
+use crate::progress::{ProgressListener, Transfer};
+use crate::proxy::ProxyConfig;
+use crate::ratelimit::RateLimiter;
+use crate::retry::{self, RetryPolicy};
+#[cfg(feature = "signatures")]
+use crate::signatures::Verifier;
+use crate::substitution::Substitutions;
+use crate::version::{DynamicVersion, ParseRangeError, VersionRange};
+use crate::withdrawn::{WithdrawalError, Withdrawals};
+use crate::{
+    ArtifactId, Classifier, GroupId, Repository, RepositoryOverrides, Version, checksum, metadata,
+};
+use reqwest::header::{AUTHORIZATION, CONTENT_LENGTH, CONTENT_TYPE, HeaderMap};
+use reqwest::{Client, ClientBuilder, Method, RequestBuilder, Response, StatusCode};
+use serde::Serialize;
+use std::fmt::Display;
 use std::fs::File;
 use std::io::{BufWriter, Cursor, Write};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use thiserror::Error;
+use tokio::sync::{Semaphore, SemaphorePermit};
+use tracing::{debug, info, warn};
 use url::Url;
+use zip::result::ZipError;
 
 #[derive(Debug, Error)]
 pub enum ResolveError {
@@ -22,73 +62,1291 @@ pub enum ResolveError {
     IO(#[from] std::io::Error),
     #[error("Http error, url={url}, status={status}")]
     GenericHttpError { url: Url, status: u16 },
+    #[error("Content of {url} is {found:?}, expected {expected}")]
+    ContentMismatch {
+        url: Url,
+        expected: ContentKind,
+        found: Option<ContentKind>,
+    },
+    #[error("Checksum mismatch for {url}, {algorithm:?} expected {expected} but was {actual}")]
+    ChecksumMismatch {
+        url: Url,
+        algorithm: ChecksumAlgorithm,
+        expected: Box<str>,
+        actual: Box<str>,
+    },
+    #[error("Repository policy of {url} does not allow {version}")]
+    PolicyViolation { url: Url, version: Version },
+    #[error("POM error: {0}")]
+    Pom(#[from] PomError),
+    #[error("{0}")]
+    Range(#[from] ParseRangeError),
+    #[error("Invalid credentials {0}")]
+    InvalidCredentials(#[from] reqwest::header::InvalidHeaderValue),
+    #[cfg(feature = "signatures")]
+    #[error(transparent)]
+    Signature(#[from] crate::signatures::SignatureError),
+    #[error("{0}")]
+    Banned(Box<Violation>),
+    #[error("{artifact} was published {published}, within the minimum release age")]
+    TooRecent { artifact: String, published: String },
+    #[error("Rate limited by {url}{}", retry_after.map(|d| format!(", retry after {}s", d.as_secs())).unwrap_or_default())]
+    RateLimited {
+        url: Url,
+        retry_after: Option<Duration>,
+    },
+    #[error("Timed out waiting for {url}{}", deadline.map(|d| format!(", deadline of {}s", d.as_secs())).unwrap_or_default())]
+    Timeout {
+        url: Url,
+        deadline: Option<Duration>,
+    },
+    #[error("Not enough space in {path}, {required} bytes needed and {available} available")]
+    InsufficientSpace {
+        path: PathBuf,
+        required: u64,
+        available: u64,
+    },
+    #[error(transparent)]
+    UnsafePath(#[from] UnsafePath),
+    #[error(transparent)]
+    Withdrawals(#[from] WithdrawalError),
+    #[error("Unable to read jar: {0}")]
+    Jar(#[from] ZipError),
+    #[error("Dependency graph exceeds the {limit} at {dependency}")]
+    LimitExceeded { limit: Limit, dependency: String },
+    #[error("No plugin found for prefix {0}")]
+    UnknownPluginPrefix(String),
+    #[error("Download of {0} cancelled")]
+    Cancelled(String),
     #[error("Resolve error {0}")]
     Message(String),
 }
 
-pub struct Resolver<'a> {
-    client: &'a Client,
-    repository: &'a Repository,
+impl ResolveError {
+    pub fn is_not_found(&self) -> bool {
+        matches!(self, ResolveError::GenericHttpError { status: 404, .. })
+    }
+}
+
+pub static DEFAULT_PLUGIN_GROUPS: [&str; 2] = ["org.apache.maven.plugins", "org.codehaus.mojo"];
+
+static DEFAULT_USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const DEFAULT_READ_TIMEOUT: Duration = Duration::from_secs(60);
+static STAGED: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone)]
+pub struct Location {
+    pub url: Url,
+    pub content_length: Option<u64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Descriptor {
+    pub url: Url,
+    pub project: Project,
+    pub flavor: PomFlavor,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum FileCheck {
+    Match {
+        algorithm: ChecksumAlgorithm,
+        value: String,
+    },
+    Mismatch {
+        url: Url,
+        algorithm: ChecksumAlgorithm,
+        expected: String,
+        actual: String,
+    },
+    NoChecksum,
+    NotFound,
+}
+
+#[derive(Debug, Clone)]
+pub struct Probe {
+    pub url: Url,
+    pub status: u16,
+    pub latency: Duration,
+}
+
+impl Probe {
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    pub fn is_authorized(&self) -> bool {
+        self.status != 401 && self.status != 403
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RangeMatch {
+    pub version: Version,
+    pub repository: Url,
 }
 
-impl Resolver<'_> {
-    pub fn new<'a>(client: &'a Client, repository: &'a Repository) -> Resolver<'a> {
-        Resolver { client, repository }
+// How long to wait for a deployed file to become available, backing off between attempts
+#[derive(Debug, Clone)]
+pub struct PollOptions {
+    pub timeout: Duration,
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for PollOptions {
+    fn default() -> Self {
+        PollOptions {
+            timeout: Duration::from_secs(600),
+            initial_delay: Duration::from_secs(5),
+            max_delay: Duration::from_secs(60),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct ResolverBuilder {
+    repositories: Vec<Repository>,
+    overrides: RepositoryOverrides,
+    client: Option<Client>,
+    user_agent: Option<String>,
+    auth: Option<Auth>,
+    credentials: Option<Arc<dyn CredentialsProvider>>,
+    connect_timeout: Option<Duration>,
+    read_timeout: Option<Duration>,
+    timeout: Option<Duration>,
+    download_deadline: Option<Duration>,
+    max_concurrent_requests: Option<usize>,
+    rate_limit: Option<RateLimiter>,
+    retry: RetryPolicy,
+    proxies: Vec<ProxyConfig>,
+    local: Option<LocalRepository>,
+    staging: Option<PathBuf>,
+    link: LinkStrategy,
+    skip_existing: bool,
+    probe_signatures: bool,
+    descriptors: Option<DescriptorCache>,
+    validators: Option<ValidatorCache>,
+    graph_limits: GraphLimits,
+    substitutions: Substitutions,
+    policy: BanPolicy,
+    min_release_age: Option<Duration>,
+    progress: Option<Arc<dyn ProgressListener>>,
+    #[cfg(feature = "signatures")]
+    verifier: Option<Verifier>,
+}
+
+impl ResolverBuilder {
+    pub fn repository(mut self, repository: Repository) -> ResolverBuilder {
+        self.repositories.push(repository);
+        self
+    }
+
+    pub fn repositories<I: IntoIterator<Item = Repository>>(
+        mut self,
+        repositories: I,
+    ) -> ResolverBuilder {
+        self.repositories.extend(repositories);
+        self
+    }
+
+    // applied to every repository, the default one included, when the resolver is built
+    pub fn repository_overrides(mut self, overrides: RepositoryOverrides) -> ResolverBuilder {
+        self.overrides = overrides;
+        self
+    }
+
+    pub fn client(mut self, client: Client) -> ResolverBuilder {
+        self.client = Some(client);
+        self
+    }
+
+    pub fn user_agent(mut self, user_agent: String) -> ResolverBuilder {
+        self.user_agent = Some(user_agent);
+        self
+    }
+
+    pub fn auth(mut self, auth: Auth) -> ResolverBuilder {
+        self.auth = Some(auth);
+        self
+    }
+
+    pub fn credentials<P: CredentialsProvider + 'static>(mut self, provider: P) -> ResolverBuilder {
+        self.credentials = Some(Arc::new(provider));
+        self
+    }
+
+    pub fn progress<P: ProgressListener + 'static>(mut self, listener: P) -> ResolverBuilder {
+        self.progress = Some(Arc::new(listener));
+        self
+    }
+
+    pub fn connect_timeout(mut self, timeout: Duration) -> ResolverBuilder {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    pub fn read_timeout(mut self, timeout: Duration) -> ResolverBuilder {
+        self.read_timeout = Some(timeout);
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> ResolverBuilder {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    // for the whole transfer of a downloaded file, across repositories and retries
+    pub fn download_deadline(mut self, deadline: Duration) -> ResolverBuilder {
+        self.download_deadline = Some(deadline);
+        self
+    }
+
+    pub fn max_concurrent_requests(mut self, max: usize) -> ResolverBuilder {
+        self.max_concurrent_requests = Some(max);
+        self
+    }
+
+    // requests per second to each host, retries included
+    pub fn rate_limit(mut self, limiter: RateLimiter) -> ResolverBuilder {
+        self.rate_limit = Some(limiter);
+        self
+    }
+
+    // for network failures and transient server errors, see RetryPolicy::default
+    pub fn retry(mut self, policy: RetryPolicy) -> ResolverBuilder {
+        self.retry = policy;
+        self
+    }
+
+    // tried in the order added, ignored with a custom client
+    pub fn proxy(mut self, proxy: ProxyConfig) -> ResolverBuilder {
+        self.proxies.push(proxy);
+        self
+    }
+
+    // bounds for the dependency graphs built with this resolver
+    pub fn graph_limits(mut self, limits: GraphLimits) -> ResolverBuilder {
+        self.graph_limits = limits;
+        self
+    }
+
+    // rewrites dependencies of the graphs built with this resolver
+    pub fn substitutions(mut self, substitutions: Substitutions) -> ResolverBuilder {
+        self.substitutions = substitutions;
+        self
+    }
+
+    // denies or warns about artifacts while they are resolved
+    pub fn ban_policy(mut self, policy: BanPolicy) -> ResolverBuilder {
+        self.policy = policy;
+        self
+    }
+
+    // releases published more recently are refused, going by directory listings
+    pub fn min_release_age(mut self, age: Duration) -> ResolverBuilder {
+        self.min_release_age = Some(age);
+        self
+    }
+
+    // parsed POMs are looked up here before they are fetched
+    pub fn descriptor_cache(mut self, cache: DescriptorCache) -> ResolverBuilder {
+        self.descriptors = Some(cache);
+        self
+    }
+
+    // where the ETag and Last-Modified of downloads are kept, to fetch them again conditionally
+    pub fn validator_cache(mut self, cache: ValidatorCache) -> ResolverBuilder {
+        self.validators = Some(cache);
+        self
+    }
+
+    // looked in before the network, and where downloads and POMs are stored
+    pub fn local_repository(mut self, local: LocalRepository) -> ResolverBuilder {
+        self.local = Some(local);
+        self
+    }
+
+    // downloads are written here rather than next to their target, and moved into place
+    // once verified
+    pub fn staging_dir(mut self, dir: impl Into<PathBuf>) -> ResolverBuilder {
+        self.staging = Some(dir.into());
+        self
+    }
+
+    // how files found in the local repository are placed, falling back to a copy
+    pub fn link_strategy(mut self, strategy: LinkStrategy) -> ResolverBuilder {
+        self.link = strategy;
+        self
+    }
+
+    // files already at the target are kept when they match the published checksum
+    pub fn skip_existing(mut self, skip: bool) -> ResolverBuilder {
+        self.skip_existing = skip;
+        self
+    }
+
+    // Downloads report whether an .asc signature is published, at the cost of a HEAD request
+    // each. Without it, or a verifier, the signature is left unchecked.
+    pub fn probe_signatures(mut self, probe: bool) -> ResolverBuilder {
+        self.probe_signatures = probe;
+        self
+    }
+
+    // downloads fail unless their .asc signature verifies
+    #[cfg(feature = "signatures")]
+    pub fn verify_signature(mut self, verifier: Verifier) -> ResolverBuilder {
+        self.verifier = Some(verifier);
+        self
+    }
+
+    pub fn from_env(mut self) -> Result<ResolverBuilder, ResolveError> {
+        if let Ok(value) = std::env::var("MAVEN_REPOSITORY") {
+            let repository = Repository::parse(&value)?;
+            self.repositories.push(match Auth::from_env() {
+                Some(auth) => repository.with_auth(auth),
+                None => repository,
+            });
+        }
+        self.proxies.extend(ProxyConfig::from_env());
+        if let Some(root) = std::env::var_os("MAVEN_REPO_LOCAL") {
+            self.local = Some(LocalRepository::new(root));
+        }
+        if let Some(root) = std::env::var_os("MAVEN_DESCRIPTOR_CACHE") {
+            self.descriptors = Some(DescriptorCache::new(root));
+        }
+        Ok(self)
+    }
+
+    pub fn build(self) -> Result<Resolver, ResolveError> {
+        let client = match self.client {
+            Some(client) => client,
+            None => {
+                let mut builder = ClientBuilder::new()
+                    .user_agent(
+                        self.user_agent
+                            .unwrap_or_else(|| String::from(DEFAULT_USER_AGENT)),
+                    )
+                    .connect_timeout(self.connect_timeout.unwrap_or(DEFAULT_CONNECT_TIMEOUT))
+                    .read_timeout(self.read_timeout.unwrap_or(DEFAULT_READ_TIMEOUT));
+                if let Some(timeout) = self.timeout {
+                    builder = builder.timeout(timeout);
+                }
+                for proxy in &self.proxies {
+                    debug!(url = %proxy.url, scheme = proxy.scheme, "using proxy");
+                    builder = builder.proxy(proxy.to_proxy()?);
+                }
+                builder.build()?
+            }
+        };
+        let mut repositories = self.repositories;
+        // the netrc default entry is for repositories someone chose, not the implied Central
+        let chosen = repositories.len();
+        if repositories.is_empty() {
+            repositories.push(Repository::maven_central());
+        }
+        if !self.overrides.is_empty() {
+            repositories = repositories
+                .into_iter()
+                .map(|r| self.overrides.apply(r))
+                .collect();
+        }
+        // only the configured repositories get the builder's auth, not every host asked
+        if let Some(auth) = self.auth {
+            for repository in repositories.iter_mut().filter(|r| r.auth.is_none()) {
+                repository.auth = Some(auth.clone());
+            }
+        }
+        let chain = CredentialsChain::new().with(RepositoryCredentials::new(&repositories));
+        let chain = match self.credentials {
+            Some(provider) => chain.with_shared(provider),
+            None => chain.with_shared(Arc::new(CredentialsChain::standard_for(
+                &repositories[..chosen],
+            ))),
+        };
+        Ok(Resolver {
+            client,
+            repositories: repositories.into(),
+            credentials: Arc::new(chain),
+            limiter: self
+                .max_concurrent_requests
+                .map(|max| Arc::new(Semaphore::new(max))),
+            rate_limit: self.rate_limit.map(Arc::new),
+            download_deadline: self.download_deadline,
+            retry: Arc::new(self.retry),
+            local: self.local.map(Arc::new),
+            staging: self.staging.map(Arc::from),
+            link: self.link,
+            skip_existing: self.skip_existing,
+            probe_signatures: self.probe_signatures,
+            descriptors: self.descriptors.map(Arc::new),
+            validators: self.validators.map(Arc::new),
+            graph_limits: self.graph_limits,
+            substitutions: Arc::new(self.substitutions),
+            policy: Arc::new(self.policy),
+            violations: Arc::default(),
+            cache_stats: Arc::default(),
+            min_release_age: self.min_release_age,
+            progress: self.progress,
+            #[cfg(feature = "signatures")]
+            verifier: self.verifier.map(Arc::new),
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Resolver {
+    client: Client,
+    repositories: Arc<[Repository]>,
+    credentials: Arc<dyn CredentialsProvider>,
+    limiter: Option<Arc<Semaphore>>,
+    rate_limit: Option<Arc<RateLimiter>>,
+    download_deadline: Option<Duration>,
+    retry: Arc<RetryPolicy>,
+    local: Option<Arc<LocalRepository>>,
+    staging: Option<Arc<Path>>,
+    link: LinkStrategy,
+    skip_existing: bool,
+    probe_signatures: bool,
+    descriptors: Option<Arc<DescriptorCache>>,
+    validators: Option<Arc<ValidatorCache>>,
+    graph_limits: GraphLimits,
+    substitutions: Arc<Substitutions>,
+    policy: Arc<BanPolicy>,
+    // warnings of the policy, shared by clones
+    violations: Arc<Mutex<Vec<Violation>>>,
+    cache_stats: Arc<Mutex<CacheStats>>,
+    min_release_age: Option<Duration>,
+    progress: Option<Arc<dyn ProgressListener>>,
+    #[cfg(feature = "signatures")]
+    verifier: Option<Arc<Verifier>>,
+}
+
+impl Resolver {
+    pub fn new(client: Client, repository: Repository) -> Resolver {
+        Resolver {
+            client,
+            credentials: Arc::new(
+                CredentialsChain::new()
+                    .with(RepositoryCredentials::new([&repository]))
+                    .with(CredentialsChain::standard_for(std::slice::from_ref(
+                        &repository,
+                    ))),
+            ),
+            repositories: Arc::new([repository]),
+            limiter: None,
+            rate_limit: None,
+            download_deadline: None,
+            retry: Arc::default(),
+            local: None,
+            staging: None,
+            link: LinkStrategy::Copy,
+            skip_existing: false,
+            probe_signatures: false,
+            descriptors: None,
+            validators: None,
+            graph_limits: GraphLimits::default(),
+            substitutions: Arc::new(Substitutions::new()),
+            policy: Arc::new(BanPolicy::new()),
+            violations: Arc::default(),
+            cache_stats: Arc::default(),
+            min_release_age: None,
+            progress: None,
+            #[cfg(feature = "signatures")]
+            verifier: None,
+        }
+    }
+
+    pub fn builder() -> ResolverBuilder {
+        ResolverBuilder::default()
+    }
+
+    pub fn central() -> Result<Resolver, ResolveError> {
+        Self::builder()
+            .repository(Repository::maven_central())
+            .build()
+    }
+
+    pub fn from_env() -> Result<Resolver, ResolveError> {
+        Self::builder().from_env()?.build()
+    }
+
+    pub fn repositories(&self) -> &[Repository] {
+        &self.repositories
+    }
+
+    pub fn graph_limits(&self) -> &GraphLimits {
+        &self.graph_limits
+    }
+
+    pub fn substitutions(&self) -> &Substitutions {
+        &self.substitutions
+    }
+
+    pub fn ban_policy(&self) -> &BanPolicy {
+        &self.policy
+    }
+
+    // artifacts resolved so far in spite of a warn rule of the ban policy
+    pub fn violations(&self) -> Vec<Violation> {
+        self.violations.lock().unwrap().clone()
+    }
+
+    pub fn min_release_age(&self) -> Option<Duration> {
+        self.min_release_age
+    }
+
+    // what the caching layers saved so far, shared by clones
+    pub fn cache_stats(&self) -> CacheStats {
+        *self.cache_stats.lock().unwrap()
+    }
+
+    fn record<F: FnOnce(&mut CacheStats)>(&self, update: F) {
+        update(&mut self.cache_stats.lock().unwrap());
+    }
+
+    fn enforce(&self, violations: Vec<Violation>) -> Result<(), ResolveError> {
+        for violation in violations {
+            if violation.enforcement == Enforcement::Deny {
+                return Err(ResolveError::Banned(Box::new(violation)));
+            }
+            let mut recorded = self.violations.lock().unwrap();
+            if !recorded.contains(&violation) {
+                debug!(%violation, "allowed by a warn rule");
+                recorded.push(violation);
+            }
+        }
+        Ok(())
+    }
+
+    // the same resolver, keeping parsed POMs in `cache` instead
+    pub fn with_descriptor_cache(mut self, cache: DescriptorCache) -> Resolver {
+        self.descriptors = Some(Arc::new(cache));
+        self
+    }
+
+    // uploads with the client and credentials of this resolver
+    pub fn deployer(&self, repository: Repository) -> Deployer {
+        Deployer::new(self.client.clone(), repository).shared_credentials(self.credentials.clone())
+    }
+
+    fn request(
+        &self,
+        method: Method,
+        url: &Url,
+        auth: Option<&Auth>,
+    ) -> Result<RequestBuilder, ResolveError> {
+        let request = self.client.request(method, url.clone());
+        match auth {
+            Some(auth) => Ok(request.header(AUTHORIZATION, auth.header_value()?)),
+            None => Ok(request),
+        }
+    }
+
+    async fn send(&self, method: Method, url: &Url) -> Result<Response, ResolveError> {
+        self.send_with(method, url, HeaderMap::new()).await
+    }
+
+    async fn send_with(
+        &self,
+        method: Method,
+        url: &Url,
+        headers: HeaderMap,
+    ) -> Result<Response, ResolveError> {
+        let mut attempt = 1;
+        loop {
+            let result = self.send_once(method.clone(), url, headers.clone()).await;
+            let (retry, requested) = match &result {
+                Ok(response) => {
+                    let status = response.status();
+                    let requested = match status {
+                        StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE => {
+                            retry::retry_after(response.headers(), SystemTime::now())
+                        }
+                        _ => None,
+                    };
+                    (self.retry.retries_status(status.as_u16()), requested)
+                }
+                Err(ResolveError::Reqwest(e)) => (self.retry.retries_error(e), None),
+                Err(_) => (false, None),
+            };
+            let exhausted = attempt >= self.retry.max_attempts
+                || requested.is_some_and(|wait| wait > self.retry.max_retry_after);
+            if !retry || exhausted {
+                return match result {
+                    Ok(response)
+                        if response.status() == StatusCode::TOO_MANY_REQUESTS
+                            || requested.is_some() =>
+                    {
+                        Err(ResolveError::RateLimited {
+                            url: url.clone(),
+                            retry_after: requested,
+                        })
+                    }
+                    result => result.map_err(|e| Self::timed_out(url, e)),
+                };
+            }
+            let delay = requested.unwrap_or_else(|| self.retry.delay(attempt));
+            match &result {
+                Ok(response) => {
+                    warn!(%url, status = response.status().as_u16(), attempt, ?delay, "retrying")
+                }
+                Err(e) => warn!(%url, attempt, ?delay, "retrying after {}", e),
+            }
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    async fn send_once(
+        &self,
+        method: Method,
+        url: &Url,
+        headers: HeaderMap,
+    ) -> Result<Response, ResolveError> {
+        self.credentials.refresh(url).await;
+        let auth = self.credentials.credentials(url);
+        self.throttle(url).await;
+        let response = self
+            .request(method.clone(), url, auth.as_ref())?
+            .headers(headers.clone())
+            .send()
+            .await?;
+        if response.status() == StatusCode::UNAUTHORIZED {
+            // credentials may have expired, retry once if the provider hands out new ones
+            self.credentials.invalidate(url);
+            self.credentials.refresh(url).await;
+            let refreshed = self.credentials.credentials(url);
+            if refreshed.is_some() && refreshed != auth {
+                debug!(%url, "retrying with refreshed credentials");
+                self.throttle(url).await;
+                return Ok(self
+                    .request(method, url, refreshed.as_ref())?
+                    .headers(headers)
+                    .send()
+                    .await?);
+            }
+        }
+        Ok(response)
+    }
+
+    // reqwest reports a timeout without saying what it was waiting for
+    fn timed_out(url: &Url, error: ResolveError) -> ResolveError {
+        match error {
+            ResolveError::Reqwest(e) if e.is_timeout() => ResolveError::Timeout {
+                url: url.clone(),
+                deadline: None,
+            },
+            e => e,
+        }
+    }
+
+    // `future` cut short at the download deadline
+    async fn before<T>(
+        &self,
+        deadline: Option<Instant>,
+        url: &Url,
+        future: impl Future<Output = Result<T, ResolveError>>,
+    ) -> Result<T, ResolveError> {
+        let Some(deadline) = deadline else {
+            return future.await;
+        };
+        tokio::time::timeout(deadline.saturating_duration_since(Instant::now()), future)
+            .await
+            .map_err(|_| ResolveError::Timeout {
+                url: url.clone(),
+                deadline: self.download_deadline,
+            })?
+    }
+
+    async fn throttle(&self, url: &Url) {
+        if let Some(rate_limit) = &self.rate_limit {
+            rate_limit.acquire(url).await;
+        }
+    }
+
+    async fn permit(&self) -> Option<SemaphorePermit<'_>> {
+        self.limiter.as_ref()?.acquire().await.ok()
+    }
+
+    fn repositories_for(&self, version: &Version) -> Result<Vec<&Repository>, ResolveError> {
+        let allowed: Vec<&Repository> = self
+            .repositories
+            .iter()
+            .filter(|r| r.allows(version))
+            .collect();
+        if allowed.is_empty() {
+            Err(ResolveError::PolicyViolation {
+                url: self.repositories[0].url.clone(),
+                version: version.clone(),
+            })
+        } else {
+            Ok(allowed)
+        }
+    }
+
+    fn policy_version(artifact: &ResolvedArtifact) -> &Version {
+        if artifact.artifact.is_snapshot() {
+            &artifact.artifact.version
+        } else {
+            &artifact.resolved_version
+        }
+    }
+
+    pub async fn metadata(
+        &self,
+        artifact: PartialArtifact,
+    ) -> Result<VersionedMetadata, ResolveError> {
+        self.metadata0(artifact.path(), &Version::from("LATEST"))
+            .await
+    }
+
+    async fn metadata0(
+        &self,
+        path: String,
+        version: &Version,
+    ) -> Result<VersionedMetadata, ResolveError> {
+        let mut last_error = None;
+        for repository in self.repositories_for(version)? {
+            match self.metadata_from(repository, &path).await {
+                Err(e) if e.is_not_found() => last_error = Some(e),
+                result => return result,
+            }
+        }
+        Err(last_error.unwrap())
+    }
+
+    async fn metadata_from(
+        &self,
+        repository: &Repository,
+        path: &str,
+    ) -> Result<VersionedMetadata, ResolveError> {
+        let bytes = self.metadata_bytes(repository, path).await?;
+        // proxies answering with an HTML error page should fail here rather than look empty
+        let (metadata, _) = VersionedMetadata::parse_with_namespace_check(
+            Cursor::new(bytes),
+            NamespaceCheck::Validate,
+        )?;
+        Ok(metadata)
+    }
+
+    pub async fn probe(&self, url: &Url) -> Result<Probe, ResolveError> {
+        let _permit = self.permit().await;
+        let start = Instant::now();
+        // not retried, a probe reports what the repository answers
+        let response = self.send_once(Method::GET, url, HeaderMap::new()).await?;
+        let latency = start.elapsed();
+        debug!(%url, status = response.status().as_u16(), ?latency, "probe");
+        Ok(Probe {
+            url: url.clone(),
+            status: response.status().as_u16(),
+            latency,
+        })
+    }
+
+    // the root only has to answer, many repository managers 404 it;
+    // the artifact metadata has to be served
+    pub async fn health(
+        &self,
+        repository: &Repository,
+        artifact: Option<&PartialArtifact>,
+    ) -> Result<Vec<Probe>, ResolveError> {
+        let mut probes = vec![self.probe(&repository.url).await?];
+        if let Some(artifact) = artifact {
+            let path = format!(
+                "{}/{}/maven-metadata.xml",
+                repository.url.path(),
+                artifact.path()
+            );
+            probes.push(self.probe(&repository.url.join(&path)?).await?);
+        }
+        Ok(probes)
+    }
+
+    pub async fn published_versions(
+        &self,
+        artifact: PartialArtifact,
+    ) -> Result<Vec<PublishedVersion>, ResolveError> {
+        let path = artifact.path();
+        let metadata = self.metadata(artifact).await?;
+        let versions = metadata.versioning.versions.unwrap_or_default();
+        let listing = self.listing(&path).await;
+        Ok(published_versions(&versions, &listing))
+    }
+
+    // a denylist feed as Withdrawals::parse reads it, served over http
+    pub async fn withdrawal_feed(&self, url: &Url) -> Result<Withdrawals, ResolveError> {
+        debug!(%url, "fetching withdrawal feed");
+        let response = self.send(Method::GET, url).await?;
+        if !response.status().is_success() {
+            return Err(ResolveError::GenericHttpError {
+                url: url.clone(),
+                status: response.status().as_u16(),
+            });
+        }
+        Ok(Withdrawals::parse(&response.text().await?)?)
+    }
+
+    // the advisories of an OSV database for the artifact, like withdrawn::OSV_QUERY_URL
+    pub async fn osv_withdrawals(
+        &self,
+        endpoint: &Url,
+        artifact: &PartialArtifact,
+    ) -> Result<Withdrawals, ResolveError> {
+        let mut withdrawals = Withdrawals::new();
+        let mut page_token = None;
+        loop {
+            debug!(url = %endpoint, %artifact, "querying advisories");
+            self.throttle(endpoint).await;
+            let response = self
+                .client
+                .post(endpoint.clone())
+                .header(CONTENT_TYPE, "application/json")
+                .body(Withdrawals::osv_query(artifact, page_token.as_deref()))
+                .send()
+                .await
+                .map_err(|e| Self::timed_out(endpoint, e.into()))?;
+            if !response.status().is_success() {
+                return Err(ResolveError::GenericHttpError {
+                    url: endpoint.clone(),
+                    status: response.status().as_u16(),
+                });
+            }
+            let (page, next) = Withdrawals::parse_osv(&response.text().await?, artifact)?;
+            withdrawals.extend(page);
+            match next {
+                Some(next) => page_token = Some(next),
+                None => return Ok(withdrawals),
+            }
+        }
+    }
+
+    // listings are a convenience of some repository managers, so failures only lose timestamps
+    async fn listing(&self, path: &str) -> Vec<ListingEntry> {
+        for repository in self.repositories.iter() {
+            let listing_path = format!("{}/{}/", repository.url.path(), path);
+            let Ok(url) = repository.url.join(&listing_path) else {
+                continue;
+            };
+            let _permit = self.permit().await;
+            debug!(%url, "fetching directory listing");
+            let response = match self.send(Method::GET, &url).await {
+                Ok(response) if response.status().is_success() => response,
+                Ok(response) => {
+                    debug!(%url, status = response.status().as_u16(), "no directory listing");
+                    continue;
+                }
+                Err(e) => {
+                    debug!(%url, error = %e, "directory listing failed");
+                    continue;
+                }
+            };
+            match response.text().await {
+                Ok(body) => {
+                    let entries = parse_listing_body(&body);
+                    if !entries.is_empty() {
+                        return entries;
+                    }
+                }
+                Err(e) => debug!(%url, error = %e, "directory listing failed"),
+            }
+        }
+        vec![]
+    }
+
+    // Releases published after the minimum release age began. Versions without a date in
+    // the listings pass, or repositories without listings could not be used at all.
+    async fn too_recent(&self, path: &str, versions: &[&Version]) -> Vec<PublishedVersion> {
+        let Some(age) = self.min_release_age else {
+            return vec![];
+        };
+        let releases: Vec<Version> = versions
+            .iter()
+            .filter(|v| !v.is_snapshot())
+            .map(|v| (*v).clone())
+            .collect();
+        if releases.is_empty() {
+            return vec![];
+        }
+        let now = SystemTime::now();
+        let cutoff = listing_timestamp(now.checked_sub(age).unwrap_or(UNIX_EPOCH));
+        let listing = self.listing(path).await;
+        published_versions(&releases, &listing)
+            .into_iter()
+            .filter(|p| match &p.published {
+                Some(published) => *published > cutoff,
+                None => {
+                    warn!(path, version = %p.version, "no publish date, minimum release age not checked");
+                    false
+                }
+            })
+            .collect()
+    }
+
+    async fn check_release_age(&self, artifact: &Artifact) -> Result<(), ResolveError> {
+        let path = PartialArtifact::from(artifact.clone()).path();
+        match self.too_recent(&path, &[&artifact.version]).await.pop() {
+            Some(recent) => Err(ResolveError::TooRecent {
+                artifact: format!(
+                    "{}:{}:{}",
+                    artifact.group_id, artifact.artifact_id, artifact.version
+                ),
+                published: recent.published.unwrap_or_default(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    // Artifacts directly under a group, from the repository's directory listing. Subgroups
+    // show up as directories too, so only those with a maven-metadata.xml are kept.
+    pub async fn list_artifacts(
+        &self,
+        group_id: &GroupId,
+    ) -> Result<Vec<ArtifactId>, ResolveError> {
+        let path = group_id.path_string();
+        let listing = self.listing(&path).await;
+        if listing.is_empty() {
+            return Err(ResolveError::Message(format!(
+                "No directory listing available for {}",
+                group_id
+            )));
+        }
+        let mut found = vec![];
+        for entry in listing.iter().filter(|e| e.directory) {
+            let artifact_path = format!("{}/{}", path, entry.name);
+            match self
+                .metadata0(artifact_path, &Version::from("LATEST"))
+                .await
+            {
+                Ok(_) => found.push(ArtifactId::from(entry.name.as_str())),
+                Err(e) if e.is_not_found() => debug!(name = %entry.name, "not an artifact"),
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(found)
+    }
+
+    // Files published for a version, from the repository's directory listing, so
+    // attachments with classifiers nobody declared are found as well.
+    pub async fn list_files(
+        &self,
+        artifact: &PartialArtifact,
+        version: &Version,
+    ) -> Result<Vec<ResolvedArtifact>, ResolveError> {
+        let path = format!("{}/{}", artifact.path(), version);
+        let listing = self.listing(&path).await;
+        if listing.is_empty() {
+            return Err(ResolveError::Message(format!(
+                "No directory listing available for {}:{}",
+                artifact, version
+            )));
+        }
+        Ok(listing
+            .iter()
+            .filter(|e| !e.directory)
+            .filter_map(|e| layout::parse_path(&Path::new(&path).join(&e.name)))
+            .collect())
+    }
+
+    pub async fn group_metadata(&self, group_id: &GroupId) -> Result<GroupMetadata, ResolveError> {
+        let path = group_id.path_string();
+        let mut last_error = None;
+        for repository in self.repositories_for(&Version::from("LATEST"))? {
+            match self.metadata_bytes(repository, &path).await {
+                Ok(bytes) => return Ok(GroupMetadata::parse(Cursor::new(bytes))?),
+                Err(e) if e.is_not_found() => last_error = Some(e),
+                Err(e) => return Err(e),
+            }
+        }
+        Err(last_error.unwrap())
+    }
+
+    pub async fn plugin_by_prefix(
+        &self,
+        prefix: &str,
+        groups: &[GroupId],
+    ) -> Result<PartialArtifact, ResolveError> {
+        for group_id in groups {
+            let metadata = match self.group_metadata(group_id).await {
+                Ok(metadata) => metadata,
+                Err(e) if e.is_not_found() => continue,
+                Err(e) => return Err(e),
+            };
+            if let Some(plugin) = metadata.find_prefix(prefix) {
+                return Ok(PartialArtifact::new(
+                    group_id.clone(),
+                    plugin.artifact_id.clone(),
+                ));
+            }
+        }
+        Err(ResolveError::UnknownPluginPrefix(prefix.to_string()))
+    }
+
+    async fn metadata_bytes(
+        &self,
+        repository: &Repository,
+        path: &str,
+    ) -> Result<Vec<u8>, ResolveError> {
+        if let Some(local) = &self.local
+            && let Some(bytes) = local.cached_metadata(path, repository)
+        {
+            debug!(path, url = %repository.url, "using cached metadata");
+            self.record(|s| {
+                s.hits += 1;
+                s.bytes_saved += bytes.len() as u64;
+            });
+            return Ok(bytes);
+        }
+        let stale = self
+            .local
+            .as_ref()
+            .and_then(|local| local.stale_metadata(path, repository));
+        let metadata_path = format!("{}/{}/maven-metadata.xml", repository.url.path(), path);
+        let url = repository.url.join(&metadata_path)?;
+        let _permit = self.permit().await;
+        debug!(%url, "fetching metadata");
+        let headers = stale
+            .as_ref()
+            .map(|(_, validators)| validators.headers())
+            .unwrap_or_default();
+        let response = self.send_with(Method::GET, &url, headers).await?;
+        debug!(%url, status = response.status().as_u16(), "metadata response");
+        if response.status() == StatusCode::NOT_MODIFIED
+            && let (Some(local), Some((bytes, _))) = (&self.local, stale)
+        {
+            debug!(%url, "metadata not modified");
+            self.record(|s| {
+                s.not_modified += 1;
+                s.bytes_saved += bytes.len() as u64;
+            });
+            if let Err(e) = local.touch_metadata(path, repository) {
+                warn!(%url, "unable to refresh cached metadata: {}", e);
+            }
+            Ok(bytes)
+        } else if response.status().is_success() {
+            let validators = Validators::from_headers(response.headers());
+            let bytes = response.bytes().await?.to_vec();
+            if let Some(local) = &self.local {
+                self.record(|s| s.misses += 1);
+                if let Err(e) = local.store_metadata(path, repository, &bytes, &validators) {
+                    warn!(%url, "unable to cache metadata: {}", e);
+                }
+            }
+            Ok(bytes)
+        } else {
+            Err(ResolveError::GenericHttpError {
+                url: url.clone(),
+                status: response.status().as_u16(),
+            })
+        }
+    }
+
+    pub async fn download(
+        &self,
+        artifact: Artifact,
+        path: &Path,
+    ) -> Result<ResolvedArtifact, ResolveError> {
+        let resolved = self.resolve(artifact).await?;
+        debug!(
+            artifact = %resolved.artifact,
+            version = %resolved.resolved_version,
+            "resolved"
+        );
+        self.download0(resolved, path).await
+    }
+
+    // Like download, giving up once `cancel` completes, say the cancelled() of a
+    // CancellationToken. Dropping a download does the same without the error: either way
+    // no partial file is left behind and a file already at the target is kept.
+    pub async fn download_until<F: Future<Output = ()>>(
+        &self,
+        artifact: Artifact,
+        path: &Path,
+        cancel: F,
+    ) -> Result<ResolvedArtifact, ResolveError> {
+        let coordinates = artifact.to_string();
+        tokio::select! {
+            biased;
+            () = cancel => {
+                info!(artifact = %coordinates, "download cancelled");
+                Err(ResolveError::Cancelled(coordinates))
+            }
+            result = self.download(artifact, path) => result,
+        }
     }
 
-    pub async fn metadata(
+    // The source file of a class, from the sources jar of `artifact` downloaded to `dir`.
+    // With a local repository the jar is only downloaded once.
+    pub async fn class_source(
+        &self,
+        artifact: Artifact,
+        class_name: &str,
+        dir: &Path,
+    ) -> Result<Option<ClassSource>, ResolveError> {
+        let sources = artifact
+            .with_classifier(Classifier::from("sources"))
+            .with_extension(String::from("jar"));
+        let downloaded = self.download(sources, dir).await?;
+        let path = downloaded.file.unwrap_or_default();
+        Ok(jar::class_source(&path, class_name)?)
+    }
+
+    pub async fn resolve_range(
         &self,
         artifact: PartialArtifact,
-    ) -> Result<VersionedMetadata, ResolveError> {
-        self.metadata0(artifact.path()).await
+        range: &VersionRange,
+    ) -> Result<RangeMatch, ResolveError> {
+        self.select_version(artifact, range, |v| range.contains(v))
+            .await
     }
 
-    async fn metadata0(&self, path: String) -> Result<VersionedMetadata, ResolveError> {
-        let metadata_path = format!("{}/{}/maven-metadata.xml", self.repository.url.path(), path);
-        let url = self.repository.url.join(&metadata_path)?;
-        let response = self.client.get(url.clone()).send().await?;
-        if response.status().is_success() {
-            let bytes = response.bytes().await?;
-            let c = Cursor::new(bytes);
-            let versioned: VersionedMetadata = VersionedMetadata::parse(c)?;
-            Ok(versioned)
+    pub async fn resolve_dynamic(
+        &self,
+        artifact: PartialArtifact,
+        dynamic: &DynamicVersion,
+    ) -> Result<RangeMatch, ResolveError> {
+        self.select_version(artifact, dynamic, |v| dynamic.matches(v))
+            .await
+    }
+
+    async fn select_version<F>(
+        &self,
+        artifact: PartialArtifact,
+        spec: &(dyn Display + Sync),
+        matches: F,
+    ) -> Result<RangeMatch, ResolveError>
+    where
+        F: Fn(&Version) -> bool,
+    {
+        let path = artifact.path();
+        // first repository listing a version wins, in configured order
+        let mut candidates: Vec<(Version, &Repository)> = vec![];
+        let mut last_error = None;
+        for repository in self.repositories.iter() {
+            let metadata = match self.metadata_from(repository, &path).await {
+                Ok(metadata) => metadata,
+                Err(e) if e.is_not_found() => {
+                    last_error = Some(e);
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            for version in metadata.versioning.versions.unwrap_or_default() {
+                if repository.allows(&version) && !candidates.iter().any(|(v, _)| *v == version) {
+                    candidates.push((version, repository));
+                }
+            }
+        }
+        if candidates.is_empty()
+            && let Some(e) = last_error
+        {
+            return Err(e);
+        }
+        let matching: Vec<&Version> = candidates
+            .iter()
+            .map(|(v, _)| v)
+            .filter(|v| matches(v))
+            .collect();
+        let recent = self.too_recent(&path, &matching).await;
+        for p in &recent {
+            debug!(%artifact, version = %p.version, published = p.published, "skipping recent release");
+        }
+        let selected = candidates
+            .iter()
+            .filter(|(v, _)| matches(v) && !recent.iter().any(|p| p.version == *v))
+            .max_by(|(a, _), (b, _)| a.compare(b));
+        match selected {
+            Some((version, repository)) => {
+                debug!(%artifact, %spec, %version, url = %repository.url, "selected version");
+                Ok(RangeMatch {
+                    version: version.clone(),
+                    repository: repository.url.clone(),
+                })
+            }
+            None if !recent.is_empty() => Err(ResolveError::Message(format!(
+                "No version of {} matching {} is older than the minimum release age",
+                artifact, spec
+            ))),
+            None => Err(ResolveError::Message(format!(
+                "No version of {} matches {}",
+                artifact, spec
+            ))),
+        }
+    }
+
+    pub async fn resolve(&self, artifact: Artifact) -> Result<ResolvedArtifact, ResolveError> {
+        let found = if artifact.version.is_range() {
+            let range = VersionRange::parse(&artifact.version)?;
+            Some(
+                self.resolve_range(PartialArtifact::from(artifact.clone()), &range)
+                    .await?,
+            )
+        } else if let Some(dynamic) = DynamicVersion::parse(&artifact.version) {
+            Some(
+                self.resolve_dynamic(PartialArtifact::from(artifact.clone()), &dynamic)
+                    .await?,
+            )
         } else {
-            Err(ResolveError::GenericHttpError {
-                url: url.clone(),
-                status: response.status().as_u16(),
-            })
+            None
+        };
+        let selected = found.is_some();
+        let resolved = match found {
+            Some(found) => {
+                let mut resolved = self
+                    .resolve_version(artifact.with_version(found.version))
+                    .await?;
+                resolved.repository = Some(found.repository);
+                resolved
+            }
+            None => self.resolve_version(artifact).await?,
+        };
+        let checked = if resolved.artifact.version.is_meta_version() {
+            resolved
+                .artifact
+                .with_version(resolved.resolved_version.clone())
+        } else {
+            resolved.artifact.clone()
+        };
+        if !self.policy.is_empty() {
+            self.enforce(self.policy.check(&checked))?;
         }
+        // versions selected from ranges are old enough already
+        if !selected {
+            self.check_release_age(&checked).await?;
+        }
+        Ok(resolved)
     }
 
-    pub async fn download(&self, artifact: Artifact, path: &Path) -> Result<PathBuf, ResolveError> {
+    async fn resolve_version(&self, artifact: Artifact) -> Result<ResolvedArtifact, ResolveError> {
+        self.repositories_for(&artifact.version)?;
         if artifact.is_snapshot() {
-            if self.repository.snapshots {
-                let meta = self.metadata0(artifact.path()).await?;
-                let versioning = meta.versioning;
-                let snapshot = versioning.snapshot.unwrap();
-                let meta_version =
-                    Version::from(format!("{}-{}", snapshot.timestamp, snapshot.buildNumber));
-                let versions = versioning.snapshot_versions.unwrap_or(vec![]);
-                let found = versions.iter().find_map(move |x| {
-                    if x.value.ends_with(meta_version.as_ref()) {
-                        Some(x.value.clone())
-                    } else {
-                        None
-                    }
-                });
+            let meta = self.metadata0(artifact.path(), &artifact.version).await?;
+            let versioning = meta.versioning;
+            let snapshot = versioning.snapshot.unwrap();
+            let meta_version =
+                Version::from(format!("{}-{}", snapshot.timestamp, snapshot.buildNumber));
+            let versions = versioning.snapshot_versions.unwrap_or(vec![]);
+            let found = versions.iter().find_map(move |x| {
+                if x.value.ends_with(meta_version.as_ref()) {
+                    Some(x.value.clone())
+                } else {
+                    None
+                }
+            });
 
-                let resolved = ResolvedArtifact {
-                    artifact: artifact.clone(),
-                    resolved_version: found.unwrap_or(artifact.version.clone()),
-                };
-                self.download0(resolved, path).await
-            } else {
-                Err(ResolveError::Message(String::from(
-                    "You may not resolve snapshots from a non-snapshot repository",
-                )))
-            }
+            let mut resolved =
+                ResolvedArtifact::new(artifact.clone(), found.unwrap_or(artifact.version.clone()));
+            resolved.snapshot = Some(snapshot);
+            Ok(resolved)
         } else if artifact.version.is_meta_version() {
-            let meta = self.metadata(artifact.clone().into()).await?;
+            let meta = self
+                .metadata0(
+                    PartialArtifact::from(artifact.clone()).path(),
+                    &artifact.version,
+                )
+                .await?;
             let versioning = meta.versioning;
             let maybe_resolved = if artifact.version.is_release() {
                 versioning.release
@@ -97,71 +1355,1065 @@ impl Resolver<'_> {
             };
             match maybe_resolved {
                 None => Err(ResolveError::Message(format!(
-                    "Failed to download artifact {}",
+                    "Failed to resolve artifact {}",
                     artifact
                 ))),
                 Some(resolved) => {
-                    self.download0(
-                        ResolvedArtifact {
-                            artifact: artifact.clone(),
-                            resolved_version: resolved,
-                        },
-                        path,
-                    )
-                    .await
+                    self.repositories_for(&resolved)?;
+                    Ok(ResolvedArtifact::new(artifact.clone(), resolved))
                 }
             }
         } else {
-            self.download0(
-                ResolvedArtifact {
-                    artifact: artifact.clone(),
-                    resolved_version: artifact.version.clone(),
-                },
-                path,
-            )
-            .await
+            Ok(ResolvedArtifact::new(
+                artifact.clone(),
+                artifact.version.clone(),
+            ))
+        }
+    }
+
+    pub async fn exists(&self, artifact: Artifact) -> Result<bool, ResolveError> {
+        let resolved = match self.resolve(artifact).await {
+            Ok(resolved) => resolved,
+            Err(e) if e.is_not_found() => return Ok(false),
+            Err(e) => return Err(e),
+        };
+        match self.locate(&resolved).await {
+            Ok(_) => Ok(true),
+            Err(e) if e.is_not_found() => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub async fn locate(&self, artifact: &ResolvedArtifact) -> Result<Location, ResolveError> {
+        let mut last_error = None;
+        for repository in self.repositories_for(Self::policy_version(artifact))? {
+            match self.locate_in(repository, artifact).await {
+                Err(e) if e.is_not_found() => last_error = Some(e),
+                result => return result,
+            }
+        }
+        Err(last_error.unwrap())
+    }
+
+    async fn locate_in(
+        &self,
+        repository: &Repository,
+        artifact: &ResolvedArtifact,
+    ) -> Result<Location, ResolveError> {
+        let url = artifact.uri(repository)?;
+        let _permit = self.permit().await;
+        debug!(%url, "probing content length");
+        let response = self.send(Method::HEAD, &url).await?;
+        if response.status().is_success() {
+            let content_length = response
+                .headers()
+                .get(CONTENT_LENGTH)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.parse::<u64>().ok());
+            Ok(Location {
+                url,
+                content_length,
+            })
+        } else {
+            Err(ResolveError::GenericHttpError {
+                url,
+                status: response.status().as_u16(),
+            })
+        }
+    }
+
+    pub async fn descriptor(&self, artifact: Artifact) -> Result<Descriptor, ResolveError> {
+        let pom = Artifact {
+            extension: Some(String::from("pom")),
+            classifier: None,
+            ..artifact
+        };
+        let fixed = !pom.version.is_range()
+            && !pom.version.is_meta_version()
+            && DynamicVersion::parse(&pom.version).is_none();
+        let Some(cache) = self.descriptors.as_ref().filter(|_| fixed) else {
+            return self.fetch_descriptor(pom).await;
+        };
+        let repositories = self.repositories_for(&pom.version)?;
+        for repository in &repositories {
+            if let Some(descriptor) = cache.get(&pom, repository) {
+                self.enforce(self.policy.check(&pom))?;
+                self.check_release_age(&pom).await?;
+                debug!(artifact = %pom, url = %descriptor.url, "using cached descriptor");
+                self.record(|s| s.hits += 1);
+                return Ok(descriptor);
+            }
+        }
+        self.record(|s| s.misses += 1);
+        let descriptor = self.fetch_descriptor(pom.clone()).await?;
+        // descriptors from the local repository are not cached, they are cheap to read
+        let source = repositories
+            .iter()
+            .find(|r| descriptor.url.as_str().starts_with(r.url.as_str()));
+        if let Some(repository) = source
+            && let Err(e) = cache.put(&pom, repository, &descriptor)
+        {
+            warn!(path = %cache.path(&pom, repository).display(), "unable to cache descriptor: {}", e);
+        }
+        Ok(descriptor)
+    }
+
+    async fn fetch_descriptor(&self, pom: Artifact) -> Result<Descriptor, ResolveError> {
+        let resolved = self.resolve(pom).await?;
+        match self.fetch(&resolved).await {
+            Ok((url, bytes)) => {
+                let project = PomParser::new().parse(Cursor::new(bytes))?;
+                let flavor = project.flavor();
+                Ok(Descriptor {
+                    url,
+                    project,
+                    flavor,
+                })
+            }
+            Err(e) if e.is_not_found() => {
+                // Maven 4 may publish only the build POM under the "build" classifier
+                let build = ResolvedArtifact::new(
+                    resolved.artifact.with_classifier(Classifier::from("build")),
+                    resolved.resolved_version.clone(),
+                );
+                match self.fetch(&build).await {
+                    Ok((url, bytes)) => Ok(Descriptor {
+                        url,
+                        project: PomParser::new().parse(Cursor::new(bytes))?,
+                        flavor: PomFlavor::Build,
+                    }),
+                    Err(b) if b.is_not_found() => Err(e),
+                    Err(b) => Err(b),
+                }
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    pub async fn effective_project(&self, artifact: Artifact) -> Result<Project, ResolveError> {
+        Ok(self.effective_descriptor(artifact).await?.project)
+    }
+
+    // effective_project, keeping the url the POM itself was fetched from
+    pub async fn effective_descriptor(
+        &self,
+        artifact: Artifact,
+    ) -> Result<Descriptor, ResolveError> {
+        let descriptor = self.descriptor(artifact.clone()).await?;
+        let project = self.inherit_parents(descriptor.project, None).await?;
+        self.enforce(self.policy.check_licenses(&artifact, &project))?;
+        Ok(Descriptor {
+            project,
+            ..descriptor
+        })
+    }
+
+    // For checkouts: parents are looked up through <relativePath> first, so unreleased
+    // parent versions of a multi-module build resolve without being installed anywhere.
+    pub async fn effective_project_at(&self, path: &Path) -> Result<Project, ResolveError> {
+        let project = PomParser::new().parse(File::open(path)?)?;
+        let dir = path.parent().map(Path::to_path_buf);
+        self.inherit_parents(project, dir).await
+    }
+
+    async fn inherit_parents(
+        &self,
+        mut project: Project,
+        mut dir: Option<PathBuf>,
+    ) -> Result<Project, ResolveError> {
+        let mut seen: Vec<Artifact> = vec![];
+        let mut next = project.parent.clone();
+        while let Some(parent) = next {
+            let artifact = parent.artifact();
+            if seen.contains(&artifact) {
+                return Err(ResolveError::Message(format!(
+                    "Cycle in parent hierarchy at {}",
+                    artifact
+                )));
+            }
+            let local = dir.as_deref().and_then(|d| parent.find_local(d));
+            let model = match local {
+                Some((path, model)) => {
+                    debug!(%artifact, path = %path.display(), "using local parent");
+                    dir = path.parent().map(Path::to_path_buf);
+                    model
+                }
+                None => {
+                    dir = None;
+                    self.descriptor(artifact.clone()).await?.project
+                }
+            };
+            next = model.parent.clone();
+            project.inherit(&model);
+            seen.push(artifact);
+        }
+        Ok(project)
+    }
+
+    async fn fetch(&self, artifact: &ResolvedArtifact) -> Result<(Url, Vec<u8>), ResolveError> {
+        let repositories = self.repositories_for(Self::policy_version(artifact))?;
+        if let Some(local) = &self.local
+            && let Some(path) = local.find(artifact, &repositories)
+        {
+            debug!(path = %path.display(), "using local repository");
+            let url = Url::from_file_path(&path)
+                .map_err(|_| ResolveError::Message(format!("Invalid path {}", path.display())))?;
+            let bytes = std::fs::read(path)?;
+            self.record(|s| {
+                s.hits += 1;
+                s.bytes_saved += bytes.len() as u64;
+            });
+            return Ok((url, bytes));
+        }
+        if self.local.is_some() {
+            self.record(|s| s.misses += 1);
+        }
+        let mut last_error = None;
+        for repository in repositories {
+            match self.fetch_from(repository, artifact).await {
+                Err(e) if e.is_not_found() => last_error = Some(e),
+                Ok((url, bytes)) => {
+                    if let Some(local) = &self.local {
+                        local.store_content(artifact, &bytes, Some(repository))?;
+                    }
+                    return Ok((url, bytes));
+                }
+                result => return result,
+            }
+        }
+        Err(last_error.unwrap())
+    }
+
+    async fn fetch_from(
+        &self,
+        repository: &Repository,
+        artifact: &ResolvedArtifact,
+    ) -> Result<(Url, Vec<u8>), ResolveError> {
+        let url = artifact.uri(repository)?;
+        let _permit = self.permit().await;
+        debug!(%url, "fetching");
+        let response = self.send(Method::GET, &url).await?;
+        if response.status().is_success() {
+            let bytes = response.bytes().await?.to_vec();
+            Ok((url, bytes))
+        } else {
+            Err(ResolveError::GenericHttpError {
+                url,
+                status: response.status().as_u16(),
+            })
+        }
+    }
+
+    pub async fn attestations(
+        &self,
+        artifact: &ResolvedArtifact,
+    ) -> Result<Vec<Attestation>, ResolveError> {
+        for repository in self.repositories_for(Self::policy_version(artifact))? {
+            let found = self.attestations_in(repository, artifact).await?;
+            if !found.is_empty() {
+                return Ok(found);
+            }
+        }
+        Ok(vec![])
+    }
+
+    async fn attestations_in(
+        &self,
+        repository: &Repository,
+        artifact: &ResolvedArtifact,
+    ) -> Result<Vec<Attestation>, ResolveError> {
+        let mut found = Vec::new();
+        for kind in AttestationKind::all() {
+            let url = artifact.sidecar_uri(repository, kind.suffix())?;
+            let _permit = self.permit().await;
+            debug!(%url, "looking for attestation");
+            let response = self.send(Method::GET, &url).await?;
+            match response.status() {
+                StatusCode::NOT_FOUND => continue,
+                status if status.is_success() => {
+                    let content = response.bytes().await?.to_vec();
+                    found.push(Attestation { kind, url, content });
+                }
+                status => {
+                    return Err(ResolveError::GenericHttpError {
+                        url,
+                        status: status.as_u16(),
+                    });
+                }
+            }
         }
+        Ok(found)
     }
+
     async fn download0(
         &self,
         artifact: ResolvedArtifact,
         dir: &Path,
-    ) -> Result<PathBuf, ResolveError> {
-        let url = artifact.uri(self.repository)?;
-        eprintln!("{}", url);
-        let mut response = self.client.get(url.clone()).send().await?;
-        let path = dir.join(artifact.artifact.file_name());
-
-        #[cfg(feature = "progressbar")]
+    ) -> Result<ResolvedArtifact, ResolveError> {
+        // the requested name is written to `dir`, the resolved one to the local repository
+        paths::check_artifact(&artifact.artifact)?;
+        paths::check_artifact(&Artifact::from(artifact.clone()))?;
+        let repositories = self.repositories_for(Self::policy_version(&artifact))?;
+        if let Some(local) = &self.local
+            && let Some(cached) = local.find(&artifact, &repositories)
         {
-            use indicatif::{ProgressBar, ProgressStyle};
+            debug!(path = %cached.display(), "using local repository");
+            let size = std::fs::metadata(&cached)?.len();
+            self.record(|s| {
+                s.hits += 1;
+                s.bytes_saved += size;
+            });
+            let path = paths::long_path(dir.join(artifact.artifact.file_name()));
+            let link = if path != cached {
+                Some(disk::link_file(&cached, &path, self.link)?)
+            } else {
+                None
+            };
+            return Ok(ResolvedArtifact {
+                file: Some(path),
+                checksums: checksum::digest_file(&cached)?,
+                link,
+                ..artifact
+            });
+        }
+        if self.local.is_some() {
+            self.record(|s| s.misses += 1);
+        }
+        let deadline = self.download_deadline.map(|d| Instant::now() + d);
+        let mut last_error = None;
+        for repository in repositories {
+            match self
+                .download_from(repository, &artifact, dir, deadline)
+                .await
+            {
+                Err(e) if e.is_not_found() => last_error = Some(e),
+                Ok(downloaded) => {
+                    if let Some(local) = &self.local
+                        && let Some(path) = &downloaded.file
+                    {
+                        local.store(&downloaded, path, Some(repository))?;
+                    }
+                    return Ok(downloaded);
+                }
+                result => return result,
+            }
+        }
+        Err(last_error.unwrap())
+    }
 
-            let pb = ProgressBar::no_length();
-            if let Some(length) = response.content_length() { pb.set_length(length) };
-            pb.set_style(
-                ProgressStyle::with_template(
-                    "{spinner:.green} [{wide_bar:.cyan/blue}] {bytes}/{total_bytes} ({eta})",
+    async fn download_from(
+        &self,
+        repository: &Repository,
+        artifact: &ResolvedArtifact,
+        dir: &Path,
+        deadline: Option<Instant>,
+    ) -> Result<ResolvedArtifact, ResolveError> {
+        let url = artifact.uri(repository)?;
+        let target = paths::long_path(dir.join(artifact.artifact.file_name()));
+        let mut conditional = true;
+        if self.skip_existing
+            && target.is_file()
+            && repository.checksum_policy != ChecksumPolicy::Ignore
+            && let Some(remote) = self.checksum_in(repository, artifact).await?
+        {
+            let checksums = checksum::digest_file(&target)?;
+            if let FileCheck::Match { algorithm, .. } = Self::compare_checksum(remote, &checksums)
+                && let Some(kept) = self
+                    .kept(repository, artifact, &url, &target, checksums, algorithm)
+                    .await?
+            {
+                return Ok(kept);
+            }
+            debug!(%url, path = %target.display(), "existing file differs, downloading");
+            // however new it is, a file known to be wrong has to be replaced
+            conditional = false;
+        }
+        // a file an earlier download wrote is only fetched again when the server has a newer one
+        let stored = self
+            .validators
+            .as_ref()
+            .filter(|_| conditional)
+            .and_then(|cache| cache.load(&target));
+        let headers = stored.map(|v| v.headers()).unwrap_or_default();
+        let permit = self.permit().await;
+        info!(%url, "downloading");
+        let mut response = self
+            .before(deadline, &url, self.send_with(Method::GET, &url, headers))
+            .await?;
+        debug!(%url, status = response.status().as_u16(), "download response");
+        if response.status() == StatusCode::NOT_MODIFIED && target.is_file() {
+            info!(%url, path = %target.display(), "not modified");
+            drop(permit);
+            let size = std::fs::metadata(&target)?.len();
+            self.record(|s| {
+                s.not_modified += 1;
+                s.bytes_saved += size;
+            });
+            let checksums = checksum::digest_file(&target)?;
+            let result = self
+                .finish_download(
+                    repository,
+                    artifact,
+                    url,
+                    target.clone(),
+                    target.clone(),
+                    checksums,
                 )
-                .unwrap()
-                .progress_chars("#>-"),
-            );
-            let mut file = BufWriter::new(pb.wrap_write(File::create(&path)?));
-            Self::write(&mut response, &mut file).await?;
+                .await;
+            // the file is left alone, but no longer claimed to be the server's copy
+            if result.is_err()
+                && let Some(cache) = &self.validators
+                && let Err(e) = cache.store(&target, &Validators::default())
+            {
+                warn!(path = %target.display(), "unable to remove validators: {}", e);
+            }
+            return result;
+        }
+        if !response.status().is_success() {
+            return Err(ResolveError::GenericHttpError {
+                url,
+                status: response.status().as_u16(),
+            });
+        }
+        // Written under a name of its own and renamed once verified, so a transfer broken off
+        // never leaves a truncated file at `target`. Named apart, artifacts of different groups
+        // can share a file name in the staging directory.
+        let staging = self.staging.as_deref().unwrap_or(dir);
+        let partial = disk::PartialFile::new(paths::long_path(staging.join(format!(
+            "{}.{}-{}.part",
+            artifact.artifact.file_name(),
+            std::process::id(),
+            STAGED.fetch_add(1, Ordering::Relaxed)
+        ))));
+        let path = partial.path();
+        if let Some(length) = response.content_length() {
+            Self::check_space(staging, length)?;
+            if staging != dir {
+                Self::check_space(dir, length)?;
+            }
+        }
+        let validators = Validators::from_headers(response.headers());
+        let mut digester = Digester::new();
+
+        let mut file = BufWriter::new(File::create(path)?);
+        let transfer = Transfer::start(self.progress.as_deref(), &url, response.content_length());
+        let written = Self::write(&transfer, &mut response, &mut file, &mut digester);
+        let result = self.before(deadline, &url, written).await;
+        let result = result.and_then(|()| Ok(file.flush()?));
+        drop(file);
+        transfer.finish(result.is_ok());
+        if let Err(e) = result {
+            return Err(Self::timed_out(&url, e));
+        }
+
+        let extension = artifact.artifact.extension.as_deref().unwrap_or("jar");
+        if let Some(expected) = ContentKind::for_extension(extension) {
+            let found = ContentKind::detect_file(path)?;
+            if found != Some(expected) {
+                return Err(ResolveError::ContentMismatch {
+                    url,
+                    expected,
+                    found,
+                });
+            }
         }
-        #[cfg(not(feature = "progressbar"))]
+        drop(permit);
+        let path = path.to_path_buf();
+        // until it is moved into place, the file is removed should this future be dropped
+        let downloaded = self
+            .finish_download(
+                repository,
+                artifact,
+                url,
+                path,
+                target.clone(),
+                digester.finish(),
+            )
+            .await?;
+        if let Some(cache) = &self.validators
+            && let Err(e) = cache.store(&target, &validators)
         {
-            let mut file = BufWriter::new(File::create(&path)?);
-            Self::write(&mut response, &mut file).await?;
+            warn!(path = %target.display(), "unable to store validators: {}", e);
+        }
+        Ok(downloaded)
+    }
+
+    // fails before a transfer of `length` bytes rather than midway through it
+    fn check_space(dir: &Path, length: u64) -> Result<(), ResolveError> {
+        // unknown space, or a directory that doesn't exist, is left to the write to report
+        match disk::available_space(dir) {
+            Ok(Some(available)) if available < length => Err(ResolveError::InsufficientSpace {
+                path: dir.to_path_buf(),
+                required: length,
+                available,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    // Checksum and signature checks of a downloaded file, which is removed when they fail
+    // and otherwise moved from `path` to `target`. A file already at `target` is not this
+    // call's to remove.
+    async fn finish_download(
+        &self,
+        repository: &Repository,
+        artifact: &ResolvedArtifact,
+        url: Url,
+        path: PathBuf,
+        target: PathBuf,
+        checksums: Vec<Checksum>,
+    ) -> Result<ResolvedArtifact, ResolveError> {
+        let verified = match self.verify_checksum(repository, artifact, &checksums).await {
+            Ok(verified) => verified,
+            Err(e) => {
+                if path != target {
+                    std::fs::remove_file(&path)?;
+                }
+                return Err(e);
+            }
+        };
+        #[cfg(feature = "signatures")]
+        let signed = match &self.verifier {
+            Some(verifier) => {
+                if let Err(e) = self
+                    .verify_signature(verifier, repository, artifact, &path)
+                    .await
+                {
+                    if path != target {
+                        std::fs::remove_file(&path)?;
+                    }
+                    return Err(e);
+                }
+                true
+            }
+            None => false,
+        };
+        if path != target {
+            disk::move_file(&path, &target)?;
+        }
+        #[cfg(feature = "signatures")]
+        let signature = match signed {
+            true => SignatureStatus::Verified,
+            false => self.signature_status(repository, artifact).await?,
+        };
+        #[cfg(not(feature = "signatures"))]
+        let signature = self.signature_status(repository, artifact).await?;
+        Ok(ResolvedArtifact {
+            repository: Some(repository.url.clone()),
+            url: Some(url),
+            file: Some(target),
+            checksums,
+            verified_checksum: verified,
+            signature,
+            ..artifact.clone()
+        })
+    }
+
+    // A file already at `target` matching the published checksum, unless a signature is
+    // required and doesn't verify
+    async fn kept(
+        &self,
+        repository: &Repository,
+        artifact: &ResolvedArtifact,
+        url: &Url,
+        target: &Path,
+        checksums: Vec<Checksum>,
+        verified: ChecksumAlgorithm,
+    ) -> Result<Option<ResolvedArtifact>, ResolveError> {
+        #[cfg(feature = "signatures")]
+        let signature = match &self.verifier {
+            Some(verifier) => {
+                if self
+                    .verify_signature(verifier, repository, artifact, target)
+                    .await
+                    .is_err()
+                {
+                    return Ok(None);
+                }
+                SignatureStatus::Verified
+            }
+            None => self.signature_status(repository, artifact).await?,
+        };
+        #[cfg(not(feature = "signatures"))]
+        let signature = self.signature_status(repository, artifact).await?;
+        info!(%url, path = %target.display(), "matches the published checksum, skipped");
+        let size = std::fs::metadata(target)?.len();
+        self.record(|s| {
+            s.hits += 1;
+            s.bytes_saved += size;
+        });
+        Ok(Some(ResolvedArtifact {
+            repository: Some(repository.url.clone()),
+            url: Some(url.clone()),
+            file: Some(target.to_path_buf()),
+            checksums,
+            verified_checksum: Some(verified),
+            signature,
+            skipped: true,
+            ..artifact.clone()
+        }))
+    }
+
+    // like Maven, only the strongest published checksum is verified
+    async fn verify_checksum(
+        &self,
+        repository: &Repository,
+        artifact: &ResolvedArtifact,
+        digests: &[Checksum],
+    ) -> Result<Option<ChecksumAlgorithm>, ResolveError> {
+        if repository.checksum_policy == ChecksumPolicy::Ignore {
+            return Ok(None);
+        }
+        let Some(remote) = self.checksum_in(repository, artifact).await? else {
+            return Ok(None);
+        };
+        match Self::compare_checksum(remote, digests) {
+            FileCheck::Match { algorithm, .. } => Ok(Some(algorithm)),
+            FileCheck::Mismatch {
+                url,
+                algorithm,
+                expected,
+                actual,
+            } if repository.checksum_policy == ChecksumPolicy::Warn => {
+                warn!(%url, ?algorithm, expected, actual, "checksum mismatch, keeping the download");
+                Ok(None)
+            }
+            FileCheck::Mismatch {
+                url,
+                algorithm,
+                expected,
+                actual,
+            } => Err(ResolveError::ChecksumMismatch {
+                url,
+                algorithm,
+                expected: expected.into(),
+                actual: actual.into(),
+            }),
+            _ => Ok(None),
+        }
+    }
+
+    // a sidecar that doesn't hold a valid checksum can't match either
+    fn compare_checksum(
+        (url, algorithm, expected): (Url, ChecksumAlgorithm, String),
+        digests: &[Checksum],
+    ) -> FileCheck {
+        let actual = digests
+            .iter()
+            .find(|c| c.algorithm() == algorithm)
+            .map(Checksum::to_string)
+            .unwrap_or_default();
+        match Checksum::parse(algorithm, &expected) {
+            Ok(parsed) if parsed.to_string() == actual => FileCheck::Match {
+                algorithm,
+                value: actual,
+            },
+            _ => FileCheck::Mismatch {
+                url,
+                algorithm,
+                expected,
+                actual,
+            },
+        }
+    }
+
+    pub async fn check_file(
+        &self,
+        artifact: &ResolvedArtifact,
+        path: &Path,
+    ) -> Result<FileCheck, ResolveError> {
+        for repository in self.repositories_for(Self::policy_version(artifact))? {
+            if let Some(remote) = self.checksum_in(repository, artifact).await? {
+                return Ok(Self::compare_checksum(
+                    remote,
+                    &checksum::digest_file(path)?,
+                ));
+            }
+        }
+        match self.locate(artifact).await {
+            Ok(_) => Ok(FileCheck::NoChecksum),
+            Err(e) if e.is_not_found() => Ok(FileCheck::NotFound),
+            Err(e) => Err(e),
+        }
+    }
+
+    // Polls until the repository serves the file and a checksum matching `path`, for
+    // gating on propagation after a deploy. Missing files and checksums are retried until
+    // the timeout and the last check is returned; a mismatch is returned right away.
+    pub async fn await_published(
+        &self,
+        artifact: &ResolvedArtifact,
+        path: &Path,
+        options: &PollOptions,
+    ) -> Result<FileCheck, ResolveError> {
+        let deadline = Instant::now() + options.timeout;
+        let mut delay = options.initial_delay;
+        loop {
+            let check = match self.check_file(artifact, path).await? {
+                // the checksum can be visible before the file itself
+                check @ FileCheck::Match { .. } => match self.locate(artifact).await {
+                    Ok(_) => return Ok(check),
+                    Err(e) if e.is_not_found() => FileCheck::NotFound,
+                    Err(e) => return Err(e),
+                },
+                check @ FileCheck::Mismatch { .. } => return Ok(check),
+                check => check,
+            };
+            let now = Instant::now();
+            if now >= deadline {
+                return Ok(check);
+            }
+            debug!(artifact = %artifact.artifact, ?delay, "not yet published");
+            tokio::time::sleep(delay.min(deadline - now)).await;
+            delay = (delay * 2).min(options.max_delay);
+        }
+    }
+
+    async fn checksum_in(
+        &self,
+        repository: &Repository,
+        artifact: &ResolvedArtifact,
+    ) -> Result<Option<(Url, ChecksumAlgorithm, String)>, ResolveError> {
+        for algorithm in [
+            ChecksumAlgorithm::Sha512,
+            ChecksumAlgorithm::Sha256,
+            ChecksumAlgorithm::Sha1,
+            ChecksumAlgorithm::Md5,
+        ] {
+            let url = artifact.sidecar_uri(repository, &format!(".{}", algorithm.extension()))?;
+            let _permit = self.permit().await;
+            debug!(%url, "fetching checksum");
+            let response = self.send(Method::GET, &url).await?;
+            match response.status() {
+                StatusCode::NOT_FOUND => continue,
+                status if status.is_success() => {
+                    let text = response.text().await?;
+                    // sidecars may carry the file name after the hash
+                    let value = text
+                        .split_whitespace()
+                        .next()
+                        .unwrap_or_default()
+                        .to_lowercase();
+                    return Ok(Some((url, algorithm, value)));
+                }
+                status => {
+                    return Err(ResolveError::GenericHttpError {
+                        url,
+                        status: status.as_u16(),
+                    });
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    async fn signature_status(
+        &self,
+        repository: &Repository,
+        artifact: &ResolvedArtifact,
+    ) -> Result<SignatureStatus, ResolveError> {
+        if !self.probe_signatures {
+            return Ok(SignatureStatus::Unchecked);
         }
+        let url = artifact.sidecar_uri(repository, ".asc")?;
+        let _permit = self.permit().await;
+        let response = self.send(Method::HEAD, &url).await?;
+        Ok(match response.status() {
+            status if status.is_success() => SignatureStatus::Present,
+            StatusCode::NOT_FOUND => SignatureStatus::Missing,
+            _ => SignatureStatus::Unchecked,
+        })
+    }
+
+    #[cfg(feature = "signatures")]
+    async fn verify_signature(
+        &self,
+        verifier: &Verifier,
+        repository: &Repository,
+        artifact: &ResolvedArtifact,
+        path: &Path,
+    ) -> Result<(), ResolveError> {
+        use crate::signatures::SignatureError;
 
-        Ok(path)
+        let url = artifact.sidecar_uri(repository, ".asc")?;
+        let response = {
+            let _permit = self.permit().await;
+            let response = self.send(Method::GET, &url).await?;
+            match response.status() {
+                StatusCode::NOT_FOUND => return Err(SignatureError::Missing(url).into()),
+                status if !status.is_success() => {
+                    return Err(ResolveError::GenericHttpError {
+                        url,
+                        status: status.as_u16(),
+                    });
+                }
+                _ => response.bytes().await?,
+            }
+        };
+        // named apart from any .asc already next to the file, and removed however this ends
+        let mut signature = path.as_os_str().to_owned();
+        signature.push(format!(
+            ".{}-{}.asc",
+            std::process::id(),
+            STAGED.fetch_add(1, Ordering::Relaxed)
+        ));
+        let signature = disk::PartialFile::new(PathBuf::from(signature));
+        std::fs::write(signature.path(), &response)?;
+        let verified = verifier.verify(path, signature.path()).await?;
+        verifier.accept(&artifact.artifact.group_id, path, &verified)?;
+        debug!(%url, fingerprint = verified.fingerprint, "signature verified");
+        Ok(())
     }
 
-    async fn write<W: Write>(response: &mut Response, file: &mut W) -> Result<(), ResolveError> {
-        // Stream the response body and write it to the file chunk by chunk
+    // a transfer broken off leaves no partial file to be taken for a complete one
+    async fn write<W: Write>(
+        transfer: &Transfer<'_>,
+        response: &mut Response,
+        file: &mut W,
+        digester: &mut Digester,
+    ) -> Result<(), ResolveError> {
+        // Stream the response body and write it to the file chunk by chunk, digesting on the way
         while let Some(chunk) = response.chunk().await? {
+            digester.update(&chunk);
             file.write_all(&chunk)?;
+            transfer.received(chunk.len() as u64);
         }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_shareable<T: Clone + Send + Sync + 'static>(_: &T) {}
+
+    fn assert_send<T: Send>(_: T) {}
+
+    // nothing from settings.xml, ~/.netrc or the credential store of whoever runs the tests
+    fn builder() -> ResolverBuilder {
+        Resolver::builder().credentials(CredentialsChain::new())
+    }
+
+    // A repository on a thread of its own, answering each request with respond(request head)
+    // and closing the connection after it
+    fn serve<F>(respond: F) -> Repository
+    where
+        F: Fn(&str) -> Vec<u8> + Send + 'static,
+    {
+        use std::io::{BufRead, BufReader, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/repo", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let mut request = String::new();
+                let mut reader = BufReader::new(&stream);
+                while reader.read_line(&mut request).is_ok_and(|n| n > 2) {}
+                let _ = (&stream).write_all(&respond(&request));
+            }
+        });
+        Repository::parse(&url).unwrap()
+    }
+
+    fn response(status: &str, headers: &[(&str, &str)], body: &[u8]) -> Vec<u8> {
+        let mut response = format!(
+            "HTTP/1.1 {}\r\nconnection: close\r\ncontent-length: {}\r\n",
+            status,
+            body.len()
+        );
+        for (name, value) in headers {
+            response.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        response.push_str("\r\n");
+        let mut response = response.into_bytes();
+        response.extend_from_slice(body);
+        response
+    }
+
+    fn sha1(content: &[u8]) -> String {
+        let mut digester = Digester::new();
+        digester.update(content);
+        let sha1 = digester.finish().into_iter();
+        sha1.filter(|c| c.algorithm() == ChecksumAlgorithm::Sha1)
+            .map(|c| c.to_string())
+            .next()
+            .unwrap()
+    }
+
+    #[test]
+    fn resolver_is_shareable() {
+        let resolver = builder().build().unwrap();
+        assert_shareable(&resolver);
+        let artifact = Artifact::parse("com.example:example:1.0").unwrap();
+        assert_send(resolver.resolve(artifact.clone()));
+        assert_send(resolver.download(artifact.clone(), Path::new(".")));
+        assert_send(resolver.download_until(
+            artifact.clone(),
+            Path::new("."),
+            std::future::pending(),
+        ));
+        assert_send(crate::graph::DependencyGraph::resolve(
+            &resolver,
+            artifact.clone(),
+        ));
+        assert_send(crate::graph::DependencyGraph::stream(&resolver, artifact));
+        let cloned = resolver.clone();
+        assert_eq!(cloned.repositories().len(), resolver.repositories().len());
+    }
+
+    #[tokio::test]
+    async fn conditional_downloads() {
+        const JAR: &[u8] = b"PK\x03\x04 the jar";
+        let published = Arc::new(Mutex::new(sha1(JAR)));
+        let requests = Arc::new(Mutex::new(vec![]));
+        let repository = {
+            let (published, requests) = (published.clone(), requests.clone());
+            serve(move |request| {
+                requests.lock().unwrap().push(request.to_lowercase());
+                let path = request.split_whitespace().nth(1).unwrap_or_default();
+                if path.ends_with("lib-1.0.jar.sha1") {
+                    response("200 OK", &[], published.lock().unwrap().as_bytes())
+                } else if !path.ends_with("lib-1.0.jar") {
+                    response("404 Not Found", &[], b"")
+                } else if request.to_lowercase().contains("if-none-match: \"v1\"") {
+                    response("304 Not Modified", &[("etag", "\"v1\"")], b"")
+                } else {
+                    response("200 OK", &[("etag", "\"v1\"")], JAR)
+                }
+            })
+        };
+        let root =
+            std::env::temp_dir().join(format!("maven-artifact-conditional-{}", std::process::id()));
+        let cache = ValidatorCache::new(root.join("validators"));
+        let resolver = builder()
+            .repository(repository)
+            .validator_cache(cache.clone())
+            .build()
+            .unwrap();
+        let artifact = Artifact::parse("org.example:lib:1.0").unwrap();
+        let dir = root.join("downloads");
+        std::fs::create_dir_all(&dir).unwrap();
+        let target = dir.join("lib-1.0.jar");
+        let conditional = || {
+            let requests = requests.lock().unwrap();
+            let jar = requests
+                .iter()
+                .rfind(|r| r.starts_with("get /repo/org/example/lib/1.0/lib-1.0.jar "));
+            let jar = jar.unwrap();
+            jar.contains("if-none-match") || jar.contains("if-modified-since")
+        };
+
+        // a file this resolver didn't write is downloaded without validators
+        std::fs::write(&target, "PK\x03\x04 not the jar").unwrap();
+        resolver.download(artifact.clone(), &dir).await.unwrap();
+        assert!(!conditional());
+        assert_eq!(std::fs::read(&target).unwrap(), JAR);
+
+        resolver.download(artifact.clone(), &dir).await.unwrap();
+        assert!(conditional());
+        assert_eq!(resolver.cache_stats().not_modified, 1);
+        // the validators are kept out of the directory downloaded to
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+        // replaced since, so the validators no longer describe it
+        std::thread::sleep(Duration::from_millis(10));
+        std::fs::write(&target, "PK\x03\x04 edited").unwrap();
+        resolver.download(artifact.clone(), &dir).await.unwrap();
+        assert!(!conditional());
+        assert_eq!(std::fs::read(&target).unwrap(), JAR);
+
+        // a file that isn't modified but fails its check is kept, without validators
+        *published.lock().unwrap() = sha1(b"something else");
+        assert!(matches!(
+            resolver.download(artifact.clone(), &dir).await,
+            Err(ResolveError::ChecksumMismatch { .. })
+        ));
+        assert!(conditional());
+        assert_eq!(std::fs::read(&target).unwrap(), JAR);
+        assert_eq!(cache.load(&target), None);
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn broken_off_download() {
+        let repository = serve(|request| {
+            if request.contains("lib-1.0.jar ") {
+                // promises more than it sends before closing the connection
+                let mut response = b"HTTP/1.1 200 OK\r\ncontent-length: 1000\r\n\r\n".to_vec();
+                response.extend_from_slice(b"PK\x03\x04 the start");
+                response
+            } else {
+                response("404 Not Found", &[], b"")
+            }
+        });
+        let resolver = builder()
+            .repository(repository)
+            .retry(RetryPolicy {
+                max_attempts: 1,
+                ..RetryPolicy::default()
+            })
+            .build()
+            .unwrap();
+        let artifact = Artifact::parse("org.example:lib:1.0").unwrap();
+        let dir =
+            std::env::temp_dir().join(format!("maven-artifact-broken-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let target = dir.join("lib-1.0.jar");
+        std::fs::write(&target, "PK\x03\x04 the old jar").unwrap();
+
+        assert!(matches!(
+            resolver.download(artifact, &dir).await,
+            Err(ResolveError::Reqwest(_))
+        ));
+        assert_eq!(std::fs::read(&target).unwrap(), b"PK\x03\x04 the old jar");
+        let names: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name())
+            .collect();
+        assert_eq!(names, ["lib-1.0.jar"]);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn builder_defaults_to_central() {
+        let resolver = builder().build().unwrap();
+        assert_eq!(resolver.repositories().len(), 1);
+        assert_eq!(
+            resolver.repositories()[0].url,
+            Repository::maven_central().url
+        );
+    }
+
+    #[test]
+    fn repositories_filtered_by_policy() {
+        let resolver = builder()
+            .repository(Repository::maven_central())
+            .repository(Repository::maven_central_snapshots())
+            .build()
+            .unwrap();
+        let releases = resolver.repositories_for(&Version::from("1.0")).unwrap();
+        assert_eq!(releases.len(), 1);
+        assert_eq!(releases[0].url, Repository::maven_central().url);
+        let snapshots = resolver
+            .repositories_for(&Version::from("1.0-SNAPSHOT"))
+            .unwrap();
+        assert_eq!(snapshots[0].url, Repository::maven_central_snapshots().url);
+        assert_eq!(
+            resolver
+                .repositories_for(&Version::from("LATEST"))
+                .unwrap()
+                .len(),
+            2
+        );
+
+        let resolver = builder()
+            .repository(Repository::maven_central())
+            .build()
+            .unwrap();
+        assert!(matches!(
+            resolver.repositories_for(&Version::from("1.0-SNAPSHOT")),
+            Err(ResolveError::PolicyViolation { .. })
+        ));
+    }
+}
@@ -0,0 +1,130 @@
+use crate::conditional::parse_http_date;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::{Duration, SystemTime};
+
+// How the Resolver repeats requests that failed for reasons that tend to pass: the delay
+// doubles from base_delay up to max_delay, and with jitter a random part of its second
+// half is waited instead, so clients that failed together don't retry together. A 429 or
+// 503 with Retry-After is retried after the wait the server asks for. Only getting the
+// response is retried, a body broken off midway fails as before.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    // attempts in all, 1 for no retries
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    pub jitter: bool,
+    pub statuses: Vec<u16>,
+    // failures to connect and timeouts
+    pub io_errors: bool,
+    // a server asking for a longer wait fails the request right away
+    pub max_retry_after: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(10),
+            jitter: true,
+            statuses: vec![408, 429, 500, 502, 503, 504],
+            io_errors: true,
+            max_retry_after: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn none() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 1,
+            ..RetryPolicy::default()
+        }
+    }
+
+    pub fn retries_status(&self, status: u16) -> bool {
+        self.statuses.contains(&status)
+    }
+
+    pub fn retries_error(&self, error: &reqwest::Error) -> bool {
+        self.io_errors && (error.is_connect() || error.is_timeout())
+    }
+
+    // the wait after the failure of `attempt`, counting from 1
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let delay = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_delay);
+        if !self.jitter {
+            return delay;
+        }
+        let half = delay / 2;
+        let random = RandomState::new().build_hasher().finish();
+        half + half.mul_f64(random as f64 / u64::MAX as f64)
+    }
+}
+
+// the wait asked for by Retry-After, given in seconds or as an HTTP date
+pub fn retry_after(headers: &HeaderMap, now: SystemTime) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    match value.parse::<u64>() {
+        Ok(seconds) => Some(Duration::from_secs(seconds)),
+        Err(_) => Some(
+            parse_http_date(value)?
+                .duration_since(now)
+                .unwrap_or_default(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exponential_delays() {
+        let policy = RetryPolicy {
+            jitter: false,
+            ..RetryPolicy::default()
+        };
+        let delays: Vec<u64> = (1..=7)
+            .map(|attempt| policy.delay(attempt).as_millis() as u64)
+            .collect();
+        assert_eq!(delays, vec![500, 1000, 2000, 4000, 8000, 10000, 10000]);
+        assert_eq!(policy.delay(u32::MAX), Duration::from_secs(10));
+
+        let jittered = RetryPolicy::default();
+        for attempt in 1..=5 {
+            let delay = jittered.delay(attempt);
+            assert!(delay >= policy.delay(attempt) / 2 && delay <= policy.delay(attempt));
+        }
+        assert!(policy.retries_status(503));
+        assert!(!policy.retries_status(404));
+    }
+
+    #[test]
+    fn retry_after_header() {
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(784111777);
+        let header = |value: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(RETRY_AFTER, value.parse().unwrap());
+            retry_after(&headers, now)
+        };
+        assert_eq!(header("120"), Some(Duration::from_secs(120)));
+        assert_eq!(
+            header("Sun, 06 Nov 1994 08:50:07 GMT"),
+            Some(Duration::from_secs(30))
+        );
+        // a date gone by asks for no wait
+        assert_eq!(
+            header("Sun, 06 Nov 1994 08:00:00 GMT"),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(header("soon"), None);
+        assert_eq!(retry_after(&HeaderMap::new(), now), None);
+    }
+}
@@ -0,0 +1,135 @@
+use crate::artifact::{Artifact, ParseArtifactError};
+use serde_json::Value;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum SbomError {
+    #[error("Invalid SBOM: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Invalid component: {0}")]
+    Component(#[from] ParseArtifactError),
+    #[error("Not a CycloneDX SBOM")]
+    NotCycloneDx,
+    #[error("Line {line}: {source}")]
+    Coordinates {
+        line: usize,
+        source: ParseArtifactError,
+    },
+}
+
+// Maven components of a CycloneDX JSON SBOM, nested components included, in document order.
+// Components of other ecosystems are skipped.
+pub fn cyclonedx_artifacts(json: &str) -> Result<Vec<Artifact>, SbomError> {
+    let document: Value = serde_json::from_str(json)?;
+    if document.get("bomFormat").and_then(Value::as_str) != Some("CycloneDX") {
+        return Err(SbomError::NotCycloneDx);
+    }
+    let mut artifacts = vec![];
+    // metadata.component is the subject of the SBOM, visited first
+    let mut pending = vec![&document];
+    pending.extend(document.get("metadata"));
+    while let Some(node) = pending.pop() {
+        if let Some(purl) = node.get("purl").and_then(Value::as_str)
+            && purl.starts_with("pkg:maven/")
+        {
+            let artifact = Artifact::parse_purl(purl)?;
+            if !artifacts.contains(&artifact) {
+                artifacts.push(artifact);
+            }
+        }
+        for key in ["component", "components"] {
+            match node.get(key) {
+                Some(Value::Array(children)) => pending.extend(children.iter().rev()),
+                Some(child @ Value::Object(_)) => pending.push(child),
+                _ => (),
+            }
+        }
+    }
+    Ok(artifacts)
+}
+
+// A CycloneDX JSON SBOM, or one artifact per line as coordinates or package URL.
+// Blank lines and lines starting with # are skipped.
+pub fn parse_artifact_list(content: &str) -> Result<Vec<Artifact>, SbomError> {
+    if content.trim_start().starts_with('{') {
+        return cyclonedx_artifacts(content);
+    }
+    let mut artifacts = vec![];
+    for (index, line) in content.lines().enumerate() {
+        artifacts.extend(parse_artifact_line(line, index + 1)?);
+    }
+    Ok(artifacts)
+}
+
+// One line of an artifact list, None when it is blank or a comment
+pub fn parse_artifact_line(line: &str, number: usize) -> Result<Option<Artifact>, SbomError> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(None);
+    }
+    let artifact = if line.starts_with("pkg:") {
+        Artifact::parse_purl(line)
+    } else {
+        Artifact::parse(line)
+    };
+    artifact.map(Some).map_err(|source| SbomError::Coordinates {
+        line: number,
+        source,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_cyclonedx_and_lists() {
+        let bom = r#"{
+  "bomFormat": "CycloneDX",
+  "specVersion": "1.5",
+  "metadata": {
+    "component": {"type": "application", "purl": "pkg:maven/org.example/app@1.0?type=war"}
+  },
+  "components": [
+    {"type": "library", "purl": "pkg:maven/org.slf4j/slf4j-api@2.0.9?type=jar",
+     "components": [{"type": "library", "purl": "pkg:maven/org.example/shaded@0.1"}]},
+    {"type": "library", "purl": "pkg:npm/left-pad@1.3.0"},
+    {"type": "library", "name": "no purl"},
+    {"type": "library", "purl": "pkg:maven/org.slf4j/slf4j-api@2.0.9"}
+  ]
+}"#;
+        let artifacts: Vec<String> = parse_artifact_list(bom)
+            .unwrap()
+            .iter()
+            .map(|a| a.to_string())
+            .collect();
+        assert_eq!(
+            artifacts,
+            vec![
+                "org.example:app:war:1.0",
+                "org.slf4j:slf4j-api:2.0.9",
+                "org.example:shaded:0.1",
+            ]
+        );
+        assert!(matches!(
+            parse_artifact_list(r#"{"spdxVersion": "SPDX-2.3"}"#),
+            Err(SbomError::NotCycloneDx)
+        ));
+
+        let list =
+            "# pinned\norg.example:lib:1.0\n\npkg:maven/org.example/other@2.0?classifier=sources\n";
+        let artifacts: Vec<String> = parse_artifact_list(list)
+            .unwrap()
+            .iter()
+            .map(|a| a.to_string())
+            .collect();
+        assert_eq!(
+            artifacts,
+            vec!["org.example:lib:1.0", "org.example:other:jar:sources:2.0"]
+        );
+        assert!(matches!(
+            parse_artifact_list("org.example:lib:1.0\norg.example"),
+            Err(SbomError::Coordinates { line: 2, .. })
+        ));
+    }
+}
@@ -0,0 +1,877 @@
+use crate::Repository;
+use crate::auth::Auth;
+use crate::cache::UpdatePolicy;
+use crate::checksum::ChecksumPolicy;
+use crate::proxy::{ProxyConfig, split_hosts};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::{BufReader, Cursor, Read};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use thiserror::Error;
+use url::Url;
+use xml::EventReader;
+use xml::reader::XmlEvent;
+
+#[derive(Debug, Error)]
+pub enum SettingsError {
+    #[error("Unable to read {path}: {source}")]
+    IO {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("{0} XML error while parsing")]
+    Xml(#[from] xml::reader::Error),
+    #[error("{0} Unexpected XML error while parsing")]
+    Unexpected(String),
+}
+
+// ~/.m2/settings.xml, see https://maven.apache.org/settings.html. ${env.NAME} and
+// ${user.home} in values are replaced while parsing; encrypted passwords are kept as is.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Settings {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub local_repository: Option<PathBuf>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub offline: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub servers: Vec<Server>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub mirrors: Vec<Mirror>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub proxies: Vec<Proxy>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub profiles: Vec<Profile>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub active_profiles: Vec<String>,
+    // searched for plugin prefixes ahead of org.apache.maven.plugins and org.codehaus.mojo
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub plugin_groups: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Server {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    // never written out, only whether one is set
+    #[serde(rename = "hasPassword", serialize_with = "is_set")]
+    pub password: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub private_key: Option<PathBuf>,
+    #[serde(skip)]
+    pub passphrase: Option<String>,
+    // <configuration><httpHeaders><property> name/value pairs
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+}
+
+impl Server {
+    // Basic credentials, unless the password is encrypted with settings-security.xml
+    pub fn auth(&self) -> Option<Auth> {
+        let password = self.password.clone()?;
+        if password.starts_with('{') && password.ends_with('}') {
+            return None;
+        }
+        Some(Auth::Basic {
+            username: self.username.clone()?,
+            password,
+        })
+    }
+}
+
+fn is_set<S: serde::Serializer>(value: &Option<String>, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_bool(value.is_some())
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Mirror {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub url: String,
+    pub mirror_of: String,
+}
+
+impl Mirror {
+    // Maven's mirrorOf syntax: a comma separated list of repository ids, where * is any
+    // repository, external:* any not on localhost or file://, external:http:* any external
+    // over plain http, and !id excludes a repository
+    pub fn matches(&self, repository_id: &str, url: &Url) -> bool {
+        let mut matched = false;
+        for pattern in self.mirror_of.split(',').map(str::trim) {
+            if let Some(excluded) = pattern.strip_prefix('!') {
+                if excluded == repository_id {
+                    return false;
+                }
+                continue;
+            }
+            let external = url.scheme() != "file"
+                && !matches!(url.host_str(), Some("localhost" | "127.0.0.1" | "[::1]"));
+            matched |= match pattern {
+                "*" => true,
+                "external:*" => external,
+                "external:http:*" => external && url.scheme() == "http",
+                id => id == repository_id,
+            };
+        }
+        matched
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Proxy {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub active: bool,
+    pub protocol: String,
+    pub host: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(rename = "hasPassword", serialize_with = "is_set")]
+    pub password: Option<String>,
+    // | separated host patterns, * as a wildcard
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub non_proxy_hosts: Option<String>,
+}
+
+impl Proxy {
+    // protocol is the scheme of the traffic going through the proxy, as with Maven
+    pub fn config(&self) -> Result<ProxyConfig, url::ParseError> {
+        let url = match self.port {
+            Some(port) => Url::parse(&format!("http://{}:{}", self.host, port))?,
+            None => Url::parse(&format!("http://{}", self.host))?,
+        };
+        let mut config = ProxyConfig::new(url).for_scheme(self.protocol.as_str());
+        if let Some(username) = &self.username {
+            config = config.with_auth(Auth::Basic {
+                username: username.clone(),
+                password: self.password.clone().unwrap_or_default(),
+            });
+        }
+        if let Some(hosts) = &self.non_proxy_hosts {
+            config = config.with_non_proxy_hosts(split_hosts(hosts));
+        }
+        Ok(config)
+    }
+}
+
+impl Default for Proxy {
+    fn default() -> Self {
+        Proxy {
+            id: None,
+            active: true,
+            protocol: String::from("http"),
+            host: String::new(),
+            port: None,
+            username: None,
+            password: None,
+            non_proxy_hosts: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Profile {
+    pub id: String,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub active_by_default: bool,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub properties: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub repositories: Vec<SettingsRepository>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub plugin_repositories: Vec<SettingsRepository>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsRepository {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub url: String,
+    pub releases: RepositoryPolicy,
+    pub snapshots: RepositoryPolicy,
+}
+
+impl SettingsRepository {
+    // Repository has a single policy of each kind, the snapshot one is taken when snapshots
+    // are enabled since they change. Policies Maven wouldn't understand either are ignored.
+    pub fn update_policy(&self) -> Option<UpdatePolicy> {
+        self.policy(|p| p.update_policy.as_deref())?.parse().ok()
+    }
+
+    pub fn checksum_policy(&self) -> Option<ChecksumPolicy> {
+        self.policy(|p| p.checksum_policy.as_deref())?.parse().ok()
+    }
+
+    fn policy<'a, F>(&'a self, field: F) -> Option<&'a str>
+    where
+        F: Fn(&'a RepositoryPolicy) -> Option<&'a str>,
+    {
+        if self.snapshots.enabled && field(&self.snapshots).is_some() {
+            field(&self.snapshots)
+        } else {
+            field(&self.releases)
+        }
+    }
+
+    // the Repository it declares, before mirrors and credentials
+    pub fn repository(&self) -> Result<Repository, url::ParseError> {
+        let mut repository = Repository::both(Url::parse(&self.url)?);
+        repository.releases = self.releases.enabled;
+        repository.snapshots = self.snapshots.enabled;
+        if let Some(policy) = self.update_policy() {
+            repository = repository.with_update_policy(policy);
+        }
+        if let Some(policy) = self.checksum_policy() {
+            repository = repository.with_checksum_policy(policy);
+        }
+        Ok(repository)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RepositoryPolicy {
+    pub enabled: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub update_policy: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checksum_policy: Option<String>,
+}
+
+impl Default for RepositoryPolicy {
+    fn default() -> Self {
+        RepositoryPolicy {
+            enabled: true,
+            update_policy: None,
+            checksum_policy: None,
+        }
+    }
+}
+
+impl Settings {
+    // $MAVEN_SETTINGS, or ~/.m2/settings.xml
+    pub fn default_path() -> Option<PathBuf> {
+        if let Some(path) = std::env::var_os("MAVEN_SETTINGS") {
+            return Some(PathBuf::from(path));
+        }
+        Some(std::env::home_dir()?.join(".m2").join("settings.xml"))
+    }
+
+    // None when there is no settings file
+    pub fn load_default() -> Result<Option<Settings>, SettingsError> {
+        match Self::default_path() {
+            Some(path) if path.is_file() => Self::load(&path).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Settings, SettingsError> {
+        let file = std::fs::File::open(path).map_err(|source| SettingsError::IO {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::parse(file)
+    }
+
+    pub fn parse<R: Read>(input: R) -> Result<Settings, SettingsError> {
+        let mut parser = EventReader::new(BufReader::new(input));
+        loop {
+            match parser.next()? {
+                XmlEvent::StartElement { name, .. } if name.local_name == "settings" => {
+                    break parse_settings(&mut parser);
+                }
+                XmlEvent::StartElement { name, .. } => {
+                    break Err(SettingsError::Unexpected(format!(
+                        "Expected <settings> root element, found <{}>",
+                        name.local_name
+                    )));
+                }
+                XmlEvent::EndDocument => {
+                    break Err(SettingsError::Unexpected(String::from(
+                        "Missing <settings> element",
+                    )));
+                }
+                _ => continue,
+            }
+        }
+    }
+
+    pub fn server(&self, id: &str) -> Option<&Server> {
+        self.servers.iter().find(|s| s.id == id)
+    }
+
+    // the first mirror matching, like Maven
+    pub fn mirror_for(&self, repository_id: &str, url: &Url) -> Option<&Mirror> {
+        self.mirrors.iter().find(|m| m.matches(repository_id, url))
+    }
+
+    // The repositories mvn resolves from with these settings: those of the active profiles,
+    // then central unless a profile redefines it, each replaced by its mirror. Repositories
+    // sharing a mirror become one, and credentials come from the <server> with the id of the
+    // repository or of its mirror.
+    pub fn repositories(&self) -> Result<Vec<Repository>, url::ParseError> {
+        let mut declared: Vec<(String, Repository)> = vec![];
+        for repository in self.active().into_iter().flat_map(|p| &p.repositories) {
+            if !declared.iter().any(|(id, _)| *id == repository.id) {
+                declared.push((repository.id.clone(), repository.repository()?));
+            }
+        }
+        if !declared.iter().any(|(id, _)| id == "central") {
+            declared.push((String::from("central"), Repository::maven_central()));
+        }
+        let mut repositories: Vec<(String, Repository)> = vec![];
+        for (id, repository) in declared {
+            // a mirror takes the policies of the first repository it replaces
+            let (id, mut repository) = match self.mirror_for(&id, &repository.url) {
+                Some(mirror) => (
+                    mirror.id.clone(),
+                    Repository {
+                        url: Repository::both(Url::parse(&mirror.url)?).url,
+                        ..repository
+                    },
+                ),
+                None => (id, repository),
+            };
+            if let Some((_, mirror)) = repositories.iter_mut().find(|(i, _)| *i == id) {
+                mirror.releases |= repository.releases;
+                mirror.snapshots |= repository.snapshots;
+                continue;
+            }
+            if let Some(auth) = self.server(&id).and_then(Server::auth) {
+                repository = repository.with_auth(auth);
+            }
+            repositories.push((id, repository));
+        }
+        Ok(repositories
+            .into_iter()
+            .map(|(_, r)| r)
+            .filter(|r| r.releases || r.snapshots)
+            .collect())
+    }
+
+    // Profiles listed in <activeProfiles>, and those active by default when none of the
+    // listed ones exist. Other activation conditions (jdk, os, property, file) are not
+    // evaluated.
+    pub fn active(&self) -> Vec<&Profile> {
+        let listed: Vec<&Profile> = self
+            .profiles
+            .iter()
+            .filter(|p| self.active_profiles.contains(&p.id))
+            .collect();
+        if listed.is_empty() {
+            self.profiles
+                .iter()
+                .filter(|p| p.active_by_default)
+                .collect()
+        } else {
+            listed
+        }
+    }
+}
+
+impl FromStr for Settings {
+    type Err = SettingsError;
+
+    fn from_str(input: &str) -> Result<Settings, SettingsError> {
+        Self::parse(Cursor::new(input))
+    }
+}
+
+type Events<R> = EventReader<BufReader<R>>;
+
+fn parse_settings<R: Read>(parser: &mut Events<R>) -> Result<Settings, SettingsError> {
+    let mut settings = Settings::default();
+    children(parser, |parser, name| {
+        match name {
+            "localRepository" => settings.local_repository = Some(PathBuf::from(text(parser)?)),
+            "offline" => settings.offline = text(parser)? == "true",
+            "servers" => settings.servers = list(parser, "server", parse_server)?,
+            "mirrors" => settings.mirrors = list(parser, "mirror", parse_mirror)?,
+            "proxies" => settings.proxies = list(parser, "proxy", parse_proxy)?,
+            "profiles" => settings.profiles = list(parser, "profile", parse_profile)?,
+            "activeProfiles" => settings.active_profiles = list(parser, "activeProfile", text)?,
+            "pluginGroups" => settings.plugin_groups = list(parser, "pluginGroup", text)?,
+            // interactiveMode and usePluginRegistry
+            _ => skip(parser)?,
+        }
+        Ok(())
+    })?;
+    Ok(settings)
+}
+
+fn parse_server<R: Read>(parser: &mut Events<R>) -> Result<Server, SettingsError> {
+    let mut server = Server::default();
+    children(parser, |parser, name| {
+        match name {
+            "id" => server.id = text(parser)?,
+            "username" => server.username = Some(text(parser)?),
+            "password" => server.password = Some(text(parser)?),
+            "privateKey" => server.private_key = Some(PathBuf::from(text(parser)?)),
+            "passphrase" => server.passphrase = Some(text(parser)?),
+            "configuration" => children(parser, |parser, name| match name {
+                "httpHeaders" => {
+                    for (name, value) in list(parser, "property", parse_property)? {
+                        server.headers.insert(name, value);
+                    }
+                    Ok(())
+                }
+                _ => skip(parser),
+            })?,
+            _ => skip(parser)?,
+        }
+        Ok(())
+    })?;
+    required("server", "id", &server.id)?;
+    Ok(server)
+}
+
+fn parse_property<R: Read>(parser: &mut Events<R>) -> Result<(String, String), SettingsError> {
+    let mut name = String::new();
+    let mut value = String::new();
+    children(parser, |parser, element| {
+        match element {
+            "name" => name = text(parser)?,
+            "value" => value = text(parser)?,
+            _ => skip(parser)?,
+        }
+        Ok(())
+    })?;
+    Ok((name, value))
+}
+
+fn parse_mirror<R: Read>(parser: &mut Events<R>) -> Result<Mirror, SettingsError> {
+    let mut mirror = Mirror::default();
+    children(parser, |parser, name| {
+        match name {
+            "id" => mirror.id = text(parser)?,
+            "name" => mirror.name = Some(text(parser)?),
+            "url" => mirror.url = text(parser)?,
+            "mirrorOf" => mirror.mirror_of = text(parser)?,
+            _ => skip(parser)?,
+        }
+        Ok(())
+    })?;
+    required("mirror", "url", &mirror.url)?;
+    required("mirror", "mirrorOf", &mirror.mirror_of)?;
+    Ok(mirror)
+}
+
+fn parse_proxy<R: Read>(parser: &mut Events<R>) -> Result<Proxy, SettingsError> {
+    let mut proxy = Proxy::default();
+    children(parser, |parser, name| {
+        match name {
+            "id" => proxy.id = Some(text(parser)?),
+            "active" => proxy.active = text(parser)? != "false",
+            "protocol" => proxy.protocol = text(parser)?,
+            "host" => proxy.host = text(parser)?,
+            "port" => {
+                let port = text(parser)?;
+                proxy.port = Some(port.parse().map_err(|_| {
+                    SettingsError::Unexpected(format!("Invalid proxy port {}", port))
+                })?);
+            }
+            "username" => proxy.username = Some(text(parser)?),
+            "password" => proxy.password = Some(text(parser)?),
+            "nonProxyHosts" => proxy.non_proxy_hosts = Some(text(parser)?),
+            _ => skip(parser)?,
+        }
+        Ok(())
+    })?;
+    required("proxy", "host", &proxy.host)?;
+    Ok(proxy)
+}
+
+fn parse_profile<R: Read>(parser: &mut Events<R>) -> Result<Profile, SettingsError> {
+    let mut profile = Profile::default();
+    children(parser, |parser, name| {
+        match name {
+            "id" => profile.id = text(parser)?,
+            "activation" => children(parser, |parser, name| match name {
+                "activeByDefault" => {
+                    profile.active_by_default = text(parser)? == "true";
+                    Ok(())
+                }
+                _ => skip(parser),
+            })?,
+            "properties" => children(parser, |parser, name| {
+                let value = text(parser)?;
+                profile.properties.insert(name.to_string(), value);
+                Ok(())
+            })?,
+            "repositories" => profile.repositories = list(parser, "repository", parse_repository)?,
+            "pluginRepositories" => {
+                profile.plugin_repositories = list(parser, "pluginRepository", parse_repository)?
+            }
+            _ => skip(parser)?,
+        }
+        Ok(())
+    })?;
+    required("profile", "id", &profile.id)?;
+    Ok(profile)
+}
+
+fn parse_repository<R: Read>(parser: &mut Events<R>) -> Result<SettingsRepository, SettingsError> {
+    let mut repository = SettingsRepository::default();
+    children(parser, |parser, name| {
+        match name {
+            "id" => repository.id = text(parser)?,
+            "name" => repository.name = Some(text(parser)?),
+            "url" => repository.url = text(parser)?,
+            "releases" => repository.releases = parse_policy(parser)?,
+            "snapshots" => repository.snapshots = parse_policy(parser)?,
+            _ => skip(parser)?,
+        }
+        Ok(())
+    })?;
+    required("repository", "id", &repository.id)?;
+    required("repository", "url", &repository.url)?;
+    Ok(repository)
+}
+
+fn parse_policy<R: Read>(parser: &mut Events<R>) -> Result<RepositoryPolicy, SettingsError> {
+    let mut policy = RepositoryPolicy::default();
+    children(parser, |parser, name| {
+        match name {
+            "enabled" => policy.enabled = text(parser)? != "false",
+            "updatePolicy" => policy.update_policy = Some(text(parser)?),
+            "checksumPolicy" => policy.checksum_policy = Some(text(parser)?),
+            _ => skip(parser)?,
+        }
+        Ok(())
+    })?;
+    Ok(policy)
+}
+
+fn required(element: &str, field: &str, value: &str) -> Result<(), SettingsError> {
+    if value.is_empty() {
+        Err(SettingsError::Unexpected(format!(
+            "<{}> without <{}>",
+            element, field
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+fn list<R: Read, T>(
+    parser: &mut Events<R>,
+    element: &str,
+    parse: fn(&mut Events<R>) -> Result<T, SettingsError>,
+) -> Result<Vec<T>, SettingsError> {
+    let mut items = Vec::new();
+    children(parser, |parser, name| {
+        if name == element {
+            items.push(parse(parser)?);
+            Ok(())
+        } else {
+            skip(parser)
+        }
+    })?;
+    Ok(items)
+}
+
+fn children<R: Read, F>(parser: &mut Events<R>, mut f: F) -> Result<(), SettingsError>
+where
+    F: FnMut(&mut Events<R>, &str) -> Result<(), SettingsError>,
+{
+    loop {
+        match parser.next()? {
+            XmlEvent::StartElement { name, .. } => f(parser, &name.local_name)?,
+            XmlEvent::EndElement { .. } => break Ok(()),
+            XmlEvent::EndDocument => {
+                break Err(SettingsError::Unexpected(String::from(
+                    "Unexpected end of document",
+                )));
+            }
+            _ => continue,
+        }
+    }
+}
+
+fn text<R: Read>(parser: &mut Events<R>) -> Result<String, SettingsError> {
+    let mut out = String::new();
+    loop {
+        match parser.next()? {
+            XmlEvent::Characters(chars) | XmlEvent::CData(chars) => out.push_str(&chars),
+            XmlEvent::Whitespace(chars) => out.push_str(&chars),
+            XmlEvent::EndElement { .. } => break Ok(interpolate(out.trim())),
+            XmlEvent::StartElement { name, .. } => {
+                break Err(SettingsError::Unexpected(format!(
+                    "Expected text, found <{}>",
+                    name.local_name
+                )));
+            }
+            XmlEvent::EndDocument => {
+                break Err(SettingsError::Unexpected(String::from(
+                    "Unexpected end of document",
+                )));
+            }
+            _ => continue,
+        }
+    }
+}
+
+fn skip<R: Read>(parser: &mut Events<R>) -> Result<(), SettingsError> {
+    let mut depth = 1;
+    while depth > 0 {
+        match parser.next()? {
+            XmlEvent::StartElement { .. } => depth += 1,
+            XmlEvent::EndElement { .. } => depth -= 1,
+            XmlEvent::EndDocument => {
+                return Err(SettingsError::Unexpected(String::from(
+                    "Unexpected end of document",
+                )));
+            }
+            _ => continue,
+        }
+    }
+    Ok(())
+}
+
+// ${env.NAME} and ${user.home}; unknown references are left alone, like Maven does
+fn interpolate(value: &str) -> String {
+    let mut out = String::new();
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        let Some(end) = rest[start..].find('}') else {
+            break;
+        };
+        let expression = &rest[start + 2..start + end];
+        let replacement = match expression.strip_prefix("env.") {
+            Some(name) => std::env::var(name).ok(),
+            None if expression == "user.home" => {
+                std::env::home_dir().map(|home| home.to_string_lossy().into_owned())
+            }
+            None => None,
+        };
+        out.push_str(&rest[..start]);
+        match replacement {
+            Some(replacement) => out.push_str(&replacement),
+            None => out.push_str(&rest[start..start + end + 1]),
+        }
+        rest = &rest[start + end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SETTINGS: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<settings xmlns="http://maven.apache.org/SETTINGS/1.2.0">
+  <localRepository>/var/cache/m2</localRepository>
+  <interactiveMode>false</interactiveMode>
+  <servers>
+    <server>
+      <id>internal</id>
+      <username>deployer</username>
+      <password>${env.MAVEN_SETTINGS_TEST_UNSET}</password>
+      <configuration>
+        <httpHeaders>
+          <property><name>X-Team</name><value>platform</value></property>
+        </httpHeaders>
+      </configuration>
+    </server>
+  </servers>
+  <mirrors>
+    <mirror>
+      <id>corporate</id>
+      <url>https://nexus.example.org/repository/maven-public/</url>
+      <mirrorOf>external:*,!snapshots</mirrorOf>
+    </mirror>
+  </mirrors>
+  <proxies>
+    <proxy>
+      <id>office</id>
+      <host>proxy.example.org</host>
+      <port>3128</port>
+      <nonProxyHosts>localhost|*.example.org</nonProxyHosts>
+    </proxy>
+  </proxies>
+  <profiles>
+    <profile>
+      <id>internal</id>
+      <properties><release.channel>stable</release.channel></properties>
+      <repositories>
+        <repository>
+          <id>snapshots</id>
+          <url>https://nexus.example.org/repository/snapshots/</url>
+          <releases><enabled>false</enabled></releases>
+          <snapshots><updatePolicy>always</updatePolicy><checksumPolicy>warn</checksumPolicy></snapshots>
+        </repository>
+      </repositories>
+    </profile>
+    <profile>
+      <id>fallback</id>
+      <activation><activeByDefault>true</activeByDefault></activation>
+    </profile>
+  </profiles>
+  <activeProfiles>
+    <activeProfile>internal</activeProfile>
+  </activeProfiles>
+  <pluginGroups>
+    <pluginGroup>org.example.plugins</pluginGroup>
+  </pluginGroups>
+</settings>"#;
+
+    #[test]
+    fn parse_settings() {
+        let settings = Settings::from_str(SETTINGS).unwrap();
+        assert_eq!(
+            settings.local_repository,
+            Some(PathBuf::from("/var/cache/m2"))
+        );
+        let server = settings.server("internal").unwrap();
+        assert_eq!(server.username.as_deref(), Some("deployer"));
+        // unknown variables are kept
+        assert_eq!(
+            server.password.as_deref(),
+            Some("${env.MAVEN_SETTINGS_TEST_UNSET}")
+        );
+        assert_eq!(
+            server.headers.get("X-Team").map(String::as_str),
+            Some("platform")
+        );
+
+        let proxy = &settings.proxies[0];
+        assert!(proxy.active);
+        assert_eq!(proxy.protocol, "http");
+        assert_eq!(proxy.port, Some(3128));
+        assert_eq!(
+            proxy.config().unwrap(),
+            ProxyConfig::new(Url::parse("http://proxy.example.org:3128").unwrap())
+                .for_scheme("http")
+                .with_non_proxy_hosts(["localhost", "*.example.org"])
+        );
+
+        let active = settings.active();
+        assert_eq!(active.len(), 1);
+        let repository = &active[0].repositories[0];
+        assert!(!repository.releases.enabled);
+        assert!(repository.snapshots.enabled);
+        assert_eq!(
+            repository.snapshots.update_policy.as_deref(),
+            Some("always")
+        );
+        assert_eq!(
+            active[0]
+                .properties
+                .get("release.channel")
+                .map(String::as_str),
+            Some("stable")
+        );
+
+        assert_eq!(
+            settings.plugin_groups,
+            vec![String::from("org.example.plugins")]
+        );
+
+        let json = serde_json::to_value(&settings).unwrap();
+        assert_eq!(json["servers"][0]["hasPassword"], true);
+        assert!(!json.to_string().contains("MAVEN_SETTINGS_TEST_UNSET"));
+    }
+
+    #[test]
+    fn mirror_of() {
+        let settings = Settings::from_str(SETTINGS).unwrap();
+        let central = Url::parse("https://repo1.maven.org/maven2/").unwrap();
+        let snapshots = Url::parse("https://nexus.example.org/repository/snapshots/").unwrap();
+        let local = Url::parse("http://localhost:8081/repository/").unwrap();
+        assert!(settings.mirror_for("central", &central).is_some());
+        assert!(settings.mirror_for("snapshots", &snapshots).is_none());
+        assert!(settings.mirror_for("dev", &local).is_none());
+
+        let mirror = |mirror_of: &str| Mirror {
+            mirror_of: mirror_of.to_string(),
+            ..Mirror::default()
+        };
+        assert!(mirror("*").matches("anything", &local));
+        assert!(mirror("central,google").matches("google", &central));
+        assert!(!mirror("external:http:*").matches("central", &central));
+        assert!(!mirror("*,!central").matches("central", &central));
+    }
+
+    #[test]
+    fn profile_repositories() {
+        let mut settings = Settings::from_str(SETTINGS).unwrap();
+        let repositories = settings.repositories().unwrap();
+        assert_eq!(repositories[0].update_policy, UpdatePolicy::Always);
+        assert_eq!(repositories[1].update_policy, UpdatePolicy::Daily);
+        assert_eq!(repositories[0].checksum_policy, ChecksumPolicy::Warn);
+        assert_eq!(repositories[1].checksum_policy, ChecksumPolicy::Fail);
+        let summary = |repositories: &[Repository]| {
+            repositories
+                .iter()
+                .map(|r| (r.url.to_string(), r.releases, r.snapshots, r.auth.is_some()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            summary(&repositories),
+            vec![
+                (
+                    String::from("https://nexus.example.org/repository/snapshots"),
+                    false,
+                    true,
+                    false
+                ),
+                (
+                    String::from("https://nexus.example.org/repository/maven-public"),
+                    true,
+                    false,
+                    false
+                ),
+            ]
+        );
+
+        // credentials of the mirror, encrypted passwords left out
+        settings.servers = vec![
+            Server {
+                id: String::from("corporate"),
+                username: Some(String::from("reader")),
+                password: Some(String::from("secret")),
+                ..Server::default()
+            },
+            Server {
+                id: String::from("snapshots"),
+                username: Some(String::from("reader")),
+                password: Some(String::from("{COQLCE6DU6GtcS5P=}")),
+                ..Server::default()
+            },
+        ];
+        settings.active_profiles.clear();
+        let repositories = settings.repositories().unwrap();
+        assert_eq!(
+            summary(&repositories),
+            vec![(
+                String::from("https://nexus.example.org/repository/maven-public"),
+                true,
+                false,
+                true
+            )]
+        );
+    }
+
+    #[test]
+    fn interpolate_values() {
+        let home = std::env::home_dir().unwrap();
+        assert_eq!(
+            interpolate("${user.home}/.m2/repository"),
+            format!("{}/.m2/repository", home.display())
+        );
+        assert_eq!(interpolate("${project.basedir}"), "${project.basedir}");
+        assert_eq!(interpolate("plain ${unclosed"), "plain ${unclosed");
+    }
+}
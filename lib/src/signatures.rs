@@ -0,0 +1,438 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum SignatureError {
+    #[error("Failed to run {executable}: {source}")]
+    Spawn {
+        executable: String,
+        source: std::io::Error,
+    },
+    #[error("No signature published at {0}")]
+    Missing(url::Url),
+    #[error("Bad signature for {0}")]
+    Bad(PathBuf),
+    #[error("{file} is signed by {key}, which is not in the keyring")]
+    UnknownKey { file: PathBuf, key: String },
+    #[error("{file} is signed by {key}, which is neither trusted by gpg nor pinned")]
+    UntrustedKey { file: PathBuf, key: String },
+    #[error("{file} is signed by {key}, which is revoked")]
+    RevokedKey { file: PathBuf, key: String },
+    #[error("{file} is signed by {key}, but {group_id} is pinned to {pinned}")]
+    UnpinnedKey {
+        file: PathBuf,
+        group_id: String,
+        key: String,
+        pinned: String,
+    },
+    #[error("Unable to write key pins to {path}: {source}")]
+    Pins {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Verifying {file} failed with {status}: {stderr}")]
+    Failed {
+        file: PathBuf,
+        status: std::process::ExitStatus,
+        stderr: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifiedSignature {
+    pub fingerprint: String,
+    pub user_id: Option<String>,
+    // gpg's trust in the key is marginal or better
+    pub trusted: bool,
+}
+
+// Verifies detached .asc signatures with gpg. Keys come from the keyring file when one is
+// set, the default gpg keyring otherwise; with a keyserver, unknown keys are fetched from it.
+#[derive(Clone, Debug)]
+pub struct Verifier {
+    executable: String,
+    keyring: Option<PathBuf>,
+    keyserver: Option<String>,
+    pins: Option<Arc<KeyPins>>,
+}
+
+impl Default for Verifier {
+    fn default() -> Self {
+        Verifier {
+            executable: String::from("gpg"),
+            keyring: None,
+            keyserver: None,
+            pins: None,
+        }
+    }
+}
+
+impl Verifier {
+    pub fn new() -> Verifier {
+        Verifier::default()
+    }
+
+    pub fn from_env() -> Verifier {
+        Verifier {
+            executable: std::env::var("MAVEN_GPG_EXECUTABLE").unwrap_or(String::from("gpg")),
+            keyring: std::env::var_os("MAVEN_GPG_KEYRING").map(PathBuf::from),
+            keyserver: std::env::var("MAVEN_GPG_KEYSERVER").ok(),
+            pins: None,
+        }
+    }
+
+    pub fn with_executable(mut self, executable: String) -> Verifier {
+        self.executable = executable;
+        self
+    }
+
+    // a keyring in gpg's binary format, as written by `gpg --export`
+    pub fn with_keyring(mut self, keyring: PathBuf) -> Verifier {
+        self.keyring = Some(keyring);
+        self
+    }
+
+    pub fn with_keyserver(mut self, keyserver: String) -> Verifier {
+        self.keyserver = Some(keyserver);
+        self
+    }
+
+    // signatures must also be made by the keys pinned for the groupId
+    pub fn with_key_pins(mut self, pins: KeyPins) -> Verifier {
+        self.pins = Some(Arc::new(pins));
+        self
+    }
+
+    pub fn key_pins(&self) -> Option<&KeyPins> {
+        self.pins.as_deref()
+    }
+
+    // Whether a verified signature is good enough for artifacts of the groupId. With a
+    // keyserver any key someone uploaded ends up in the keyring, so one gpg has no trust in
+    // must have been pinned for the group before; it is never pinned on first use.
+    pub fn accept(
+        &self,
+        group_id: &str,
+        file: &Path,
+        verified: &VerifiedSignature,
+    ) -> Result<(), SignatureError> {
+        if self.keyserver.is_some()
+            && !verified.trusted
+            && !self
+                .pins
+                .as_ref()
+                .is_some_and(|pins| pins.is_pinned(group_id, &verified.fingerprint))
+        {
+            return Err(SignatureError::UntrustedKey {
+                file: file.to_path_buf(),
+                key: verified.fingerprint.clone(),
+            });
+        }
+        match &self.pins {
+            Some(pins) => pins.check(group_id, file, &verified.fingerprint),
+            None => Ok(()),
+        }
+    }
+
+    pub async fn verify(
+        &self,
+        file: &Path,
+        signature: &Path,
+    ) -> Result<VerifiedSignature, SignatureError> {
+        let mut command = tokio::process::Command::new(&self.executable);
+        command.args(["--batch", "--no-tty", "--status-fd", "1"]);
+        if let Some(keyring) = &self.keyring {
+            // gpg looks up relative keyrings in its home directory
+            let keyring = std::path::absolute(keyring).unwrap_or_else(|_| keyring.clone());
+            command
+                .arg("--no-default-keyring")
+                .arg("--keyring")
+                .arg(keyring);
+        }
+        if let Some(keyserver) = &self.keyserver {
+            command
+                .arg("--keyserver")
+                .arg(keyserver)
+                .arg("--auto-key-retrieve");
+        }
+        command
+            .arg("--verify")
+            .arg(signature)
+            .arg(file)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+
+        let output = command
+            .output()
+            .await
+            .map_err(|source| SignatureError::Spawn {
+                executable: self.executable.clone(),
+                source,
+            })?;
+        parse_status(&String::from_utf8_lossy(&output.stdout)).ok_or_else(|| {
+            let status = String::from_utf8_lossy(&output.stdout);
+            let keyword = |name: &str| {
+                status.lines().find_map(|line| {
+                    let mut words = line.strip_prefix("[GNUPG:] ")?.split_whitespace();
+                    (words.next()? == name).then(|| words.next().unwrap_or_default().to_string())
+                })
+            };
+            let file = file.to_path_buf();
+            if keyword("BADSIG").is_some() {
+                SignatureError::Bad(file)
+            } else if let Some(key) = keyword("REVKEYSIG") {
+                SignatureError::RevokedKey { file, key }
+            } else if let Some(key) = keyword("NO_PUBKEY") {
+                SignatureError::UnknownKey { file, key }
+            } else {
+                SignatureError::Failed {
+                    file,
+                    status: output.status,
+                    stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+                }
+            }
+        })
+    }
+}
+
+// Trust on first use: the first key seen signing artifacts of a groupId is pinned, and
+// signatures by any other key fail until it is approved. Stored as JSON, groupId to the
+// fingerprints of its keys.
+#[derive(Debug)]
+pub struct KeyPins {
+    path: PathBuf,
+    groups: Mutex<BTreeMap<String, BTreeSet<String>>>,
+}
+
+impl KeyPins {
+    // $MAVEN_KEY_PINS, or key-pins.json next to the stored credentials
+    pub fn default_path() -> Option<PathBuf> {
+        if let Some(path) = std::env::var_os("MAVEN_KEY_PINS") {
+            return Some(PathBuf::from(path));
+        }
+        let config = match std::env::var_os("XDG_CONFIG_HOME") {
+            Some(dir) => PathBuf::from(dir),
+            None => std::env::home_dir()?.join(".config"),
+        };
+        Some(config.join("maven-artifact").join("key-pins.json"))
+    }
+
+    pub fn load(path: &Path) -> std::io::Result<KeyPins> {
+        let groups = match std::fs::read(path) {
+            Ok(content) => serde_json::from_slice(&content)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(e),
+        };
+        Ok(KeyPins {
+            path: path.to_path_buf(),
+            groups: Mutex::new(groups),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn groups(&self) -> BTreeMap<String, BTreeSet<String>> {
+        self.groups.lock().unwrap().clone()
+    }
+
+    // false when the key was approved already
+    pub fn approve(&self, group_id: &str, fingerprint: &str) -> bool {
+        self.groups
+            .lock()
+            .unwrap()
+            .entry(group_id.to_string())
+            .or_default()
+            .insert(normalize(fingerprint))
+    }
+
+    pub fn is_pinned(&self, group_id: &str, fingerprint: &str) -> bool {
+        self.groups
+            .lock()
+            .unwrap()
+            .get(group_id)
+            .is_some_and(|pinned| pinned.contains(&normalize(fingerprint)))
+    }
+
+    pub fn save(&self) -> std::io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let content = serde_json::to_vec_pretty(&*self.groups.lock().unwrap())?;
+        std::fs::write(&self.path, content)
+    }
+
+    pub fn check(
+        &self,
+        group_id: &str,
+        file: &Path,
+        fingerprint: &str,
+    ) -> Result<(), SignatureError> {
+        let fingerprint = normalize(fingerprint);
+        let mut groups = self.groups.lock().unwrap();
+        match groups.get(group_id) {
+            Some(pinned) if pinned.contains(&fingerprint) => Ok(()),
+            Some(pinned) => Err(SignatureError::UnpinnedKey {
+                file: file.to_path_buf(),
+                group_id: group_id.to_string(),
+                key: fingerprint,
+                pinned: pinned.iter().cloned().collect::<Vec<_>>().join(", "),
+            }),
+            None => {
+                groups.insert(group_id.to_string(), BTreeSet::from([fingerprint]));
+                drop(groups);
+                self.save().map_err(|source| SignatureError::Pins {
+                    path: self.path.clone(),
+                    source,
+                })
+            }
+        }
+    }
+}
+
+fn normalize(fingerprint: &str) -> String {
+    fingerprint
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_uppercase()
+}
+
+// A signature is only accepted when gpg reports it both good and valid, see
+// doc/DETAILS in the GnuPG sources for the status lines.
+fn parse_status(status: &str) -> Option<VerifiedSignature> {
+    let mut fingerprint = None;
+    let mut user_id = None;
+    let mut good = false;
+    let mut trusted = false;
+    for line in status.lines() {
+        let Some(line) = line.strip_prefix("[GNUPG:] ") else {
+            continue;
+        };
+        let (keyword, rest) = line.split_once(' ').unwrap_or((line, ""));
+        match keyword {
+            "GOODSIG" => {
+                good = true;
+                user_id = rest
+                    .split_once(' ')
+                    .map(|(_, uid)| uid.to_string())
+                    .filter(|uid| !uid.is_empty());
+            }
+            "VALIDSIG" => fingerprint = rest.split_whitespace().next().map(String::from),
+            "TRUST_MARGINAL" | "TRUST_FULLY" | "TRUST_ULTIMATE" => trusted = true,
+            "BADSIG" | "ERRSIG" | "REVKEYSIG" => return None,
+            _ => (),
+        }
+    }
+    Some(VerifiedSignature {
+        fingerprint: fingerprint.filter(|_| good)?,
+        user_id,
+        trusted,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_gpg_status() {
+        let good = "[GNUPG:] NEWSIG
+[GNUPG:] KEY_CONSIDERED 6BDACA2C0493CCA133B372D09C4F7E9D98B1CC53 0
+[GNUPG:] SIG_ID 2v4Ya0n0lvT/Lh3fLyUc1zzz0y8 2024-01-02 1704164645
+[GNUPG:] GOODSIG 9C4F7E9D98B1CC53 Jane Doe <jane@example.org>
+[GNUPG:] VALIDSIG 6BDACA2C0493CCA133B372D09C4F7E9D98B1CC53 2024-01-02 1704164645 0 4 0 1 10 00 6BDACA2C0493CCA133B372D09C4F7E9D98B1CC53
+[GNUPG:] TRUST_UNDEFINED 0 pgp";
+        assert_eq!(
+            parse_status(good),
+            Some(VerifiedSignature {
+                fingerprint: String::from("6BDACA2C0493CCA133B372D09C4F7E9D98B1CC53"),
+                user_id: Some(String::from("Jane Doe <jane@example.org>")),
+                trusted: false,
+            })
+        );
+        let trusted = good.replace("TRUST_UNDEFINED 0 pgp", "TRUST_FULLY 0 pgp");
+        assert!(parse_status(&trusted).unwrap().trusted);
+        let bad = "[GNUPG:] NEWSIG
+[GNUPG:] BADSIG 9C4F7E9D98B1CC53 Jane Doe <jane@example.org>";
+        assert_eq!(parse_status(bad), None);
+        let unknown = "[GNUPG:] NEWSIG
+[GNUPG:] ERRSIG 9C4F7E9D98B1CC53 1 10 00 1704164645 9 6BDACA2C0493CCA133B372D09C4F7E9D98B1CC53
+[GNUPG:] NO_PUBKEY 9C4F7E9D98B1CC53";
+        assert_eq!(parse_status(unknown), None);
+    }
+
+    #[test]
+    fn keyserver_keys_need_trust_or_a_pin() {
+        let dir = std::env::temp_dir().join(format!("key-pins-trust-{}", std::process::id()));
+        let jar = Path::new("lib-1.0.jar");
+        let fetched = VerifiedSignature {
+            fingerprint: String::from("6BDACA2C0493CCA133B372D09C4F7E9D98B1CC53"),
+            user_id: None,
+            trusted: false,
+        };
+        Verifier::new()
+            .accept("org.example", jar, &fetched)
+            .unwrap();
+
+        let keyserver = Verifier::new()
+            .with_keyserver(String::from("hkps://keys.example.org"))
+            .with_key_pins(KeyPins::load(&dir.join("pins.json")).unwrap());
+        assert!(matches!(
+            keyserver.accept("org.example", jar, &fetched),
+            Err(SignatureError::UntrustedKey { .. })
+        ));
+        // refused keys are not pinned on the way
+        assert!(keyserver.key_pins().unwrap().groups().is_empty());
+        let trusted = VerifiedSignature {
+            trusted: true,
+            ..fetched.clone()
+        };
+        keyserver.accept("org.example", jar, &trusted).unwrap();
+        keyserver.accept("org.example", jar, &fetched).unwrap();
+        assert!(matches!(
+            keyserver.accept("org.other", jar, &fetched),
+            Err(SignatureError::UntrustedKey { .. })
+        ));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn pin_on_first_use() {
+        let dir = std::env::temp_dir().join(format!("key-pins-{}", std::process::id()));
+        let path = dir.join("pins.json");
+        let pins = KeyPins::load(&path).unwrap();
+        let jar = Path::new("lib-1.0.jar");
+        let first = "6BDA CA2C 0493 CCA1 33B3  72D0 9C4F 7E9D 98B1 CC53";
+        let other = "0123456789ABCDEF0123456789ABCDEF01234567";
+
+        pins.check("org.example", jar, first).unwrap();
+        pins.check("org.example", jar, &first.to_lowercase())
+            .unwrap();
+        match pins.check("org.example", jar, other) {
+            Err(SignatureError::UnpinnedKey { key, pinned, .. }) => {
+                assert_eq!(key, other);
+                assert_eq!(pinned, "6BDACA2C0493CCA133B372D09C4F7E9D98B1CC53");
+            }
+            result => panic!("{:?}", result),
+        }
+        pins.check("org.other", jar, other).unwrap();
+
+        // pinned keys were saved, approvals only once saved
+        let reloaded = KeyPins::load(&path).unwrap();
+        assert_eq!(reloaded.groups().len(), 2);
+        assert!(reloaded.approve("org.example", other));
+        reloaded.save().unwrap();
+        KeyPins::load(&path)
+            .unwrap()
+            .check("org.example", jar, other)
+            .unwrap();
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,112 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum SignError {
+    #[error("Failed to run {executable}: {source}")]
+    Spawn {
+        executable: String,
+        source: std::io::Error,
+    },
+    #[error("IO operation failed, {0}")]
+    IO(#[from] std::io::Error),
+    #[error("Signing {file} failed with {status}: {stderr}")]
+    Failed {
+        file: PathBuf,
+        status: std::process::ExitStatus,
+        stderr: String,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub struct Signer {
+    executable: String,
+    key: Option<String>,
+    passphrase: Option<String>,
+}
+
+impl Default for Signer {
+    fn default() -> Self {
+        Signer {
+            executable: String::from("gpg"),
+            key: None,
+            passphrase: None,
+        }
+    }
+}
+
+impl Signer {
+    pub fn new() -> Signer {
+        Signer::default()
+    }
+
+    pub fn from_env() -> Signer {
+        Signer {
+            executable: std::env::var("MAVEN_GPG_EXECUTABLE").unwrap_or(String::from("gpg")),
+            key: std::env::var("MAVEN_GPG_KEY").ok(),
+            passphrase: std::env::var("MAVEN_GPG_PASSPHRASE").ok(),
+        }
+    }
+
+    pub fn with_executable(mut self, executable: String) -> Signer {
+        self.executable = executable;
+        self
+    }
+
+    pub fn with_key(mut self, key: String) -> Signer {
+        self.key = Some(key);
+        self
+    }
+
+    pub fn with_passphrase(mut self, passphrase: String) -> Signer {
+        self.passphrase = Some(passphrase);
+        self
+    }
+
+    pub fn signature_path(file: &Path) -> PathBuf {
+        let mut name = file.as_os_str().to_owned();
+        name.push(".asc");
+        PathBuf::from(name)
+    }
+
+    pub fn sign(&self, file: &Path) -> Result<PathBuf, SignError> {
+        let output = Self::signature_path(file);
+        let mut command = Command::new(&self.executable);
+        command.args(["--batch", "--yes", "--armor", "--detach-sign"]);
+        if let Some(key) = &self.key {
+            command.arg("--local-user").arg(key);
+        }
+        if self.passphrase.is_some() {
+            command.args(["--pinentry-mode", "loopback", "--passphrase-fd", "0"]);
+        }
+        command
+            .arg("--output")
+            .arg(&output)
+            .arg(file)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped());
+
+        let mut child = command.spawn().map_err(|source| SignError::Spawn {
+            executable: self.executable.clone(),
+            source,
+        })?;
+        if let Some(mut stdin) = child.stdin.take()
+            && let Some(passphrase) = &self.passphrase
+        {
+            writeln!(stdin, "{}", passphrase)?;
+        }
+        let result = child.wait_with_output()?;
+        if result.status.success() {
+            Ok(output)
+        } else {
+            Err(SignError::Failed {
+                file: file.to_path_buf(),
+                status: result.status,
+                stderr: String::from_utf8_lossy(&result.stderr).trim().to_string(),
+            })
+        }
+    }
+}
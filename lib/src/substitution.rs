@@ -0,0 +1,287 @@
+use crate::pom::Dependency;
+use crate::{ArtifactId, GroupId, Version};
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum SubstitutionError {
+    #[error("Unable to read {path}: {source}")]
+    IO {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Invalid rule on line {line}: {message}")]
+    Parse { line: usize, message: String },
+}
+
+// groupId:artifactId where * matches any run of characters, like org.apache.logging.*:*
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+    pub group_id: String,
+    pub artifact_id: String,
+}
+
+impl Pattern {
+    pub fn parse(input: &str) -> Option<Pattern> {
+        let (group_id, artifact_id) = input.split_once(':')?;
+        if group_id.is_empty() || artifact_id.is_empty() || artifact_id.contains(':') {
+            return None;
+        }
+        Some(Pattern {
+            group_id: group_id.to_string(),
+            artifact_id: artifact_id.to_string(),
+        })
+    }
+
+    pub fn matches(&self, group_id: &str, artifact_id: &str) -> bool {
+        glob(&self.group_id, group_id) && glob(&self.artifact_id, artifact_id)
+    }
+}
+
+impl Display for Pattern {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.group_id, self.artifact_id)
+    }
+}
+
+pub(crate) fn glob(pattern: &str, value: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == value,
+        Some((prefix, rest)) => {
+            let Some(value) = value.strip_prefix(prefix) else {
+                return false;
+            };
+            (0..=value.len())
+                .filter(|&i| value.is_char_boundary(i))
+                .any(|i| glob(rest, &value[i..]))
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Substitution {
+    // another groupId:artifactId, with the declared version unless one is given
+    Replace {
+        from: Pattern,
+        group_id: GroupId,
+        artifact_id: ArtifactId,
+        version: Option<Version>,
+    },
+    Force {
+        pattern: Pattern,
+        version: Version,
+    },
+}
+
+// Rules rewriting dependencies while a graph is built, for artifacts that are patched or
+// rehosted under other coordinates. They apply after dependencyManagement, so they win over
+// it, and the first matching rule of each kind applies: a dependency can be replaced and
+// then have the version of the replacement forced.
+#[derive(Debug, Clone, Default)]
+pub struct Substitutions {
+    rules: Vec<Substitution>,
+}
+
+impl Substitutions {
+    pub fn new() -> Substitutions {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    pub fn rules(&self) -> &[Substitution] {
+        &self.rules
+    }
+
+    pub fn with(mut self, rule: Substitution) -> Substitutions {
+        self.rules.push(rule);
+        self
+    }
+
+    // One rule per line, # starts a comment:
+    //   replace com.example:lib com.example.patched:lib[:version]
+    //   force org.apache.logging.log4j:* 2.17.1
+    pub fn parse(content: &str) -> Result<Substitutions, SubstitutionError> {
+        let mut rules = vec![];
+        for (index, line) in content.lines().enumerate() {
+            let invalid = |message: &str| SubstitutionError::Parse {
+                line: index + 1,
+                message: message.to_string(),
+            };
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let rule = match words.as_slice() {
+                [] => continue,
+                ["replace", from, to] => {
+                    let from = Pattern::parse(from)
+                        .ok_or_else(|| invalid("expected groupId:artifactId"))?;
+                    let mut parts = to.split(':');
+                    let (Some(group_id), Some(artifact_id), version, None) =
+                        (parts.next(), parts.next(), parts.next(), parts.next())
+                    else {
+                        return Err(invalid("expected groupId:artifactId[:version]"));
+                    };
+                    if [group_id, artifact_id]
+                        .iter()
+                        .any(|p| p.is_empty() || p.contains('*'))
+                    {
+                        return Err(invalid("replacements can't be patterns"));
+                    }
+                    Substitution::Replace {
+                        from,
+                        group_id: GroupId::from(group_id),
+                        artifact_id: ArtifactId::from(artifact_id),
+                        version: version.filter(|v| !v.is_empty()).map(Version::from),
+                    }
+                }
+                ["force", pattern, version] => Substitution::Force {
+                    pattern: Pattern::parse(pattern)
+                        .ok_or_else(|| invalid("expected groupId:artifactId"))?,
+                    version: Version::from(*version),
+                },
+                _ => {
+                    return Err(invalid("expected replace FROM TO or force PATTERN VERSION"));
+                }
+            };
+            rules.push(rule);
+        }
+        Ok(Substitutions { rules })
+    }
+
+    pub fn load(path: &Path) -> Result<Substitutions, SubstitutionError> {
+        let content = std::fs::read_to_string(path).map_err(|source| SubstitutionError::IO {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::parse(&content)
+    }
+
+    // true when the dependency was changed
+    pub fn apply(&self, dependency: &mut Dependency) -> bool {
+        let before = (
+            dependency.group_id.clone(),
+            dependency.artifact_id.clone(),
+            dependency.version.clone(),
+        );
+        let replacement = self.rules.iter().find_map(|rule| match rule {
+            Substitution::Replace {
+                from,
+                group_id,
+                artifact_id,
+                version,
+            } if from.matches(&dependency.group_id, &dependency.artifact_id) => {
+                Some((group_id, artifact_id, version))
+            }
+            _ => None,
+        });
+        if let Some((group_id, artifact_id, version)) = replacement {
+            dependency.group_id = group_id.clone();
+            dependency.artifact_id = artifact_id.clone();
+            if version.is_some() {
+                dependency.version = version.clone();
+            }
+        }
+        let forced = self.rules.iter().find_map(|rule| match rule {
+            Substitution::Force { pattern, version }
+                if pattern.matches(&dependency.group_id, &dependency.artifact_id) =>
+            {
+                Some(version)
+            }
+            _ => None,
+        });
+        if let Some(version) = forced {
+            dependency.version = Some(version.clone());
+        }
+        before
+            != (
+                dependency.group_id.clone(),
+                dependency.artifact_id.clone(),
+                dependency.version.clone(),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dependency(coordinates: &str) -> Dependency {
+        let parts: Vec<&str> = coordinates.split(':').collect();
+        Dependency::new(
+            GroupId::from(parts[0]),
+            ArtifactId::from(parts[1]),
+            Some(Version::from(parts[2])),
+        )
+    }
+
+    fn coordinates(dependency: &Dependency) -> String {
+        format!(
+            "{}:{}:{}",
+            dependency.group_id,
+            dependency.artifact_id,
+            dependency.version.as_deref().unwrap_or_default()
+        )
+    }
+
+    #[test]
+    fn parse_and_apply() {
+        let substitutions = Substitutions::parse(
+            "# patched in house
+replace com.example:lib com.example.patched:lib
+replace javax.activation:* jakarta.activation:jakarta.activation-api:1.2.2
+
+force org.apache.logging.log4j:* 2.17.1 # CVE-2021-44228
+force com.example.patched:lib 1.0-patched-3",
+        )
+        .unwrap();
+        assert_eq!(substitutions.rules().len(), 4);
+
+        let mut log4j = dependency("org.apache.logging.log4j:log4j-core:2.14.0");
+        assert!(substitutions.apply(&mut log4j));
+        assert_eq!(
+            coordinates(&log4j),
+            "org.apache.logging.log4j:log4j-core:2.17.1"
+        );
+
+        // replaced, then the replacement's version forced
+        let mut lib = dependency("com.example:lib:1.0");
+        assert!(substitutions.apply(&mut lib));
+        assert_eq!(coordinates(&lib), "com.example.patched:lib:1.0-patched-3");
+
+        let mut activation = dependency("javax.activation:activation:1.1");
+        assert!(substitutions.apply(&mut activation));
+        assert_eq!(
+            coordinates(&activation),
+            "jakarta.activation:jakarta.activation-api:1.2.2"
+        );
+
+        let mut other = dependency("com.example:other:1.0");
+        assert!(!substitutions.apply(&mut other));
+        let mut forced = dependency("org.apache.logging.log4j:log4j-api:2.17.1");
+        assert!(!substitutions.apply(&mut forced));
+    }
+
+    #[test]
+    fn invalid_rules() {
+        let error = |content| match Substitutions::parse(content) {
+            Err(SubstitutionError::Parse { line, .. }) => line,
+            other => panic!("{:?}", other),
+        };
+        assert_eq!(error("force a:b 1.0\nforce a 1.0"), 2);
+        assert_eq!(error("replace a:b c:*"), 1);
+        assert_eq!(error("exclude a:b"), 1);
+    }
+
+    #[test]
+    fn glob_patterns() {
+        assert!(glob("*", "anything"));
+        assert!(glob("org.apache.*", "org.apache.commons"));
+        assert!(!glob("org.apache.*", "org.apachex"));
+        assert!(glob("*-api", "jakarta.servlet-api"));
+        assert!(glob("a*c*e", "abcde"));
+        assert!(!glob("a*c*e", "abcd"));
+    }
+}
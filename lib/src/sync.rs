@@ -0,0 +1,165 @@
+use crate::artifact::{Artifact, PartialArtifact};
+use crate::deploy::{DeployError, StagingRepository};
+use crate::resolver::{FileCheck, ResolveError, Resolver};
+use crate::{GroupId, Version};
+use serde::{Serialize, Serializer};
+use std::fmt::Display;
+use std::path::PathBuf;
+use thiserror::Error;
+use tracing::debug;
+
+#[derive(Debug, Error)]
+pub enum SyncError {
+    #[error(transparent)]
+    Resolve(#[from] Box<ResolveError>),
+    #[error(transparent)]
+    Deploy(#[from] DeployError),
+    #[error("Unable to write {path}: {source}")]
+    IO {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SyncReason {
+    Missing,
+    Differs,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncEntry {
+    #[serde(rename = "coordinates", serialize_with = "display")]
+    pub artifact: Artifact,
+    // relative to the target root
+    pub path: PathBuf,
+    pub reason: SyncReason,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MissingVersions {
+    #[serde(rename = "coordinates", serialize_with = "display")]
+    pub artifact: PartialArtifact,
+    pub versions: Vec<Version>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncPlan {
+    pub transfers: Vec<SyncEntry>,
+    // versions the target's artifact metadata does not list yet
+    pub versions: Vec<MissingVersions>,
+    pub unchanged: usize,
+}
+
+impl SyncPlan {
+    pub fn is_empty(&self) -> bool {
+        self.transfers.is_empty() && self.versions.is_empty()
+    }
+}
+
+fn display<T: Display, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
+
+// Compares a directory repository with the source repositories for every artifact in the
+// groups: a published file is transferred when the target lacks it or its checksum differs
+// from the one the source publishes. Artifacts and files are found through directory
+// listings. Snapshots are left out, their builds are not meant to be mirrored.
+pub async fn plan(
+    source: &Resolver,
+    target: &StagingRepository,
+    groups: &[GroupId],
+) -> Result<SyncPlan, SyncError> {
+    let mut plan = SyncPlan::default();
+    for group_id in groups {
+        for artifact_id in source.list_artifacts(group_id).await.map_err(Box::new)? {
+            let artifact = PartialArtifact::new(group_id.clone(), artifact_id);
+            let published = source
+                .metadata(artifact.clone())
+                .await
+                .map_err(Box::new)?
+                .versioning
+                .versions
+                .unwrap_or_default();
+            let known = target
+                .metadata(&artifact)?
+                .and_then(|m| m.versioning.versions)
+                .unwrap_or_default();
+            let mut missing = vec![];
+            for version in published.into_iter().filter(|v| !v.is_snapshot()) {
+                for file in source
+                    .list_files(&artifact, &version)
+                    .await
+                    .map_err(Box::new)?
+                {
+                    let coordinates = Artifact::from(file.clone());
+                    let path = PathBuf::from(coordinates.path()).join(coordinates.file_name());
+                    let local = target.root.join(&path);
+                    let reason = if !local.is_file() {
+                        Some(SyncReason::Missing)
+                    } else {
+                        match source.check_file(&file, &local).await.map_err(Box::new)? {
+                            FileCheck::Mismatch { .. } => Some(SyncReason::Differs),
+                            _ => None,
+                        }
+                    };
+                    match reason {
+                        Some(reason) => plan.transfers.push(SyncEntry {
+                            artifact: coordinates,
+                            path,
+                            reason,
+                        }),
+                        None => plan.unchanged += 1,
+                    }
+                }
+                if !known.contains(&version) {
+                    missing.push(version);
+                }
+            }
+            if !missing.is_empty() {
+                plan.versions.push(MissingVersions {
+                    artifact,
+                    versions: missing,
+                });
+            }
+        }
+    }
+    Ok(plan)
+}
+
+// Downloads every transfer of the plan, verifying checksums, into the target and adds the
+// missing versions to its metadata. Returns every file written, relative to the target root.
+pub async fn apply(
+    source: &Resolver,
+    target: &StagingRepository,
+    plan: &SyncPlan,
+    timestamp: &str,
+) -> Result<Vec<PathBuf>, SyncError> {
+    let dir = std::env::temp_dir().join(format!("maven-artifact-sync-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).map_err(|source| SyncError::IO {
+        path: dir.clone(),
+        source,
+    })?;
+    let mut written = vec![];
+    for entry in &plan.transfers {
+        debug!(artifact = %entry.artifact, reason = ?entry.reason, "transferring");
+        let downloaded = source
+            .download(entry.artifact.clone(), &dir)
+            .await
+            .map_err(Box::new)?;
+        let file = downloaded
+            .file
+            .unwrap_or_else(|| dir.join(entry.artifact.file_name()));
+        written.extend(target.store(&entry.path, &file)?);
+        let _ = std::fs::remove_file(file);
+    }
+    let _ = std::fs::remove_dir_all(&dir);
+    for missing in &plan.versions {
+        written.extend(target.add_versions(&missing.artifact, &missing.versions, timestamp)?);
+    }
+    Ok(written)
+}
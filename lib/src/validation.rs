@@ -0,0 +1,182 @@
+use crate::Version;
+use crate::pom::{Dependency, Project};
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum Violation {
+    MissingElement { element: &'static str },
+    IncompleteLicense { index: usize, missing: &'static str },
+    IncompleteDeveloper { index: usize },
+    IncompleteScm { missing: &'static str },
+    SnapshotVersion { version: Version },
+    SnapshotDependency { dependency: String },
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Violation::MissingElement { element } => write!(f, "missing <{}>", element),
+            Violation::IncompleteLicense { index, missing } => {
+                write!(f, "license #{} is missing <{}>", index + 1, missing)
+            }
+            Violation::IncompleteDeveloper { index } => {
+                write!(f, "developer #{} has neither <name> nor <email>", index + 1)
+            }
+            Violation::IncompleteScm { missing } => write!(f, "<scm> is missing <{}>", missing),
+            Violation::SnapshotVersion { version } => {
+                write!(f, "project version {} is a SNAPSHOT", version)
+            }
+            Violation::SnapshotDependency { dependency } => {
+                write!(f, "dependency {} is a SNAPSHOT", dependency)
+            }
+        }
+    }
+}
+
+fn blank(value: &Option<String>) -> bool {
+    value.as_deref().is_none_or(|v| v.trim().is_empty())
+}
+
+pub fn validate_for_central(project: &Project) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    if project.effective_group_id().is_none_or(|g| g.is_empty()) {
+        violations.push(Violation::MissingElement { element: "groupId" });
+    }
+    match project.effective_version() {
+        None => violations.push(Violation::MissingElement { element: "version" }),
+        Some(v) => {
+            let version = Version::from(project.interpolate(v));
+            if version.is_snapshot() {
+                violations.push(Violation::SnapshotVersion { version })
+            }
+        }
+    }
+    if blank(&project.name) {
+        violations.push(Violation::MissingElement { element: "name" });
+    }
+    if blank(&project.description) {
+        violations.push(Violation::MissingElement {
+            element: "description",
+        });
+    }
+    if blank(&project.url) {
+        violations.push(Violation::MissingElement { element: "url" });
+    }
+
+    if project.licenses.is_empty() {
+        violations.push(Violation::MissingElement {
+            element: "licenses",
+        });
+    }
+    for (index, license) in project.licenses.iter().enumerate() {
+        if blank(&license.name) {
+            violations.push(Violation::IncompleteLicense {
+                index,
+                missing: "name",
+            });
+        }
+        if blank(&license.url) {
+            violations.push(Violation::IncompleteLicense {
+                index,
+                missing: "url",
+            });
+        }
+    }
+
+    if project.developers.is_empty() {
+        violations.push(Violation::MissingElement {
+            element: "developers",
+        });
+    }
+    for (index, developer) in project.developers.iter().enumerate() {
+        if blank(&developer.name) && blank(&developer.email) {
+            violations.push(Violation::IncompleteDeveloper { index });
+        }
+    }
+
+    match &project.scm {
+        None => violations.push(Violation::MissingElement { element: "scm" }),
+        Some(scm) => {
+            if blank(&scm.url) {
+                violations.push(Violation::IncompleteScm { missing: "url" });
+            }
+            if blank(&scm.connection) {
+                violations.push(Violation::IncompleteScm {
+                    missing: "connection",
+                });
+            }
+        }
+    }
+
+    let managed = project
+        .dependency_management
+        .iter()
+        .flat_map(|m| m.dependencies.iter());
+    for dependency in project.dependencies.iter().chain(managed) {
+        if let Some(violation) = snapshot_dependency(project, dependency) {
+            violations.push(violation);
+        }
+    }
+    violations
+}
+
+fn snapshot_dependency(project: &Project, dependency: &Dependency) -> Option<Violation> {
+    let version = Version::from(project.interpolate(dependency.version.as_deref()?));
+    if version.is_snapshot() {
+        Some(Violation::SnapshotDependency {
+            dependency: format!(
+                "{}:{}:{}",
+                dependency.group_id, dependency.artifact_id, version
+            ),
+        })
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pom::PomParser;
+
+    #[test]
+    fn valid_fixture() {
+        let input = std::fs::read_to_string(
+            "test-files/pom/com/example/example-lib/1.0.0/example-lib-1.0.0.pom",
+        )
+        .unwrap();
+        let project = PomParser::new().from_str(&input).unwrap();
+        assert_eq!(validate_for_central(&project), vec![]);
+    }
+
+    #[test]
+    fn minimal_project() {
+        let pom = r##"<project><groupId>com.example</groupId><artifactId>example</artifactId><version>1.0.0-SNAPSHOT</version><properties><dep.version>2.0-SNAPSHOT</dep.version></properties><dependencies><dependency><groupId>g</groupId><artifactId>a</artifactId><version>${dep.version}</version></dependency></dependencies></project>"##;
+        let project = PomParser::new().from_str(pom).unwrap();
+        assert_eq!(
+            validate_for_central(&project),
+            vec![
+                Violation::SnapshotVersion {
+                    version: Version::from("1.0.0-SNAPSHOT")
+                },
+                Violation::MissingElement { element: "name" },
+                Violation::MissingElement {
+                    element: "description"
+                },
+                Violation::MissingElement { element: "url" },
+                Violation::MissingElement {
+                    element: "licenses"
+                },
+                Violation::MissingElement {
+                    element: "developers"
+                },
+                Violation::MissingElement { element: "scm" },
+                Violation::SnapshotDependency {
+                    dependency: String::from("g:a:2.0-SNAPSHOT")
+                },
+            ]
+        )
+    }
+}
@@ -0,0 +1,964 @@
+use crate::Version;
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+use thiserror::Error;
+
+// Maven's ComparableVersion: "1.0" == "1.0.0" == "1-ga", "1.0-alpha-1" < "1.0-rc" < "1.0" < "1.0-sp"
+#[derive(Debug, Clone)]
+pub struct ComparableVersion {
+    items: Vec<Item>,
+}
+
+#[derive(Debug, Clone)]
+enum Item {
+    Int(String),
+    Str(String),
+    List(Vec<Item>),
+}
+
+static QUALIFIERS: [&str; 7] = ["alpha", "beta", "milestone", "rc", "snapshot", "", "sp"];
+
+fn comparable_qualifier(qualifier: &str) -> String {
+    match QUALIFIERS.iter().position(|q| *q == qualifier) {
+        Some(i) => i.to_string(),
+        None => format!("{}-{}", QUALIFIERS.len(), qualifier),
+    }
+}
+
+fn release_index() -> String {
+    comparable_qualifier("")
+}
+
+impl Item {
+    fn int(digits: &str) -> Item {
+        let trimmed = digits.trim_start_matches('0');
+        Item::Int(trimmed.to_string())
+    }
+
+    fn string(value: &str, followed_by_digit: bool) -> Item {
+        let value = match value {
+            "a" if followed_by_digit => "alpha",
+            "b" if followed_by_digit => "beta",
+            "m" if followed_by_digit => "milestone",
+            "ga" | "final" | "release" => "",
+            "cr" => "rc",
+            other => other,
+        };
+        Item::Str(value.to_string())
+    }
+
+    fn parse(is_digit: bool, value: &str) -> Item {
+        if is_digit {
+            Item::int(value)
+        } else {
+            Item::string(value, false)
+        }
+    }
+
+    fn is_null(&self) -> bool {
+        match self {
+            Item::Int(digits) => digits.is_empty(),
+            Item::Str(value) => value.is_empty(),
+            Item::List(items) => items.is_empty(),
+        }
+    }
+
+    fn compare(&self, other: Option<&Item>) -> Ordering {
+        match (self, other) {
+            (Item::Int(digits), None) => {
+                if digits.is_empty() {
+                    Ordering::Equal
+                } else {
+                    Ordering::Greater
+                }
+            }
+            (Item::Int(a), Some(Item::Int(b))) => a.len().cmp(&b.len()).then_with(|| a.cmp(b)),
+            (Item::Int(_), Some(_)) => Ordering::Greater,
+            (Item::Str(value), None) => comparable_qualifier(value).cmp(&release_index()),
+            (Item::Str(_), Some(Item::Int(_))) => Ordering::Less,
+            (Item::Str(a), Some(Item::Str(b))) => {
+                comparable_qualifier(a).cmp(&comparable_qualifier(b))
+            }
+            (Item::Str(_), Some(Item::List(_))) => Ordering::Less,
+            (Item::List(items), None) => match items.first() {
+                Some(first) => first.compare(None),
+                None => Ordering::Equal,
+            },
+            (Item::List(_), Some(Item::Int(_))) => Ordering::Less,
+            (Item::List(_), Some(Item::Str(_))) => Ordering::Greater,
+            (Item::List(a), Some(Item::List(b))) => compare_lists(a, b),
+        }
+    }
+}
+
+fn compare_lists(left: &[Item], right: &[Item]) -> Ordering {
+    let mut left = left.iter();
+    let mut right = right.iter();
+    loop {
+        let result = match (left.next(), right.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(r)) => r.compare(None).reverse(),
+            (Some(l), r) => l.compare(r),
+        };
+        if result != Ordering::Equal {
+            return result;
+        }
+    }
+}
+
+// trailing nulls are dropped, looking past non-null sub lists like Maven does
+fn normalize(items: &mut Vec<Item>) {
+    let mut i = items.len();
+    while i > 0 {
+        i -= 1;
+        if items[i].is_null() {
+            items.remove(i);
+        } else if !matches!(items[i], Item::List(_)) {
+            break;
+        }
+    }
+}
+
+impl ComparableVersion {
+    pub fn parse(version: &str) -> ComparableVersion {
+        let version = version.to_lowercase();
+        // each '-' or digit/letter transition opens a nested list
+        let mut stack: Vec<Vec<Item>> = vec![vec![]];
+        let mut is_digit = false;
+        let mut start = 0;
+        let chars: Vec<(usize, char)> = version.char_indices().collect();
+        for &(i, c) in &chars {
+            if c == '.' {
+                let list = stack.last_mut().unwrap();
+                if i == start {
+                    list.push(Item::int("0"));
+                } else {
+                    list.push(Item::parse(is_digit, &version[start..i]));
+                }
+                start = i + 1;
+            } else if c == '-' {
+                let list = stack.last_mut().unwrap();
+                if i == start {
+                    list.push(Item::int("0"));
+                } else {
+                    list.push(Item::parse(is_digit, &version[start..i]));
+                }
+                start = i + 1;
+                stack.push(vec![]);
+            } else if c.is_ascii_digit() {
+                if !is_digit && i > start {
+                    stack
+                        .last_mut()
+                        .unwrap()
+                        .push(Item::string(&version[start..i], true));
+                    start = i;
+                    stack.push(vec![]);
+                }
+                is_digit = true;
+            } else {
+                if is_digit && i > start {
+                    stack
+                        .last_mut()
+                        .unwrap()
+                        .push(Item::parse(true, &version[start..i]));
+                    start = i;
+                    stack.push(vec![]);
+                }
+                is_digit = false;
+            }
+        }
+        if version.len() > start {
+            stack
+                .last_mut()
+                .unwrap()
+                .push(Item::parse(is_digit, &version[start..]));
+        }
+        while stack.len() > 1 {
+            let mut list = stack.pop().unwrap();
+            normalize(&mut list);
+            stack.last_mut().unwrap().push(Item::List(list));
+        }
+        let mut items = stack.pop().unwrap();
+        normalize(&mut items);
+        ComparableVersion { items }
+    }
+}
+
+impl PartialEq for ComparableVersion {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for ComparableVersion {}
+
+impl PartialOrd for ComparableVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ComparableVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        compare_lists(&self.items, &other.items)
+    }
+}
+
+impl Version {
+    pub fn comparable(&self) -> ComparableVersion {
+        ComparableVersion::parse(self.as_ref())
+    }
+
+    pub fn compare(&self, other: &Version) -> Ordering {
+        self.comparable().cmp(&other.comparable())
+    }
+
+    pub fn is_range(&self) -> bool {
+        self.starts_with('[') || self.starts_with('(')
+    }
+
+    // Maven's DefaultArtifactVersion: <major>.<minor>.<incremental>-<qualifier|buildNumber>.
+    // Versions that don't fit, like 1.0.0.1 or 1.01, keep everything as the qualifier.
+    pub fn parse_components(&self) -> VersionComponents {
+        let (numbers, rest) = match self.split_once('-') {
+            Some((numbers, rest)) => (numbers, Some(rest)),
+            None => (self.as_ref(), None),
+        };
+        let parsed = numbers
+            .split('.')
+            .map(parse_component)
+            .collect::<Option<Vec<u64>>>()
+            .filter(|parts| parts.len() <= 3);
+        let Some(parts) = parsed else {
+            return VersionComponents {
+                qualifier: Some(self.to_string()),
+                ..VersionComponents::default()
+            };
+        };
+        let build_number = rest.and_then(parse_component);
+        VersionComponents {
+            major: parts.first().copied(),
+            minor: parts.get(1).copied(),
+            incremental: parts.get(2).copied(),
+            qualifier: rest.filter(|_| build_number.is_none()).map(str::to_string),
+            build_number,
+        }
+    }
+
+    // the leading number, also of versions parse_components can't split like 1.0.0.1 or 2.0.Final
+    pub fn major(&self) -> Option<u64> {
+        let end = self
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(self.len());
+        self[..end].parse().ok()
+    }
+
+    // Qualifiers Maven orders before the release itself: alpha, beta, milestone, rc and
+    // snapshot, including their a1/b1/m1/cr spellings.
+    pub fn is_pre_release(&self) -> bool {
+        fn pre_release(items: &[Item]) -> bool {
+            items.iter().any(|item| match item {
+                Item::Int(_) => false,
+                Item::Str(value) => comparable_qualifier(value) < release_index(),
+                Item::List(items) => pre_release(items),
+            })
+        }
+        pre_release(&self.comparable().items)
+    }
+
+    pub fn base_version(&self) -> Version {
+        match self.strip_suffix("-SNAPSHOT") {
+            Some(base) => Version::from(base),
+            None => self.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionComponents {
+    pub major: Option<u64>,
+    pub minor: Option<u64>,
+    pub incremental: Option<u64>,
+    pub qualifier: Option<String>,
+    pub build_number: Option<u64>,
+}
+
+// digits only, and no leading zeros, like Maven's integer tokens
+fn parse_component(value: &str) -> Option<u64> {
+    if value.is_empty()
+        || !value.bytes().all(|b| b.is_ascii_digit())
+        || value.len() > 1 && value.starts_with('0')
+    {
+        return None;
+    }
+    value.parse().ok()
+}
+
+// The newest version of each major line, newest line first. A line is represented by its
+// newest release, or by its newest pre-release while it has none. Snapshots and versions
+// without a leading number are left out.
+pub fn latest_per_major<'a, I>(versions: I) -> Vec<(u64, &'a Version)>
+where
+    I: IntoIterator<Item = &'a Version>,
+{
+    let mut lines: Vec<(u64, &'a Version)> = vec![];
+    for version in versions.into_iter().filter(|v| !v.is_snapshot()) {
+        let Some(major) = version.major() else {
+            continue;
+        };
+        // (is a release, version) orders releases after pre-releases
+        let newer = |current: &Version| {
+            (!version.is_pre_release(), version.comparable())
+                > (!current.is_pre_release(), current.comparable())
+        };
+        match lines.iter_mut().find(|(m, _)| *m == major) {
+            Some(line) if newer(line.1) => line.1 = version,
+            Some(_) => {}
+            None => lines.push((major, version)),
+        }
+    }
+    lines.sort_by_key(|(major, _)| std::cmp::Reverse(*major));
+    lines
+}
+
+#[derive(Debug, Clone, Error)]
+#[error("Invalid version range {range}: {reason}")]
+pub struct ParseRangeError {
+    range: String,
+    reason: &'static str,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Restriction {
+    pub lower: Option<Version>,
+    pub lower_inclusive: bool,
+    pub upper: Option<Version>,
+    pub upper_inclusive: bool,
+}
+
+impl Restriction {
+    pub fn contains(&self, version: &Version) -> bool {
+        let lower = match &self.lower {
+            Some(lower) => match version.compare(lower) {
+                Ordering::Greater => true,
+                Ordering::Equal => self.lower_inclusive,
+                Ordering::Less => false,
+            },
+            None => true,
+        };
+        let upper = match &self.upper {
+            Some(upper) => match version.compare(upper) {
+                Ordering::Less => true,
+                Ordering::Equal => self.upper_inclusive,
+                Ordering::Greater => false,
+            },
+            None => true,
+        };
+        lower && upper
+    }
+
+    fn unbounded() -> Restriction {
+        Restriction {
+            lower: None,
+            lower_inclusive: false,
+            upper: None,
+            upper_inclusive: false,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        match (&self.lower, &self.upper) {
+            (Some(lower), Some(upper)) => match lower.compare(upper) {
+                Ordering::Greater => true,
+                Ordering::Equal => !(self.lower_inclusive && self.upper_inclusive),
+                Ordering::Less => false,
+            },
+            _ => false,
+        }
+    }
+
+    fn intersect(self, other: Restriction) -> Restriction {
+        let (lower, lower_inclusive) = match (&self.lower, &other.lower) {
+            (Some(a), Some(b)) => match a.compare(b) {
+                Ordering::Greater => (self.lower, self.lower_inclusive),
+                Ordering::Less => (other.lower, other.lower_inclusive),
+                Ordering::Equal => (self.lower, self.lower_inclusive && other.lower_inclusive),
+            },
+            (Some(_), None) => (self.lower, self.lower_inclusive),
+            (None, _) => (other.lower, other.lower_inclusive),
+        };
+        let (upper, upper_inclusive) = match (&self.upper, &other.upper) {
+            (Some(a), Some(b)) => match a.compare(b) {
+                Ordering::Less => (self.upper, self.upper_inclusive),
+                Ordering::Greater => (other.upper, other.upper_inclusive),
+                Ordering::Equal => (self.upper, self.upper_inclusive && other.upper_inclusive),
+            },
+            (Some(_), None) => (self.upper, self.upper_inclusive),
+            (None, _) => (other.upper, other.upper_inclusive),
+        };
+        Restriction {
+            lower,
+            lower_inclusive,
+            upper,
+            upper_inclusive,
+        }
+    }
+}
+
+impl Display for Restriction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.lower_inclusive
+            && self.upper_inclusive
+            && self.lower == self.upper
+            && let Some(v) = &self.lower
+        {
+            return write!(f, "[{}]", v);
+        }
+        let open = if self.lower_inclusive { '[' } else { '(' };
+        let close = if self.upper_inclusive { ']' } else { ')' };
+        let lower = self
+            .lower
+            .as_ref()
+            .map(|v| v.to_string())
+            .unwrap_or_default();
+        let upper = self
+            .upper
+            .as_ref()
+            .map(|v| v.to_string())
+            .unwrap_or_default();
+        write!(f, "{}{},{}{}", open, lower, upper, close)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VersionRange {
+    pub recommended: Option<Version>,
+    pub restrictions: Vec<Restriction>,
+}
+
+impl VersionRange {
+    pub fn parse(spec: &str) -> Result<VersionRange, ParseRangeError> {
+        let error = |reason| ParseRangeError {
+            range: spec.to_string(),
+            reason,
+        };
+        let mut rest = spec.trim();
+        if !(rest.starts_with('[') || rest.starts_with('(')) {
+            if rest.is_empty() || rest.contains([',', ']', ')']) {
+                return Err(error("not a version or range"));
+            }
+            return Ok(VersionRange {
+                recommended: Some(Version::from(rest)),
+                restrictions: vec![],
+            });
+        }
+        let mut restrictions: Vec<Restriction> = vec![];
+        while !rest.is_empty() {
+            let lower_inclusive = match rest.chars().next() {
+                Some('[') => true,
+                Some('(') => false,
+                _ => return Err(error("expected [ or (")),
+            };
+            let Some(end) = rest.find([']', ')']) else {
+                return Err(error("unbounded restriction"));
+            };
+            let upper_inclusive = rest[end..].starts_with(']');
+            let inner = rest[1..end].trim();
+            let restriction = match inner.split_once(',') {
+                None => {
+                    if !lower_inclusive || !upper_inclusive || inner.is_empty() {
+                        return Err(error("single version must be surrounded by []"));
+                    }
+                    let version = Some(Version::from(inner));
+                    Restriction {
+                        lower: version.clone(),
+                        lower_inclusive,
+                        upper: version,
+                        upper_inclusive,
+                    }
+                }
+                Some((lower, upper)) => {
+                    let (lower, upper) = (lower.trim(), upper.trim());
+                    if upper.contains(',') {
+                        return Err(error("too many bounds"));
+                    }
+                    let lower = (!lower.is_empty()).then(|| Version::from(lower));
+                    let upper = (!upper.is_empty()).then(|| Version::from(upper));
+                    if let (Some(l), Some(u)) = (&lower, &upper)
+                        && u.compare(l) == Ordering::Less
+                    {
+                        return Err(error("upper bound is below lower bound"));
+                    }
+                    if lower.is_none() && lower_inclusive || upper.is_none() && upper_inclusive {
+                        return Err(error("an open bound cannot be inclusive"));
+                    }
+                    Restriction {
+                        lower,
+                        lower_inclusive,
+                        upper,
+                        upper_inclusive,
+                    }
+                }
+            };
+            restrictions.push(restriction);
+            rest = rest[end + 1..].trim_start();
+            if let Some(next) = rest.strip_prefix(',') {
+                rest = next.trim_start();
+                if rest.is_empty() {
+                    return Err(error("trailing comma"));
+                }
+            } else if !rest.is_empty() {
+                return Err(error("restrictions must be separated by ,"));
+            }
+        }
+        Ok(VersionRange {
+            recommended: None,
+            restrictions,
+        })
+    }
+
+    pub fn contains(&self, version: &Version) -> bool {
+        if self.restrictions.is_empty() {
+            return self
+                .recommended
+                .as_ref()
+                .is_some_and(|r| r.compare(version) == Ordering::Equal);
+        }
+        self.restrictions.iter().any(|r| r.contains(version))
+    }
+
+    pub fn select<'a, I>(&self, candidates: I) -> Option<&'a Version>
+    where
+        I: IntoIterator<Item = &'a Version>,
+    {
+        candidates
+            .into_iter()
+            .filter(|v| self.contains(v))
+            .max_by(|a, b| a.compare(b))
+    }
+}
+
+// Cargo/npm style queries, translated to a single Maven range:
+//   ^5.2 -> [5.2,6-alpha)   ^0.2.3 -> [0.2.3,0.3-alpha)   ~5.2.1 -> [5.2.1,5.3-alpha)
+//   5.* -> [5,6-alpha)      >=5, <6 -> [5,6-alpha)        =1.0 -> [1.0]
+// A bare 5.2 means ^5.2, as in Cargo. Maven orders 6.0-RC1 before 6, so exclusive upper
+// bounds become the lowest pre-release, 6-alpha, for <6 to leave out 6.0-RC1 like Cargo does.
+impl VersionRange {
+    pub fn from_query(query: &str) -> Result<VersionRange, ParseRangeError> {
+        let error = |reason| ParseRangeError {
+            range: query.to_string(),
+            reason,
+        };
+        let mut restriction = Restriction::unbounded();
+        for term in query.split(',') {
+            let term = query_term(term.trim()).ok_or_else(|| error("invalid query term"))?;
+            restriction = restriction.intersect(term);
+        }
+        if restriction.is_empty() {
+            return Err(error("query matches no version"));
+        }
+        Ok(VersionRange {
+            recommended: None,
+            restrictions: vec![restriction],
+        })
+    }
+}
+
+fn query_term(term: &str) -> Option<Restriction> {
+    let bound = |version: &str| (!version.trim().is_empty()).then(|| Version::from(version.trim()));
+    if term == "*" {
+        return Some(Restriction::unbounded());
+    }
+    let restriction = if let Some(v) = term.strip_prefix(">=") {
+        Restriction {
+            lower: Some(bound(v)?),
+            lower_inclusive: true,
+            ..Restriction::unbounded()
+        }
+    } else if let Some(v) = term.strip_prefix("<=") {
+        Restriction {
+            upper: Some(bound(v)?),
+            upper_inclusive: true,
+            ..Restriction::unbounded()
+        }
+    } else if let Some(v) = term.strip_prefix('>') {
+        Restriction {
+            lower: Some(bound(v)?),
+            ..Restriction::unbounded()
+        }
+    } else if let Some(v) = term.strip_prefix('<') {
+        let upper = match numeric_parts(v.trim()) {
+            Some(_) => before_prereleases(v.trim()),
+            None => bound(v)?,
+        };
+        Restriction {
+            upper: Some(upper),
+            ..Restriction::unbounded()
+        }
+    } else if let Some(v) = term.strip_prefix('=') {
+        let version = bound(v)?;
+        Restriction {
+            lower: Some(version.clone()),
+            lower_inclusive: true,
+            upper: Some(version),
+            upper_inclusive: true,
+        }
+    } else if let Some(v) = term.strip_prefix('~') {
+        let parts = numeric_parts(v.trim())?;
+        half_open(v.trim(), &parts, parts.len().min(2) - 1)
+    } else if let Some(v) = term.strip_suffix(".*") {
+        let parts = numeric_parts(v)?;
+        half_open(v, &parts, parts.len() - 1)
+    } else {
+        let v = term.strip_prefix('^').unwrap_or(term).trim();
+        let parts = numeric_parts(v)?;
+        let significant = parts
+            .iter()
+            .position(|p| *p != 0)
+            .unwrap_or(parts.len() - 1);
+        half_open(v, &parts, significant)
+    };
+    Some(restriction)
+}
+
+fn numeric_parts(version: &str) -> Option<Vec<u64>> {
+    let parts = version
+        .split('.')
+        .map(|p| p.parse::<u64>().ok())
+        .collect::<Option<Vec<u64>>>()?;
+    (1..=3).contains(&parts.len()).then_some(parts)
+}
+
+// [version, parts[..=index] with the last one bumped, before its pre-releases)
+fn half_open(version: &str, parts: &[u64], index: usize) -> Restriction {
+    let mut upper = parts[..=index].to_vec();
+    upper[index] += 1;
+    let upper: Vec<String> = upper.iter().map(|p| p.to_string()).collect();
+    Restriction {
+        lower: Some(Version::from(version)),
+        lower_inclusive: true,
+        upper: Some(before_prereleases(&upper.join("."))),
+        upper_inclusive: false,
+    }
+}
+
+// alpha is the lowest qualifier, so every pre-release of version sorts at or above this
+fn before_prereleases(version: &str) -> Version {
+    Version::from(format!("{}-alpha", version))
+}
+
+impl Display for VersionRange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some(recommended) = &self.recommended {
+            return write!(f, "{}", recommended);
+        }
+        let parts: Vec<String> = self.restrictions.iter().map(|r| r.to_string()).collect();
+        f.write_str(&parts.join(","))
+    }
+}
+
+// Gradle dynamic versions, resolved against maven-metadata.xml:
+//   latest.release      highest version that is not a -SNAPSHOT
+//   latest.integration  highest version, snapshots included
+//   1.+                 highest version starting with "1." (1.+ does not match 10.0, 1+ does)
+//   +                   highest version of any kind
+// Maven repositories carry no Gradle status, so snapshots are the only "integration" builds.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DynamicVersion {
+    LatestRelease,
+    LatestIntegration,
+    Prefix(String),
+}
+
+impl DynamicVersion {
+    pub fn parse(spec: &str) -> Option<DynamicVersion> {
+        match spec {
+            "latest.release" => Some(DynamicVersion::LatestRelease),
+            "latest.integration" => Some(DynamicVersion::LatestIntegration),
+            _ => spec
+                .strip_suffix('+')
+                .filter(|prefix| !prefix.contains(['[', '(', ',']))
+                .map(|prefix| DynamicVersion::Prefix(prefix.to_string())),
+        }
+    }
+
+    pub fn matches(&self, version: &Version) -> bool {
+        match self {
+            DynamicVersion::LatestRelease => !version.is_snapshot(),
+            DynamicVersion::LatestIntegration => true,
+            DynamicVersion::Prefix(prefix) => version.starts_with(prefix.as_str()),
+        }
+    }
+
+    pub fn select<'a, I>(&self, candidates: I) -> Option<&'a Version>
+    where
+        I: IntoIterator<Item = &'a Version>,
+    {
+        candidates
+            .into_iter()
+            .filter(|v| self.matches(v))
+            .max_by(|a, b| a.compare(b))
+    }
+}
+
+impl Display for DynamicVersion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DynamicVersion::LatestRelease => f.write_str("latest.release"),
+            DynamicVersion::LatestIntegration => f.write_str("latest.integration"),
+            DynamicVersion::Prefix(prefix) => write!(f, "{}+", prefix),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_order(versions: &[&str]) {
+        for pair in versions.windows(2) {
+            let (low, high) = (Version::from(pair[0]), Version::from(pair[1]));
+            assert_eq!(low.compare(&high), Ordering::Less, "{} < {}", low, high);
+            assert_eq!(high.compare(&low), Ordering::Greater, "{} > {}", high, low);
+        }
+    }
+
+    #[test]
+    fn maven_ordering() {
+        assert_order(&[
+            "1-alpha-1",
+            "1-alpha-2",
+            "1-beta",
+            "1-milestone-1",
+            "1-rc-1",
+            "1-SNAPSHOT",
+            "1",
+            "1-sp",
+            "1-abc",
+            "1-1",
+            "1.1",
+            "1.2",
+            "1.10",
+            "2.0",
+        ]);
+        assert_order(&["1.0-alpha1", "1.0-beta1", "1.0-M1", "1.0-RC1", "1.0"]);
+        for (a, b) in [
+            ("1", "1.0.0"),
+            ("1.0", "1-ga"),
+            ("1.0", "1.FINAL"),
+            ("1-cr1", "1-rc1"),
+            ("1.0-a1", "1.0-alpha-1"),
+            ("01.2", "1.2"),
+            ("1.0-1", "1-1"),
+        ] {
+            assert_eq!(
+                Version::from(a).compare(&Version::from(b)),
+                Ordering::Equal,
+                "{} == {}",
+                a,
+                b
+            );
+        }
+    }
+
+    #[test]
+    fn latest_of_each_major() {
+        let versions: Vec<Version> = [
+            "1.0",
+            "1.2.1",
+            "1.10",
+            "2.0-RC1",
+            "2.0",
+            "2.1-SNAPSHOT",
+            "3.0-M1",
+            "3.0-M2",
+            "RELEASE",
+            "2.0.0.1",
+        ]
+        .into_iter()
+        .map(Version::from)
+        .collect();
+        let latest: Vec<(u64, &str)> = latest_per_major(&versions)
+            .into_iter()
+            .map(|(major, v)| (major, v.as_ref()))
+            .collect();
+        assert_eq!(latest, vec![(3, "3.0-M2"), (2, "2.0.0.1"), (1, "1.10")]);
+        assert_eq!(Version::from("2023.1.4").major(), Some(2023));
+        assert_eq!(Version::from("v1").major(), None);
+    }
+
+    #[test]
+    fn version_components() {
+        let components = |v: &str| Version::from(v).parse_components();
+        let full = components("1.2.3-beta-1");
+        assert_eq!(
+            (full.major, full.minor, full.incremental),
+            (Some(1), Some(2), Some(3))
+        );
+        assert_eq!(full.qualifier.as_deref(), Some("beta-1"));
+        assert_eq!(full.build_number, None);
+
+        let build = components("2.0-7");
+        assert_eq!((build.major, build.minor), (Some(2), Some(0)));
+        assert_eq!(build.incremental, None);
+        assert_eq!(build.build_number, Some(7));
+        assert_eq!(build.qualifier, None);
+
+        assert_eq!(components("5").major, Some(5));
+        for odd in ["1.0.0.1", "1.01", "RELEASE", "1.0.Final"] {
+            assert_eq!(
+                components(odd),
+                VersionComponents {
+                    qualifier: Some(odd.to_string()),
+                    ..VersionComponents::default()
+                },
+                "{}",
+                odd
+            );
+        }
+
+        for pre in [
+            "1.0-SNAPSHOT",
+            "1.0-alpha-1",
+            "1.0-M2",
+            "2.0.0-RC1",
+            "1.0.cr1",
+            "3-b1",
+        ] {
+            assert!(Version::from(pre).is_pre_release(), "{}", pre);
+        }
+        for release in ["1.0", "1.0.Final", "1.0-sp1", "1.0-jre", "1.0-1", "1.0-GA"] {
+            assert!(!Version::from(release).is_pre_release(), "{}", release);
+        }
+
+        assert_eq!(
+            Version::from("1.0-SNAPSHOT").base_version(),
+            Version::from("1.0")
+        );
+        assert_eq!(
+            Version::from("1.0-rc1").base_version(),
+            Version::from("1.0-rc1")
+        );
+    }
+
+    #[test]
+    fn parse_ranges() {
+        let range = VersionRange::parse("[1.0,2.0)").unwrap();
+        assert!(range.contains(&Version::from("1.0")));
+        assert!(range.contains(&Version::from("1.9.9")));
+        assert!(!range.contains(&Version::from("2.0")));
+        assert!(range.contains(&Version::from("2.0-alpha-1")));
+        assert_eq!(range.to_string(), "[1.0,2.0)");
+
+        let exact = VersionRange::parse("[1.5]").unwrap();
+        assert!(exact.contains(&Version::from("1.5.0")));
+        assert!(!exact.contains(&Version::from("1.6")));
+        assert_eq!(exact.to_string(), "[1.5]");
+
+        let union = VersionRange::parse("(,1.0],[1.2,)").unwrap();
+        assert!(union.contains(&Version::from("0.9")));
+        assert!(!union.contains(&Version::from("1.1")));
+        assert!(union.contains(&Version::from("3")));
+
+        let soft = VersionRange::parse("1.0").unwrap();
+        assert_eq!(soft.recommended, Some(Version::from("1.0")));
+
+        for invalid in [
+            "[1.0,2.0",
+            "[,1.0]",
+            "[2.0,1.0]",
+            "(1.0)",
+            "[1.0],",
+            "[1,2,3]",
+        ] {
+            assert!(VersionRange::parse(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn select_highest() {
+        let candidates: Vec<Version> = ["1.0", "1.5", "1.10", "2.0"]
+            .into_iter()
+            .map(Version::from)
+            .collect();
+        let range = VersionRange::parse("[1.0,2.0)").unwrap();
+        assert_eq!(range.select(&candidates), Some(&Version::from("1.10")));
+        let none = VersionRange::parse("[3,)").unwrap();
+        assert_eq!(none.select(&candidates), None);
+    }
+
+    #[test]
+    fn dynamic_versions() {
+        let candidates: Vec<Version> = ["1.2", "1.10", "1.11-SNAPSHOT", "10.0", "2.0-SNAPSHOT"]
+            .into_iter()
+            .map(Version::from)
+            .collect();
+        let select = |spec: &str| {
+            DynamicVersion::parse(spec)
+                .unwrap()
+                .select(&candidates)
+                .map(|v| v.to_string())
+        };
+        assert_eq!(select("latest.release").as_deref(), Some("10.0"));
+        assert_eq!(select("latest.integration").as_deref(), Some("10.0"));
+        assert_eq!(select("1.+").as_deref(), Some("1.11-SNAPSHOT"));
+        assert_eq!(select("1+").as_deref(), Some("10.0"));
+        assert_eq!(select("2.+").as_deref(), Some("2.0-SNAPSHOT"));
+        assert_eq!(select("3.+"), None);
+        assert_eq!(DynamicVersion::parse("1.0"), None);
+        assert_eq!(DynamicVersion::parse("[1.0,)"), None);
+        assert_eq!(DynamicVersion::parse("1.+").unwrap().to_string(), "1.+");
+    }
+
+    #[test]
+    fn semver_queries() {
+        for (query, range) in [
+            ("^5.2", "[5.2,6-alpha)"),
+            ("5.2", "[5.2,6-alpha)"),
+            ("^0.2.3", "[0.2.3,0.3-alpha)"),
+            ("^0.0.3", "[0.0.3,0.0.4-alpha)"),
+            ("~5.2.1", "[5.2.1,5.3-alpha)"),
+            ("~5", "[5,6-alpha)"),
+            ("5.*", "[5,6-alpha)"),
+            (">=5, <6", "[5,6-alpha)"),
+            (">=5, >=5.1, <=7, <6", "[5.1,6-alpha)"),
+            ("=1.0", "[1.0]"),
+            ("<1.0", "(,1.0-alpha)"),
+            ("<2.0-RC2", "(,2.0-RC2)"),
+            ("*", "(,)"),
+        ] {
+            assert_eq!(
+                VersionRange::from_query(query).unwrap().to_string(),
+                range,
+                "{}",
+                query
+            );
+        }
+        let range = VersionRange::from_query("^5.2").unwrap();
+        assert!(range.contains(&Version::from("5.10.0")));
+        assert!(!range.contains(&Version::from("5.1")));
+        // pre-releases of the upper bound are left out, as in Cargo
+        for prerelease in [
+            "6.0-RC1",
+            "6-alpha",
+            "6.0.0-alpha-1",
+            "6-m2",
+            "6.0-SNAPSHOT",
+            "6",
+        ] {
+            assert!(
+                !range.contains(&Version::from(prerelease)),
+                "{}",
+                prerelease
+            );
+        }
+        assert!(range.contains(&Version::from("5.99-SNAPSHOT")));
+        let range = VersionRange::from_query("<2.0-RC2").unwrap();
+        assert!(range.contains(&Version::from("2.0-RC1")));
+        for invalid in ["", ">=6, <5", "^a.b", "~", "^1.2.3.4", ">5, <5"] {
+            assert!(VersionRange::from_query(invalid).is_err(), "{}", invalid);
+        }
+    }
+}
@@ -0,0 +1,323 @@
+use crate::Version;
+use crate::artifact::PartialArtifact;
+use crate::substitution::Pattern;
+use crate::version::{Restriction, VersionRange};
+use serde::Serialize;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+pub const OSV_QUERY_URL: &str = "https://api.osv.dev/v1/query";
+
+#[derive(Debug, Error)]
+pub enum WithdrawalError {
+    #[error("Unable to read {path}: {source}")]
+    IO {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Invalid withdrawal on line {line}: {message}")]
+    Parse { line: usize, message: String },
+    #[error("Invalid OSV response: {0}")]
+    Osv(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Withdrawal {
+    pub pattern: Pattern,
+    pub versions: VersionRange,
+    // an advisory id, or whatever the feed says
+    pub reason: String,
+}
+
+// why a version should not be picked
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Marker {
+    pub version: Version,
+    pub reasons: Vec<String>,
+}
+
+// Versions documented as withdrawn or known to be bad, so listings can flag them. Read
+// from denylist feeds and from advisory databases speaking the OSV API.
+#[derive(Debug, Clone, Default)]
+pub struct Withdrawals {
+    entries: Vec<Withdrawal>,
+}
+
+impl Withdrawals {
+    pub fn new() -> Withdrawals {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn entries(&self) -> &[Withdrawal] {
+        &self.entries
+    }
+
+    pub fn extend(&mut self, other: Withdrawals) {
+        self.entries.extend(other.entries);
+    }
+
+    // One entry per line, # starts a comment, the rest of the line is the reason:
+    //   org.apache.logging.log4j:log4j-core [2.0-beta9,2.15.0) CVE-2021-44228
+    //   org.example:lib 1.4.2 published with a broken POM
+    pub fn parse(content: &str) -> Result<Withdrawals, WithdrawalError> {
+        let mut entries = vec![];
+        for (index, line) in content.lines().enumerate() {
+            let invalid = |message: String| WithdrawalError::Parse {
+                line: index + 1,
+                message,
+            };
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let mut words = line.splitn(3, char::is_whitespace);
+            let pattern = words
+                .next()
+                .and_then(Pattern::parse)
+                .ok_or_else(|| invalid(String::from("expected groupId:artifactId")))?;
+            let versions = words
+                .next()
+                .ok_or_else(|| invalid(String::from("expected a version or range")))
+                .and_then(|v| VersionRange::parse(v).map_err(|e| invalid(e.to_string())))?;
+            entries.push(Withdrawal {
+                pattern,
+                versions,
+                reason: words
+                    .next()
+                    .map(str::trim)
+                    .filter(|r| !r.is_empty())
+                    .unwrap_or("no reason given")
+                    .to_string(),
+            });
+        }
+        Ok(Withdrawals { entries })
+    }
+
+    pub fn load(path: &Path) -> Result<Withdrawals, WithdrawalError> {
+        let content = std::fs::read_to_string(path).map_err(|source| WithdrawalError::IO {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::parse(&content)
+    }
+
+    // The body of an OSV query for all advisories of an artifact
+    pub fn osv_query(artifact: &PartialArtifact, page_token: Option<&str>) -> String {
+        let mut query = serde_json::json!({
+            "package": { "name": artifact.to_string(), "ecosystem": "Maven" },
+        });
+        if let Some(token) = page_token {
+            query["page_token"] = Value::from(token);
+        }
+        query.to_string()
+    }
+
+    // The advisories of an OSV query response, and the token of the next page if there is
+    // one. Affected versions are taken from the explicit lists and from ECOSYSTEM ranges,
+    // where `introduced` opens a restriction and `fixed` or `last_affected` closes it.
+    pub fn parse_osv(
+        json: &str,
+        artifact: &PartialArtifact,
+    ) -> Result<(Withdrawals, Option<String>), WithdrawalError> {
+        let value: Value =
+            serde_json::from_str(json).map_err(|e| WithdrawalError::Osv(e.to_string()))?;
+        let name = artifact.to_string();
+        let pattern = Pattern::parse(&name).ok_or_else(|| WithdrawalError::Osv(name.clone()))?;
+        let str_field = |value: &Value, field: &str| value.get(field)?.as_str().map(String::from);
+        let mut entries = vec![];
+        for vuln in value
+            .get("vulns")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            let Some(id) = str_field(vuln, "id") else {
+                continue;
+            };
+            let reason = match str_field(vuln, "summary") {
+                Some(summary) => format!("{} {}", id, summary),
+                None => id,
+            };
+            let affected = vuln.get("affected").and_then(Value::as_array);
+            for affected in affected.into_iter().flatten() {
+                let package = affected.get("package");
+                if package.and_then(|p| str_field(p, "ecosystem")).as_deref() != Some("Maven")
+                    || package.and_then(|p| str_field(p, "name")).as_deref() != Some(name.as_str())
+                {
+                    continue;
+                }
+                let mut restrictions: Vec<Restriction> = vec![];
+                let versions = affected.get("versions").and_then(Value::as_array);
+                for version in versions.into_iter().flatten().filter_map(Value::as_str) {
+                    restrictions.push(Restriction {
+                        lower: Some(Version::from(version)),
+                        lower_inclusive: true,
+                        upper: Some(Version::from(version)),
+                        upper_inclusive: true,
+                    });
+                }
+                let ranges = affected.get("ranges").and_then(Value::as_array);
+                for range in ranges.into_iter().flatten() {
+                    if str_field(range, "type").as_deref() != Some("ECOSYSTEM") {
+                        continue;
+                    }
+                    let events = range.get("events").and_then(Value::as_array);
+                    restrictions.extend(osv_restrictions(events.into_iter().flatten()));
+                }
+                if !restrictions.is_empty() {
+                    entries.push(Withdrawal {
+                        pattern: pattern.clone(),
+                        versions: VersionRange {
+                            recommended: None,
+                            restrictions,
+                        },
+                        reason: reason.clone(),
+                    });
+                }
+            }
+        }
+        let next_page = str_field(&value, "next_page_token").filter(|t| !t.is_empty());
+        Ok((Withdrawals { entries }, next_page))
+    }
+
+    // reasons to avoid the version, in the order the entries were added
+    pub fn check(&self, artifact: &PartialArtifact, version: &Version) -> Option<Marker> {
+        let mut reasons: Vec<String> = vec![];
+        for entry in &self.entries {
+            if entry
+                .pattern
+                .matches(&artifact.group_id, &artifact.artifact_id)
+                && entry.versions.contains(version)
+                && !reasons.contains(&entry.reason)
+            {
+                reasons.push(entry.reason.clone());
+            }
+        }
+        (!reasons.is_empty()).then(|| Marker {
+            version: version.clone(),
+            reasons,
+        })
+    }
+}
+
+fn osv_restrictions<'a>(events: impl Iterator<Item = &'a Value>) -> Vec<Restriction> {
+    let mut restrictions = vec![];
+    let mut open: Option<Option<Version>> = None;
+    for event in events {
+        if let Some(introduced) = event.get("introduced").and_then(Value::as_str) {
+            // "0" stands for every version before the first fix
+            open = Some((introduced != "0").then(|| Version::from(introduced)));
+            continue;
+        }
+        let (upper, upper_inclusive) = match (
+            event.get("fixed").and_then(Value::as_str),
+            event.get("last_affected").and_then(Value::as_str),
+        ) {
+            (Some(fixed), _) => (fixed, false),
+            (None, Some(last)) => (last, true),
+            (None, None) => continue,
+        };
+        if let Some(lower) = open.take() {
+            restrictions.push(Restriction {
+                lower_inclusive: lower.is_some(),
+                lower,
+                upper: Some(Version::from(upper)),
+                upper_inclusive,
+            });
+        }
+    }
+    if let Some(lower) = open {
+        restrictions.push(Restriction {
+            lower_inclusive: lower.is_some(),
+            lower,
+            upper: None,
+            upper_inclusive: false,
+        });
+    }
+    restrictions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log4j() -> PartialArtifact {
+        PartialArtifact::parse("org.apache.logging.log4j:log4j-core").unwrap()
+    }
+
+    #[test]
+    fn denylist_feed() {
+        let withdrawals = Withdrawals::parse(
+            "# known bad
+org.apache.logging.log4j:log4j-core [2.0-beta9,2.15.0) CVE-2021-44228
+org.apache.logging.log4j:log4j-core 2.15.0
+org.example:* [1.0]  published by mistake",
+        )
+        .unwrap();
+        assert_eq!(withdrawals.entries().len(), 3);
+        let reasons = |version: &str| {
+            withdrawals
+                .check(&log4j(), &Version::from(version))
+                .map(|m| m.reasons)
+        };
+        assert_eq!(
+            reasons("2.14.1"),
+            Some(vec![String::from("CVE-2021-44228")])
+        );
+        assert_eq!(
+            reasons("2.15.0"),
+            Some(vec![String::from("no reason given")])
+        );
+        assert_eq!(reasons("2.17.1"), None);
+        let other = PartialArtifact::parse("org.example:lib").unwrap();
+        let marker = withdrawals.check(&other, &Version::from("1.0")).unwrap();
+        assert_eq!(marker.reasons, vec![String::from("published by mistake")]);
+
+        let error = Withdrawals::parse("org.example:lib").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Invalid withdrawal on line 1: expected a version or range"
+        );
+    }
+
+    #[test]
+    fn osv_response() {
+        let json = r#"{"vulns": [
+            {"id": "GHSA-jfh8-c2jp-5v3q", "summary": "Remote code injection in Log4j",
+             "affected": [{"package": {"ecosystem": "Maven", "name": "org.apache.logging.log4j:log4j-core"},
+                           "ranges": [{"type": "ECOSYSTEM", "events": [{"introduced": "2.13.0"}, {"fixed": "2.15.0"},
+                                                                       {"introduced": "0"}, {"last_affected": "2.3.1"}]}]},
+                          {"package": {"ecosystem": "Maven", "name": "org.apache.logging.log4j:log4j-api"},
+                           "versions": ["2.14.0"]}]},
+            {"id": "GHSA-7rjr-3q55-vv33",
+             "affected": [{"package": {"ecosystem": "Maven", "name": "org.apache.logging.log4j:log4j-core"},
+                           "versions": ["2.15.0"],
+                           "ranges": [{"type": "GIT", "events": [{"introduced": "abc"}]}]}]}
+        ], "next_page_token": "page2"}"#;
+        let (withdrawals, next) = Withdrawals::parse_osv(json, &log4j()).unwrap();
+        assert_eq!(next.as_deref(), Some("page2"));
+        assert_eq!(withdrawals.entries().len(), 2);
+        assert_eq!(
+            withdrawals.entries()[0].versions.to_string(),
+            "[2.13.0,2.15.0),(,2.3.1]"
+        );
+        let reasons = |version: &str| {
+            withdrawals
+                .check(&log4j(), &Version::from(version))
+                .map(|m| m.reasons.len())
+        };
+        assert_eq!(reasons("2.14.1"), Some(1));
+        assert_eq!(reasons("2.2"), Some(1));
+        assert_eq!(reasons("2.15.0"), Some(1));
+        assert_eq!(reasons("2.16.0"), None);
+        assert_eq!(
+            Withdrawals::osv_query(&log4j(), None),
+            r#"{"package":{"ecosystem":"Maven","name":"org.apache.logging.log4j:log4j-core"}}"#
+        );
+    }
+}
@@ -0,0 +1,320 @@
+use crate::artifact::PartialArtifact;
+use crate::pom::{PomError, PomParser, Project};
+use crate::resolver::{ResolveError, Resolver};
+use crate::{ArtifactId, GroupId, Version};
+use serde::Serialize;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum WorkspaceError {
+    #[error("Unable to read {path}: {source}")]
+    IO {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Unable to parse {path}: {source}")]
+    Pom { path: PathBuf, source: PomError },
+    #[error("Unable to build effective model: {0}")]
+    Resolve(#[from] Box<ResolveError>),
+    #[error("Module {0} has no groupId or version")]
+    Coordinates(PathBuf),
+    #[error("Modules depend on each other in a cycle: {0}")]
+    Cycle(String),
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Module {
+    pub path: PathBuf,
+    pub group_id: GroupId,
+    pub artifact_id: ArtifactId,
+    pub version: Version,
+    #[serde(skip)]
+    pub project: Project,
+}
+
+impl Module {
+    pub fn coordinates(&self) -> PartialArtifact {
+        PartialArtifact::new(self.group_id.clone(), self.artifact_id.clone())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModuleDependency {
+    pub from: usize,
+    pub to: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    // the dependent declares `to` as its <parent> rather than as a dependency
+    pub parent: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Workspace {
+    pub root: PathBuf,
+    pub modules: Vec<Module>,
+}
+
+impl Workspace {
+    // Loads the aggregator and every module it lists, recursively. Parents are inherited
+    // when found through <relativePath>; parents outside the checkout are not fetched.
+    pub fn load(path: &Path) -> Result<Workspace, WorkspaceError> {
+        let mut paths = vec![];
+        Self::collect(&Self::pom_path(path), &mut paths)?;
+        let mut modules = Vec::with_capacity(paths.len());
+        for (path, project) in paths {
+            let project = Self::inherit_local(&path, project);
+            modules.push(Self::module(path, project)?);
+        }
+        Ok(Workspace {
+            root: Self::pom_path(path),
+            modules,
+        })
+    }
+
+    // Like load, but parents that are not part of the checkout come from the resolver's
+    // repositories.
+    pub async fn load_with(path: &Path, resolver: &Resolver) -> Result<Workspace, WorkspaceError> {
+        let mut paths = vec![];
+        Self::collect(&Self::pom_path(path), &mut paths)?;
+        let mut modules = Vec::with_capacity(paths.len());
+        for (path, _) in paths {
+            let project = resolver
+                .effective_project_at(&path)
+                .await
+                .map_err(Box::new)?;
+            modules.push(Self::module(path, project)?);
+        }
+        Ok(Workspace {
+            root: Self::pom_path(path),
+            modules,
+        })
+    }
+
+    fn pom_path(path: &Path) -> PathBuf {
+        if path.is_dir() {
+            path.join("pom.xml")
+        } else {
+            path.to_path_buf()
+        }
+    }
+
+    fn collect(path: &Path, found: &mut Vec<(PathBuf, Project)>) -> Result<(), WorkspaceError> {
+        if found.iter().any(|(p, _)| p == path) {
+            return Ok(());
+        }
+        let file = File::open(path).map_err(|source| WorkspaceError::IO {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let project = PomParser::new()
+            .parse(file)
+            .map_err(|source| WorkspaceError::Pom {
+                path: path.to_path_buf(),
+                source,
+            })?;
+        let dir = path.parent().unwrap_or(Path::new("."));
+        let children: Vec<PathBuf> = project
+            .child_projects()
+            .map(|child| Self::pom_path(&dir.join(child)))
+            .collect();
+        found.push((path.to_path_buf(), project));
+        for child in children {
+            Self::collect(&child, found)?;
+        }
+        Ok(())
+    }
+
+    fn inherit_local(path: &Path, mut project: Project) -> Project {
+        let mut dir = path.parent().map(Path::to_path_buf);
+        let mut next = project.parent.clone();
+        let mut depth = 0;
+        while let (Some(parent), Some(base)) = (next, dir) {
+            let Some((path, model)) = parent.find_local(&base) else {
+                break;
+            };
+            project.inherit(&model);
+            next = model.parent.clone();
+            dir = path.parent().map(Path::to_path_buf);
+            depth += 1;
+            if depth > 32 {
+                break;
+            }
+        }
+        project
+    }
+
+    fn module(path: PathBuf, project: Project) -> Result<Module, WorkspaceError> {
+        let group_id = project.effective_group_id().map(|g| project.interpolate(g));
+        let version = project.effective_version().map(|v| project.interpolate(v));
+        match (group_id, version) {
+            (Some(group_id), Some(version)) => Ok(Module {
+                path,
+                group_id: GroupId::from(group_id),
+                artifact_id: project.artifact_id.clone(),
+                version: Version::from(version),
+                project,
+            }),
+            _ => Err(WorkspaceError::Coordinates(path)),
+        }
+    }
+
+    pub fn find(&self, group_id: &str, artifact_id: &str) -> Option<usize> {
+        self.modules
+            .iter()
+            .position(|m| m.group_id.as_ref() == group_id && m.artifact_id.as_ref() == artifact_id)
+    }
+
+    pub fn dependencies(&self) -> Vec<ModuleDependency> {
+        let mut edges = vec![];
+        for (from, module) in self.modules.iter().enumerate() {
+            let project = &module.project;
+            if let Some(parent) = &project.parent
+                && let Some(to) = self.find(&parent.group_id, &parent.artifact_id)
+            {
+                edges.push(ModuleDependency {
+                    from,
+                    to,
+                    scope: None,
+                    parent: true,
+                });
+            }
+            for dependency in &project.dependencies {
+                let group_id = project.interpolate(&dependency.group_id);
+                let artifact_id = project.interpolate(&dependency.artifact_id);
+                if let Some(to) = self.find(&group_id, &artifact_id)
+                    && to != from
+                {
+                    edges.push(ModuleDependency {
+                        from,
+                        to,
+                        scope: dependency.scope.clone(),
+                        parent: false,
+                    });
+                }
+            }
+        }
+        edges
+    }
+
+    pub fn dependencies_of(&self, module: usize) -> Vec<&Module> {
+        self.dependencies()
+            .into_iter()
+            .filter(|d| d.from == module)
+            .map(|d| &self.modules[d.to])
+            .collect()
+    }
+
+    pub fn dependents_of(&self, module: usize) -> Vec<&Module> {
+        self.dependencies()
+            .into_iter()
+            .filter(|d| d.to == module)
+            .map(|d| &self.modules[d.from])
+            .collect()
+    }
+
+    // Modules ordered so each comes after everything it depends on, keeping declaration
+    // order where there is a choice, like the Maven reactor.
+    pub fn build_order(&self) -> Result<Vec<&Module>, WorkspaceError> {
+        let edges = self.dependencies();
+        let mut done = vec![false; self.modules.len()];
+        let mut order = Vec::with_capacity(self.modules.len());
+        while order.len() < self.modules.len() {
+            let ready = (0..self.modules.len()).find(|&i| {
+                !done[i] && edges.iter().all(|e| e.from != i || done[e.to] || e.to == i)
+            });
+            match ready {
+                Some(i) => {
+                    done[i] = true;
+                    order.push(&self.modules[i]);
+                }
+                None => {
+                    let remaining: Vec<String> = (0..self.modules.len())
+                        .filter(|&i| !done[i])
+                        .map(|i| self.modules[i].coordinates().to_string())
+                        .collect();
+                    return Err(WorkspaceError::Cycle(remaining.join(", ")));
+                }
+            }
+        }
+        Ok(order)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(dir: &Path, path: &str, content: &str) {
+        let path = dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn load_modules() {
+        let dir =
+            std::env::temp_dir().join(format!("maven-artifact-workspace-{}", std::process::id()));
+        write(
+            &dir,
+            "pom.xml",
+            r#"<project>
+  <groupId>org.example</groupId>
+  <artifactId>root</artifactId>
+  <version>${revision}</version>
+  <packaging>pom</packaging>
+  <properties><revision>1.2.0</revision></properties>
+  <modules><module>app</module><module>core</module></modules>
+</project>"#,
+        );
+        let parent = r#"<parent>
+    <groupId>org.example</groupId>
+    <artifactId>root</artifactId>
+    <version>${revision}</version>
+  </parent>"#;
+        write(
+            &dir,
+            "app/pom.xml",
+            &format!(
+                r#"<project>{}<artifactId>app</artifactId>
+  <dependencies>
+    <dependency><groupId>${{project.groupId}}</groupId><artifactId>core</artifactId><version>${{project.version}}</version></dependency>
+    <dependency><groupId>org.slf4j</groupId><artifactId>slf4j-api</artifactId><version>2.0.9</version></dependency>
+  </dependencies>
+</project>"#,
+                parent
+            ),
+        );
+        write(
+            &dir,
+            "core/pom.xml",
+            &format!("<project>{}<artifactId>core</artifactId></project>", parent),
+        );
+
+        let workspace = Workspace::load(&dir).unwrap();
+        assert_eq!(workspace.modules.len(), 3);
+        let app = workspace.find("org.example", "app").unwrap();
+        let core = workspace.find("org.example", "core").unwrap();
+        assert_eq!(workspace.modules[core].version, Version::from("1.2.0"));
+
+        let deps: Vec<_> = workspace
+            .dependencies_of(app)
+            .iter()
+            .map(|m| m.artifact_id.to_string())
+            .collect();
+        assert_eq!(deps, vec!["root", "core"]);
+        assert_eq!(workspace.dependents_of(core).len(), 1);
+
+        let order: Vec<_> = workspace
+            .build_order()
+            .unwrap()
+            .iter()
+            .map(|m| m.artifact_id.to_string())
+            .collect();
+        assert_eq!(order, vec!["root", "core", "app"]);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}